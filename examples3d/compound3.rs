@@ -1,6 +1,6 @@
-use na::Point3;
+use na::{Isometry3, Point3, Vector3};
 use rapier3d::dynamics::{JointSet, RigidBodyBuilder, RigidBodySet};
-use rapier3d::geometry::{ColliderBuilder, ColliderSet};
+use rapier3d::geometry::{ColliderBuilder, ColliderSet, ColliderShape};
 use rapier_testbed3d::Testbed;
 
 pub fn init_world(testbed: &mut Testbed) {
@@ -47,16 +47,22 @@ pub fn init_world(testbed: &mut Testbed) {
                 // Build the rigid body.
                 let rigid_body = RigidBodyBuilder::new_dynamic().translation(x, y, z).build();
                 let handle = bodies.insert(rigid_body);
-                let collider1 = ColliderBuilder::cuboid(rad * 10.0, rad, rad).build();
-                let collider2 = ColliderBuilder::cuboid(rad, rad * 10.0, rad)
-                    .translation(rad * 10.0, rad * 10.0, 0.0)
-                    .build();
-                let collider3 = ColliderBuilder::cuboid(rad, rad * 10.0, rad)
-                    .translation(-rad * 10.0, rad * 10.0, 0.0)
-                    .build();
-                colliders.insert(collider1, handle, &mut bodies);
-                colliders.insert(collider2, handle, &mut bodies);
-                colliders.insert(collider3, handle, &mut bodies);
+                let shapes = vec![
+                    (
+                        Isometry3::identity(),
+                        ColliderShape::cuboid(Vector3::new(rad * 10.0, rad, rad)),
+                    ),
+                    (
+                        Isometry3::translation(rad * 10.0, rad * 10.0, 0.0),
+                        ColliderShape::cuboid(Vector3::new(rad, rad * 10.0, rad)),
+                    ),
+                    (
+                        Isometry3::translation(-rad * 10.0, rad * 10.0, 0.0),
+                        ColliderShape::cuboid(Vector3::new(rad, rad * 10.0, rad)),
+                    ),
+                ];
+                let collider = ColliderBuilder::compound(shapes).build();
+                colliders.insert(collider, handle, &mut bodies);
             }
         }
 
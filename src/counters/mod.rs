@@ -214,6 +214,7 @@ impl Display for Counters {
         self.stages.fmt(f)?;
         self.cd.fmt(f)?;
         self.solver.fmt(f)?;
+        self.ccd.fmt(f)?;
         writeln!(f, "Custom timer: {}", self.custom)
     }
 }
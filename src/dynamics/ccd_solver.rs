@@ -0,0 +1,834 @@
+use crate::counters::Counters;
+use crate::dynamics::{
+    CoefficientCombineRule, IntegrationParameters, RigidBody, RigidBodyHandle, RigidBodySet,
+};
+use crate::geometry::{
+    self, ColliderHandle, ColliderSet, ContactPairFilter, PairFilterContext, Proximity,
+    ProximityEvent, ProximityPairFilter, RigidMotion, SolverFlags, TOIStatus, WQuadtree, AABB,
+};
+use crate::math::{Isometry, Point, Vector};
+use crate::pipeline::EventHandler;
+use crate::utils::{WAngularInertia, WCross, WDot};
+use ncollide::bounding_volume::BoundingVolume;
+use std::collections::{HashMap, HashSet};
+
+// The motion of a rigid-body, interpolated between its pose at the time `t0`
+// and its pose at the end of the timestep (`t = 1`).
+//
+// Times are expressed as fractions of the timestep, shifted by `time_offset`.
+#[derive(Copy, Clone, Debug)]
+struct CCDMotion {
+    start: Isometry<f32>,
+    end: Isometry<f32>,
+    t0: f32,
+    time_offset: f32,
+    delta: Isometry<f32>,
+}
+
+impl CCDMotion {
+    fn new(start: Isometry<f32>, end: Isometry<f32>, t0: f32) -> Self {
+        Self {
+            start,
+            end,
+            t0,
+            time_offset: 0.0,
+            delta: Isometry::identity(),
+        }
+    }
+
+    fn with_time_offset(mut self, time_offset: f32) -> Self {
+        self.time_offset = time_offset;
+        self
+    }
+
+    fn with_delta(mut self, delta: Isometry<f32>) -> Self {
+        self.delta = delta;
+        self
+    }
+}
+
+impl RigidMotion<f32> for CCDMotion {
+    fn position_at_time(&self, t: f32) -> Isometry<f32> {
+        let t = self.time_offset + t;
+
+        if self.t0 >= 1.0 {
+            return self.end * self.delta;
+        }
+
+        let s = na::clamp((t - self.t0) / (1.0 - self.t0), 0.0, 1.0);
+        self.start.lerp_slerp(&self.end, s) * self.delta
+    }
+}
+
+struct CCDBody {
+    handle: RigidBodyHandle,
+    motion: CCDMotion,
+    // The motions already performed by this body during the current timestep,
+    // with the time they ended at.
+    past_motions: Vec<(CCDMotion, f32)>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct CCDImpact {
+    toi: f32,
+    body1: usize,
+    collider1: ColliderHandle,
+    collider2: ColliderHandle,
+    // World-space contact point on the first collider, at the time of impact.
+    point1: Point<f32>,
+    // World-space contact point on the second collider, at the time of impact.
+    point2: Point<f32>,
+    // World-space contact normal pointing toward the second collider.
+    normal: Vector<f32>,
+    penetrating: bool,
+}
+
+/// Solver responsible for preventing fast CCD-enabled rigid-bodies from tunnelling
+/// through other colliders.
+///
+/// The CCD resolution is performed after the constraints solver, by sweeping the colliders
+/// of fast bodies from their positions at the beginning of the timestep to their positions
+/// at the end of the timestep. Each time of impact found this way is resolved by moving the
+/// involved bodies at their time of impact and applying an impulse along the contact normal,
+/// before resuming their motion for the rest of the timestep.
+pub(crate) struct CCDSolver {
+    ccd_bodies: Vec<CCDBody>,
+    ccd_body_ids: HashMap<RigidBodyHandle, usize>,
+    quadtree: WQuadtree<ColliderHandle>,
+    candidates: Vec<ColliderHandle>,
+    sensor_pairs: HashSet<(ColliderHandle, ColliderHandle)>,
+}
+
+impl CCDSolver {
+    pub fn new() -> Self {
+        Self {
+            ccd_bodies: Vec::new(),
+            ccd_body_ids: HashMap::new(),
+            quadtree: WQuadtree::new(),
+            candidates: Vec::new(),
+            sensor_pairs: HashSet::new(),
+        }
+    }
+
+    /// Records the positions of the CCD-enabled bodies at the beginning of the timestep.
+    ///
+    /// This must be called before the constraints solver integrates the positions of the bodies.
+    pub fn init(&mut self, params: &IntegrationParameters, bodies: &RigidBodySet) {
+        self.ccd_bodies.clear();
+        self.ccd_body_ids.clear();
+
+        if params.max_ccd_substeps == 0 {
+            // CCD is disabled.
+            return;
+        }
+
+        for (handle, rb) in bodies.iter_active_dynamic() {
+            if rb.ccd_enabled && !rb.colliders.is_empty() {
+                let _ = self.ccd_body_ids.insert(handle, self.ccd_bodies.len());
+                self.ccd_bodies.push(CCDBody {
+                    handle,
+                    motion: CCDMotion::new(rb.position, rb.position, 0.0),
+                    past_motions: Vec::new(),
+                });
+            }
+        }
+    }
+
+    /// Detects and resolves the impacts of the CCD-enabled bodies moving fast during this timestep.
+    ///
+    /// This must be called after the constraints solver integrated the positions of the bodies,
+    /// and before the positions of the colliders are updated.
+    pub fn solve(
+        &mut self,
+        params: &IntegrationParameters,
+        bodies: &mut RigidBodySet,
+        colliders: &ColliderSet,
+        contact_pair_filter: Option<&dyn ContactPairFilter>,
+        proximity_pair_filter: Option<&dyn ProximityPairFilter>,
+        events: &dyn EventHandler,
+        counters: &mut Counters,
+    ) {
+        counters.ccd.reset();
+
+        // Only keep the bodies that move fast enough to tunnel through something.
+        for ccd_body in &mut self.ccd_bodies {
+            ccd_body.motion.end = bodies[ccd_body.handle].position;
+        }
+
+        self.ccd_bodies.retain(|ccd_body| {
+            is_moving_fast(&bodies[ccd_body.handle], &ccd_body.motion, colliders)
+        });
+
+        if self.ccd_bodies.is_empty() {
+            return;
+        }
+
+        self.ccd_body_ids.clear();
+        for (i, ccd_body) in self.ccd_bodies.iter().enumerate() {
+            let _ = self.ccd_body_ids.insert(ccd_body.handle, i);
+        }
+
+        counters.ccd.broad_phase_time.start();
+        self.update_quadtree(params, bodies, colliders);
+        counters.ccd.broad_phase_time.pause();
+
+        let mut time = 0.0;
+        let mut impacts = Vec::new();
+
+        loop {
+            counters.ccd.toi_computation_time.resume();
+            impacts.clear();
+            for i in 0..self.ccd_bodies.len() {
+                let impact =
+                    self.find_first_impact(i, time, params, bodies, colliders, contact_pair_filter);
+                impacts.push(impact);
+            }
+            counters.ccd.toi_computation_time.pause();
+
+            let first_impact = impacts.iter().filter_map(|impact| *impact).fold(
+                None,
+                |first: Option<CCDImpact>, impact| match first {
+                    Some(first) if first.toi <= impact.toi => Some(first),
+                    _ => Some(impact),
+                },
+            );
+
+            let first_impact = match first_impact {
+                Some(impact) => impact,
+                None => break,
+            };
+
+            counters.ccd.solver_time.resume();
+
+            if counters.ccd.num_substeps < params.max_ccd_substeps {
+                // Resolve the earliest impact and resume the motion of the involved bodies.
+                counters.ccd.num_substeps += 1;
+                time = first_impact.toi;
+                self.resolve_impact(
+                    &first_impact,
+                    !params.return_after_ccd_substep,
+                    params,
+                    bodies,
+                    colliders,
+                );
+
+                if !params.return_after_ccd_substep {
+                    counters.ccd.solver_time.pause();
+                    continue;
+                }
+
+                // Other bodies still have to be stopped at their time of impact.
+                impacts.clear();
+                counters.ccd.toi_computation_time.resume();
+                for i in 0..self.ccd_bodies.len() {
+                    let impact = self.find_first_impact(
+                        i,
+                        time,
+                        params,
+                        bodies,
+                        colliders,
+                        contact_pair_filter,
+                    );
+                    impacts.push(impact);
+                }
+                counters.ccd.toi_computation_time.pause();
+            }
+
+            // We ran out of substeps: stop all the remaining bodies at their first time of impact.
+            for impact in impacts.iter().filter_map(|impact| *impact) {
+                self.resolve_impact(&impact, false, params, bodies, colliders);
+            }
+
+            counters.ccd.solver_time.pause();
+            break;
+        }
+
+        // Move the bodies to their final positions.
+        for ccd_body in &mut self.ccd_bodies {
+            let rb = &mut bodies[ccd_body.handle];
+            rb.position = ccd_body.motion.end;
+            rb.update_world_mass_properties();
+            ccd_body.past_motions.push((ccd_body.motion, 1.0));
+        }
+
+        counters.ccd.narrow_phase_time.start();
+        self.detect_sensor_crossings(params, bodies, colliders, proximity_pair_filter, events);
+        counters.ccd.narrow_phase_time.pause();
+    }
+
+    // Builds the acceleration structure with the AABBs swept by all the colliders during this timestep.
+    fn update_quadtree(
+        &mut self,
+        params: &IntegrationParameters,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+    ) {
+        let ccd_body_ids = &self.ccd_body_ids;
+        let ccd_bodies = &self.ccd_bodies;
+        let data = colliders.iter().map(|(handle, collider)| {
+            let motion = match ccd_body_ids.get(&collider.parent) {
+                Some(id) => ccd_bodies[*id].motion.with_delta(collider.delta),
+                None => {
+                    collider_motion(&bodies[collider.parent], collider.position, &collider.delta)
+                        .with_delta(collider.delta)
+                }
+            };
+
+            (
+                handle,
+                swept_aabb(collider.shape(), &motion, 1.0).loosened(params.prediction_distance),
+            )
+        });
+
+        self.quadtree.clear_and_rebuild(data, 0.0);
+    }
+
+    // Finds the first impact of the i-th CCD body after the given time.
+    fn find_first_impact(
+        &mut self,
+        i: usize,
+        time: f32,
+        params: &IntegrationParameters,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        contact_pair_filter: Option<&dyn ContactPairFilter>,
+    ) -> Option<CCDImpact> {
+        let ccd_body = &self.ccd_bodies[i];
+        let rb1 = &bodies[ccd_body.handle];
+        let max_toi = 1.0 - time;
+        let mut result: Option<CCDImpact> = None;
+
+        if max_toi <= 0.0 || ccd_body.motion.t0 >= 1.0 {
+            return None;
+        }
+
+        for handle1 in &rb1.colliders {
+            let co1 = &colliders[*handle1];
+
+            if co1.is_sensor() {
+                continue;
+            }
+
+            let motion1 = ccd_body.motion.with_delta(co1.delta).with_time_offset(time);
+            let swept_aabb = swept_aabb(co1.shape(), &motion1, max_toi);
+
+            self.candidates.clear();
+            self.quadtree
+                .intersect_aabb(&swept_aabb, &mut self.candidates);
+
+            for handle2 in &self.candidates {
+                let co2 = &colliders[*handle2];
+
+                if co2.parent == co1.parent
+                    || co2.is_sensor()
+                    || !co1.collision_groups.test(co2.collision_groups)
+                    || !co1.solver_groups.test(co2.solver_groups)
+                {
+                    continue;
+                }
+
+                let rb2 = &bodies[co2.parent];
+
+                if let Some(filter) = contact_pair_filter {
+                    let context = PairFilterContext {
+                        rigid_body1: rb1,
+                        rigid_body2: rb2,
+                        collider1: co1,
+                        collider2: co2,
+                    };
+
+                    let solver_flags = filter.filter_contact_pair(&context);
+                    if !solver_flags
+                        .map(|flags| flags.contains(SolverFlags::COMPUTE_IMPULSES))
+                        .unwrap_or(false)
+                    {
+                        continue;
+                    }
+                }
+
+                let motion2 = self
+                    .motion(co2.parent)
+                    .unwrap_or_else(|| collider_motion(rb2, co2.position, &co2.delta))
+                    .with_delta(co2.delta)
+                    .with_time_offset(time);
+
+                let toi = match geometry::nonlinear_time_of_impact(
+                    &motion1,
+                    co1.shape(),
+                    &motion2,
+                    co2.shape(),
+                    max_toi,
+                    0.0,
+                ) {
+                    Some(toi) => toi,
+                    None => continue,
+                };
+
+                let impact_time = time + toi.toi;
+
+                if result.map(|r| r.toi <= impact_time).unwrap_or(false) {
+                    continue;
+                }
+
+                let pos1 = motion1.position_at_time(toi.toi);
+                let pos2 = motion2.position_at_time(toi.toi);
+
+                let (point1, point2, normal, penetrating) = match toi.status {
+                    TOIStatus::Converged => (
+                        pos1 * toi.witness1,
+                        pos2 * toi.witness2,
+                        pos1 * toi.normal1.into_inner(),
+                        false,
+                    ),
+                    TOIStatus::Penetrating if !params.ccd_on_penetration_enabled => continue,
+                    _ => match geometry::contact(&pos1, co1.shape(), &pos2, co2.shape(), 0.0) {
+                        Some(c) => (c.world1, c.world2, c.normal.into_inner(), true),
+                        None => continue,
+                    },
+                };
+
+                // Ignore the impacts where the bodies are already moving apart.
+                let com1 = motion1.position_at_time(toi.toi)
+                    * co1.delta.inverse()
+                    * rb1.mass_properties.local_com;
+                let vel1 = rb1.linvel + rb1.angvel.gcross(point1 - com1);
+                let vel2 = if self.ccd_body_ids.contains_key(&co2.parent) {
+                    let com2 = pos2 * co2.delta.inverse() * rb2.mass_properties.local_com;
+                    rb2.linvel + rb2.angvel.gcross(point2 - com2)
+                } else {
+                    rb2.velocity_at_point(&point2)
+                };
+
+                if (vel1 - vel2).dot(&normal) <= 0.0 {
+                    continue;
+                }
+
+                result = Some(CCDImpact {
+                    toi: impact_time,
+                    body1: i,
+                    collider1: *handle1,
+                    collider2: *handle2,
+                    point1,
+                    point2,
+                    normal,
+                    penetrating,
+                });
+            }
+        }
+
+        result
+    }
+
+    // The motion of the given rigid-body, if it is handled by the CCD solver.
+    fn motion(&self, handle: RigidBodyHandle) -> Option<CCDMotion> {
+        self.ccd_body_ids
+            .get(&handle)
+            .map(|id| self.ccd_bodies[*id].motion)
+    }
+
+    // Moves the bodies involved in `impact` to their time of impact and applies an impulse
+    // that stops them from getting closer.
+    //
+    // If `resume_motion` is `true`, the bodies will continue to move with their new velocities
+    // for the rest of the timestep. Otherwise they are stopped at their time of impact.
+    fn resolve_impact(
+        &mut self,
+        impact: &CCDImpact,
+        resume_motion: bool,
+        params: &IntegrationParameters,
+        bodies: &mut RigidBodySet,
+        colliders: &ColliderSet,
+    ) {
+        let co1 = &colliders[impact.collider1];
+        let co2 = &colliders[impact.collider2];
+        let handle1 = co1.parent;
+        let handle2 = co2.parent;
+        let id2 = self.ccd_body_ids.get(&handle2).copied();
+
+        // Move the CCD bodies to their time of impact.
+        for id in Some(impact.body1).into_iter().chain(id2) {
+            let ccd_body = &mut self.ccd_bodies[id];
+            let pos = ccd_body.motion.position_at_time(impact.toi);
+            ccd_body.past_motions.push((ccd_body.motion, impact.toi));
+            ccd_body.motion = CCDMotion::new(pos, pos, impact.toi);
+
+            let rb = &mut bodies[ccd_body.handle];
+            rb.position = pos;
+            rb.update_world_mass_properties();
+        }
+
+        // Apply the impulse along the contact normal.
+        let restitution = CoefficientCombineRule::combine(
+            co1.restitution,
+            co2.restitution,
+            co1.restitution_combine_rule,
+            co2.restitution_combine_rule,
+        );
+        let (rb1, rb2) = bodies.get2_mut_internal(handle1, handle2);
+        let (rb1, rb2) = (rb1.unwrap(), rb2.unwrap());
+        let dp1 = impact.point1 - rb1.world_com;
+        let dp2 = impact.point2 - rb2.world_com;
+        let normal_vel = (rb1.velocity_at_point(&impact.point1)
+            - rb2.velocity_at_point(&impact.point2))
+        .dot(&impact.normal);
+
+        if normal_vel > 0.0 {
+            let gcross1 = rb1
+                .world_inv_inertia_sqrt
+                .transform_vector(dp1.gcross(impact.normal));
+            let gcross2 = if rb2.is_dynamic() {
+                rb2.world_inv_inertia_sqrt
+                    .transform_vector(dp2.gcross(-impact.normal))
+            } else {
+                na::zero()
+            };
+            let im2 = if rb2.is_dynamic() {
+                rb2.effective_inv_mass
+            } else {
+                na::zero()
+            };
+            let inv_r = impact
+                .normal
+                .dot(&(rb1.effective_inv_mass + im2).component_mul(&impact.normal))
+                + gcross1.gdot(gcross1)
+                + gcross2.gdot(gcross2);
+
+            if inv_r != 0.0 {
+                let restitution = if normal_vel > params.restitution_velocity_threshold {
+                    restitution
+                } else {
+                    0.0
+                };
+                let impulse = (1.0 + restitution) * normal_vel / inv_r;
+
+                rb1.linvel -= impact.normal.component_mul(&rb1.effective_inv_mass) * impulse;
+                rb1.angvel -= rb1
+                    .world_inv_inertia_sqrt
+                    .transform_vector(gcross1 * impulse);
+
+                rb1.project_velocities_on_free_axes();
+
+                if rb2.is_dynamic() {
+                    rb2.linvel += impact.normal.component_mul(&im2) * impulse;
+                    rb2.angvel += rb2
+                        .world_inv_inertia_sqrt
+                        .transform_vector(gcross2 * impulse);
+                    rb2.project_velocities_on_free_axes();
+                }
+            }
+        }
+
+        if rb2.is_dynamic() && id2.is_none() {
+            bodies.wake_up(handle2, true);
+        }
+
+        // Push the CCD bodies out of penetration.
+        if impact.penetrating {
+            for _ in 0..params.max_ccd_position_iterations {
+                let pos1 = bodies[handle1].position * co1.delta;
+                let pos2 = match id2 {
+                    Some(_) => bodies[handle2].position * co2.delta,
+                    None => collider_motion(&bodies[handle2], co2.position, &co2.delta)
+                        .position_at_time(impact.toi),
+                };
+
+                let contact = match geometry::contact(&pos1, co1.shape(), &pos2, co2.shape(), 0.0) {
+                    Some(contact) if contact.depth > params.allowed_linear_error => contact,
+                    _ => break,
+                };
+
+                let correction =
+                    (contact.depth - params.allowed_linear_error).min(params.max_linear_correction);
+                let shift = contact.normal.into_inner() * correction;
+
+                if id2.is_some() {
+                    bodies[handle1].position.translation.vector -= shift * 0.5;
+                    bodies[handle2].position.translation.vector += shift * 0.5;
+                } else {
+                    bodies[handle1].position.translation.vector -= shift;
+                }
+            }
+        }
+
+        // Resume or stop the motion of the CCD bodies.
+        for id in Some(impact.body1).into_iter().chain(id2) {
+            let ccd_body = &mut self.ccd_bodies[id];
+            let rb = &mut bodies[ccd_body.handle];
+            rb.update_world_mass_properties();
+            let start = rb.position;
+            let end = if resume_motion {
+                rb.integrate_velocity((1.0 - impact.toi) * params.dt()) * start
+            } else {
+                start
+            };
+            ccd_body.motion = CCDMotion::new(start, end, impact.toi);
+        }
+    }
+
+    // Emits proximity events for the sensors that have been completely traversed by a CCD body
+    // during this timestep, and would therefore not be detected by the narrow-phase.
+    fn detect_sensor_crossings(
+        &mut self,
+        params: &IntegrationParameters,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        proximity_pair_filter: Option<&dyn ProximityPairFilter>,
+        events: &dyn EventHandler,
+    ) {
+        self.sensor_pairs.clear();
+
+        for ccd_body in &self.ccd_bodies {
+            let rb1 = &bodies[ccd_body.handle];
+            let mut t0 = 0.0;
+
+            for (motion, t1) in &ccd_body.past_motions {
+                if *t1 <= t0 {
+                    continue;
+                }
+
+                for handle1 in &rb1.colliders {
+                    let co1 = &colliders[*handle1];
+                    let motion1 = motion.with_delta(co1.delta).with_time_offset(t0);
+                    let swept_aabb = swept_aabb(co1.shape(), &motion1, t1 - t0);
+
+                    self.candidates.clear();
+                    self.quadtree
+                        .intersect_aabb(&swept_aabb, &mut self.candidates);
+
+                    for handle2 in &self.candidates {
+                        let co2 = &colliders[*handle2];
+
+                        if co2.parent == co1.parent
+                            || !(co1.is_sensor() || co2.is_sensor())
+                            || !co1.collision_groups.test(co2.collision_groups)
+                        {
+                            continue;
+                        }
+
+                        let pair = (*handle1, *handle2);
+                        if !params.multiple_ccd_substep_sensor_events_enabled
+                            && self.sensor_pairs.contains(&pair)
+                        {
+                            continue;
+                        }
+
+                        let rb2 = &bodies[co2.parent];
+
+                        if let Some(filter) = proximity_pair_filter {
+                            let context = PairFilterContext {
+                                rigid_body1: rb1,
+                                rigid_body2: rb2,
+                                collider1: co1,
+                                collider2: co2,
+                            };
+
+                            if !filter.filter_proximity_pair(&context) {
+                                continue;
+                            }
+                        }
+
+                        let other_motion = self
+                            .motion(co2.parent)
+                            .unwrap_or_else(|| collider_motion(rb2, co2.position, &co2.delta));
+                        let motion2 = other_motion.with_delta(co2.delta).with_time_offset(t0);
+
+                        let toi = geometry::nonlinear_time_of_impact(
+                            &motion1,
+                            co1.shape(),
+                            &motion2,
+                            co2.shape(),
+                            t1 - t0,
+                            0.0,
+                        );
+
+                        // If the colliders are intersecting at the beginning or at the end of
+                        // this motion, the narrow-phase takes care of the events.
+                        match toi {
+                            Some(toi) if toi.status != TOIStatus::Penetrating => {}
+                            _ => continue,
+                        }
+
+                        if geometry::intersection_test(
+                            &motion1.position_at_time(t1 - t0),
+                            co1.shape(),
+                            &motion2.position_at_time(t1 - t0),
+                            co2.shape(),
+                        ) {
+                            continue;
+                        }
+
+                        let _ = self.sensor_pairs.insert(pair);
+                        events.handle_proximity_event(ProximityEvent::new(
+                            pair.0,
+                            pair.1,
+                            Proximity::Disjoint,
+                            Proximity::Intersecting,
+                        ));
+                        events.handle_proximity_event(ProximityEvent::new(
+                            pair.0,
+                            pair.1,
+                            Proximity::Intersecting,
+                            Proximity::Disjoint,
+                        ));
+                    }
+                }
+
+                t0 = *t1;
+            }
+        }
+    }
+}
+
+// The motion of a collider attached to a body not handled by the CCD solver, from its
+// position at the beginning of the timestep to its position at the end of the timestep.
+fn collider_motion(rb: &RigidBody, start: Isometry<f32>, delta: &Isometry<f32>) -> CCDMotion {
+    // The collider delta is re-applied by the caller.
+    let start = start * delta.inverse();
+    let end = if rb.is_kinematic() {
+        rb.predicted_position
+    } else {
+        rb.position
+    };
+
+    CCDMotion::new(start, end, 0.0)
+}
+
+// The AABB containing the given shape during the first `max_toi` units of time of its motion.
+fn swept_aabb(shape: &dyn geometry::Shape, motion: &CCDMotion, max_toi: f32) -> AABB {
+    let start = motion.position_at_time(0.0);
+    let end = motion.position_at_time(max_toi);
+    let start_aabb = shape.compute_aabb(&start);
+    let end_aabb = shape.compute_aabb(&end);
+
+    // The body rotates around its origin while its origin moves along a straight line.
+    // Compared to a motion keeping the start orientation, each point of the shape then moves
+    // by at most the chord of its rotation, once to reach the end AABB and once in-between.
+    let angle = (start.rotation.inverse() * end.rotation).angle().abs();
+    let radius = motion.delta.translation.vector.norm() + local_bounding_radius(shape);
+    let max_chord = angle.min(2.0) * radius;
+
+    start_aabb.merged(&end_aabb).loosened(max_chord * 2.0)
+}
+
+fn local_bounding_radius(shape: &dyn geometry::Shape) -> f32 {
+    let aabb = shape.compute_aabb(&Isometry::identity());
+    aabb.mins.coords.abs().sup(&aabb.maxs.coords.abs()).norm()
+}
+
+// Checks if the given body moves more than the thickness of its thinnest collider.
+fn is_moving_fast(rb: &RigidBody, motion: &CCDMotion, colliders: &ColliderSet) -> bool {
+    let mut min_thickness = f32::MAX;
+    let mut max_radius = 0.0f32;
+
+    for handle in &rb.colliders {
+        let collider = &colliders[*handle];
+        let local_aabb = collider.shape().compute_aabb(&Isometry::identity());
+        min_thickness = min_thickness.min(local_aabb.half_extents().min());
+        max_radius = max_radius.max(
+            collider.delta.translation.vector.norm() + local_bounding_radius(collider.shape()),
+        );
+    }
+
+    let linear_motion = (motion.end.translation.vector - motion.start.translation.vector).norm();
+    let angular_motion = (motion.start.rotation.inverse() * motion.end.rotation).angle();
+
+    linear_motion + angular_motion * max_radius > min_thickness
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dynamics::RigidBodyBuilder;
+    use crate::geometry::{ColliderBuilder, ColliderShape};
+    use crate::math::{Isometry, Point, Vector};
+    use crate::pipeline::test_world::TestWorld;
+
+    #[test]
+    fn ccd_prevents_tunnelling() {
+        // Thin walls with their left side at x = 5.
+        let mut half_extents = Vector::repeat(10.0);
+        half_extents.x = 0.05;
+        let wall = ColliderShape::cuboid(half_extents);
+        let wall_pos = Isometry::new(Vector::x() * 5.05, na::zero());
+        let mut voxel_shift = Vector::repeat(-0.05);
+        voxel_shift.x = 0.0;
+        let mut voxels = Vec::new();
+        for j in -20..20 {
+            #[cfg(feature = "dim2")]
+            voxels.push(Point::new(50, j));
+            #[cfg(feature = "dim3")]
+            for k in -20..20 {
+                voxels.push(Point::new(50, j, k));
+            }
+        }
+        let walls = vec![
+            (wall_pos, wall.clone()),
+            (
+                Isometry::identity(),
+                ColliderShape::compound(vec![(wall_pos, wall)]),
+            ),
+            // Shifted so that the ball hits the center of a voxel face.
+            (
+                Isometry::new(voxel_shift, na::zero()),
+                ColliderShape::voxels(Vector::repeat(0.1), &voxels),
+            ),
+        ];
+
+        for (wall_pos, wall) in walls {
+            let mut world = TestWorld::new();
+            let rb = RigidBodyBuilder::new_static().position(wall_pos).build();
+            let _ = world.insert(rb, vec![ColliderBuilder::new(wall).build()]);
+
+            // A fast ball moving toward the wall.
+            let mut rb = RigidBodyBuilder::new_dynamic().ccd_enabled(true).build();
+            rb.set_linvel(Vector::x() * 1000.0, true);
+            let ball = world.insert(rb, vec![ColliderBuilder::ball(0.1).build()]);
+
+            world.step();
+
+            assert!(world.bodies[ball].position().translation.vector.x < 5.0);
+            assert!(world.bodies[ball].linvel().x < 1.0);
+        }
+    }
+
+    #[test]
+    fn ccd_detects_impacts_of_rotating_bodies() {
+        let mut world = TestWorld::new();
+
+        // A small obstacle above the origin.
+        let rb = RigidBodyBuilder::new_static()
+            .position(Isometry::new(Vector::y() * 1.5, na::zero()))
+            .build();
+        let _ = world.insert(
+            rb,
+            vec![ColliderBuilder::new(ColliderShape::cuboid(Vector::repeat(0.1))).build()],
+        );
+
+        // A thin horizontal bar rotating fast around its center. It is horizontal at the
+        // beginning and almost horizontal at the end of the timestep, so the obstacle is
+        // outside of its start and end AABBs.
+        let angvel = std::f32::consts::PI * 0.9 * world.params.inv_dt();
+        #[cfg(feature = "dim2")]
+        let mut rb = RigidBodyBuilder::new_dynamic().ccd_enabled(true).build();
+        #[cfg(feature = "dim2")]
+        rb.set_angvel(angvel, true);
+        #[cfg(feature = "dim3")]
+        let mut rb = RigidBodyBuilder::new_dynamic().ccd_enabled(true).build();
+        #[cfg(feature = "dim3")]
+        rb.set_angvel(Vector::z() * angvel, true);
+        let mut half_extents = Vector::repeat(0.05);
+        half_extents.x = 2.0;
+        let collider = ColliderBuilder::new(ColliderShape::cuboid(half_extents)).build();
+        let bar = world.insert(rb, vec![collider]);
+
+        world.step();
+
+        // The bar hit the obstacle, which pushed it away and slowed down its rotation.
+        let rb = &world.bodies[bar];
+        #[cfg(feature = "dim2")]
+        let new_angvel = rb.angvel();
+        #[cfg(feature = "dim3")]
+        let new_angvel = rb.angvel().z;
+        assert!(new_angvel < angvel * 0.5);
+        assert!(rb.linvel().norm() > 1.0);
+    }
+}
@@ -0,0 +1,65 @@
+/// Rules used to combine two coefficients.
+///
+/// This is used to determine the effective restitution and
+/// friction coefficients for a contact between two colliders.
+/// Each collider has its combination rule of type
+/// `CoefficientCombineRule`. And the rule
+/// actually used is given by `max(first_combine_rule as usize, second_combine_rule as usize)`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum CoefficientCombineRule {
+    /// The two coefficients are averaged.
+    Average = 0,
+    /// The smallest coefficient is chosen.
+    Min,
+    /// The two coefficients are multiplied.
+    Multiply,
+    /// The greatest coefficient is chosen.
+    Max,
+}
+
+impl Default for CoefficientCombineRule {
+    fn default() -> Self {
+        CoefficientCombineRule::Average
+    }
+}
+
+impl CoefficientCombineRule {
+    /// Combines two coefficients, each associated to its own combine rule.
+    pub fn combine(coeff1: f32, coeff2: f32, rule1: Self, rule2: Self) -> f32 {
+        match rule1.max(rule2) {
+            CoefficientCombineRule::Average => (coeff1 + coeff2) / 2.0,
+            CoefficientCombineRule::Min => coeff1.min(coeff2),
+            CoefficientCombineRule::Multiply => coeff1 * coeff2,
+            CoefficientCombineRule::Max => coeff1.max(coeff2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CoefficientCombineRule;
+
+    #[test]
+    fn combine_rule_precedence() {
+        let average = CoefficientCombineRule::Average;
+        let min = CoefficientCombineRule::Min;
+        let multiply = CoefficientCombineRule::Multiply;
+        let max = CoefficientCombineRule::Max;
+
+        assert_eq!(
+            CoefficientCombineRule::combine(0.2, 0.6, average, average),
+            0.4
+        );
+        assert_eq!(CoefficientCombineRule::combine(0.2, 0.6, average, min), 0.2);
+        assert_eq!(
+            CoefficientCombineRule::combine(0.2, 0.6, multiply, min),
+            0.2 * 0.6
+        );
+        assert_eq!(
+            CoefficientCombineRule::combine(0.2, 0.6, max, multiply),
+            0.6
+        );
+        assert_eq!(CoefficientCombineRule::combine(0.2, 0.6, max, average), 0.6);
+    }
+}
@@ -1,8 +1,13 @@
 use crate::math::{Point, Vector};
+#[cfg(feature = "dim3")]
+use {crate::utils::WBasis, na::Unit};
 
 #[derive(Copy, Clone)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 /// A joint that removes all relative linear motion between a pair of points on two bodies.
+///
+/// In 3D, the relative rotation of the attached bodies can be restricted by a swing cone
+/// and a twist range around a local axis attached to each body.
 pub struct BallJoint {
     /// Where the ball joint is attached on the first body, expressed in the first body local frame.
     pub local_anchor1: Point<f32>,
@@ -12,6 +17,36 @@ pub struct BallJoint {
     ///
     /// The impulse applied to the second body is given by `-impulse`.
     pub impulse: Vector<f32>,
+    /// Whether or not this joint should enforce its swing and twist limits.
+    #[cfg(feature = "dim3")]
+    pub limits_enabled: bool,
+    /// The axis of the swing cone and twist limits, expressed in the local space of the first body.
+    #[cfg(feature = "dim3")]
+    pub limits_local_axis1: Unit<Vector<f32>>,
+    /// The axis of the swing cone and twist limits, expressed in the local space of the second body.
+    #[cfg(feature = "dim3")]
+    pub limits_local_axis2: Unit<Vector<f32>>,
+    /// A vector orthogonal to `limits_local_axis1`, used as a reference for measuring the twist angle.
+    #[cfg(feature = "dim3")]
+    pub limits_basis1: Vector<f32>,
+    /// A vector orthogonal to `limits_local_axis2`, used as a reference for measuring the twist angle.
+    ///
+    /// The twist angle is zero when `limits_basis1` and `limits_basis2` are aligned after the
+    /// swing has been removed.
+    #[cfg(feature = "dim3")]
+    pub limits_basis2: Vector<f32>,
+    /// The maximum angle between the limit axes of the attached bodies.
+    #[cfg(feature = "dim3")]
+    pub swing_limit: f32,
+    /// The min and max relative angle of the attached bodies around the limit axis.
+    #[cfg(feature = "dim3")]
+    pub twist_limits: [f32; 2],
+    /// The angular impulse applied by this joint on the second body to enforce the swing limit.
+    #[cfg(feature = "dim3")]
+    pub swing_impulse: f32,
+    /// The angular impulse applied by this joint on the second body to enforce the twist limits.
+    #[cfg(feature = "dim3")]
+    pub twist_impulse: f32,
 }
 
 impl BallJoint {
@@ -29,6 +64,90 @@ impl BallJoint {
             local_anchor1,
             local_anchor2,
             impulse,
+            #[cfg(feature = "dim3")]
+            limits_enabled: false,
+            #[cfg(feature = "dim3")]
+            limits_local_axis1: Vector::x_axis(),
+            #[cfg(feature = "dim3")]
+            limits_local_axis2: Vector::x_axis(),
+            #[cfg(feature = "dim3")]
+            limits_basis1: Vector::y(),
+            #[cfg(feature = "dim3")]
+            limits_basis2: Vector::y(),
+            #[cfg(feature = "dim3")]
+            swing_limit: f32::MAX,
+            #[cfg(feature = "dim3")]
+            twist_limits: [-f32::MAX, f32::MAX],
+            #[cfg(feature = "dim3")]
+            swing_impulse: 0.0,
+            #[cfg(feature = "dim3")]
+            twist_impulse: 0.0,
+        }
+    }
+
+    /// Enables the swing and twist limits of this joint.
+    ///
+    /// The angle between `local_axis1` and `local_axis2`, expressed in the local space of the
+    /// first and second body respectively, cannot exceed `swing_limit`. The relative rotation of the
+    /// attached bodies around these axes is kept within `twist_limits`.
+    #[cfg(feature = "dim3")]
+    pub fn configure_limits(
+        &mut self,
+        local_axis1: Unit<Vector<f32>>,
+        local_axis2: Unit<Vector<f32>>,
+        swing_limit: f32,
+        twist_limits: [f32; 2],
+    ) {
+        self.limits_enabled = true;
+        self.limits_local_axis1 = local_axis1;
+        self.limits_local_axis2 = local_axis2;
+        self.limits_basis1 = local_axis1.orthonormal_basis()[0];
+        self.limits_basis2 = local_axis2.orthonormal_basis()[0];
+        self.swing_limit = swing_limit;
+        self.twist_limits = twist_limits;
+    }
+}
+
+#[cfg(all(test, feature = "dim3"))]
+mod test {
+    use super::BallJoint;
+    use crate::dynamics::RigidBodyBuilder;
+    use crate::geometry::ColliderBuilder;
+    use crate::math::{Point, Vector};
+    use crate::pipeline::test_world::TestWorld;
+
+    #[test]
+    fn swing_twist_limits() {
+        let mut world = TestWorld::new();
+        let ground = world.insert(RigidBodyBuilder::new_static().build(), None);
+
+        // One body swings away from the limit axis, the other twists around it.
+        let mut handles = Vec::new();
+        for (i, angvel) in [Vector::y() * 10.0, Vector::x() * 10.0].iter().enumerate() {
+            let rb = RigidBodyBuilder::new_dynamic()
+                .translation(0.0, 0.0, i as f32 * 5.0)
+                .angvel(*angvel)
+                .build();
+            let body = world.insert(rb, Some(ColliderBuilder::cuboid(0.5, 0.5, 0.5).build()));
+
+            let mut joint = BallJoint::new(Point::new(0.0, 0.0, i as f32 * 5.0), Point::origin());
+            joint.configure_limits(Vector::x_axis(), Vector::x_axis(), 0.5, [-0.3, 0.3]);
+            let joint = world.joints.insert(&mut world.bodies, ground, body, joint);
+            handles.push((body, joint));
         }
+
+        world.run(60);
+
+        let (swinging, swing_joint) = handles[0];
+        let axis = world.bodies[swinging].position() * Vector::x();
+        assert!(axis.angle(&Vector::x()) < 0.5 + 1.0e-2);
+        let joint = world.joints.get(swing_joint).unwrap();
+        assert!(joint.params.as_ball_joint().unwrap().swing_impulse >= 0.0);
+
+        let (twisting, twist_joint) = handles[1];
+        let rotation = world.bodies[twisting].position().rotation;
+        assert!(rotation.angle() < 0.3 + 1.0e-2);
+        let joint = world.joints.get(twist_joint).unwrap();
+        assert!(joint.params.as_ball_joint().unwrap().twist_impulse >= 0.0);
     }
 }
@@ -1,6 +1,6 @@
-#[cfg(feature = "dim3")]
-use crate::dynamics::RevoluteJoint;
-use crate::dynamics::{BallJoint, FixedJoint, JointHandle, PrismaticJoint, RigidBodyHandle};
+use crate::dynamics::{
+    BallJoint, FixedJoint, JointHandle, PrismaticJoint, RevoluteJoint, RigidBodyHandle,
+};
 
 #[derive(Copy, Clone)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
@@ -13,7 +13,6 @@ pub enum JointParams {
     /// A prismatic joint that removes all degrees of degrees of freedom between the affected
     /// bodies except for the translation along one axis.
     PrismaticJoint(PrismaticJoint),
-    #[cfg(feature = "dim3")]
     /// A revolute joint that removes all degrees of degrees of freedom between the affected
     /// bodies except for the translation along one axis.
     RevoluteJoint(RevoluteJoint),
@@ -26,7 +25,6 @@ impl JointParams {
             JointParams::BallJoint(_) => 0,
             JointParams::FixedJoint(_) => 1,
             JointParams::PrismaticJoint(_) => 2,
-            #[cfg(feature = "dim3")]
             JointParams::RevoluteJoint(_) => 3,
         }
     }
@@ -59,7 +57,6 @@ impl JointParams {
     }
 
     /// Gets a reference to the underlying revolute joint, if `self` is one.
-    #[cfg(feature = "dim3")]
     pub fn as_revolute_joint(&self) -> Option<&RevoluteJoint> {
         if let JointParams::RevoluteJoint(j) = self {
             Some(j)
@@ -81,7 +78,6 @@ impl From<FixedJoint> for JointParams {
     }
 }
 
-#[cfg(feature = "dim3")]
 impl From<RevoluteJoint> for JointParams {
     fn from(j: RevoluteJoint) -> Self {
         JointParams::RevoluteJoint(j)
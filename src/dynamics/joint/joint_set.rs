@@ -114,7 +114,8 @@ impl JointSet {
         J: Into<JointParams>,
     {
         let handle = self.joint_ids.insert(0.into());
-        let joint = Joint {
+        #[allow(unused_mut)] // Mut is only needed in 2D.
+        let mut joint = Joint {
             body1,
             body2,
             handle,
@@ -131,6 +132,15 @@ impl JointSet {
             rb2.expect("Attempt to attach a joint to a non-existing body."),
         );
 
+        // In 2D, the angle of a revolute joint is measured from the
+        // relative angle of its bodies at the time it is inserted.
+        #[cfg(feature = "dim2")]
+        {
+            if let JointParams::RevoluteJoint(revolute) = &mut joint.params {
+                revolute.reference_angle = rb1.position.rotation.angle_to(&rb2.position.rotation);
+            }
+        }
+
         // NOTE: the body won't have a graph index if it does not
         // have any joint attached.
         if !InteractionGraph::<Joint>::is_graph_index_valid(rb1.joint_graph_index) {
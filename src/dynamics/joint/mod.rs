@@ -4,7 +4,6 @@ pub use self::joint::{Joint, JointParams};
 pub(crate) use self::joint_set::{JointGraphEdge, JointIndex};
 pub use self::joint_set::{JointHandle, JointSet};
 pub use self::prismatic_joint::PrismaticJoint;
-#[cfg(feature = "dim3")]
 pub use self::revolute_joint::RevoluteJoint;
 
 mod ball_joint;
@@ -12,5 +11,4 @@ mod fixed_joint;
 mod joint;
 mod joint_set;
 mod prismatic_joint;
-#[cfg(feature = "dim3")]
 mod revolute_joint;
@@ -36,10 +36,26 @@ pub struct PrismaticJoint {
     ///
     /// The impulse applied to the second body is given by `-impulse`.
     pub limits_impulse: f32,
-    // pub motor_enabled: bool,
-    // pub target_motor_vel: f32,
-    // pub max_motor_impulse: f32,
-    // pub motor_impulse: f32,
+    /// Whether or not this joint should drive the relative motion of the attached bodies along its axis.
+    pub motor_enabled: bool,
+    /// The target relative velocity of the attached bodies along this joint's axis.
+    pub target_motor_vel: f32,
+    /// The target relative position of the attached bodies along this joint's axis.
+    ///
+    /// This is only taken into account if `motor_stiffness` is non-zero.
+    pub target_motor_pos: f32,
+    /// The stiffness of the motor driving this joint toward `target_motor_pos`.
+    ///
+    /// If set to zero, the motor only drives this joint toward `target_motor_vel`.
+    pub motor_stiffness: f32,
+    /// The damping of the motor driving this joint toward `target_motor_pos`.
+    pub motor_damping: f32,
+    /// The maximum impulse the motor can apply at each timestep.
+    pub max_motor_impulse: f32,
+    /// The impulse applied by the motor of this joint on the second body along this joint's axis.
+    ///
+    /// The impulse applied to the first body is given by `-motor_impulse`.
+    pub motor_impulse: f32,
 }
 
 impl PrismaticJoint {
@@ -63,10 +79,13 @@ impl PrismaticJoint {
             limits_enabled: false,
             limits: [-f32::MAX, f32::MAX],
             limits_impulse: 0.0,
-            // motor_enabled: false,
-            // target_motor_vel: 0.0,
-            // max_motor_impulse: f32::MAX,
-            // motor_impulse: 0.0,
+            motor_enabled: false,
+            target_motor_vel: 0.0,
+            target_motor_pos: 0.0,
+            motor_stiffness: 0.0,
+            motor_damping: 0.0,
+            max_motor_impulse: f32::MAX,
+            motor_impulse: 0.0,
         }
     }
 
@@ -118,10 +137,13 @@ impl PrismaticJoint {
             limits_enabled: false,
             limits: [-f32::MAX, f32::MAX],
             limits_impulse: 0.0,
-            // motor_enabled: false,
-            // target_motor_vel: 0.0,
-            // max_motor_impulse: f32::MAX,
-            // motor_impulse: 0.0,
+            motor_enabled: false,
+            target_motor_vel: 0.0,
+            target_motor_pos: 0.0,
+            motor_stiffness: 0.0,
+            motor_damping: 0.0,
+            max_motor_impulse: f32::MAX,
+            motor_impulse: 0.0,
         }
     }
 
@@ -135,6 +157,39 @@ impl PrismaticJoint {
         self.local_axis2
     }
 
+    /// Configures the motor of this joint so it drives the attached bodies toward the relative
+    /// velocity `target_vel` along this joint's axis.
+    ///
+    /// The motor cannot apply an impulse greater than `max_impulse` at each timestep.
+    pub fn configure_velocity_motor(&mut self, target_vel: f32, max_impulse: f32) {
+        self.motor_enabled = true;
+        self.target_motor_vel = target_vel;
+        self.target_motor_pos = 0.0;
+        self.motor_stiffness = 0.0;
+        self.motor_damping = 0.0;
+        self.max_motor_impulse = max_impulse;
+    }
+
+    /// Configures the motor of this joint so it drives the attached bodies toward the relative
+    /// position `target_pos` along this joint's axis.
+    ///
+    /// The motor behaves like a spring with the given `stiffness` and `damping`, and cannot
+    /// apply an impulse greater than `max_impulse` at each timestep.
+    pub fn configure_position_motor(
+        &mut self,
+        target_pos: f32,
+        stiffness: f32,
+        damping: f32,
+        max_impulse: f32,
+    ) {
+        self.motor_enabled = true;
+        self.target_motor_vel = 0.0;
+        self.target_motor_pos = target_pos;
+        self.motor_stiffness = stiffness;
+        self.motor_damping = damping;
+        self.max_motor_impulse = max_impulse;
+    }
+
     // FIXME: precompute this?
     #[cfg(feature = "dim2")]
     pub(crate) fn local_frame1(&self) -> Isometry<f32> {
@@ -191,3 +246,62 @@ impl PrismaticJoint {
         Isometry::from_parts(translation, rotation)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::PrismaticJoint;
+    use crate::dynamics::{RigidBodyBuilder, RigidBodyHandle};
+    use crate::geometry::ColliderBuilder;
+    use crate::math::{Point, Vector};
+    use crate::pipeline::test_world::TestWorld;
+
+    // A ball attached to a static body by a prismatic joint along the `x` axis.
+    fn setup(configure: impl FnOnce(&mut PrismaticJoint)) -> (TestWorld, RigidBodyHandle) {
+        let mut world = TestWorld::new();
+        let ground = world.insert(RigidBodyBuilder::new_static().build(), None);
+        let body = world.insert(
+            RigidBodyBuilder::new_dynamic().build(),
+            Some(ColliderBuilder::ball(0.5).build()),
+        );
+
+        #[cfg(feature = "dim2")]
+        let mut joint = PrismaticJoint::new(
+            Point::origin(),
+            Vector::x_axis(),
+            Point::origin(),
+            Vector::x_axis(),
+        );
+        #[cfg(feature = "dim3")]
+        let mut joint = PrismaticJoint::new(
+            Point::origin(),
+            Vector::x_axis(),
+            Vector::zeros(),
+            Point::origin(),
+            Vector::x_axis(),
+            Vector::zeros(),
+        );
+        configure(&mut joint);
+        world.joints.insert(&mut world.bodies, ground, body, joint);
+
+        (world, body)
+    }
+
+    #[test]
+    fn velocity_motor() {
+        let (mut world, body) = setup(|joint| joint.configure_velocity_motor(2.0, f32::MAX));
+        world.run(10);
+
+        assert!((world.bodies[body].linvel().x - 2.0).abs() < 1.0e-3);
+        assert!(world.bodies[body].position().translation.vector.x > 0.0);
+    }
+
+    #[test]
+    fn position_motor() {
+        let (mut world, body) =
+            setup(|joint| joint.configure_position_motor(1.0, 100.0, 20.0, f32::MAX));
+        world.run(300);
+
+        assert!((world.bodies[body].position().translation.x - 1.0).abs() < 1.0e-2);
+        assert!(world.bodies[body].linvel().norm() < 1.0e-2);
+    }
+}
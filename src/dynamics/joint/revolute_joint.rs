@@ -1,32 +1,108 @@
-use crate::math::{Point, Vector};
-use crate::utils::WBasis;
-use na::{Unit, Vector5};
+use crate::math::{Isometry, Point};
+#[cfg(feature = "dim2")]
+use {crate::math::Rotation, na::Vector2};
+#[cfg(feature = "dim3")]
+use {
+    crate::math::Vector,
+    crate::utils::WBasis,
+    na::{Unit, Vector5},
+};
 
 #[derive(Copy, Clone)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 /// A joint that removes all relative motion between two bodies, except for the rotations along one axis.
+///
+/// In 2D, this axis is always orthogonal to the plane, so this joint only removes the relative
+/// translations between the attached bodies.
 pub struct RevoluteJoint {
     /// Where the revolute joint is attached on the first body, expressed in the local space of the first attached body.
     pub local_anchor1: Point<f32>,
     /// Where the revolute joint is attached on the second body, expressed in the local space of the second attached body.
     pub local_anchor2: Point<f32>,
     /// The rotation axis of this revolute joint expressed in the local space of the first attached body.
+    #[cfg(feature = "dim3")]
     pub local_axis1: Unit<Vector<f32>>,
     /// The rotation axis of this revolute joint expressed in the local space of the second attached body.
+    #[cfg(feature = "dim3")]
     pub local_axis2: Unit<Vector<f32>>,
+    /// The relative angle of the attached bodies for which the angle of this joint is zero.
+    ///
+    /// This is set to the relative angle of the attached bodies when this joint is inserted
+    /// into a `JointSet`.
+    #[cfg(feature = "dim2")]
+    pub reference_angle: f32,
     /// The basis orthonormal to `local_axis1`, expressed in the local space of the first attached body.
+    #[cfg(feature = "dim3")]
     pub basis1: [Vector<f32>; 2],
     /// The basis orthonormal to `local_axis2`, expressed in the local space of the second attached body.
+    #[cfg(feature = "dim3")]
     pub basis2: [Vector<f32>; 2],
     /// The impulse applied by this joint on the first body.
     ///
     /// The impulse applied to the second body is given by `-impulse`.
+    #[cfg(feature = "dim3")]
     pub impulse: Vector5<f32>,
+    /// The impulse applied by this joint on the first body.
+    ///
+    /// The impulse applied to the second body is given by `-impulse`.
+    #[cfg(feature = "dim2")]
+    pub impulse: Vector2<f32>,
+    /// Whether or not this joint should enforce angular limits along its axis.
+    pub limits_enabled: bool,
+    /// The min an max relative angle of the attached bodies along this joint's axis.
+    pub limits: [f32; 2],
+    /// The angular impulse applied by this joint on the second body to enforce the angular limits along this joint's axis.
+    ///
+    /// The impulse applied to the first body is given by `-limits_impulse`.
+    pub limits_impulse: f32,
+    /// Whether or not this joint should drive the relative rotation of the attached bodies along its axis.
+    pub motor_enabled: bool,
+    /// The target relative angular velocity of the attached bodies along this joint's axis.
+    pub target_motor_vel: f32,
+    /// The target relative angle of the attached bodies along this joint's axis.
+    ///
+    /// This is only taken into account if `motor_stiffness` is non-zero.
+    pub target_motor_pos: f32,
+    /// The stiffness of the motor driving this joint toward `target_motor_pos`.
+    ///
+    /// If set to zero, the motor only drives this joint toward `target_motor_vel`.
+    pub motor_stiffness: f32,
+    /// The damping of the motor driving this joint toward `target_motor_pos`.
+    pub motor_damping: f32,
+    /// The maximum angular impulse the motor can apply at each timestep.
+    pub max_motor_impulse: f32,
+    /// The angular impulse applied by the motor of this joint on the second body along this joint's axis.
+    ///
+    /// The impulse applied to the first body is given by `-motor_impulse`.
+    pub motor_impulse: f32,
 }
 
 impl RevoluteJoint {
+    /// Creates a new revolute joint with the given point of applications, all expressed
+    /// in the local-space of the affected bodies.
+    #[cfg(feature = "dim2")]
+    pub fn new(local_anchor1: Point<f32>, local_anchor2: Point<f32>) -> Self {
+        Self {
+            local_anchor1,
+            local_anchor2,
+            reference_angle: 0.0,
+            impulse: na::zero(),
+            limits_enabled: false,
+            limits: [-f32::MAX, f32::MAX],
+            limits_impulse: 0.0,
+            motor_enabled: false,
+            target_motor_vel: 0.0,
+            target_motor_pos: 0.0,
+            motor_stiffness: 0.0,
+            motor_damping: 0.0,
+            max_motor_impulse: f32::MAX,
+            motor_impulse: 0.0,
+        }
+    }
+
     /// Creates a new revolute joint with the given point of applications and axis, all expressed
     /// in the local-space of the affected bodies.
+    #[cfg(feature = "dim3")]
     pub fn new(
         local_anchor1: Point<f32>,
         local_axis1: Unit<Vector<f32>>,
@@ -41,6 +117,186 @@ impl RevoluteJoint {
             basis1: local_axis1.orthonormal_basis(),
             basis2: local_axis2.orthonormal_basis(),
             impulse: na::zero(),
+            limits_enabled: false,
+            limits: [-f32::MAX, f32::MAX],
+            limits_impulse: 0.0,
+            motor_enabled: false,
+            target_motor_vel: 0.0,
+            target_motor_pos: 0.0,
+            motor_stiffness: 0.0,
+            motor_damping: 0.0,
+            max_motor_impulse: f32::MAX,
+            motor_impulse: 0.0,
         }
     }
+
+    /// Configures the motor of this joint so it drives the attached bodies toward the relative
+    /// angular velocity `target_vel` along this joint's axis.
+    ///
+    /// The motor cannot apply an angular impulse greater than `max_impulse` at each timestep.
+    pub fn configure_velocity_motor(&mut self, target_vel: f32, max_impulse: f32) {
+        self.motor_enabled = true;
+        self.target_motor_vel = target_vel;
+        self.target_motor_pos = 0.0;
+        self.motor_stiffness = 0.0;
+        self.motor_damping = 0.0;
+        self.max_motor_impulse = max_impulse;
+    }
+
+    /// Configures the motor of this joint so it drives the attached bodies toward the relative
+    /// angle `target_pos` along this joint's axis.
+    ///
+    /// The motor behaves like a spring with the given `stiffness` and `damping`, and cannot
+    /// apply an angular impulse greater than `max_impulse` at each timestep.
+    pub fn configure_position_motor(
+        &mut self,
+        target_pos: f32,
+        stiffness: f32,
+        damping: f32,
+        max_impulse: f32,
+    ) {
+        self.motor_enabled = true;
+        self.target_motor_vel = 0.0;
+        self.target_motor_pos = target_pos;
+        self.motor_stiffness = stiffness;
+        self.motor_damping = damping;
+        self.max_motor_impulse = max_impulse;
+    }
+
+    /// The relative angle of the attached bodies, given their positions.
+    ///
+    /// The angle is zero when the relative angle of the bodies is `reference_angle`.
+    #[cfg(feature = "dim2")]
+    pub(crate) fn estimate_angle(&self, pos1: &Isometry<f32>, pos2: &Isometry<f32>) -> f32 {
+        (pos1.rotation * Rotation::new(self.reference_angle)).angle_to(&pos2.rotation)
+    }
+
+    /// The relative angle of the attached bodies along this joint's axis, given their positions.
+    ///
+    /// The angle is zero when the first vectors of `basis1` and `basis2` are aligned.
+    #[cfg(feature = "dim3")]
+    pub(crate) fn estimate_angle(&self, pos1: &Isometry<f32>, pos2: &Isometry<f32>) -> f32 {
+        let axis1 = pos1 * self.local_axis1;
+        let ref1 = pos1 * self.basis1[0];
+        let ref2 = pos2 * self.basis2[0];
+        axis1.dot(&ref1.cross(&ref2)).atan2(ref1.dot(&ref2))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RevoluteJoint;
+    use crate::dynamics::{JointHandle, RigidBodyBuilder, RigidBodyHandle};
+    use crate::geometry::ColliderBuilder;
+    use crate::math::Point;
+    #[cfg(feature = "dim3")]
+    use crate::math::Vector;
+    use crate::pipeline::test_world::TestWorld;
+
+    // A cube attached to a static body by a revolute joint at its center.
+    fn setup(
+        configure: impl FnOnce(&mut RevoluteJoint),
+    ) -> (TestWorld, RigidBodyHandle, JointHandle) {
+        let mut world = TestWorld::new();
+        let ground = world.insert(RigidBodyBuilder::new_static().build(), None);
+        #[cfg(feature = "dim2")]
+        let collider = ColliderBuilder::cuboid(0.5, 0.5).build();
+        #[cfg(feature = "dim3")]
+        let collider = ColliderBuilder::cuboid(0.5, 0.5, 0.5).build();
+        let body = world.insert(RigidBodyBuilder::new_dynamic().build(), Some(collider));
+
+        #[cfg(feature = "dim2")]
+        let mut joint = RevoluteJoint::new(Point::origin(), Point::origin());
+        #[cfg(feature = "dim3")]
+        let mut joint = RevoluteJoint::new(
+            Point::origin(),
+            Vector::y_axis(),
+            Point::origin(),
+            Vector::y_axis(),
+        );
+        configure(&mut joint);
+        let handle = world.joints.insert(&mut world.bodies, ground, body, joint);
+
+        (world, body, handle)
+    }
+
+    fn joint_angle(world: &TestWorld, handle: JointHandle) -> f32 {
+        let joint = world.joints.get(handle).unwrap();
+        let pos1 = world.bodies[joint.body1].position();
+        let pos2 = world.bodies[joint.body2].position();
+        joint
+            .params
+            .as_revolute_joint()
+            .unwrap()
+            .estimate_angle(pos1, pos2)
+    }
+
+    #[test]
+    fn velocity_motor() {
+        let (mut world, body, handle) =
+            setup(|joint| joint.configure_velocity_motor(2.0, f32::MAX));
+        world.run(10);
+
+        #[cfg(feature = "dim2")]
+        let angvel = world.bodies[body].angvel();
+        #[cfg(feature = "dim3")]
+        let angvel = world.bodies[body].angvel().y;
+        assert!((angvel - 2.0).abs() < 1.0e-3);
+        assert!(joint_angle(&world, handle) > 0.0);
+    }
+
+    #[test]
+    fn position_motor() {
+        let (mut world, body, handle) =
+            setup(|joint| joint.configure_position_motor(0.5, 100.0, 20.0, f32::MAX));
+        world.run(300);
+
+        assert!((joint_angle(&world, handle) - 0.5).abs() < 1.0e-2);
+        #[cfg(feature = "dim2")]
+        assert!(world.bodies[body].angvel().abs() < 1.0e-2);
+        #[cfg(feature = "dim3")]
+        assert!(world.bodies[body].angvel().norm() < 1.0e-2);
+    }
+
+    #[test]
+    fn angular_limits() {
+        let (mut world, body, handle) = setup(|joint| {
+            joint.limits_enabled = true;
+            joint.limits = [-0.5, 0.5];
+        });
+        #[cfg(feature = "dim2")]
+        let angvel = 10.0;
+        #[cfg(feature = "dim3")]
+        let angvel = Vector::y() * 10.0;
+        world.bodies.get_mut(body).unwrap().set_angvel(angvel, true);
+        world.run(60);
+
+        assert!(joint_angle(&world, handle).abs() < 0.5 + 1.0e-2);
+        let joint = world.joints.get(handle).unwrap();
+        assert!(joint.params.as_revolute_joint().unwrap().limits_impulse >= 0.0);
+    }
+
+    #[cfg(feature = "dim2")]
+    #[test]
+    fn angle_is_relative_to_initial_rotation() {
+        let mut world = TestWorld::new();
+        let ground = world.insert(RigidBodyBuilder::new_static().build(), None);
+        let collider = ColliderBuilder::cuboid(0.5, 0.5).build();
+        let rb = RigidBodyBuilder::new_dynamic().rotation(1.0).build();
+        let body = world.insert(rb, Some(collider));
+
+        let mut joint = RevoluteJoint::new(Point::origin(), Point::origin());
+        joint.limits_enabled = true;
+        joint.limits = [-0.2, 0.2];
+        joint.configure_position_motor(0.1, 100.0, 20.0, f32::MAX);
+        let handle = world.joints.insert(&mut world.bodies, ground, body, joint);
+        assert!(joint_angle(&world, handle).abs() < 1.0e-5);
+
+        world.run(300);
+
+        // The motor drives the body 0.1 radians away from its initial rotation,
+        // without the limits snapping it back toward the rotation of the ground.
+        assert!((joint_angle(&world, handle) - 0.1).abs() < 1.0e-2);
+        assert!((world.bodies[body].position().rotation.angle() - 1.1).abs() < 1.0e-2);
+    }
 }
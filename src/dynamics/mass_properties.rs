@@ -402,4 +402,44 @@ mod test {
             epsilon = 1.0e-6
         );
     }
+
+    #[cfg(feature = "dim3")]
+    #[test]
+    fn convex_polyhedron_mass_properties() {
+        // The convex hull of the vertices of a box must have the same mass properties as the box.
+        use crate::math::{Point, Vector};
+
+        let half_extents = Vector::new(1.0, 2.0, 3.0);
+        let center = Vector::new(0.5, -1.0, 2.0);
+        let mut points = Vec::new();
+        for i in 0..8 {
+            let corner = Vector::new(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            points.push(Point::from(center + corner.component_mul(&half_extents)));
+        }
+
+        let convex = ColliderBuilder::convex_hull(&points)
+            .unwrap()
+            .density(2.0)
+            .build();
+        let cuboid = ColliderBuilder::cuboid(half_extents.x, half_extents.y, half_extents.z)
+            .translation(center.x, center.y, center.z)
+            .density(2.0)
+            .build();
+
+        let m1 = convex.mass_properties();
+        let m2 = cuboid
+            .mass_properties()
+            .transform_by(cuboid.position_wrt_parent());
+        assert_relative_eq!(m1.local_com, m2.local_com, epsilon = 1.0e-4);
+        assert_relative_eq!(m1.inv_mass, m2.inv_mass, epsilon = 1.0e-6);
+        assert_relative_eq!(
+            m1.reconstruct_inertia_matrix(),
+            m2.reconstruct_inertia_matrix(),
+            epsilon = 1.0e-3
+        );
+    }
 }
@@ -1,5 +1,3 @@
-#![allow(dead_code)] // TODO: remove this
-
 use crate::dynamics::MassProperties;
 use crate::math::Point;
 
@@ -0,0 +1,102 @@
+use crate::dynamics::MassProperties;
+use crate::math::{Point, Rotation, Vector};
+use na::{Matrix3, Point3};
+
+impl MassProperties {
+    pub(crate) fn from_convex_polyhedron(
+        density: f32,
+        vertices: &[Point<f32>],
+        indices: &[Point3<u32>],
+    ) -> MassProperties {
+        let (volume, com) = convex_mesh_volume_and_center_of_mass(vertices, indices);
+
+        if volume == 0.0 {
+            return MassProperties::new(com, 0.0, Vector::zeros());
+        }
+
+        // Sum the inertia tensors of the tetrahedra joining each triangle to the center of mass.
+        let mut itot = Matrix3::zeros();
+
+        for idx in indices {
+            let p1 = vertices[idx.x as usize] - com;
+            let p2 = vertices[idx.y as usize] - com;
+            let p3 = vertices[idx.z as usize] - com;
+            let vol = p1.dot(&p2.cross(&p3)) / 6.0;
+
+            itot += tetrahedron_inertia_tensor(vol, [p1, p2, p3]);
+        }
+
+        let eigen = (itot * density).symmetric_eigen();
+        let principal_inertia_local_frame =
+            Rotation::from_matrix_eps(&eigen.eigenvectors, 1.0e-6, 10, na::one());
+
+        Self::with_principal_inertia_frame(
+            com,
+            volume * density,
+            eigen.eigenvalues,
+            principal_inertia_local_frame,
+        )
+    }
+}
+
+/// The inertia tensor, with a unit density, of a tetrahedron with one vertex at the origin.
+///
+/// See "Explicit exact formulas for the 3-D tetrahedron inertia tensor in terms of its vertex
+/// coordinates", F. Tonon, 2004.
+fn tetrahedron_inertia_tensor(volume: f32, p: [Vector<f32>; 3]) -> Matrix3<f32> {
+    // The fourth vertex is the origin so its contributions vanish.
+    let diag = |i: usize| {
+        p[0][i] * p[0][i]
+            + p[1][i] * p[1][i]
+            + p[2][i] * p[2][i]
+            + p[0][i] * p[1][i]
+            + p[0][i] * p[2][i]
+            + p[1][i] * p[2][i]
+    };
+    let off_diag = |i: usize, j: usize| {
+        let sum_i = p[0][i] + p[1][i] + p[2][i];
+        let sum_j = p[0][j] + p[1][j] + p[2][j];
+        sum_i * sum_j + p[0][i] * p[0][j] + p[1][i] * p[1][j] + p[2][i] * p[2][j]
+    };
+
+    let (dx, dy, dz) = (diag(0), diag(1), diag(2));
+    let ixx = volume * (dy + dz) / 10.0;
+    let iyy = volume * (dx + dz) / 10.0;
+    let izz = volume * (dx + dy) / 10.0;
+    let ixy = -volume * off_diag(0, 1) / 20.0;
+    let ixz = -volume * off_diag(0, 2) / 20.0;
+    let iyz = -volume * off_diag(1, 2) / 20.0;
+
+    Matrix3::new(ixx, ixy, ixz, ixy, iyy, iyz, ixz, iyz, izz)
+}
+
+fn convex_mesh_volume_and_center_of_mass(
+    vertices: &[Point<f32>],
+    indices: &[Point3<u32>],
+) -> (f32, Point<f32>) {
+    let geometric_center = vertices
+        .iter()
+        .fold(Point::origin(), |e1, e2| e1 + e2.coords)
+        / vertices.len() as f32;
+    let mut res = Point::origin();
+    let mut vol = 0.0;
+
+    for idx in indices {
+        let p1 = vertices[idx.x as usize];
+        let p2 = vertices[idx.y as usize];
+        let p3 = vertices[idx.z as usize];
+        let tetra_vol = (p1 - geometric_center)
+            .dot(&(p2 - geometric_center).cross(&(p3 - geometric_center)))
+            / 6.0;
+        let tetra_center = (p1.coords + p2.coords + p3.coords + geometric_center.coords) / 4.0;
+
+        res += tetra_center * tetra_vol;
+        vol += tetra_vol;
+    }
+
+    if vol == 0.0 {
+        (vol, geometric_center)
+    } else {
+        (vol, res / vol)
+    }
+}
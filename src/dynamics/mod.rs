@@ -1,15 +1,15 @@
 //! Structures related to dynamics: bodies, joints, etc.
 
+pub(crate) use self::ccd_solver::CCDSolver;
+pub use self::coefficient_combine_rule::CoefficientCombineRule;
 pub use self::integration_parameters::IntegrationParameters;
 pub(crate) use self::joint::JointIndex;
-#[cfg(feature = "dim3")]
-pub use self::joint::RevoluteJoint;
 pub use self::joint::{
-    BallJoint, FixedJoint, Joint, JointHandle, JointParams, JointSet, PrismaticJoint,
+    BallJoint, FixedJoint, Joint, JointHandle, JointParams, JointSet, PrismaticJoint, RevoluteJoint,
 };
 pub use self::mass_properties::MassProperties;
-pub use self::rigid_body::{ActivationStatus, BodyStatus, RigidBody, RigidBodyBuilder};
-pub use self::rigid_body_set::{BodyPair, RigidBodyHandle, RigidBodyMut, RigidBodySet};
+pub use self::rigid_body::{ActivationStatus, BodyStatus, LockedAxes, RigidBody, RigidBodyBuilder};
+pub use self::rigid_body_set::{BodyPair, RigidBodyHandle, RigidBodyMut, RigidBodySet, SleepEvent};
 // #[cfg(not(feature = "parallel"))]
 pub(crate) use self::joint::JointGraphEdge;
 #[cfg(not(feature = "parallel"))]
@@ -17,6 +17,8 @@ pub(crate) use self::solver::IslandSolver;
 #[cfg(feature = "parallel")]
 pub(crate) use self::solver::ParallelIslandSolver;
 
+mod ccd_solver;
+mod coefficient_combine_rule;
 mod integration_parameters;
 mod joint;
 mod mass_properties;
@@ -28,6 +30,8 @@ mod mass_properties_cuboid;
 mod mass_properties_cylinder;
 #[cfg(feature = "dim2")]
 mod mass_properties_polygon;
+#[cfg(feature = "dim3")]
+mod mass_properties_polyhedron;
 mod rigid_body;
 mod rigid_body_set;
 mod solver;
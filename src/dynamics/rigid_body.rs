@@ -1,6 +1,6 @@
 use crate::dynamics::MassProperties;
 use crate::geometry::{Collider, ColliderHandle, InteractionGraph, RigidBodyGraphIndex};
-use crate::math::{AngVector, AngularInertia, Isometry, Point, Rotation, Translation, Vector};
+use crate::math::{AngVector, AngularInertia, Isometry, Point, Rotation, Translation, Vector, DIM};
 use crate::utils::{WCross, WDot};
 use num::Zero;
 
@@ -23,6 +23,75 @@ pub enum BodyStatus {
     // Disabled,
 }
 
+bitflags::bitflags! {
+    #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+    /// Flags restricting the translations and rotations of a dynamic rigid-body along the
+    /// world-space coordinate axes.
+    ///
+    /// In 2D, only the X and Y translations and the rotation around Z are meaningful.
+    pub struct LockedAxes: u8 {
+        /// The rigid-body cannot translate along the X axis.
+        const TRANSLATION_LOCKED_X = 1 << 0;
+        /// The rigid-body cannot translate along the Y axis.
+        const TRANSLATION_LOCKED_Y = 1 << 1;
+        /// The rigid-body cannot translate along the Z axis.
+        const TRANSLATION_LOCKED_Z = 1 << 2;
+        /// The rigid-body cannot translate at all.
+        const TRANSLATION_LOCKED = Self::TRANSLATION_LOCKED_X.bits
+            | Self::TRANSLATION_LOCKED_Y.bits
+            | Self::TRANSLATION_LOCKED_Z.bits;
+        /// The rigid-body cannot rotate around the X axis.
+        const ROTATION_LOCKED_X = 1 << 3;
+        /// The rigid-body cannot rotate around the Y axis.
+        const ROTATION_LOCKED_Y = 1 << 4;
+        /// The rigid-body cannot rotate around the Z axis.
+        const ROTATION_LOCKED_Z = 1 << 5;
+        /// The rigid-body cannot rotate at all.
+        const ROTATION_LOCKED = Self::ROTATION_LOCKED_X.bits
+            | Self::ROTATION_LOCKED_Y.bits
+            | Self::ROTATION_LOCKED_Z.bits;
+    }
+}
+
+impl LockedAxes {
+    fn translation_locked(self, i: usize) -> bool {
+        self.bits & (1 << i) != 0
+    }
+
+    // Zeroes the components of `v` along the locked translation axes.
+    fn project_linear(self, mut v: Vector<f32>) -> Vector<f32> {
+        for i in 0..DIM {
+            if self.translation_locked(i) {
+                v[i] = 0.0;
+            }
+        }
+
+        v
+    }
+
+    // Zeroes the components of `angvel` along the locked rotation axes.
+    #[cfg(feature = "dim2")]
+    fn project_angular(self, angvel: f32) -> f32 {
+        if self.contains(Self::ROTATION_LOCKED_Z) {
+            0.0
+        } else {
+            angvel
+        }
+    }
+
+    // Zeroes the components of `angvel` along the locked rotation axes.
+    #[cfg(feature = "dim3")]
+    fn project_angular(self, mut angvel: Vector<f32>) -> Vector<f32> {
+        for i in 0..3 {
+            if self.bits & (1 << (i + 3)) != 0 {
+                angvel[i] = 0.0;
+            }
+        }
+
+        angvel
+    }
+}
+
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 /// A rigid body.
 ///
@@ -37,7 +106,13 @@ pub struct RigidBody {
     /// The world-space center of mass of the rigid-body.
     pub world_com: Point<f32>,
     /// The square-root of the inverse angular inertia tensor of the rigid-body.
+    ///
+    /// Its rows and columns corresponding to the locked rotation axes are zero.
     pub world_inv_inertia_sqrt: AngularInertia<f32>,
+    /// The inverse mass of the rigid-body along each axis, as seen by the constraints solver.
+    ///
+    /// Its components corresponding to the locked translation axes are zero.
+    pub(crate) effective_inv_mass: Vector<f32>,
     /// The linear velocity of the rigid-body.
     pub(crate) linvel: Vector<f32>,
     /// The angular velocity of the rigid-body.
@@ -56,6 +131,8 @@ pub struct RigidBody {
     pub(crate) active_set_id: usize,
     pub(crate) active_set_offset: usize,
     pub(crate) active_set_timestamp: u32,
+    pub(crate) ccd_enabled: bool,
+    pub(crate) locked_axes: LockedAxes,
     /// The status of the body, governing how it is affected by external forces.
     pub body_status: BodyStatus,
     /// User-defined data associated to this rigid-body.
@@ -70,6 +147,7 @@ impl RigidBody {
             mass_properties: MassProperties::zero(),
             world_com: Point::origin(),
             world_inv_inertia_sqrt: AngularInertia::zero(),
+            effective_inv_mass: Vector::zeros(),
             linvel: Vector::zeros(),
             angvel: na::zero(),
             linacc: Vector::zeros(),
@@ -83,6 +161,8 @@ impl RigidBody {
             active_set_id: 0,
             active_set_offset: 0,
             active_set_timestamp: 0,
+            ccd_enabled: false,
+            locked_axes: LockedAxes::empty(),
             body_status: BodyStatus::Dynamic,
             user_data: 0,
         }
@@ -101,6 +181,7 @@ impl RigidBody {
         if self.mass_properties.inv_mass != 0.0 {
             self.linvel += (gravity + self.linacc) * dt;
             self.angvel += self.angacc * dt;
+            self.project_velocities_on_free_axes();
 
             // Reset the accelerations.
             self.linacc = na::zero();
@@ -108,6 +189,54 @@ impl RigidBody {
         }
     }
 
+    /// The axes along which the translations and rotations of this rigid-body are locked.
+    pub fn locked_axes(&self) -> LockedAxes {
+        self.locked_axes
+    }
+
+    /// Sets the axes along which the translations and rotations of this rigid-body are locked.
+    ///
+    /// The velocity components along the locked axes are set to zero.
+    pub fn set_locked_axes(&mut self, locked_axes: LockedAxes, wake_up: bool) {
+        self.locked_axes = locked_axes;
+        self.project_velocities_on_free_axes();
+        self.update_world_mass_properties();
+
+        if self.is_dynamic() && wake_up {
+            self.wake_up(true)
+        }
+    }
+
+    pub(crate) fn project_velocities_on_free_axes(&mut self) {
+        self.linvel = self.locked_axes.project_linear(self.linvel);
+        self.angvel = self.locked_axes.project_angular(self.angvel);
+    }
+
+    /// Sets the position computed by the position solver without moving the
+    /// center-of-mass along the locked translation axes.
+    pub(crate) fn set_solver_position(&mut self, mut pos: Isometry<f32>) {
+        if self.locked_axes.intersects(LockedAxes::TRANSLATION_LOCKED) {
+            let old_com = self.position * self.mass_properties.local_com;
+            let new_com = pos * self.mass_properties.local_com;
+            let shift = new_com - old_com;
+            pos.translation.vector -= shift - self.locked_axes.project_linear(shift);
+        }
+
+        self.set_position(pos, false)
+    }
+
+    /// Is continuous collision detection enabled for this rigid-body?
+    pub fn is_ccd_enabled(&self) -> bool {
+        self.ccd_enabled
+    }
+
+    /// Enables or disables continuous collision detection for this rigid-body.
+    ///
+    /// CCD prevents fast-moving dynamic rigid-bodies from tunnelling through thin obstacles.
+    pub fn enable_ccd(&mut self, enabled: bool) {
+        self.ccd_enabled = enabled;
+    }
+
     /// The handles of colliders attached to this rigid body.
     pub fn colliders(&self) -> &[ColliderHandle] {
         &self.colliders[..]
@@ -178,7 +307,6 @@ impl RigidBody {
     /// it is waken up. It can be woken manually with `self.wake_up` or automatically due to
     /// external forces like contacts.
     pub fn sleep(&mut self) {
-        self.activation.energy = 0.0;
         self.activation.sleeping = true;
         self.linvel = na::zero();
         self.angvel = na::zero();
@@ -189,18 +317,28 @@ impl RigidBody {
     /// If `strong` is `true` then it is assured that the rigid-body will
     /// remain awake during multiple subsequent timesteps.
     pub fn wake_up(&mut self, strong: bool) {
-        self.activation.sleeping = false;
-
-        if (strong || self.activation.energy == 0.0) && self.is_dynamic() {
-            self.activation.energy = self.activation.threshold.abs() * 2.0;
+        if (strong || self.activation.sleeping) && self.is_dynamic() {
+            self.activation.time_since_can_sleep = 0.0;
         }
+
+        self.activation.sleeping = false;
     }
 
-    pub(crate) fn update_energy(&mut self) {
-        let mix_factor = 0.01;
-        let new_energy = (1.0 - mix_factor) * self.activation.energy
-            + mix_factor * (self.linvel.norm_squared() + self.angvel.gdot(self.angvel));
-        self.activation.energy = new_energy.min(self.activation.threshold.abs() * 4.0);
+    /// Updates the time elapsed since the velocity of this body dropped below its
+    /// sleep thresholds.
+    pub(crate) fn update_sleep_timer(&mut self, dt: f32) {
+        let linear_threshold = self.activation.linear_threshold;
+        let angular_threshold = self.activation.angular_threshold;
+
+        if linear_threshold >= 0.0
+            && angular_threshold >= 0.0
+            && self.linvel.norm_squared() <= linear_threshold * linear_threshold
+            && self.angvel.gdot(self.angvel) <= angular_threshold * angular_threshold
+        {
+            self.activation.time_since_can_sleep += dt;
+        } else {
+            self.activation.time_since_can_sleep = 0.0;
+        }
     }
 
     /// Is this rigid body sleeping?
@@ -217,7 +355,7 @@ impl RigidBody {
         !self.linvel.is_zero() || !self.angvel.is_zero()
     }
 
-    fn integrate_velocity(&self, dt: f32) -> Isometry<f32> {
+    pub(crate) fn integrate_velocity(&self, dt: f32) -> Isometry<f32> {
         let com = &self.position * self.mass_properties.local_com;
         let shift = Translation::from(com.coords);
         shift * Isometry::new(self.linvel * dt, self.angvel * dt) * shift.inverse()
@@ -341,6 +479,37 @@ impl RigidBody {
         self.world_inv_inertia_sqrt = self
             .mass_properties
             .world_inv_inertia_sqrt(&self.position.rotation);
+
+        // The solver can't apply any linear impulse along a locked translation axis.
+        self.effective_inv_mass = self
+            .locked_axes
+            .project_linear(Vector::repeat(self.mass_properties.inv_mass));
+
+        // The solver can't apply any angular impulse along a locked rotation axis.
+        #[cfg(feature = "dim2")]
+        if self.locked_axes.contains(LockedAxes::ROTATION_LOCKED_Z) {
+            self.world_inv_inertia_sqrt = 0.0;
+        }
+        #[cfg(feature = "dim3")]
+        {
+            let ii = &mut self.world_inv_inertia_sqrt;
+
+            if self.locked_axes.contains(LockedAxes::ROTATION_LOCKED_X) {
+                ii.m11 = 0.0;
+                ii.m12 = 0.0;
+                ii.m13 = 0.0;
+            }
+            if self.locked_axes.contains(LockedAxes::ROTATION_LOCKED_Y) {
+                ii.m12 = 0.0;
+                ii.m22 = 0.0;
+                ii.m23 = 0.0;
+            }
+            if self.locked_axes.contains(LockedAxes::ROTATION_LOCKED_Z) {
+                ii.m13 = 0.0;
+                ii.m23 = 0.0;
+                ii.m33 = 0.0;
+            }
+        }
     }
 
     /*
@@ -455,6 +624,11 @@ pub struct RigidBodyBuilder {
     mass_properties: MassProperties,
     can_sleep: bool,
     sleeping: bool,
+    linear_sleep_threshold: f32,
+    angular_sleep_threshold: f32,
+    time_until_sleep: f32,
+    locked_axes: LockedAxes,
+    ccd_enabled: bool,
     user_data: u128,
 }
 
@@ -471,6 +645,11 @@ impl RigidBodyBuilder {
             mass_properties: MassProperties::zero(),
             can_sleep: true,
             sleeping: false,
+            linear_sleep_threshold: ActivationStatus::default_linear_threshold(),
+            angular_sleep_threshold: ActivationStatus::default_angular_threshold(),
+            time_until_sleep: ActivationStatus::default_time_until_sleep(),
+            locked_axes: LockedAxes::empty(),
+            ccd_enabled: false,
             user_data: 0,
         }
     }
@@ -593,6 +772,49 @@ impl RigidBodyBuilder {
         self
     }
 
+    /// Prevents the rigid-body to be created from rotating.
+    pub fn lock_rotations(mut self) -> Self {
+        self.locked_axes.insert(LockedAxes::ROTATION_LOCKED);
+        self
+    }
+
+    /// Only allows the rigid-body to be created to rotate around the world-space coordinate
+    /// axes set to `true`.
+    #[cfg(feature = "dim3")]
+    pub fn restrict_rotations(mut self, allow_x: bool, allow_y: bool, allow_z: bool) -> Self {
+        self.locked_axes
+            .set(LockedAxes::ROTATION_LOCKED_X, !allow_x);
+        self.locked_axes
+            .set(LockedAxes::ROTATION_LOCKED_Y, !allow_y);
+        self.locked_axes
+            .set(LockedAxes::ROTATION_LOCKED_Z, !allow_z);
+        self
+    }
+
+    /// Only allows the rigid-body to be created to translate along the world-space coordinate
+    /// axes set to `true`.
+    #[cfg(feature = "dim2")]
+    pub fn restrict_translations(mut self, allow_x: bool, allow_y: bool) -> Self {
+        self.locked_axes
+            .set(LockedAxes::TRANSLATION_LOCKED_X, !allow_x);
+        self.locked_axes
+            .set(LockedAxes::TRANSLATION_LOCKED_Y, !allow_y);
+        self
+    }
+
+    /// Only allows the rigid-body to be created to translate along the world-space coordinate
+    /// axes set to `true`.
+    #[cfg(feature = "dim3")]
+    pub fn restrict_translations(mut self, allow_x: bool, allow_y: bool, allow_z: bool) -> Self {
+        self.locked_axes
+            .set(LockedAxes::TRANSLATION_LOCKED_X, !allow_x);
+        self.locked_axes
+            .set(LockedAxes::TRANSLATION_LOCKED_Y, !allow_y);
+        self.locked_axes
+            .set(LockedAxes::TRANSLATION_LOCKED_Z, !allow_z);
+        self
+    }
+
     /// Sets whether or not the rigid-body to be created can sleep if it reaches a dynamic equilibrium.
     pub fn can_sleep(mut self, can_sleep: bool) -> Self {
         self.can_sleep = can_sleep;
@@ -605,6 +827,34 @@ impl RigidBodyBuilder {
         self
     }
 
+    /// Sets the linear velocity below which the rigid-body to be created can fall asleep.
+    pub fn linear_sleep_threshold(mut self, threshold: f32) -> Self {
+        self.linear_sleep_threshold = threshold;
+        self
+    }
+
+    /// Sets the angular velocity below which the rigid-body to be created can fall asleep.
+    pub fn angular_sleep_threshold(mut self, threshold: f32) -> Self {
+        self.angular_sleep_threshold = threshold;
+        self
+    }
+
+    /// Sets the time, in seconds, during which the velocity of the rigid-body to be created
+    /// must stay below its sleep thresholds before it falls asleep.
+    pub fn time_until_sleep(mut self, time: f32) -> Self {
+        self.time_until_sleep = time;
+        self
+    }
+
+    /// Enabled continuous collision-detection for this rigid-body.
+    ///
+    /// This only affects dynamic rigid-bodies and prevents them from tunnelling through
+    /// other colliders when they move fast.
+    pub fn ccd_enabled(mut self, enabled: bool) -> Self {
+        self.ccd_enabled = enabled;
+        self
+    }
+
     /// Build a new rigid-body with the parameters configured with this builder.
     pub fn build(&self) -> RigidBody {
         let mut rb = RigidBody::new();
@@ -617,13 +867,20 @@ impl RigidBodyBuilder {
         rb.mass_properties = self.mass_properties;
         rb.linear_damping = self.linear_damping;
         rb.angular_damping = self.angular_damping;
+        rb.ccd_enabled = self.ccd_enabled;
+        rb.set_locked_axes(self.locked_axes, false);
+        rb.activation.linear_threshold = self.linear_sleep_threshold;
+        rb.activation.angular_threshold = self.angular_sleep_threshold;
+        rb.activation.time_until_sleep = self.time_until_sleep;
 
         if self.can_sleep && self.sleeping {
             rb.sleep();
+            rb.activation.notified_sleeping = true;
         }
 
         if !self.can_sleep {
-            rb.activation.threshold = -1.0;
+            rb.activation.linear_threshold = -1.0;
+            rb.activation.angular_threshold = -1.0;
         }
 
         rb
@@ -633,45 +890,207 @@ impl RigidBodyBuilder {
 /// The activation status of a body.
 ///
 /// This controls whether a body is sleeping or not.
-/// If the threshold is negative, the body never sleeps.
+/// A body falls asleep once its linear and angular velocities stayed below their thresholds
+/// for `time_until_sleep` seconds. If one of the thresholds is negative, the body never sleeps.
 #[derive(Copy, Clone, Debug)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct ActivationStatus {
-    /// The threshold pseudo-kinetic energy bellow which the body can fall asleep.
-    pub threshold: f32,
-    /// The current pseudo-kinetic energy of the body.
-    pub energy: f32,
+    /// The linear velocity below which the body can fall asleep.
+    pub linear_threshold: f32,
+    /// The angular velocity below which the body can fall asleep.
+    pub angular_threshold: f32,
+    /// The time, in seconds, during which the velocities of the body must stay below
+    /// the thresholds before it falls asleep.
+    pub time_until_sleep: f32,
+    /// The time, in seconds, elapsed since the velocities of the body dropped below the thresholds.
+    pub time_since_can_sleep: f32,
     /// Is this body already sleeping?
     pub sleeping: bool,
+    // Whether the body was sleeping the last time a sleep event was emitted for it.
+    pub(crate) notified_sleeping: bool,
 }
 
 impl ActivationStatus {
-    /// The default amount of energy bellow which a body can be put to sleep by nphysics.
-    pub fn default_threshold() -> f32 {
-        0.01
+    /// The default linear velocity below which a body can be put to sleep.
+    pub fn default_linear_threshold() -> f32 {
+        0.1
+    }
+
+    /// The default angular velocity below which a body can be put to sleep.
+    pub fn default_angular_threshold() -> f32 {
+        0.1
+    }
+
+    /// The default time, in seconds, during which the velocities of a body must stay
+    /// below the thresholds before it is put to sleep.
+    pub fn default_time_until_sleep() -> f32 {
+        2.0
     }
 
-    /// Create a new activation status initialised with the default activation threshold and is active.
+    /// Create a new activation status initialised with the default activation thresholds and is active.
     pub fn new_active() -> Self {
         ActivationStatus {
-            threshold: Self::default_threshold(),
-            energy: Self::default_threshold() * 4.0,
+            linear_threshold: Self::default_linear_threshold(),
+            angular_threshold: Self::default_angular_threshold(),
+            time_until_sleep: Self::default_time_until_sleep(),
+            time_since_can_sleep: 0.0,
             sleeping: false,
+            notified_sleeping: false,
         }
     }
 
-    /// Create a new activation status initialised with the default activation threshold and is inactive.
+    /// Create a new activation status initialised with the default activation thresholds and is inactive.
     pub fn new_inactive() -> Self {
         ActivationStatus {
-            threshold: Self::default_threshold(),
-            energy: 0.0,
+            linear_threshold: Self::default_linear_threshold(),
+            angular_threshold: Self::default_angular_threshold(),
+            time_until_sleep: Self::default_time_until_sleep(),
+            time_since_can_sleep: Self::default_time_until_sleep(),
             sleeping: true,
+            notified_sleeping: true,
         }
     }
 
     /// Returns `true` if the body is not asleep.
     #[inline]
     pub fn is_active(&self) -> bool {
-        self.energy != 0.0
+        !self.sleeping
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dynamics::RigidBodyBuilder;
+    use crate::geometry::ColliderBuilder;
+    use crate::math::{Point, Vector};
+    use crate::pipeline::test_world::TestWorld;
+
+    #[test]
+    fn locked_axes() {
+        let mut world = TestWorld::with_gravity();
+        world.insert_ground(50.0);
+
+        // A box that can only fall vertically, without rotating.
+        #[cfg(feature = "dim2")]
+        let (rb, co) = (
+            RigidBodyBuilder::new_dynamic()
+                .translation(0.0, 3.0)
+                .lock_rotations()
+                .restrict_translations(false, true),
+            ColliderBuilder::cuboid(0.5, 0.5),
+        );
+        #[cfg(feature = "dim3")]
+        let (rb, co) = (
+            RigidBodyBuilder::new_dynamic()
+                .translation(0.0, 3.0, 0.0)
+                .lock_rotations()
+                .restrict_translations(false, true, false),
+            ColliderBuilder::cuboid(0.5, 0.5, 0.5),
+        );
+        let handle = world.insert(rb.build(), Some(co.build()));
+
+        // Push it sideways, off-center.
+        let mut impulse = Vector::repeat(5.0);
+        impulse.y = 0.0;
+        let point = world.bodies[handle].position() * Point::from(Vector::repeat(0.5));
+        world.bodies[handle].apply_impulse_at_point(impulse, point, true);
+
+        world.run(120);
+
+        let rb = &world.bodies[handle];
+        let mut translation = rb.position().translation.vector;
+        assert!((translation.y - 0.6).abs() < 0.05);
+        translation.y = 0.0;
+        assert!(translation.norm() < 1.0e-5);
+        assert!(rb.position().rotation.angle() < 1.0e-5);
+    }
+
+    #[test]
+    fn partially_locked_body_rests_on_slope() {
+        let slope_angle = std::f32::consts::FRAC_PI_4;
+        let mut world = TestWorld::with_gravity();
+
+        // A 45° slope, with its upper face at a distance of 0.1 from the origin.
+        #[cfg(feature = "dim2")]
+        let (rb, co) = (
+            RigidBodyBuilder::new_static().rotation(slope_angle),
+            ColliderBuilder::cuboid(50.0, 0.1),
+        );
+        #[cfg(feature = "dim3")]
+        let (rb, co) = (
+            RigidBodyBuilder::new_static().rotation(Vector::z() * slope_angle),
+            ColliderBuilder::cuboid(50.0, 0.1, 50.0),
+        );
+        world.insert(rb.build(), Some(co.build()));
+
+        // A ball that can't move along the X axis, so it has to stop on the
+        // slope instead of rolling down.
+        #[cfg(feature = "dim2")]
+        let rb = RigidBodyBuilder::new_dynamic()
+            .translation(0.0, 2.0)
+            .restrict_translations(false, true);
+        #[cfg(feature = "dim3")]
+        let rb = RigidBodyBuilder::new_dynamic()
+            .translation(0.0, 2.0, 0.0)
+            .restrict_translations(false, true, true);
+        let handle = world.insert(rb.build(), Some(ColliderBuilder::ball(0.5).build()));
+
+        world.run(200);
+
+        // The contact normal isn't aligned with the only free axis, so the solver
+        // must account for the locked axis to find the right impulses.
+        let rb = &world.bodies[handle];
+        let translation = rb.position().translation.vector;
+        let rest_height = (0.1 + 0.5) / slope_angle.cos();
+        assert_eq!(translation.x, 0.0);
+        assert!((translation.y - rest_height).abs() < 0.02);
+        assert!(rb.linvel().norm() < 1.0e-2);
+    }
+
+    #[test]
+    fn partially_locked_body_bounces_on_slope() {
+        let slope_angle = std::f32::consts::FRAC_PI_4;
+        let mut world = TestWorld::new();
+
+        #[cfg(feature = "dim2")]
+        let (rb, co) = (
+            RigidBodyBuilder::new_static().rotation(slope_angle),
+            ColliderBuilder::cuboid(50.0, 0.1),
+        );
+        #[cfg(feature = "dim3")]
+        let (rb, co) = (
+            RigidBodyBuilder::new_static().rotation(Vector::z() * slope_angle),
+            ColliderBuilder::cuboid(50.0, 0.1, 50.0),
+        );
+        world.insert(rb.build(), Some(co.friction(0.0).restitution(1.0).build()));
+
+        // A ball that can only move vertically, thrown at the slope.
+        #[cfg(feature = "dim2")]
+        let rb = RigidBodyBuilder::new_dynamic()
+            .translation(0.0, 2.0)
+            .linvel(0.0, -5.0)
+            .restrict_translations(false, true);
+        #[cfg(feature = "dim3")]
+        let rb = RigidBodyBuilder::new_dynamic()
+            .translation(0.0, 2.0, 0.0)
+            .linvel(0.0, -5.0, 0.0)
+            .restrict_translations(false, true, true);
+        let co = ColliderBuilder::ball(0.5).friction(0.0).restitution(1.0);
+        let handle = world.insert(rb.build(), Some(co.build()));
+
+        for _ in 0..60 {
+            world.step();
+
+            if world.bodies[handle].linvel().y > 0.0 {
+                break;
+            }
+        }
+
+        // A perfectly elastic and frictionless bounce sends the ball back up at
+        // the same speed, neither losing nor gaining energy.
+        let rb = &world.bodies[handle];
+        assert_eq!(rb.position().translation.x, 0.0);
+        assert!((rb.linvel().norm() - 5.0).abs() < 0.05);
+        assert!(rb.linvel().y > 4.95);
     }
 }
@@ -4,6 +4,8 @@ use rayon::prelude::*;
 use crate::data::arena::Arena;
 use crate::dynamics::{BodyStatus, Joint, JointSet, RigidBody};
 use crate::geometry::{ColliderHandle, ColliderSet, ContactPair, InteractionGraph};
+use crate::math::Isometry;
+use crate::pipeline::EventHandler;
 use crossbeam::channel::{Receiver, Sender};
 use std::ops::{Deref, DerefMut, Index, IndexMut};
 
@@ -11,6 +13,7 @@ use std::ops::{Deref, DerefMut, Index, IndexMut};
 pub struct RigidBodyMut<'a> {
     rb: &'a mut RigidBody,
     was_sleeping: bool,
+    position: Isometry<f32>,
     handle: RigidBodyHandle,
     sender: &'a Sender<RigidBodyHandle>,
 }
@@ -23,6 +26,7 @@ impl<'a> RigidBodyMut<'a> {
     ) -> Self {
         Self {
             was_sleeping: rb.is_sleeping(),
+            position: rb.position,
             handle,
             sender,
             rb,
@@ -45,7 +49,7 @@ impl<'a> DerefMut for RigidBodyMut<'a> {
 
 impl<'a> Drop for RigidBodyMut<'a> {
     fn drop(&mut self) {
-        if self.was_sleeping && !self.rb.is_sleeping() {
+        if (self.was_sleeping && !self.rb.is_sleeping()) || self.rb.position != self.position {
             self.sender.send(self.handle).unwrap();
         }
     }
@@ -64,6 +68,15 @@ pub struct BodyPair {
     pub body2: RigidBodyHandle,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// Event triggered when a dynamic rigid-body falls asleep or wakes up.
+pub enum SleepEvent {
+    /// The rigid-body fell asleep: it no longer moves until it wakes up.
+    FellAsleep(RigidBodyHandle),
+    /// The rigid-body woke up.
+    WokeUp(RigidBodyHandle),
+}
+
 impl BodyPair {
     pub(crate) fn new(body1: RigidBodyHandle, body2: RigidBodyHandle) -> Self {
         BodyPair { body1, body2 }
@@ -109,7 +122,7 @@ impl RigidBodySet {
             active_dynamic_set: Vec::new(),
             active_kinematic_set: Vec::new(),
             modified_inactive_set: Vec::new(),
-            active_islands: Vec::new(),
+            active_islands: vec![0],
             active_set_timestamp: 0,
             can_sleep: Vec::new(),
             stack: Vec::new(),
@@ -203,6 +216,13 @@ impl RigidBodySet {
             }
         }
 
+        // The island ranges no longer match the active dynamic set.
+        // They will be recomputed at the next timestep.
+        if self.active_dynamic_set.len() < *self.active_islands.last().unwrap() {
+            self.active_islands.clear();
+            self.active_islands.push(0);
+        }
+
         /*
          * Remove colliders attached to this rigid-body.
          */
@@ -218,14 +238,70 @@ impl RigidBodySet {
         Some(rb)
     }
 
-    pub(crate) fn num_islands(&self) -> usize {
+    /// The number of active islands computed during the last timestep.
+    ///
+    /// An island is a set of awake dynamic rigid-bodies interacting with each other through
+    /// contacts or joints. Small groups of interacting rigid-bodies are merged into the same island
+    /// until it contains at least `IntegrationParameters::min_island_size` rigid-bodies.
+    pub fn num_islands(&self) -> usize {
         self.active_islands.len() - 1
     }
 
+    /// The handles of the rigid-bodies of the `island_id`-th active island.
+    ///
+    /// Panics if `island_id >= self.num_islands()`.
+    pub fn active_island(&self, island_id: usize) -> &[RigidBodyHandle] {
+        &self.active_dynamic_set[self.active_island_range(island_id)]
+    }
+
+    /// Iterates through the rigid-bodies of each active island computed during the last timestep.
+    pub fn active_islands(&self) -> impl ExactSizeIterator<Item = &[RigidBodyHandle]> {
+        (0..self.num_islands()).map(move |island_id| self.active_island(island_id))
+    }
+
+    /// The index of the active island containing the given rigid-body.
+    ///
+    /// Returns `None` if the rigid-body does not belong to any island, i.e., if it is not
+    /// dynamic, if it is sleeping, or if it was woken up or added since the last timestep.
+    pub fn island_id(&self, handle: RigidBodyHandle) -> Option<usize> {
+        let rb = self.bodies.get(handle)?;
+
+        if rb.is_dynamic()
+            && !rb.is_sleeping()
+            && rb.active_set_timestamp == self.active_set_timestamp
+            && rb.active_set_id < *self.active_islands.last().unwrap()
+            && self.active_dynamic_set[rb.active_set_id] == handle
+        {
+            Some(rb.active_island_id)
+        } else {
+            None
+        }
+    }
+
+    /// Puts to sleep all the rigid-bodies of the `island_id`-th active island.
+    ///
+    /// Sleeping rigid-bodies don't belong to any island. Use `self.wake_up` on any of them to
+    /// wake up the whole group. Only that rigid-body is woken up immediately: the rest of the
+    /// group is woken up by the next timestep. Until then, the other rigid-bodies of the group
+    /// still report `is_sleeping()`, and `self.island_id` returns `None` for all of them.
+    ///
+    /// Panics if `island_id >= self.num_islands()`.
+    pub fn sleep_island(&mut self, island_id: usize) {
+        for i in self.active_island_range(island_id) {
+            let handle = self.active_dynamic_set[i];
+            self.bodies[handle].sleep();
+        }
+    }
+
     /// Forces the specified rigid-body to wake up if it is dynamic.
     ///
     /// If `strong` is `true` then it is assured that the rigid-body will
     /// remain awake during multiple subsequent timesteps.
+    ///
+    /// Waking up the sleeping rigid-bodies interacting with this one, directly or indirectly,
+    /// through contacts or joints, is deferred to the next timestep. Until then, they still
+    /// report `is_sleeping()`, and `self.island_id` returns `None` for this rigid-body and
+    /// for them.
     pub fn wake_up(&mut self, handle: RigidBodyHandle, strong: bool) {
         if let Some(rb) = self.bodies.get_mut(handle) {
             // TODO: what about kinematic bodies?
@@ -429,10 +505,6 @@ impl RigidBodySet {
         self.active_islands[island_id]..self.active_islands[island_id + 1]
     }
 
-    pub(crate) fn active_island(&self, island_id: usize) -> &[RigidBodyHandle] {
-        &self.active_dynamic_set[self.active_island_range(island_id)]
-    }
-
     pub(crate) fn maintain_active_set(&mut self) {
         for handle in self.activation_channel.1.try_iter() {
             if let Some(rb) = self.bodies.get_mut(handle) {
@@ -444,6 +516,25 @@ impl RigidBodySet {
                 {
                     rb.active_set_id = self.active_dynamic_set.len(); // This will handle the case where the activation_channel contains duplicates.
                     self.active_dynamic_set.push(handle);
+                } else if rb.is_static() || rb.is_sleeping() {
+                    // The body may have been moved by the user, but its colliders
+                    // won't be updated by the timestep since it is not active.
+                    self.modified_inactive_set.push(handle);
+                }
+            }
+        }
+    }
+
+    /// Updates the positions of the colliders attached to the modified inactive bodies,
+    /// and publishes their modification.
+    pub(crate) fn update_modified_inactive_colliders(&self, colliders: &mut ColliderSet) {
+        for handle in &self.modified_inactive_set {
+            if let Some(rb) = self.bodies.get(*handle) {
+                for collider_handle in &rb.colliders {
+                    let collider = &mut colliders[*collider_handle];
+                    collider.position = rb.position * collider.delta;
+                    collider.predicted_position = rb.predicted_position * collider.delta;
+                    colliders.modified_colliders.publish(*collider_handle);
                 }
             }
         }
@@ -451,17 +542,19 @@ impl RigidBodySet {
 
     pub(crate) fn update_active_set_with_contacts(
         &mut self,
+        dt: f32,
         colliders: &ColliderSet,
         contact_graph: &InteractionGraph<ContactPair>,
         joint_graph: &InteractionGraph<Joint>,
         min_island_size: usize,
+        events: &dyn EventHandler,
     ) {
         assert!(
             min_island_size > 0,
             "The minimum island size must be at least 1."
         );
 
-        // Update the energy of every rigid body and
+        // Update the sleep timer of every rigid body and
         // keep only those that may not sleep.
         //        let t = instant::now();
         self.active_set_timestamp += 1;
@@ -474,12 +567,12 @@ impl RigidBodySet {
         // debugging slightly nicer so we keep this rev.
         for h in self.active_dynamic_set.drain(..).rev() {
             let rb = &mut self.bodies[h];
-            rb.update_energy();
-            if rb.activation.energy <= rb.activation.threshold {
-                // Mark them as sleeping for now. This will
-                // be set to false during the graph traversal
-                // if it should not be put to sleep.
-                rb.activation.sleeping = true;
+            rb.update_sleep_timer(dt);
+            if rb.activation.sleeping // May happen if the body was put to sleep manually.
+                || rb.activation.time_since_can_sleep >= rb.activation.time_until_sleep
+            {
+                // These will be put to sleep unless they are
+                // reached by the graph traversal.
                 self.can_sleep.push(h);
             } else {
                 self.stack.push(h);
@@ -584,10 +677,25 @@ impl RigidBodySet {
         //        let t = instant::now();
         for h in &self.can_sleep {
             let b = &mut self.bodies[*h];
-            if b.activation.sleeping {
+            if b.active_set_timestamp != self.active_set_timestamp {
                 b.sleep();
             }
         }
+
+        // Emit the sleep events of the bodies that fell asleep or woke up.
+        for h in self.active_dynamic_set.iter().chain(&self.can_sleep) {
+            let activation = &mut self.bodies[*h].activation;
+
+            if activation.sleeping != activation.notified_sleeping {
+                activation.notified_sleeping = activation.sleeping;
+
+                if activation.sleeping {
+                    events.handle_sleep_event(SleepEvent::FellAsleep(*h));
+                } else {
+                    events.handle_sleep_event(SleepEvent::WokeUp(*h));
+                }
+            }
+        }
         //        println!("Activation: {}", instant::now() - t);
     }
 }
@@ -605,3 +713,174 @@ impl IndexMut<RigidBodyHandle> for RigidBodySet {
         &mut self.bodies[index]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::dynamics::{RigidBodyBuilder, SleepEvent};
+    use crate::geometry::{ColliderBuilder, ColliderShape};
+    use crate::math::{Translation, Vector};
+    use crate::pipeline::test_world::TestWorld;
+    use crate::pipeline::ChannelEventCollector;
+    use std::collections::HashMap;
+
+    #[test]
+    fn sleep_thresholds_and_events() {
+        let mut world = TestWorld::with_gravity();
+        let ground = world.insert(RigidBodyBuilder::new_static().build(), None);
+        #[cfg(feature = "dim2")]
+        let co = ColliderBuilder::cuboid(50.0, 0.5);
+        #[cfg(feature = "dim3")]
+        let co = ColliderBuilder::cuboid(50.0, 0.5, 50.0);
+        let _ = world.insert_collider(co.friction(0.0).build(), ground);
+
+        // Frictionless cuboids resting on the ground, some of them sliding slowly.
+        let mut add_cuboid = |i: usize, linvel: f32, builder: RigidBodyBuilder| {
+            let mut rb = builder
+                .position(Translation::from(Vector::x() * (i as f32 * 5.0) + Vector::y()).into())
+                .build();
+            rb.set_linvel(Vector::x() * linvel, true);
+            let co = ColliderBuilder::new(ColliderShape::cuboid(Vector::repeat(0.5)))
+                .friction(0.0)
+                .build();
+            world.insert(rb, Some(co))
+        };
+
+        let default = add_cuboid(0, 0.0, RigidBodyBuilder::new_dynamic());
+        let quick = add_cuboid(
+            1,
+            0.0,
+            RigidBodyBuilder::new_dynamic().time_until_sleep(0.5),
+        );
+        let insomniac = add_cuboid(2, 0.0, RigidBodyBuilder::new_dynamic().can_sleep(false));
+        let slow = add_cuboid(3, 0.5, RigidBodyBuilder::new_dynamic());
+        let slow_tolerant = add_cuboid(
+            4,
+            0.5,
+            RigidBodyBuilder::new_dynamic().linear_sleep_threshold(1.0),
+        );
+
+        let proximity_channel = crossbeam::channel::unbounded();
+        let contact_channel = crossbeam::channel::unbounded();
+        let (sleep_sender, sleep_receiver) = crossbeam::channel::unbounded();
+        let events = ChannelEventCollector::new(proximity_channel.0, contact_channel.0)
+            .with_sleep_event_sender(sleep_sender);
+        let mut fell_asleep = HashMap::new();
+
+        let step = |world: &mut TestWorld, fell_asleep: &mut HashMap<_, _>, i: usize| {
+            world.step_with(None, &events);
+
+            let mut woken_up = Vec::new();
+            while let Ok(event) = sleep_receiver.try_recv() {
+                match event {
+                    SleepEvent::FellAsleep(handle) => {
+                        assert!(world.bodies[handle].is_sleeping());
+                        assert!(fell_asleep.insert(handle, i).is_none());
+                    }
+                    SleepEvent::WokeUp(handle) => {
+                        assert!(!world.bodies[handle].is_sleeping());
+                        woken_up.push(handle);
+                    }
+                }
+            }
+            woken_up
+        };
+
+        for i in 1..=240 {
+            assert!(step(&mut world, &mut fell_asleep, i).is_empty());
+        }
+
+        // The bodies fall asleep once their velocities stayed below their
+        // thresholds during their time until sleep.
+        let dt = world.params.dt();
+        let time = |handle| fell_asleep[&handle] as f32 * dt;
+        assert!(time(quick) >= 0.5 && time(quick) < 1.0);
+        assert!(time(default) >= 2.0 && time(default) < 2.5);
+        assert!(time(slow_tolerant) >= 2.0 && time(slow_tolerant) < 2.5);
+        assert!(!fell_asleep.contains_key(&insomniac));
+        assert!(!fell_asleep.contains_key(&slow));
+        assert_eq!(fell_asleep.len(), 3);
+
+        // Waking up a body emits an event.
+        world
+            .bodies
+            .get_mut(quick)
+            .unwrap()
+            .apply_impulse(Vector::x(), true);
+        assert_eq!(step(&mut world, &mut fell_asleep, 241), vec![quick]);
+    }
+
+    #[test]
+    fn island_introspection_and_forced_sleep() {
+        let mut world = TestWorld::with_gravity();
+        // Don't merge small islands.
+        world.params.min_island_size = 1;
+        let ground = world.insert(RigidBodyBuilder::new_static().build(), None);
+        #[cfg(feature = "dim2")]
+        let co = ColliderBuilder::cuboid(50.0, 0.5).build();
+        #[cfg(feature = "dim3")]
+        let co = ColliderBuilder::cuboid(50.0, 0.5, 50.0).build();
+        let _ = world.insert_collider(co, ground);
+
+        // A stack of two cuboids, and a lone cuboid far away.
+        let mut add_cuboid = |x: f32, y: f32| {
+            let rb = RigidBodyBuilder::new_dynamic()
+                .position(Translation::from(Vector::x() * x + Vector::y() * y).into())
+                .build();
+            let co = ColliderBuilder::new(ColliderShape::cuboid(Vector::repeat(0.5))).build();
+            world.insert(rb, Some(co))
+        };
+        let bottom = add_cuboid(0.0, 1.0);
+        let top = add_cuboid(0.0, 2.0);
+        let lone = add_cuboid(10.0, 1.0);
+
+        world.run(10);
+
+        let bodies = &mut world.bodies;
+        assert_eq!(bodies.num_islands(), 2);
+        let stack_island = bodies.island_id(bottom).unwrap();
+        let lone_island = bodies.island_id(lone).unwrap();
+        assert_ne!(stack_island, lone_island);
+        assert_eq!(bodies.island_id(top), Some(stack_island));
+        assert_eq!(bodies.island_id(ground), None);
+
+        let mut stack = bodies.active_island(stack_island).to_vec();
+        stack.sort();
+        let mut expected = vec![bottom, top];
+        expected.sort();
+        assert_eq!(stack, expected);
+        assert_eq!(bodies.active_island(lone_island), &[lone][..]);
+        assert_eq!(
+            bodies
+                .active_islands()
+                .map(|island| island.len())
+                .sum::<usize>(),
+            3
+        );
+
+        // Force the stack asleep: it no longer belongs to any island.
+        bodies.sleep_island(stack_island);
+        assert!(bodies[bottom].is_sleeping() && bodies[top].is_sleeping());
+        world.step();
+        let bodies = &mut world.bodies;
+        assert!(bodies[bottom].is_sleeping() && bodies[top].is_sleeping());
+        assert!(!bodies[lone].is_sleeping());
+        assert_eq!(bodies.num_islands(), 1);
+        assert_eq!(bodies.island_id(top), None);
+        assert_eq!(bodies.island_id(lone), Some(0));
+
+        // Waking up one body of the stack wakes up the whole stack.
+        bodies.wake_up(bottom, true);
+        world.step();
+        let bodies = &mut world.bodies;
+        assert!(!bodies[bottom].is_sleeping() && !bodies[top].is_sleeping());
+        assert_eq!(bodies.num_islands(), 2);
+        assert_eq!(bodies.island_id(top), bodies.island_id(bottom));
+        assert!(bodies.island_id(top).is_some());
+
+        // Removing a body invalidates the islands until the next timestep.
+        bodies.remove(lone, &mut world.colliders, &mut world.joints);
+        assert_eq!(world.bodies.num_islands(), 0);
+        world.step();
+        assert_eq!(world.bodies.num_islands(), 1);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::dynamics::{BallJoint, IntegrationParameters};
+use crate::math::{AngularInertia, Isometry, Rotation, Vector};
+use crate::utils::WAngularInertia;
+
+/// The swing and twist limits of a ball joint.
+///
+/// If the attached bodies are flipped, the local axes are swapped and the twist limits are negated
+/// so that the limits can be evaluated as if the first body was the second one.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct BallLimits {
+    local_axis1: Vector<f32>,
+    local_axis2: Vector<f32>,
+    local_basis1: Vector<f32>,
+    local_basis2: Vector<f32>,
+    swing_limit: f32,
+    twist_limits: [f32; 2],
+}
+
+impl BallLimits {
+    pub fn from_params(cparams: &BallJoint, flipped: bool) -> Option<Self> {
+        if !cparams.limits_enabled {
+            return None;
+        }
+
+        if flipped {
+            Some(Self {
+                local_axis1: cparams.limits_local_axis2.into_inner(),
+                local_axis2: cparams.limits_local_axis1.into_inner(),
+                local_basis1: cparams.limits_basis2,
+                local_basis2: cparams.limits_basis1,
+                swing_limit: cparams.swing_limit,
+                // The twist measured from the second body is the opposite of the joint's twist.
+                twist_limits: [-cparams.twist_limits[1], -cparams.twist_limits[0]],
+            })
+        } else {
+            Some(Self {
+                local_axis1: cparams.limits_local_axis1.into_inner(),
+                local_axis2: cparams.limits_local_axis2.into_inner(),
+                local_basis1: cparams.limits_basis1,
+                local_basis2: cparams.limits_basis2,
+                swing_limit: cparams.swing_limit,
+                twist_limits: cparams.twist_limits,
+            })
+        }
+    }
+
+    /// The violations of the swing limit and of the twist limits, in that order.
+    ///
+    /// Each violation is given as the unit axis around which the second body has to rotate,
+    /// relative to the first body, to fix the violation, and the angle of this rotation.
+    pub fn errors(
+        &self,
+        position1: &Isometry<f32>,
+        position2: &Isometry<f32>,
+    ) -> [Option<(Vector<f32>, f32)>; 2] {
+        let axis1 = position1 * self.local_axis1;
+        let axis2 = position2 * self.local_axis2;
+        let mut result = [None, None];
+
+        let swing = axis1.angle(&axis2);
+        if swing > self.swing_limit {
+            // Rotating the second body around `axis2 x axis1` brings `axis2` closer to `axis1`.
+            if let Some(swing_axis) = axis2.cross(&axis1).try_normalize(1.0e-6) {
+                result[0] = Some((swing_axis, swing - self.swing_limit));
+            }
+        }
+
+        // Remove the swing before measuring the twist around `axis1`.
+        if let Some(unswing) = Rotation::rotation_between(&axis2, &axis1) {
+            let basis1 = position1 * self.local_basis1;
+            let basis2 = unswing * (position2 * self.local_basis2);
+            let twist = axis1.dot(&basis1.cross(&basis2)).atan2(basis1.dot(&basis2));
+
+            if twist < self.twist_limits[0] {
+                result[1] = Some((axis1, self.twist_limits[0] - twist));
+            } else if twist > self.twist_limits[1] {
+                result[1] = Some((-axis1, twist - self.twist_limits[1]));
+            }
+        }
+
+        result
+    }
+
+    pub fn solve(
+        &self,
+        params: &IntegrationParameters,
+        ii1: &AngularInertia<f32>,
+        ii2: &AngularInertia<f32>,
+        position1: &mut Isometry<f32>,
+        position2: &mut Isometry<f32>,
+    ) {
+        for (axis, err) in self.errors(position1, position2).iter().flatten() {
+            let ii1_axis = ii1.transform_vector(*axis);
+            let ii2_axis = ii2.transform_vector(*axis);
+            let inv_lhs = crate::utils::inv(axis.dot(&(ii1_axis + ii2_axis)));
+            let ang_impulse = err * params.joint_erp * inv_lhs;
+
+            position1.rotation = Rotation::new(ii1_axis * -ang_impulse) * position1.rotation;
+            position2.rotation = Rotation::new(ii2_axis * ang_impulse) * position2.rotation;
+        }
+    }
+
+    pub fn solve_ground(
+        &self,
+        params: &IntegrationParameters,
+        position1: &Isometry<f32>,
+        position2: &mut Isometry<f32>,
+    ) {
+        for (axis, err) in self.errors(position1, position2).iter().flatten() {
+            position2.rotation =
+                Rotation::new(axis * (err * params.joint_erp)) * position2.rotation;
+        }
+    }
+}
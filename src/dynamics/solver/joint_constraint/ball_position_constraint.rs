@@ -1,7 +1,9 @@
+#[cfg(feature = "dim3")]
+use super::BallLimits;
 use crate::dynamics::{BallJoint, IntegrationParameters, RigidBody};
 #[cfg(feature = "dim2")]
 use crate::math::SdpMatrix;
-use crate::math::{AngularInertia, Isometry, Point, Rotation};
+use crate::math::{AngularInertia, Isometry, Point, Rotation, Vector};
 use crate::utils::{WAngularInertia, WCross, WCrossMatrix};
 
 #[derive(Debug)]
@@ -12,14 +14,17 @@ pub(crate) struct BallPositionConstraint {
     local_com1: Point<f32>,
     local_com2: Point<f32>,
 
-    im1: f32,
-    im2: f32,
+    im1: Vector<f32>,
+    im2: Vector<f32>,
 
     ii1: AngularInertia<f32>,
     ii2: AngularInertia<f32>,
 
     local_anchor1: Point<f32>,
     local_anchor2: Point<f32>,
+
+    #[cfg(feature = "dim3")]
+    limits: Option<BallLimits>,
 }
 
 impl BallPositionConstraint {
@@ -27,12 +32,14 @@ impl BallPositionConstraint {
         Self {
             local_com1: rb1.mass_properties.local_com,
             local_com2: rb2.mass_properties.local_com,
-            im1: rb1.mass_properties.inv_mass,
-            im2: rb2.mass_properties.inv_mass,
+            im1: rb1.effective_inv_mass,
+            im2: rb2.effective_inv_mass,
             ii1: rb1.world_inv_inertia_sqrt.squared(),
             ii2: rb2.world_inv_inertia_sqrt.squared(),
             local_anchor1: cparams.local_anchor1,
             local_anchor2: cparams.local_anchor2,
+            #[cfg(feature = "dim3")]
+            limits: BallLimits::from_params(cparams, false),
             position1: rb1.active_set_offset,
             position2: rb2.active_set_offset,
         }
@@ -59,27 +66,31 @@ impl BallPositionConstraint {
         // NOTE: the -cmat1 is just a simpler way of doing cmat1.transpose()
         // because it is anti-symmetric.
         #[cfg(feature = "dim3")]
-        let lhs = self.ii1.quadform(&cmat1).add_diagonal(self.im1)
-            + self.ii2.quadform(&cmat2).add_diagonal(self.im2);
+        let lhs = self.ii1.quadform(&cmat1).add_diagonal_elements(self.im1)
+            + self.ii2.quadform(&cmat2).add_diagonal_elements(self.im2);
 
         // In 2D we just unroll the computation because
         // it's just easier that way. It is also
         // faster because in 2D lhs will be symmetric.
         #[cfg(feature = "dim2")]
         let lhs = {
-            let m11 =
-                self.im1 + self.im2 + cmat1.x * cmat1.x * self.ii1 + cmat2.x * cmat2.x * self.ii2;
+            let m11 = self.im1.x
+                + self.im2.x
+                + cmat1.x * cmat1.x * self.ii1
+                + cmat2.x * cmat2.x * self.ii2;
             let m12 = cmat1.x * cmat1.y * self.ii1 + cmat2.x * cmat2.y * self.ii2;
-            let m22 =
-                self.im1 + self.im2 + cmat1.y * cmat1.y * self.ii1 + cmat2.y * cmat2.y * self.ii2;
+            let m22 = self.im1.y
+                + self.im2.y
+                + cmat1.y * cmat1.y * self.ii1
+                + cmat2.y * cmat2.y * self.ii2;
             SdpMatrix::new(m11, m12, m22)
         };
 
         let inv_lhs = lhs.inverse_unchecked();
         let impulse = inv_lhs * -(err * params.joint_erp);
 
-        position1.translation.vector += self.im1 * impulse;
-        position2.translation.vector -= self.im2 * impulse;
+        position1.translation.vector += impulse.component_mul(&self.im1);
+        position2.translation.vector -= impulse.component_mul(&self.im2);
 
         let angle1 = self.ii1.transform_vector(centered_anchor1.gcross(impulse));
         let angle2 = self.ii2.transform_vector(centered_anchor2.gcross(-impulse));
@@ -87,6 +98,13 @@ impl BallPositionConstraint {
         position1.rotation = Rotation::new(angle1) * position1.rotation;
         position2.rotation = Rotation::new(angle2) * position2.rotation;
 
+        #[cfg(feature = "dim3")]
+        {
+            if let Some(limits) = &self.limits {
+                limits.solve(params, &self.ii1, &self.ii2, &mut position1, &mut position2);
+            }
+        }
+
         positions[self.position1 as usize] = position1;
         positions[self.position2 as usize] = position2;
     }
@@ -96,10 +114,14 @@ impl BallPositionConstraint {
 pub(crate) struct BallPositionGroundConstraint {
     position2: usize,
     anchor1: Point<f32>,
-    im2: f32,
+    im2: Vector<f32>,
     ii2: AngularInertia<f32>,
     local_anchor2: Point<f32>,
     local_com2: Point<f32>,
+    #[cfg(feature = "dim3")]
+    position1: Isometry<f32>,
+    #[cfg(feature = "dim3")]
+    limits: Option<BallLimits>,
 }
 
 impl BallPositionGroundConstraint {
@@ -115,20 +137,28 @@ impl BallPositionGroundConstraint {
             // already been flipped by the caller.
             Self {
                 anchor1: rb1.predicted_position * cparams.local_anchor2,
-                im2: rb2.mass_properties.inv_mass,
+                im2: rb2.effective_inv_mass,
                 ii2: rb2.world_inv_inertia_sqrt.squared(),
                 local_anchor2: cparams.local_anchor1,
                 position2: rb2.active_set_offset,
                 local_com2: rb2.mass_properties.local_com,
+                #[cfg(feature = "dim3")]
+                position1: rb1.predicted_position,
+                #[cfg(feature = "dim3")]
+                limits: BallLimits::from_params(cparams, true),
             }
         } else {
             Self {
                 anchor1: rb1.predicted_position * cparams.local_anchor1,
-                im2: rb2.mass_properties.inv_mass,
+                im2: rb2.effective_inv_mass,
                 ii2: rb2.world_inv_inertia_sqrt.squared(),
                 local_anchor2: cparams.local_anchor2,
                 position2: rb2.active_set_offset,
                 local_com2: rb2.mass_properties.local_com,
+                #[cfg(feature = "dim3")]
+                position1: rb1.predicted_position,
+                #[cfg(feature = "dim3")]
+                limits: BallLimits::from_params(cparams, false),
             }
         }
     }
@@ -144,22 +174,30 @@ impl BallPositionGroundConstraint {
         let cmat2 = centered_anchor2.gcross_matrix();
 
         #[cfg(feature = "dim3")]
-        let lhs = self.ii2.quadform(&cmat2).add_diagonal(self.im2);
+        let lhs = self.ii2.quadform(&cmat2).add_diagonal_elements(self.im2);
 
         #[cfg(feature = "dim2")]
         let lhs = {
-            let m11 = self.im2 + cmat2.x * cmat2.x * self.ii2;
+            let m11 = self.im2.x + cmat2.x * cmat2.x * self.ii2;
             let m12 = cmat2.x * cmat2.y * self.ii2;
-            let m22 = self.im2 + cmat2.y * cmat2.y * self.ii2;
+            let m22 = self.im2.y + cmat2.y * cmat2.y * self.ii2;
             SdpMatrix::new(m11, m12, m22)
         };
 
         let inv_lhs = lhs.inverse_unchecked();
         let impulse = inv_lhs * -(err * params.joint_erp);
-        position2.translation.vector -= self.im2 * impulse;
+        position2.translation.vector -= impulse.component_mul(&self.im2);
 
         let angle2 = self.ii2.transform_vector(centered_anchor2.gcross(-impulse));
         position2.rotation = Rotation::new(angle2) * position2.rotation;
+
+        #[cfg(feature = "dim3")]
+        {
+            if let Some(limits) = &self.limits {
+                limits.solve_ground(params, &self.position1, &mut position2);
+            }
+        }
+
         positions[self.position2 as usize] = position2;
     }
 }
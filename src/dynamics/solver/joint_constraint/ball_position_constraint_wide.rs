@@ -1,7 +1,9 @@
+#[cfg(feature = "dim3")]
+use super::BallLimits;
 use crate::dynamics::{BallJoint, IntegrationParameters, RigidBody};
 #[cfg(feature = "dim2")]
 use crate::math::SdpMatrix;
-use crate::math::{AngularInertia, Isometry, Point, Rotation, SimdFloat, SIMD_WIDTH};
+use crate::math::{AngularInertia, Isometry, Point, Rotation, SimdFloat, Vector, SIMD_WIDTH};
 use crate::utils::{WAngularInertia, WCross, WCrossMatrix};
 use simba::simd::SimdValue;
 
@@ -13,14 +15,23 @@ pub(crate) struct WBallPositionConstraint {
     local_com1: Point<SimdFloat>,
     local_com2: Point<SimdFloat>,
 
-    im1: SimdFloat,
-    im2: SimdFloat,
+    im1: Vector<SimdFloat>,
+    im2: Vector<SimdFloat>,
 
     ii1: AngularInertia<SimdFloat>,
     ii2: AngularInertia<SimdFloat>,
 
     local_anchor1: Point<SimdFloat>,
     local_anchor2: Point<SimdFloat>,
+
+    // The limits are enforced lane by lane, so we need the
+    // non-SIMD inertias of each body as well.
+    #[cfg(feature = "dim3")]
+    limits: [Option<BallLimits>; SIMD_WIDTH],
+    #[cfg(feature = "dim3")]
+    limits_ii1: [AngularInertia<f32>; SIMD_WIDTH],
+    #[cfg(feature = "dim3")]
+    limits_ii2: [AngularInertia<f32>; SIMD_WIDTH],
 }
 
 impl WBallPositionConstraint {
@@ -31,8 +42,8 @@ impl WBallPositionConstraint {
     ) -> Self {
         let local_com1 = Point::from(array![|ii| rbs1[ii].mass_properties.local_com; SIMD_WIDTH]);
         let local_com2 = Point::from(array![|ii| rbs2[ii].mass_properties.local_com; SIMD_WIDTH]);
-        let im1 = SimdFloat::from(array![|ii| rbs1[ii].mass_properties.inv_mass; SIMD_WIDTH]);
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im1 = Vector::from(array![|ii| rbs1[ii].effective_inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii1 = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs1[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         )
@@ -55,6 +66,12 @@ impl WBallPositionConstraint {
             ii2,
             local_anchor1,
             local_anchor2,
+            #[cfg(feature = "dim3")]
+            limits: array![|ii| BallLimits::from_params(cparams[ii], false); SIMD_WIDTH],
+            #[cfg(feature = "dim3")]
+            limits_ii1: array![|ii| rbs1[ii].world_inv_inertia_sqrt.squared(); SIMD_WIDTH],
+            #[cfg(feature = "dim3")]
+            limits_ii2: array![|ii| rbs2[ii].world_inv_inertia_sqrt.squared(); SIMD_WIDTH],
             position1,
             position2,
         }
@@ -81,26 +98,30 @@ impl WBallPositionConstraint {
         // NOTE: the -cmat1 is just a simpler way of doing cmat1.transpose()
         // because it is anti-symmetric.
         #[cfg(feature = "dim3")]
-        let lhs = self.ii1.quadform(&cmat1).add_diagonal(self.im1)
-            + self.ii2.quadform(&cmat2).add_diagonal(self.im2);
+        let lhs = self.ii1.quadform(&cmat1).add_diagonal_elements(self.im1)
+            + self.ii2.quadform(&cmat2).add_diagonal_elements(self.im2);
 
         // In 2D we just unroll the computation because
         // it's just easier that way.
         #[cfg(feature = "dim2")]
         let lhs = {
-            let m11 =
-                self.im1 + self.im2 + cmat1.x * cmat1.x * self.ii1 + cmat2.x * cmat2.x * self.ii2;
+            let m11 = self.im1.x
+                + self.im2.x
+                + cmat1.x * cmat1.x * self.ii1
+                + cmat2.x * cmat2.x * self.ii2;
             let m12 = cmat1.x * cmat1.y * self.ii1 + cmat2.x * cmat2.y * self.ii2;
-            let m22 =
-                self.im1 + self.im2 + cmat1.y * cmat1.y * self.ii1 + cmat2.y * cmat2.y * self.ii2;
+            let m22 = self.im1.y
+                + self.im2.y
+                + cmat1.y * cmat1.y * self.ii1
+                + cmat2.y * cmat2.y * self.ii2;
             SdpMatrix::new(m11, m12, m22)
         };
 
         let inv_lhs = lhs.inverse_unchecked();
         let impulse = inv_lhs * -(err * SimdFloat::splat(params.joint_erp));
 
-        position1.translation.vector += impulse * self.im1;
-        position2.translation.vector -= impulse * self.im2;
+        position1.translation.vector += impulse.component_mul(&self.im1);
+        position2.translation.vector -= impulse.component_mul(&self.im2);
 
         let angle1 = self.ii1.transform_vector(centered_anchor1.gcross(impulse));
         let angle2 = self.ii2.transform_vector(centered_anchor2.gcross(-impulse));
@@ -114,6 +135,23 @@ impl WBallPositionConstraint {
         for ii in 0..SIMD_WIDTH {
             positions[self.position2[ii]] = position2.extract(ii);
         }
+
+        #[cfg(feature = "dim3")]
+        for ii in 0..SIMD_WIDTH {
+            if let Some(limits) = &self.limits[ii] {
+                let mut position1 = positions[self.position1[ii]];
+                let mut position2 = positions[self.position2[ii]];
+                limits.solve(
+                    params,
+                    &self.limits_ii1[ii],
+                    &self.limits_ii2[ii],
+                    &mut position1,
+                    &mut position2,
+                );
+                positions[self.position1[ii]] = position1;
+                positions[self.position2[ii]] = position2;
+            }
+        }
     }
 }
 
@@ -121,10 +159,14 @@ impl WBallPositionConstraint {
 pub(crate) struct WBallPositionGroundConstraint {
     position2: [usize; SIMD_WIDTH],
     anchor1: Point<SimdFloat>,
-    im2: SimdFloat,
+    im2: Vector<SimdFloat>,
     ii2: AngularInertia<SimdFloat>,
     local_anchor2: Point<SimdFloat>,
     local_com2: Point<SimdFloat>,
+    #[cfg(feature = "dim3")]
+    limits: [Option<BallLimits>; SIMD_WIDTH],
+    #[cfg(feature = "dim3")]
+    limits_position1: [Isometry<f32>; SIMD_WIDTH],
 }
 
 impl WBallPositionGroundConstraint {
@@ -141,7 +183,7 @@ impl WBallPositionGroundConstraint {
             } else {
                 cparams[ii].local_anchor1
             }; SIMD_WIDTH]);
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii2 = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs2[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         )
@@ -161,6 +203,10 @@ impl WBallPositionGroundConstraint {
             local_anchor2,
             position2,
             local_com2,
+            #[cfg(feature = "dim3")]
+            limits: array![|ii| BallLimits::from_params(cparams[ii], flipped[ii]); SIMD_WIDTH],
+            #[cfg(feature = "dim3")]
+            limits_position1: array![|ii| rbs1[ii].predicted_position; SIMD_WIDTH],
         }
     }
 
@@ -175,19 +221,19 @@ impl WBallPositionGroundConstraint {
         let cmat2 = centered_anchor2.gcross_matrix();
 
         #[cfg(feature = "dim3")]
-        let lhs = self.ii2.quadform(&cmat2).add_diagonal(self.im2);
+        let lhs = self.ii2.quadform(&cmat2).add_diagonal_elements(self.im2);
 
         #[cfg(feature = "dim2")]
         let lhs = {
-            let m11 = self.im2 + cmat2.x * cmat2.x * self.ii2;
+            let m11 = self.im2.x + cmat2.x * cmat2.x * self.ii2;
             let m12 = cmat2.x * cmat2.y * self.ii2;
-            let m22 = self.im2 + cmat2.y * cmat2.y * self.ii2;
+            let m22 = self.im2.y + cmat2.y * cmat2.y * self.ii2;
             SdpMatrix::new(m11, m12, m22)
         };
 
         let inv_lhs = lhs.inverse_unchecked();
         let impulse = inv_lhs * -(err * SimdFloat::splat(params.joint_erp));
-        position2.translation.vector -= impulse * self.im2;
+        position2.translation.vector -= impulse.component_mul(&self.im2);
 
         let angle2 = self.ii2.transform_vector(centered_anchor2.gcross(-impulse));
         position2.rotation = Rotation::new(angle2) * position2.rotation;
@@ -195,5 +241,16 @@ impl WBallPositionGroundConstraint {
         for ii in 0..SIMD_WIDTH {
             positions[self.position2[ii]] = position2.extract(ii);
         }
+
+        #[cfg(feature = "dim3")]
+        for ii in 0..SIMD_WIDTH {
+            if let Some(limits) = &self.limits[ii] {
+                limits.solve_ground(
+                    params,
+                    &self.limits_position1[ii],
+                    &mut positions[self.position2[ii]],
+                );
+            }
+        }
     }
 }
@@ -1,7 +1,11 @@
+#[cfg(feature = "dim3")]
+use super::BallLimits;
 use crate::dynamics::solver::DeltaVel;
 use crate::dynamics::{
     BallJoint, IntegrationParameters, JointGraphEdge, JointIndex, JointParams, RigidBody,
 };
+#[cfg(feature = "dim3")]
+use crate::math::AngularInertia;
 use crate::math::{SdpMatrix, Vector};
 use crate::utils::{WAngularInertia, WCross, WCrossMatrix};
 
@@ -20,8 +24,23 @@ pub(crate) struct BallVelocityConstraint {
 
     inv_lhs: SdpMatrix<f32>,
 
-    im1: f32,
-    im2: f32,
+    im1: Vector<f32>,
+    im2: Vector<f32>,
+
+    // The swing limit and the twist limit, in that order.
+    #[cfg(feature = "dim3")]
+    limits_axis1: [Option<Vector<f32>>; 2],
+    #[cfg(feature = "dim3")]
+    limits_rhs: [f32; 2],
+    #[cfg(feature = "dim3")]
+    limits_inv_lhs: [f32; 2],
+    #[cfg(feature = "dim3")]
+    limits_impulse: [f32; 2],
+
+    #[cfg(feature = "dim3")]
+    ii1_sqrt: AngularInertia<f32>,
+    #[cfg(feature = "dim3")]
+    ii2_sqrt: AngularInertia<f32>,
 }
 
 impl BallVelocityConstraint {
@@ -37,8 +56,8 @@ impl BallVelocityConstraint {
 
         let vel1 = rb1.linvel + rb1.angvel.gcross(anchor1);
         let vel2 = rb2.linvel + rb2.angvel.gcross(anchor2);
-        let im1 = rb1.mass_properties.inv_mass;
-        let im2 = rb2.mass_properties.inv_mass;
+        let im1 = rb1.effective_inv_mass;
+        let im2 = rb2.effective_inv_mass;
 
         let rhs = -(vel1 - vel2);
         let lhs;
@@ -52,12 +71,12 @@ impl BallVelocityConstraint {
                 .world_inv_inertia_sqrt
                 .squared()
                 .quadform(&cmat2)
-                .add_diagonal(im2)
+                .add_diagonal_elements(im2)
                 + rb1
                     .world_inv_inertia_sqrt
                     .squared()
                     .quadform(&cmat1)
-                    .add_diagonal(im1);
+                    .add_diagonal_elements(im1);
         }
 
         // In 2D we just unroll the computation because
@@ -66,9 +85,9 @@ impl BallVelocityConstraint {
         {
             let ii1 = rb1.world_inv_inertia_sqrt.squared();
             let ii2 = rb2.world_inv_inertia_sqrt.squared();
-            let m11 = im1 + im2 + cmat1.x * cmat1.x * ii1 + cmat2.x * cmat2.x * ii2;
+            let m11 = im1.x + im2.x + cmat1.x * cmat1.x * ii1 + cmat2.x * cmat2.x * ii2;
             let m12 = cmat1.x * cmat1.y * ii1 + cmat2.x * cmat2.y * ii2;
-            let m22 = im1 + im2 + cmat1.y * cmat1.y * ii1 + cmat2.y * cmat2.y * ii2;
+            let m22 = im1.y + im2.y + cmat1.y * cmat1.y * ii1 + cmat2.y * cmat2.y * ii2;
             lhs = SdpMatrix::new(m11, m12, m22)
         }
 
@@ -77,6 +96,35 @@ impl BallVelocityConstraint {
 
         let inv_lhs = lhs.inverse_unchecked();
 
+        #[cfg(feature = "dim3")]
+        let mut limits_axis1 = [None; 2];
+        #[cfg(feature = "dim3")]
+        let mut limits_rhs = [0.0; 2];
+        #[cfg(feature = "dim3")]
+        let mut limits_inv_lhs = [0.0; 2];
+        #[cfg(feature = "dim3")]
+        let mut limits_impulse = [0.0; 2];
+
+        #[cfg(feature = "dim3")]
+        {
+            if let Some(limits) = BallLimits::from_params(cparams, false) {
+                let errors = limits.errors(&rb1.position, &rb2.position);
+                let impulses = [cparams.swing_impulse, cparams.twist_impulse];
+
+                for k in 0..2 {
+                    if let Some((axis, _)) = errors[k] {
+                        let ii1_axis = rb1.world_inv_inertia_sqrt.transform_vector(axis);
+                        let ii2_axis = rb2.world_inv_inertia_sqrt.transform_vector(axis);
+                        limits_axis1[k] = Some(axis);
+                        limits_rhs[k] = axis.dot(&(rb2.angvel - rb1.angvel));
+                        limits_inv_lhs[k] =
+                            crate::utils::inv(ii1_axis.norm_squared() + ii2_axis.norm_squared());
+                        limits_impulse[k] = impulses[k] * params.warmstart_coeff;
+                    }
+                }
+            }
+        }
+
         BallVelocityConstraint {
             joint_id,
             mj_lambda1: rb1.active_set_offset,
@@ -88,6 +136,18 @@ impl BallVelocityConstraint {
             gcross2,
             rhs,
             inv_lhs,
+            #[cfg(feature = "dim3")]
+            limits_axis1,
+            #[cfg(feature = "dim3")]
+            limits_rhs,
+            #[cfg(feature = "dim3")]
+            limits_inv_lhs,
+            #[cfg(feature = "dim3")]
+            limits_impulse,
+            #[cfg(feature = "dim3")]
+            ii1_sqrt: rb1.world_inv_inertia_sqrt,
+            #[cfg(feature = "dim3")]
+            ii2_sqrt: rb2.world_inv_inertia_sqrt,
         }
     }
 
@@ -95,11 +155,20 @@ impl BallVelocityConstraint {
         let mut mj_lambda1 = mj_lambdas[self.mj_lambda1 as usize];
         let mut mj_lambda2 = mj_lambdas[self.mj_lambda2 as usize];
 
-        mj_lambda1.linear += self.im1 * self.impulse;
+        mj_lambda1.linear += self.impulse.component_mul(&self.im1);
         mj_lambda1.angular += self.gcross1.gcross(self.impulse);
-        mj_lambda2.linear -= self.im2 * self.impulse;
+        mj_lambda2.linear -= self.impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self.gcross2.gcross(self.impulse);
 
+        #[cfg(feature = "dim3")]
+        for k in 0..2 {
+            if let Some(limits_axis1) = self.limits_axis1[k] {
+                let ang_impulse = limits_axis1 * self.limits_impulse[k];
+                mj_lambda1.angular -= self.ii1_sqrt.transform_vector(ang_impulse);
+                mj_lambda2.angular += self.ii2_sqrt.transform_vector(ang_impulse);
+            }
+        }
+
         mj_lambdas[self.mj_lambda1 as usize] = mj_lambda1;
         mj_lambdas[self.mj_lambda2 as usize] = mj_lambda2;
     }
@@ -115,12 +184,33 @@ impl BallVelocityConstraint {
         let impulse = self.inv_lhs * dvel;
         self.impulse += impulse;
 
-        mj_lambda1.linear += self.im1 * impulse;
+        mj_lambda1.linear += impulse.component_mul(&self.im1);
         mj_lambda1.angular += self.gcross1.gcross(impulse);
 
-        mj_lambda2.linear -= self.im2 * impulse;
+        mj_lambda2.linear -= impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self.gcross2.gcross(impulse);
 
+        /*
+         * Swing and twist limits.
+         */
+        #[cfg(feature = "dim3")]
+        for k in 0..2 {
+            if let Some(limits_axis1) = self.limits_axis1[k] {
+                let ang_vel1 = self.ii1_sqrt.transform_vector(mj_lambda1.angular);
+                let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+                let ang_dvel = limits_axis1.dot(&(ang_vel2 - ang_vel1)) + self.limits_rhs[k];
+                let new_impulse =
+                    (self.limits_impulse[k] - ang_dvel * self.limits_inv_lhs[k]).max(0.0);
+                let dimpulse = new_impulse - self.limits_impulse[k];
+                self.limits_impulse[k] = new_impulse;
+
+                let ang_impulse = limits_axis1 * dimpulse;
+                mj_lambda1.angular -= self.ii1_sqrt.transform_vector(ang_impulse);
+                mj_lambda2.angular += self.ii2_sqrt.transform_vector(ang_impulse);
+            }
+        }
+
         mj_lambdas[self.mj_lambda1 as usize] = mj_lambda1;
         mj_lambdas[self.mj_lambda2 as usize] = mj_lambda2;
     }
@@ -128,7 +218,12 @@ impl BallVelocityConstraint {
     pub fn writeback_impulses(&self, joints_all: &mut [JointGraphEdge]) {
         let joint = &mut joints_all[self.joint_id].weight;
         if let JointParams::BallJoint(ball) = &mut joint.params {
-            ball.impulse = self.impulse
+            ball.impulse = self.impulse;
+            #[cfg(feature = "dim3")]
+            {
+                ball.swing_impulse = self.limits_impulse[0];
+                ball.twist_impulse = self.limits_impulse[1];
+            }
         }
     }
 }
@@ -141,7 +236,17 @@ pub(crate) struct BallVelocityGroundConstraint {
     impulse: Vector<f32>,
     gcross2: Vector<f32>,
     inv_lhs: SdpMatrix<f32>,
-    im2: f32,
+    im2: Vector<f32>,
+    #[cfg(feature = "dim3")]
+    limits_axis2: [Option<Vector<f32>>; 2],
+    #[cfg(feature = "dim3")]
+    limits_rhs: [f32; 2],
+    #[cfg(feature = "dim3")]
+    limits_inv_lhs: [f32; 2],
+    #[cfg(feature = "dim3")]
+    limits_impulse: [f32; 2],
+    #[cfg(feature = "dim3")]
+    ii2_sqrt: AngularInertia<f32>,
 }
 
 impl BallVelocityGroundConstraint {
@@ -165,7 +270,7 @@ impl BallVelocityGroundConstraint {
             )
         };
 
-        let im2 = rb2.mass_properties.inv_mass;
+        let im2 = rb2.effective_inv_mass;
         let vel1 = rb1.linvel + rb1.angvel.gcross(anchor1);
         let vel2 = rb2.linvel + rb2.angvel.gcross(anchor2);
         let rhs = vel2 - vel1;
@@ -181,20 +286,48 @@ impl BallVelocityGroundConstraint {
                 .world_inv_inertia_sqrt
                 .squared()
                 .quadform(&cmat2)
-                .add_diagonal(im2);
+                .add_diagonal_elements(im2);
         }
 
         #[cfg(feature = "dim2")]
         {
             let ii2 = rb2.world_inv_inertia_sqrt.squared();
-            let m11 = im2 + cmat2.x * cmat2.x * ii2;
+            let m11 = im2.x + cmat2.x * cmat2.x * ii2;
             let m12 = cmat2.x * cmat2.y * ii2;
-            let m22 = im2 + cmat2.y * cmat2.y * ii2;
+            let m22 = im2.y + cmat2.y * cmat2.y * ii2;
             lhs = SdpMatrix::new(m11, m12, m22)
         }
 
         let inv_lhs = lhs.inverse_unchecked();
 
+        #[cfg(feature = "dim3")]
+        let mut limits_axis2 = [None; 2];
+        #[cfg(feature = "dim3")]
+        let mut limits_rhs = [0.0; 2];
+        #[cfg(feature = "dim3")]
+        let mut limits_inv_lhs = [0.0; 2];
+        #[cfg(feature = "dim3")]
+        let mut limits_impulse = [0.0; 2];
+
+        #[cfg(feature = "dim3")]
+        {
+            // The limits are flipped too, so the errors are expressed relative to `rb1`.
+            if let Some(limits) = BallLimits::from_params(cparams, flipped) {
+                let errors = limits.errors(&rb1.position, &rb2.position);
+                let impulses = [cparams.swing_impulse, cparams.twist_impulse];
+
+                for k in 0..2 {
+                    if let Some((axis, _)) = errors[k] {
+                        let ii2_axis = rb2.world_inv_inertia_sqrt.transform_vector(axis);
+                        limits_axis2[k] = Some(axis);
+                        limits_rhs[k] = axis.dot(&(rb2.angvel - rb1.angvel));
+                        limits_inv_lhs[k] = crate::utils::inv(ii2_axis.norm_squared());
+                        limits_impulse[k] = impulses[k] * params.warmstart_coeff;
+                    }
+                }
+            }
+        }
+
         BallVelocityGroundConstraint {
             joint_id,
             mj_lambda2: rb2.active_set_offset,
@@ -203,13 +336,33 @@ impl BallVelocityGroundConstraint {
             gcross2,
             rhs,
             inv_lhs,
+            #[cfg(feature = "dim3")]
+            limits_axis2,
+            #[cfg(feature = "dim3")]
+            limits_rhs,
+            #[cfg(feature = "dim3")]
+            limits_inv_lhs,
+            #[cfg(feature = "dim3")]
+            limits_impulse,
+            #[cfg(feature = "dim3")]
+            ii2_sqrt: rb2.world_inv_inertia_sqrt,
         }
     }
 
     pub fn warmstart(&self, mj_lambdas: &mut [DeltaVel<f32>]) {
         let mut mj_lambda2 = mj_lambdas[self.mj_lambda2 as usize];
-        mj_lambda2.linear -= self.im2 * self.impulse;
+        mj_lambda2.linear -= self.impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self.gcross2.gcross(self.impulse);
+
+        #[cfg(feature = "dim3")]
+        for k in 0..2 {
+            if let Some(limits_axis2) = self.limits_axis2[k] {
+                mj_lambda2.angular += self
+                    .ii2_sqrt
+                    .transform_vector(limits_axis2 * self.limits_impulse[k]);
+            }
+        }
+
         mj_lambdas[self.mj_lambda2 as usize] = mj_lambda2;
     }
 
@@ -222,9 +375,27 @@ impl BallVelocityGroundConstraint {
         let impulse = self.inv_lhs * dvel;
         self.impulse += impulse;
 
-        mj_lambda2.linear -= self.im2 * impulse;
+        mj_lambda2.linear -= impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self.gcross2.gcross(impulse);
 
+        /*
+         * Swing and twist limits.
+         */
+        #[cfg(feature = "dim3")]
+        for k in 0..2 {
+            if let Some(limits_axis2) = self.limits_axis2[k] {
+                let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+                let ang_dvel = limits_axis2.dot(&ang_vel2) + self.limits_rhs[k];
+                let new_impulse =
+                    (self.limits_impulse[k] - ang_dvel * self.limits_inv_lhs[k]).max(0.0);
+                let dimpulse = new_impulse - self.limits_impulse[k];
+                self.limits_impulse[k] = new_impulse;
+
+                mj_lambda2.angular += self.ii2_sqrt.transform_vector(limits_axis2 * dimpulse);
+            }
+        }
+
         mj_lambdas[self.mj_lambda2 as usize] = mj_lambda2;
     }
 
@@ -232,7 +403,12 @@ impl BallVelocityGroundConstraint {
     pub fn writeback_impulses(&self, joints_all: &mut [JointGraphEdge]) {
         let joint = &mut joints_all[self.joint_id].weight;
         if let JointParams::BallJoint(ball) = &mut joint.params {
-            ball.impulse = self.impulse
+            ball.impulse = self.impulse;
+            #[cfg(feature = "dim3")]
+            {
+                ball.swing_impulse = self.limits_impulse[0];
+                ball.twist_impulse = self.limits_impulse[1];
+            }
         }
     }
 }
@@ -1,3 +1,5 @@
+#[cfg(feature = "dim3")]
+use super::BallLimits;
 use crate::dynamics::solver::DeltaVel;
 use crate::dynamics::{
     BallJoint, IntegrationParameters, JointGraphEdge, JointIndex, JointParams, RigidBody,
@@ -7,6 +9,11 @@ use crate::math::{
 };
 use crate::utils::{WAngularInertia, WCross, WCrossMatrix};
 use simba::simd::SimdValue;
+#[cfg(feature = "dim3")]
+use {
+    crate::math::SimdBool,
+    simba::simd::{SimdBool as _, SimdPartialOrd},
+};
 
 #[derive(Debug)]
 pub(crate) struct WBallVelocityConstraint {
@@ -23,8 +30,23 @@ pub(crate) struct WBallVelocityConstraint {
 
     inv_lhs: SdpMatrix<SimdFloat>,
 
-    im1: SimdFloat,
-    im2: SimdFloat,
+    im1: Vector<SimdFloat>,
+    im2: Vector<SimdFloat>,
+
+    // The swing limit and the twist limit, in that order.
+    #[cfg(feature = "dim3")]
+    limits_axis1: [Option<Vector<SimdFloat>>; 2],
+    #[cfg(feature = "dim3")]
+    limits_rhs: [SimdFloat; 2],
+    #[cfg(feature = "dim3")]
+    limits_inv_lhs: [SimdFloat; 2],
+    #[cfg(feature = "dim3")]
+    limits_impulse: [SimdFloat; 2],
+
+    #[cfg(feature = "dim3")]
+    ii1_sqrt: AngularInertia<SimdFloat>,
+    #[cfg(feature = "dim3")]
+    ii2_sqrt: AngularInertia<SimdFloat>,
 }
 
 impl WBallVelocityConstraint {
@@ -39,7 +61,7 @@ impl WBallVelocityConstraint {
         let linvel1 = Vector::from(array![|ii| rbs1[ii].linvel; SIMD_WIDTH]);
         let angvel1 = AngVector::<SimdFloat>::from(array![|ii| rbs1[ii].angvel; SIMD_WIDTH]);
         let world_com1 = Point::from(array![|ii| rbs1[ii].world_com; SIMD_WIDTH]);
-        let im1 = SimdFloat::from(array![|ii| rbs1[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im1 = Vector::from(array![|ii| rbs1[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii1_sqrt = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs1[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         );
@@ -49,7 +71,7 @@ impl WBallVelocityConstraint {
         let linvel2 = Vector::from(array![|ii| rbs2[ii].linvel; SIMD_WIDTH]);
         let angvel2 = AngVector::<SimdFloat>::from(array![|ii| rbs2[ii].angvel; SIMD_WIDTH]);
         let world_com2 = Point::from(array![|ii| rbs2[ii].world_com; SIMD_WIDTH]);
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii2_sqrt = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs2[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         );
@@ -72,8 +94,14 @@ impl WBallVelocityConstraint {
 
         #[cfg(feature = "dim3")]
         {
-            lhs = ii2_sqrt.squared().quadform(&cmat2).add_diagonal(im2)
-                + ii1_sqrt.squared().quadform(&cmat1).add_diagonal(im1);
+            lhs = ii2_sqrt
+                .squared()
+                .quadform(&cmat2)
+                .add_diagonal_elements(im2)
+                + ii1_sqrt
+                    .squared()
+                    .quadform(&cmat1)
+                    .add_diagonal_elements(im1);
         }
 
         // In 2D we just unroll the computation because
@@ -82,9 +110,9 @@ impl WBallVelocityConstraint {
         {
             let ii1 = ii1_sqrt.squared();
             let ii2 = ii2_sqrt.squared();
-            let m11 = im1 + im2 + cmat1.x * cmat1.x * ii1 + cmat2.x * cmat2.x * ii2;
+            let m11 = im1.x + im2.x + cmat1.x * cmat1.x * ii1 + cmat2.x * cmat2.x * ii2;
             let m12 = cmat1.x * cmat1.y * ii1 + cmat2.x * cmat2.y * ii2;
-            let m22 = im1 + im2 + cmat1.y * cmat1.y * ii1 + cmat2.y * cmat2.y * ii2;
+            let m22 = im1.y + im2.y + cmat1.y * cmat1.y * ii1 + cmat2.y * cmat2.y * ii2;
             lhs = SdpMatrix::new(m11, m12, m22)
         }
 
@@ -93,6 +121,49 @@ impl WBallVelocityConstraint {
 
         let inv_lhs = lhs.inverse_unchecked();
 
+        #[cfg(feature = "dim3")]
+        let mut limits_axis1 = [None; 2];
+        #[cfg(feature = "dim3")]
+        let mut limits_rhs = [na::zero(); 2];
+        #[cfg(feature = "dim3")]
+        let mut limits_inv_lhs = [na::zero(); 2];
+        #[cfg(feature = "dim3")]
+        let mut limits_impulse = [na::zero(); 2];
+
+        #[cfg(feature = "dim3")]
+        {
+            let limits = array![|ii| BallLimits::from_params(cparams[ii], false); SIMD_WIDTH];
+
+            if limits.iter().any(|l| l.is_some()) {
+                let _0: SimdFloat = na::zero();
+                let _1: SimdFloat = na::one();
+                let errors = array![|ii| limits[ii].map(|l| l.errors(&rbs1[ii].position, &rbs2[ii].position)).unwrap_or([None, None]); SIMD_WIDTH];
+                let impulses = [
+                    SimdFloat::from(array![|ii| cparams[ii].swing_impulse; SIMD_WIDTH]),
+                    SimdFloat::from(array![|ii| cparams[ii].twist_impulse; SIMD_WIDTH]),
+                ];
+
+                for k in 0..2 {
+                    let active = SimdBool::from(array![|ii| errors[ii][k].is_some(); SIMD_WIDTH]);
+
+                    if active.any() {
+                        let axis = Vector::from(
+                            array![|ii| errors[ii][k].map(|e| e.0).unwrap_or(Vector::zeros()); SIMD_WIDTH],
+                        );
+                        let ii1_axis = ii1_sqrt.transform_vector(axis);
+                        let ii2_axis = ii2_sqrt.transform_vector(axis);
+                        let eff_inv = ii1_axis.norm_squared() + ii2_axis.norm_squared();
+
+                        limits_axis1[k] = Some(axis);
+                        limits_rhs[k] = axis.dot(&(angvel2 - angvel1));
+                        limits_inv_lhs[k] = (_1 / eff_inv).select(active, _0);
+                        limits_impulse[k] = impulses[k].select(active, _0)
+                            * SimdFloat::splat(params.warmstart_coeff);
+                    }
+                }
+            }
+        }
+
         WBallVelocityConstraint {
             joint_id,
             mj_lambda1,
@@ -104,6 +175,18 @@ impl WBallVelocityConstraint {
             gcross2,
             rhs,
             inv_lhs,
+            #[cfg(feature = "dim3")]
+            limits_axis1,
+            #[cfg(feature = "dim3")]
+            limits_rhs,
+            #[cfg(feature = "dim3")]
+            limits_inv_lhs,
+            #[cfg(feature = "dim3")]
+            limits_impulse,
+            #[cfg(feature = "dim3")]
+            ii1_sqrt,
+            #[cfg(feature = "dim3")]
+            ii2_sqrt,
         }
     }
 
@@ -125,11 +208,20 @@ impl WBallVelocityConstraint {
             ),
         };
 
-        mj_lambda1.linear += self.impulse * self.im1;
+        mj_lambda1.linear += self.impulse.component_mul(&self.im1);
         mj_lambda1.angular += self.gcross1.gcross(self.impulse);
-        mj_lambda2.linear -= self.impulse * self.im2;
+        mj_lambda2.linear -= self.impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self.gcross2.gcross(self.impulse);
 
+        #[cfg(feature = "dim3")]
+        for k in 0..2 {
+            if let Some(limits_axis1) = self.limits_axis1[k] {
+                let ang_impulse = limits_axis1 * self.limits_impulse[k];
+                mj_lambda1.angular -= self.ii1_sqrt.transform_vector(ang_impulse);
+                mj_lambda2.angular += self.ii2_sqrt.transform_vector(ang_impulse);
+            }
+        }
+
         for ii in 0..SIMD_WIDTH {
             mj_lambdas[self.mj_lambda1[ii] as usize].linear = mj_lambda1.linear.extract(ii);
             mj_lambdas[self.mj_lambda1[ii] as usize].angular = mj_lambda1.angular.extract(ii);
@@ -165,12 +257,33 @@ impl WBallVelocityConstraint {
         let impulse = self.inv_lhs * dvel;
         self.impulse += impulse;
 
-        mj_lambda1.linear += impulse * self.im1;
+        mj_lambda1.linear += impulse.component_mul(&self.im1);
         mj_lambda1.angular += self.gcross1.gcross(impulse);
 
-        mj_lambda2.linear -= impulse * self.im2;
+        mj_lambda2.linear -= impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self.gcross2.gcross(impulse);
 
+        /*
+         * Swing and twist limits.
+         */
+        #[cfg(feature = "dim3")]
+        for k in 0..2 {
+            if let Some(limits_axis1) = self.limits_axis1[k] {
+                let ang_vel1 = self.ii1_sqrt.transform_vector(mj_lambda1.angular);
+                let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+                let ang_dvel = limits_axis1.dot(&(ang_vel2 - ang_vel1)) + self.limits_rhs[k];
+                let new_impulse = (self.limits_impulse[k] - ang_dvel * self.limits_inv_lhs[k])
+                    .simd_max(na::zero());
+                let dimpulse = new_impulse - self.limits_impulse[k];
+                self.limits_impulse[k] = new_impulse;
+
+                let ang_impulse = limits_axis1 * dimpulse;
+                mj_lambda1.angular -= self.ii1_sqrt.transform_vector(ang_impulse);
+                mj_lambda2.angular += self.ii2_sqrt.transform_vector(ang_impulse);
+            }
+        }
+
         for ii in 0..SIMD_WIDTH {
             mj_lambdas[self.mj_lambda1[ii] as usize].linear = mj_lambda1.linear.extract(ii);
             mj_lambdas[self.mj_lambda1[ii] as usize].angular = mj_lambda1.angular.extract(ii);
@@ -185,7 +298,12 @@ impl WBallVelocityConstraint {
         for ii in 0..SIMD_WIDTH {
             let joint = &mut joints_all[self.joint_id[ii]].weight;
             if let JointParams::BallJoint(ball) = &mut joint.params {
-                ball.impulse = self.impulse.extract(ii)
+                ball.impulse = self.impulse.extract(ii);
+                #[cfg(feature = "dim3")]
+                {
+                    ball.swing_impulse = self.limits_impulse[0].extract(ii);
+                    ball.twist_impulse = self.limits_impulse[1].extract(ii);
+                }
             }
         }
     }
@@ -199,7 +317,17 @@ pub(crate) struct WBallVelocityGroundConstraint {
     pub(crate) impulse: Vector<SimdFloat>,
     gcross2: Vector<SimdFloat>,
     inv_lhs: SdpMatrix<SimdFloat>,
-    im2: SimdFloat,
+    im2: Vector<SimdFloat>,
+    #[cfg(feature = "dim3")]
+    limits_axis2: [Option<Vector<SimdFloat>>; 2],
+    #[cfg(feature = "dim3")]
+    limits_rhs: [SimdFloat; 2],
+    #[cfg(feature = "dim3")]
+    limits_inv_lhs: [SimdFloat; 2],
+    #[cfg(feature = "dim3")]
+    limits_impulse: [SimdFloat; 2],
+    #[cfg(feature = "dim3")]
+    ii2_sqrt: AngularInertia<SimdFloat>,
 }
 
 impl WBallVelocityGroundConstraint {
@@ -223,7 +351,7 @@ impl WBallVelocityGroundConstraint {
         let linvel2 = Vector::from(array![|ii| rbs2[ii].linvel; SIMD_WIDTH]);
         let angvel2 = AngVector::<SimdFloat>::from(array![|ii| rbs2[ii].angvel; SIMD_WIDTH]);
         let world_com2 = Point::from(array![|ii| rbs2[ii].world_com; SIMD_WIDTH]);
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii2_sqrt = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs2[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         );
@@ -247,7 +375,10 @@ impl WBallVelocityGroundConstraint {
 
         #[cfg(feature = "dim3")]
         {
-            lhs = ii2_sqrt.squared().quadform(&cmat2).add_diagonal(im2);
+            lhs = ii2_sqrt
+                .squared()
+                .quadform(&cmat2)
+                .add_diagonal_elements(im2);
         }
 
         // In 2D we just unroll the computation because
@@ -255,14 +386,56 @@ impl WBallVelocityGroundConstraint {
         #[cfg(feature = "dim2")]
         {
             let ii2 = ii2_sqrt.squared();
-            let m11 = im2 + cmat2.x * cmat2.x * ii2;
+            let m11 = im2.x + cmat2.x * cmat2.x * ii2;
             let m12 = cmat2.x * cmat2.y * ii2;
-            let m22 = im2 + cmat2.y * cmat2.y * ii2;
+            let m22 = im2.y + cmat2.y * cmat2.y * ii2;
             lhs = SdpMatrix::new(m11, m12, m22)
         }
 
         let inv_lhs = lhs.inverse_unchecked();
 
+        #[cfg(feature = "dim3")]
+        let mut limits_axis2 = [None; 2];
+        #[cfg(feature = "dim3")]
+        let mut limits_rhs = [na::zero(); 2];
+        #[cfg(feature = "dim3")]
+        let mut limits_inv_lhs = [na::zero(); 2];
+        #[cfg(feature = "dim3")]
+        let mut limits_impulse = [na::zero(); 2];
+
+        #[cfg(feature = "dim3")]
+        {
+            // The limits are flipped too, so the errors are expressed relative to `rbs1`.
+            let limits = array![|ii| BallLimits::from_params(cparams[ii], flipped[ii]); SIMD_WIDTH];
+
+            if limits.iter().any(|l| l.is_some()) {
+                let _0: SimdFloat = na::zero();
+                let _1: SimdFloat = na::one();
+                let errors = array![|ii| limits[ii].map(|l| l.errors(&rbs1[ii].position, &rbs2[ii].position)).unwrap_or([None, None]); SIMD_WIDTH];
+                let impulses = [
+                    SimdFloat::from(array![|ii| cparams[ii].swing_impulse; SIMD_WIDTH]),
+                    SimdFloat::from(array![|ii| cparams[ii].twist_impulse; SIMD_WIDTH]),
+                ];
+
+                for k in 0..2 {
+                    let active = SimdBool::from(array![|ii| errors[ii][k].is_some(); SIMD_WIDTH]);
+
+                    if active.any() {
+                        let axis = Vector::from(
+                            array![|ii| errors[ii][k].map(|e| e.0).unwrap_or(Vector::zeros()); SIMD_WIDTH],
+                        );
+                        let ii2_axis = ii2_sqrt.transform_vector(axis);
+
+                        limits_axis2[k] = Some(axis);
+                        limits_rhs[k] = axis.dot(&(angvel2 - angvel1));
+                        limits_inv_lhs[k] = (_1 / ii2_axis.norm_squared()).select(active, _0);
+                        limits_impulse[k] = impulses[k].select(active, _0)
+                            * SimdFloat::splat(params.warmstart_coeff);
+                    }
+                }
+            }
+        }
+
         WBallVelocityGroundConstraint {
             joint_id,
             mj_lambda2,
@@ -271,6 +444,16 @@ impl WBallVelocityGroundConstraint {
             gcross2,
             rhs,
             inv_lhs,
+            #[cfg(feature = "dim3")]
+            limits_axis2,
+            #[cfg(feature = "dim3")]
+            limits_rhs,
+            #[cfg(feature = "dim3")]
+            limits_inv_lhs,
+            #[cfg(feature = "dim3")]
+            limits_impulse,
+            #[cfg(feature = "dim3")]
+            ii2_sqrt,
         }
     }
 
@@ -284,9 +467,18 @@ impl WBallVelocityGroundConstraint {
             ),
         };
 
-        mj_lambda2.linear -= self.impulse * self.im2;
+        mj_lambda2.linear -= self.impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self.gcross2.gcross(self.impulse);
 
+        #[cfg(feature = "dim3")]
+        for k in 0..2 {
+            if let Some(limits_axis2) = self.limits_axis2[k] {
+                mj_lambda2.angular += self
+                    .ii2_sqrt
+                    .transform_vector(limits_axis2 * self.limits_impulse[k]);
+            }
+        }
+
         for ii in 0..SIMD_WIDTH {
             mj_lambdas[self.mj_lambda2[ii] as usize].linear = mj_lambda2.linear.extract(ii);
             mj_lambdas[self.mj_lambda2[ii] as usize].angular = mj_lambda2.angular.extract(ii);
@@ -309,9 +501,27 @@ impl WBallVelocityGroundConstraint {
         let impulse = self.inv_lhs * dvel;
         self.impulse += impulse;
 
-        mj_lambda2.linear -= impulse * self.im2;
+        mj_lambda2.linear -= impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self.gcross2.gcross(impulse);
 
+        /*
+         * Swing and twist limits.
+         */
+        #[cfg(feature = "dim3")]
+        for k in 0..2 {
+            if let Some(limits_axis2) = self.limits_axis2[k] {
+                let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+                let ang_dvel = limits_axis2.dot(&ang_vel2) + self.limits_rhs[k];
+                let new_impulse = (self.limits_impulse[k] - ang_dvel * self.limits_inv_lhs[k])
+                    .simd_max(na::zero());
+                let dimpulse = new_impulse - self.limits_impulse[k];
+                self.limits_impulse[k] = new_impulse;
+
+                mj_lambda2.angular += self.ii2_sqrt.transform_vector(limits_axis2 * dimpulse);
+            }
+        }
+
         for ii in 0..SIMD_WIDTH {
             mj_lambdas[self.mj_lambda2[ii] as usize].linear = mj_lambda2.linear.extract(ii);
             mj_lambdas[self.mj_lambda2[ii] as usize].angular = mj_lambda2.angular.extract(ii);
@@ -322,7 +532,12 @@ impl WBallVelocityGroundConstraint {
         for ii in 0..SIMD_WIDTH {
             let joint = &mut joints_all[self.joint_id[ii]].weight;
             if let JointParams::BallJoint(ball) = &mut joint.params {
-                ball.impulse = self.impulse.extract(ii)
+                ball.impulse = self.impulse.extract(ii);
+                #[cfg(feature = "dim3")]
+                {
+                    ball.swing_impulse = self.limits_impulse[0].extract(ii);
+                    ball.twist_impulse = self.limits_impulse[1].extract(ii);
+                }
             }
         }
     }
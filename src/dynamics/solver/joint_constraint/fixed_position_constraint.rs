@@ -1,5 +1,5 @@
 use crate::dynamics::{FixedJoint, IntegrationParameters, RigidBody};
-use crate::math::{AngularInertia, Isometry, Point, Rotation};
+use crate::math::{AngularInertia, Isometry, Point, Rotation, Vector};
 use crate::utils::WAngularInertia;
 
 #[derive(Debug)]
@@ -10,12 +10,12 @@ pub(crate) struct FixedPositionConstraint {
     local_anchor2: Isometry<f32>,
     local_com1: Point<f32>,
     local_com2: Point<f32>,
-    im1: f32,
-    im2: f32,
+    im1: Vector<f32>,
+    im2: Vector<f32>,
     ii1: AngularInertia<f32>,
     ii2: AngularInertia<f32>,
 
-    lin_inv_lhs: f32,
+    lin_inv_lhs: Vector<f32>,
     ang_inv_lhs: AngularInertia<f32>,
 }
 
@@ -23,9 +23,9 @@ impl FixedPositionConstraint {
     pub fn from_params(rb1: &RigidBody, rb2: &RigidBody, cparams: &FixedJoint) -> Self {
         let ii1 = rb1.world_inv_inertia_sqrt.squared();
         let ii2 = rb2.world_inv_inertia_sqrt.squared();
-        let im1 = rb1.mass_properties.inv_mass;
-        let im2 = rb2.mass_properties.inv_mass;
-        let lin_inv_lhs = 1.0 / (im1 + im2);
+        let im1 = rb1.effective_inv_mass;
+        let im2 = rb2.effective_inv_mass;
+        let lin_inv_lhs = (im1 + im2).map(crate::utils::inv);
         let ang_inv_lhs = (ii1 + ii2).inverse();
 
         Self {
@@ -69,9 +69,9 @@ impl FixedPositionConstraint {
         let anchor1 = position1 * Point::from(self.local_anchor1.translation.vector);
         let anchor2 = position2 * Point::from(self.local_anchor2.translation.vector);
         let err = anchor2 - anchor1;
-        let impulse = err * (self.lin_inv_lhs * params.joint_erp);
-        position1.translation.vector += self.im1 * impulse;
-        position2.translation.vector -= self.im2 * impulse;
+        let impulse = err.component_mul(&self.lin_inv_lhs) * params.joint_erp;
+        position1.translation.vector += impulse.component_mul(&self.im1);
+        position2.translation.vector -= impulse.component_mul(&self.im2);
 
         positions[self.position1 as usize] = position1;
         positions[self.position2 as usize] = position2;
@@ -84,7 +84,8 @@ pub(crate) struct FixedPositionGroundConstraint {
     anchor1: Isometry<f32>,
     local_anchor2: Isometry<f32>,
     local_com2: Point<f32>,
-    im2: f32,
+    im2: Vector<f32>,
+    lin_inv_lhs: Vector<f32>,
     ii2: AngularInertia<f32>,
     impulse: f32,
 }
@@ -111,7 +112,8 @@ impl FixedPositionGroundConstraint {
             anchor1,
             local_anchor2,
             position2: rb2.active_set_offset,
-            im2: rb2.mass_properties.inv_mass,
+            im2: rb2.effective_inv_mass,
+            lin_inv_lhs: rb2.effective_inv_mass.map(crate::utils::inv),
             ii2: rb2.world_inv_inertia_sqrt.squared(),
             local_com2: rb2.mass_properties.local_com,
             impulse: 0.0,
@@ -130,9 +132,8 @@ impl FixedPositionGroundConstraint {
         let anchor1 = Point::from(self.anchor1.translation.vector);
         let anchor2 = position2 * Point::from(self.local_anchor2.translation.vector);
         let err = anchor2 - anchor1;
-        // NOTE: no need to divide by im2 just to multiply right after.
-        let impulse = err * params.joint_erp;
-        position2.translation.vector -= impulse;
+        let impulse = err.component_mul(&self.lin_inv_lhs) * params.joint_erp;
+        position2.translation.vector -= impulse.component_mul(&self.im2);
 
         positions[self.position2 as usize] = position2;
     }
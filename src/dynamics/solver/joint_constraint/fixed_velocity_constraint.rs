@@ -28,8 +28,8 @@ pub(crate) struct FixedVelocityConstraint {
     #[cfg(feature = "dim2")]
     rhs: Vector3<f32>,
 
-    im1: f32,
-    im2: f32,
+    im1: Vector<f32>,
+    im2: Vector<f32>,
 
     ii1: AngularInertia<f32>,
     ii2: AngularInertia<f32>,
@@ -51,8 +51,8 @@ impl FixedVelocityConstraint {
     ) -> Self {
         let anchor1 = rb1.position * cparams.local_anchor1;
         let anchor2 = rb2.position * cparams.local_anchor2;
-        let im1 = rb1.mass_properties.inv_mass;
-        let im2 = rb2.mass_properties.inv_mass;
+        let im1 = rb1.effective_inv_mass;
+        let im2 = rb2.effective_inv_mass;
         let ii1 = rb1.world_inv_inertia_sqrt.squared();
         let ii2 = rb2.world_inv_inertia_sqrt.squared();
         let r1 = anchor1.translation.vector - rb1.world_com.coords;
@@ -65,8 +65,8 @@ impl FixedVelocityConstraint {
 
         #[cfg(feature = "dim3")]
         {
-            let lhs00 =
-                ii1.quadform(&rmat1).add_diagonal(im1) + ii2.quadform(&rmat2).add_diagonal(im2);
+            let lhs00 = ii1.quadform(&rmat1).add_diagonal_elements(im1)
+                + ii2.quadform(&rmat2).add_diagonal_elements(im2);
             let lhs10 = ii1.transform_matrix(&rmat1) + ii2.transform_matrix(&rmat2);
             let lhs11 = (ii1 + ii2).into_matrix();
 
@@ -83,9 +83,9 @@ impl FixedVelocityConstraint {
         // it's just easier that way.
         #[cfg(feature = "dim2")]
         {
-            let m11 = im1 + im2 + rmat1.x * rmat1.x * ii1 + rmat2.x * rmat2.x * ii2;
+            let m11 = im1.x + im2.x + rmat1.x * rmat1.x * ii1 + rmat2.x * rmat2.x * ii2;
             let m12 = rmat1.x * rmat1.y * ii1 + rmat2.x * rmat2.y * ii2;
-            let m22 = im1 + im2 + rmat1.y * rmat1.y * ii1 + rmat2.y * rmat2.y * ii2;
+            let m22 = im1.y + im2.y + rmat1.y * rmat1.y * ii1 + rmat2.y * rmat2.y * ii2;
             let m13 = rmat1.x * ii1 + rmat2.x * ii2;
             let m23 = rmat1.y * ii1 + rmat2.y * ii2;
             let m33 = ii1 + ii2;
@@ -138,12 +138,12 @@ impl FixedVelocityConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = self.impulse.fixed_rows::<U3>(3).into_owned();
 
-        mj_lambda1.linear += self.im1 * lin_impulse;
+        mj_lambda1.linear += lin_impulse.component_mul(&self.im1);
         mj_lambda1.angular += self
             .ii1_sqrt
             .transform_vector(ang_impulse + self.r1.gcross(lin_impulse));
 
-        mj_lambda2.linear -= self.im2 * lin_impulse;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
@@ -179,12 +179,12 @@ impl FixedVelocityConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = impulse.fixed_rows::<U3>(3).into_owned();
 
-        mj_lambda1.linear += self.im1 * lin_impulse;
+        mj_lambda1.linear += lin_impulse.component_mul(&self.im1);
         mj_lambda1.angular += self
             .ii1_sqrt
             .transform_vector(ang_impulse + self.r1.gcross(lin_impulse));
 
-        mj_lambda2.linear -= self.im2 * lin_impulse;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
@@ -219,7 +219,7 @@ pub(crate) struct FixedVelocityGroundConstraint {
     #[cfg(feature = "dim2")]
     rhs: Vector3<f32>,
 
-    im2: f32,
+    im2: Vector<f32>,
     ii2: AngularInertia<f32>,
     ii2_sqrt: AngularInertia<f32>,
     r2: Vector<f32>,
@@ -248,7 +248,7 @@ impl FixedVelocityGroundConstraint {
 
         let r1 = anchor1.translation.vector - rb1.world_com.coords;
 
-        let im2 = rb2.mass_properties.inv_mass;
+        let im2 = rb2.effective_inv_mass;
         let ii2 = rb2.world_inv_inertia_sqrt.squared();
         let r2 = anchor2.translation.vector - rb2.world_com.coords;
         let rmat2 = r2.gcross_matrix();
@@ -258,7 +258,7 @@ impl FixedVelocityGroundConstraint {
 
         #[cfg(feature = "dim3")]
         {
-            let lhs00 = ii2.quadform(&rmat2).add_diagonal(im2);
+            let lhs00 = ii2.quadform(&rmat2).add_diagonal_elements(im2);
             let lhs10 = ii2.transform_matrix(&rmat2);
             let lhs11 = ii2.into_matrix();
 
@@ -275,9 +275,9 @@ impl FixedVelocityGroundConstraint {
         // it's just easier that way.
         #[cfg(feature = "dim2")]
         {
-            let m11 = im2 + rmat2.x * rmat2.x * ii2;
+            let m11 = im2.x + rmat2.x * rmat2.x * ii2;
             let m12 = rmat2.x * rmat2.y * ii2;
-            let m22 = im2 + rmat2.y * rmat2.y * ii2;
+            let m22 = im2.y + rmat2.y * rmat2.y * ii2;
             let m13 = rmat2.x * ii2;
             let m23 = rmat2.y * ii2;
             let m33 = ii2;
@@ -321,7 +321,7 @@ impl FixedVelocityGroundConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = self.impulse.fixed_rows::<U3>(3).into_owned();
 
-        mj_lambda2.linear -= self.im2 * lin_impulse;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
@@ -352,7 +352,7 @@ impl FixedVelocityGroundConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = impulse.fixed_rows::<U3>(3).into_owned();
 
-        mj_lambda2.linear -= self.im2 * lin_impulse;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
@@ -36,8 +36,8 @@ pub(crate) struct WFixedVelocityConstraint {
     #[cfg(feature = "dim2")]
     rhs: Vector3<SimdFloat>,
 
-    im1: SimdFloat,
-    im2: SimdFloat,
+    im1: Vector<SimdFloat>,
+    im2: Vector<SimdFloat>,
 
     ii1: AngularInertia<SimdFloat>,
     ii2: AngularInertia<SimdFloat>,
@@ -61,7 +61,7 @@ impl WFixedVelocityConstraint {
         let linvel1 = Vector::from(array![|ii| rbs1[ii].linvel; SIMD_WIDTH]);
         let angvel1 = AngVector::<SimdFloat>::from(array![|ii| rbs1[ii].angvel; SIMD_WIDTH]);
         let world_com1 = Point::from(array![|ii| rbs1[ii].world_com; SIMD_WIDTH]);
-        let im1 = SimdFloat::from(array![|ii| rbs1[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im1 = Vector::from(array![|ii| rbs1[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii1_sqrt = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs1[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         );
@@ -71,7 +71,7 @@ impl WFixedVelocityConstraint {
         let linvel2 = Vector::from(array![|ii| rbs2[ii].linvel; SIMD_WIDTH]);
         let angvel2 = AngVector::<SimdFloat>::from(array![|ii| rbs2[ii].angvel; SIMD_WIDTH]);
         let world_com2 = Point::from(array![|ii| rbs2[ii].world_com; SIMD_WIDTH]);
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii2_sqrt = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs2[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         );
@@ -95,8 +95,8 @@ impl WFixedVelocityConstraint {
 
         #[cfg(feature = "dim3")]
         {
-            let lhs00 =
-                ii1.quadform(&rmat1).add_diagonal(im1) + ii2.quadform(&rmat2).add_diagonal(im2);
+            let lhs00 = ii1.quadform(&rmat1).add_diagonal_elements(im1)
+                + ii2.quadform(&rmat2).add_diagonal_elements(im2);
             let lhs10 = ii1.transform_matrix(&rmat1) + ii2.transform_matrix(&rmat2);
             let lhs11 = (ii1 + ii2).into_matrix();
 
@@ -113,9 +113,9 @@ impl WFixedVelocityConstraint {
         // it's just easier that way.
         #[cfg(feature = "dim2")]
         {
-            let m11 = im1 + im2 + rmat1.x * rmat1.x * ii1 + rmat2.x * rmat2.x * ii2;
+            let m11 = im1.x + im2.x + rmat1.x * rmat1.x * ii1 + rmat2.x * rmat2.x * ii2;
             let m12 = rmat1.x * rmat1.y * ii1 + rmat2.x * rmat2.y * ii2;
-            let m22 = im1 + im2 + rmat1.y * rmat1.y * ii1 + rmat2.y * rmat2.y * ii2;
+            let m22 = im1.y + im2.y + rmat1.y * rmat1.y * ii1 + rmat2.y * rmat2.y * ii2;
             let m13 = rmat1.x * ii1 + rmat2.x * ii2;
             let m23 = rmat1.y * ii1 + rmat2.y * ii2;
             let m33 = ii1 + ii2;
@@ -182,12 +182,12 @@ impl WFixedVelocityConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = self.impulse.fixed_rows::<U3>(3).into_owned();
 
-        mj_lambda1.linear += lin_impulse * self.im1;
+        mj_lambda1.linear += lin_impulse.component_mul(&self.im1);
         mj_lambda1.angular += self
             .ii1_sqrt
             .transform_vector(ang_impulse + self.r1.gcross(lin_impulse));
 
-        mj_lambda2.linear -= lin_impulse * self.im2;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
@@ -243,12 +243,12 @@ impl WFixedVelocityConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = impulse.fixed_rows::<U3>(3).into_owned();
 
-        mj_lambda1.linear += lin_impulse * self.im1;
+        mj_lambda1.linear += lin_impulse.component_mul(&self.im1);
         mj_lambda1.angular += self
             .ii1_sqrt
             .transform_vector(ang_impulse + self.r1.gcross(lin_impulse));
 
-        mj_lambda2.linear -= lin_impulse * self.im2;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
@@ -291,7 +291,7 @@ pub(crate) struct WFixedVelocityGroundConstraint {
     #[cfg(feature = "dim2")]
     rhs: Vector3<SimdFloat>,
 
-    im2: SimdFloat,
+    im2: Vector<SimdFloat>,
     ii2: AngularInertia<SimdFloat>,
     ii2_sqrt: AngularInertia<SimdFloat>,
     r2: Vector<SimdFloat>,
@@ -315,7 +315,7 @@ impl WFixedVelocityGroundConstraint {
         let linvel2 = Vector::from(array![|ii| rbs2[ii].linvel; SIMD_WIDTH]);
         let angvel2 = AngVector::<SimdFloat>::from(array![|ii| rbs2[ii].angvel; SIMD_WIDTH]);
         let world_com2 = Point::from(array![|ii| rbs2[ii].world_com; SIMD_WIDTH]);
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii2_sqrt = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs2[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         );
@@ -341,7 +341,7 @@ impl WFixedVelocityGroundConstraint {
 
         #[cfg(feature = "dim3")]
         {
-            let lhs00 = ii2.quadform(&rmat2).add_diagonal(im2);
+            let lhs00 = ii2.quadform(&rmat2).add_diagonal_elements(im2);
             let lhs10 = ii2.transform_matrix(&rmat2);
             let lhs11 = ii2.into_matrix();
 
@@ -356,9 +356,9 @@ impl WFixedVelocityGroundConstraint {
         // it's just easier that way.
         #[cfg(feature = "dim2")]
         {
-            let m11 = im2 + rmat2.x * rmat2.x * ii2;
+            let m11 = im2.x + rmat2.x * rmat2.x * ii2;
             let m12 = rmat2.x * rmat2.y * ii2;
-            let m22 = im2 + rmat2.y * rmat2.y * ii2;
+            let m22 = im2.y + rmat2.y * rmat2.y * ii2;
             let m13 = rmat2.x * ii2;
             let m23 = rmat2.y * ii2;
             let m33 = ii2;
@@ -409,7 +409,7 @@ impl WFixedVelocityGroundConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = self.impulse.fixed_rows::<U3>(3).into_owned();
 
-        mj_lambda2.linear -= lin_impulse * self.im2;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
@@ -449,7 +449,7 @@ impl WFixedVelocityGroundConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = impulse.fixed_rows::<U3>(3).into_owned();
 
-        mj_lambda2.linear -= lin_impulse * self.im2;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
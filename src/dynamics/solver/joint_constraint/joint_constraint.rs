@@ -2,7 +2,6 @@ use super::{
     BallVelocityConstraint, BallVelocityGroundConstraint, FixedVelocityConstraint,
     FixedVelocityGroundConstraint, PrismaticVelocityConstraint, PrismaticVelocityGroundConstraint,
 };
-#[cfg(feature = "dim3")]
 use super::{RevoluteVelocityConstraint, RevoluteVelocityGroundConstraint};
 #[cfg(feature = "simd-is-enabled")]
 use super::{
@@ -10,7 +9,6 @@ use super::{
     WFixedVelocityGroundConstraint, WPrismaticVelocityConstraint,
     WPrismaticVelocityGroundConstraint,
 };
-#[cfg(feature = "dim3")]
 #[cfg(feature = "simd-is-enabled")]
 use super::{WRevoluteVelocityConstraint, WRevoluteVelocityGroundConstraint};
 use crate::dynamics::solver::DeltaVel;
@@ -39,14 +37,10 @@ pub(crate) enum AnyJointVelocityConstraint {
     WPrismaticConstraint(WPrismaticVelocityConstraint),
     #[cfg(feature = "simd-is-enabled")]
     WPrismaticGroundConstraint(WPrismaticVelocityGroundConstraint),
-    #[cfg(feature = "dim3")]
     RevoluteConstraint(RevoluteVelocityConstraint),
-    #[cfg(feature = "dim3")]
     RevoluteGroundConstraint(RevoluteVelocityGroundConstraint),
-    #[cfg(feature = "dim3")]
     #[cfg(feature = "simd-is-enabled")]
     WRevoluteConstraint(WRevoluteVelocityConstraint),
-    #[cfg(feature = "dim3")]
     #[cfg(feature = "simd-is-enabled")]
     WRevoluteGroundConstraint(WRevoluteVelocityGroundConstraint),
     #[allow(dead_code)] // The Empty variant is only used with parallel code.
@@ -78,7 +72,6 @@ impl AnyJointVelocityConstraint {
             JointParams::PrismaticJoint(p) => AnyJointVelocityConstraint::PrismaticConstraint(
                 PrismaticVelocityConstraint::from_params(params, joint_id, rb1, rb2, p),
             ),
-            #[cfg(feature = "dim3")]
             JointParams::RevoluteJoint(p) => AnyJointVelocityConstraint::RevoluteConstraint(
                 RevoluteVelocityConstraint::from_params(params, joint_id, rb1, rb2, p),
             ),
@@ -115,7 +108,6 @@ impl AnyJointVelocityConstraint {
                     WPrismaticVelocityConstraint::from_params(params, joint_id, rbs1, rbs2, joints),
                 )
             }
-            #[cfg(feature = "dim3")]
             JointParams::RevoluteJoint(_) => {
                 let joints =
                     array![|ii| joints[ii].params.as_revolute_joint().unwrap(); SIMD_WIDTH];
@@ -154,7 +146,6 @@ impl AnyJointVelocityConstraint {
                     ),
                 )
             }
-            #[cfg(feature = "dim3")]
             JointParams::RevoluteJoint(p) => AnyJointVelocityConstraint::RevoluteGroundConstraint(
                 RevoluteVelocityGroundConstraint::from_params(
                     params, joint_id, rb1, rb2, p, flipped,
@@ -207,7 +198,6 @@ impl AnyJointVelocityConstraint {
                     ),
                 )
             }
-            #[cfg(feature = "dim3")]
             JointParams::RevoluteJoint(_) => {
                 let joints =
                     array![|ii| joints[ii].params.as_revolute_joint().unwrap(); SIMD_WIDTH];
@@ -240,14 +230,10 @@ impl AnyJointVelocityConstraint {
             AnyJointVelocityConstraint::WPrismaticConstraint(c) => c.warmstart(mj_lambdas),
             #[cfg(feature = "simd-is-enabled")]
             AnyJointVelocityConstraint::WPrismaticGroundConstraint(c) => c.warmstart(mj_lambdas),
-            #[cfg(feature = "dim3")]
             AnyJointVelocityConstraint::RevoluteConstraint(c) => c.warmstart(mj_lambdas),
-            #[cfg(feature = "dim3")]
             AnyJointVelocityConstraint::RevoluteGroundConstraint(c) => c.warmstart(mj_lambdas),
-            #[cfg(feature = "dim3")]
             #[cfg(feature = "simd-is-enabled")]
             AnyJointVelocityConstraint::WRevoluteConstraint(c) => c.warmstart(mj_lambdas),
-            #[cfg(feature = "dim3")]
             #[cfg(feature = "simd-is-enabled")]
             AnyJointVelocityConstraint::WRevoluteGroundConstraint(c) => c.warmstart(mj_lambdas),
             AnyJointVelocityConstraint::Empty => unreachable!(),
@@ -274,14 +260,10 @@ impl AnyJointVelocityConstraint {
             AnyJointVelocityConstraint::WPrismaticConstraint(c) => c.solve(mj_lambdas),
             #[cfg(feature = "simd-is-enabled")]
             AnyJointVelocityConstraint::WPrismaticGroundConstraint(c) => c.solve(mj_lambdas),
-            #[cfg(feature = "dim3")]
             AnyJointVelocityConstraint::RevoluteConstraint(c) => c.solve(mj_lambdas),
-            #[cfg(feature = "dim3")]
             AnyJointVelocityConstraint::RevoluteGroundConstraint(c) => c.solve(mj_lambdas),
-            #[cfg(feature = "dim3")]
             #[cfg(feature = "simd-is-enabled")]
             AnyJointVelocityConstraint::WRevoluteConstraint(c) => c.solve(mj_lambdas),
-            #[cfg(feature = "dim3")]
             #[cfg(feature = "simd-is-enabled")]
             AnyJointVelocityConstraint::WRevoluteGroundConstraint(c) => c.solve(mj_lambdas),
             AnyJointVelocityConstraint::Empty => unreachable!(),
@@ -320,16 +302,12 @@ impl AnyJointVelocityConstraint {
             AnyJointVelocityConstraint::WPrismaticGroundConstraint(c) => {
                 c.writeback_impulses(joints_all)
             }
-            #[cfg(feature = "dim3")]
             AnyJointVelocityConstraint::RevoluteConstraint(c) => c.writeback_impulses(joints_all),
-            #[cfg(feature = "dim3")]
             AnyJointVelocityConstraint::RevoluteGroundConstraint(c) => {
                 c.writeback_impulses(joints_all)
             }
-            #[cfg(feature = "dim3")]
             #[cfg(feature = "simd-is-enabled")]
             AnyJointVelocityConstraint::WRevoluteConstraint(c) => c.writeback_impulses(joints_all),
-            #[cfg(feature = "dim3")]
             #[cfg(feature = "simd-is-enabled")]
             AnyJointVelocityConstraint::WRevoluteGroundConstraint(c) => {
                 c.writeback_impulses(joints_all)
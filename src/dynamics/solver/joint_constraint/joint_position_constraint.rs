@@ -2,7 +2,6 @@ use super::{
     BallPositionConstraint, BallPositionGroundConstraint, FixedPositionConstraint,
     FixedPositionGroundConstraint, PrismaticPositionConstraint, PrismaticPositionGroundConstraint,
 };
-#[cfg(feature = "dim3")]
 use super::{RevolutePositionConstraint, RevolutePositionGroundConstraint};
 #[cfg(feature = "simd-is-enabled")]
 use super::{WBallPositionConstraint, WBallPositionGroundConstraint};
@@ -22,9 +21,7 @@ pub(crate) enum AnyJointPositionConstraint {
     FixedGroundConstraint(FixedPositionGroundConstraint),
     PrismaticJoint(PrismaticPositionConstraint),
     PrismaticGroundConstraint(PrismaticPositionGroundConstraint),
-    #[cfg(feature = "dim3")]
     RevoluteJoint(RevolutePositionConstraint),
-    #[cfg(feature = "dim3")]
     RevoluteGroundConstraint(RevolutePositionGroundConstraint),
     #[allow(dead_code)] // The Empty variant is only used with parallel code.
     Empty,
@@ -63,7 +60,6 @@ impl AnyJointPositionConstraint {
             JointParams::PrismaticJoint(p) => AnyJointPositionConstraint::PrismaticJoint(
                 PrismaticPositionConstraint::from_params(rb1, rb2, p),
             ),
-            #[cfg(feature = "dim3")]
             JointParams::RevoluteJoint(p) => AnyJointPositionConstraint::RevoluteJoint(
                 RevolutePositionConstraint::from_params(rb1, rb2, p),
             ),
@@ -84,7 +80,6 @@ impl AnyJointPositionConstraint {
             }
             JointParams::FixedJoint(_) => None,
             JointParams::PrismaticJoint(_) => None,
-            #[cfg(feature = "dim3")]
             JointParams::RevoluteJoint(_) => None,
         }
     }
@@ -110,7 +105,6 @@ impl AnyJointPositionConstraint {
                     PrismaticPositionGroundConstraint::from_params(rb1, rb2, p, flipped),
                 )
             }
-            #[cfg(feature = "dim3")]
             JointParams::RevoluteJoint(p) => AnyJointPositionConstraint::RevoluteGroundConstraint(
                 RevolutePositionGroundConstraint::from_params(rb1, rb2, p, flipped),
             ),
@@ -142,7 +136,6 @@ impl AnyJointPositionConstraint {
             }
             JointParams::FixedJoint(_) => None,
             JointParams::PrismaticJoint(_) => None,
-            #[cfg(feature = "dim3")]
             JointParams::RevoluteJoint(_) => None,
         }
     }
@@ -159,9 +152,7 @@ impl AnyJointPositionConstraint {
             AnyJointPositionConstraint::FixedGroundConstraint(c) => c.solve(params, positions),
             AnyJointPositionConstraint::PrismaticJoint(c) => c.solve(params, positions),
             AnyJointPositionConstraint::PrismaticGroundConstraint(c) => c.solve(params, positions),
-            #[cfg(feature = "dim3")]
             AnyJointPositionConstraint::RevoluteJoint(c) => c.solve(params, positions),
-            #[cfg(feature = "dim3")]
             AnyJointPositionConstraint::RevoluteGroundConstraint(c) => c.solve(params, positions),
             AnyJointPositionConstraint::Empty => unreachable!(),
         }
@@ -1,3 +1,5 @@
+#[cfg(feature = "dim3")]
+pub(self) use ball_limits::BallLimits;
 pub(self) use ball_position_constraint::{BallPositionConstraint, BallPositionGroundConstraint};
 #[cfg(feature = "simd-is-enabled")]
 pub(self) use ball_position_constraint_wide::{
@@ -26,20 +28,19 @@ pub(self) use prismatic_velocity_constraint::{
 pub(self) use prismatic_velocity_constraint_wide::{
     WPrismaticVelocityConstraint, WPrismaticVelocityGroundConstraint,
 };
-#[cfg(feature = "dim3")]
 pub(self) use revolute_position_constraint::{
     RevolutePositionConstraint, RevolutePositionGroundConstraint,
 };
-#[cfg(feature = "dim3")]
 pub(self) use revolute_velocity_constraint::{
     RevoluteVelocityConstraint, RevoluteVelocityGroundConstraint,
 };
-#[cfg(feature = "dim3")]
 #[cfg(feature = "simd-is-enabled")]
 pub(self) use revolute_velocity_constraint_wide::{
     WRevoluteVelocityConstraint, WRevoluteVelocityGroundConstraint,
 };
 
+#[cfg(feature = "dim3")]
+mod ball_limits;
 mod ball_position_constraint;
 #[cfg(feature = "simd-is-enabled")]
 mod ball_position_constraint_wide;
@@ -56,10 +57,23 @@ mod prismatic_position_constraint;
 mod prismatic_velocity_constraint;
 #[cfg(feature = "simd-is-enabled")]
 mod prismatic_velocity_constraint_wide;
-#[cfg(feature = "dim3")]
 mod revolute_position_constraint;
-#[cfg(feature = "dim3")]
 mod revolute_velocity_constraint;
-#[cfg(feature = "dim3")]
 #[cfg(feature = "simd-is-enabled")]
 mod revolute_velocity_constraint_wide;
+
+/// Computes the regularization coefficient and the velocity bias of a joint motor.
+///
+/// A motor with a zero `stiffness` is a pure velocity motor. Otherwise, it behaves like a
+/// spring-damper pulling the joint toward its target position, `pos_err` being the current
+/// deviation from that target.
+pub(self) fn motor_coefficients(dt: f32, stiffness: f32, damping: f32, pos_err: f32) -> (f32, f32) {
+    if stiffness == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let denom = damping + dt * stiffness;
+    let gamma = crate::utils::inv(dt * denom);
+    let bias = pos_err * stiffness * crate::utils::inv(denom);
+    (gamma, bias)
+}
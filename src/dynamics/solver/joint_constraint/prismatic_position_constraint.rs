@@ -8,13 +8,13 @@ pub(crate) struct PrismaticPositionConstraint {
     position1: usize,
     position2: usize,
 
-    im1: f32,
-    im2: f32,
+    im1: Vector<f32>,
+    im2: Vector<f32>,
 
     ii1: AngularInertia<f32>,
     ii2: AngularInertia<f32>,
 
-    lin_inv_lhs: f32,
+    lin_inv_lhs: Vector<f32>,
     ang_inv_lhs: AngularInertia<f32>,
 
     limits: [f32; 2],
@@ -30,9 +30,9 @@ impl PrismaticPositionConstraint {
     pub fn from_params(rb1: &RigidBody, rb2: &RigidBody, cparams: &PrismaticJoint) -> Self {
         let ii1 = rb1.world_inv_inertia_sqrt.squared();
         let ii2 = rb2.world_inv_inertia_sqrt.squared();
-        let im1 = rb1.mass_properties.inv_mass;
-        let im2 = rb2.mass_properties.inv_mass;
-        let lin_inv_lhs = 1.0 / (im1 + im2);
+        let im1 = rb1.effective_inv_mass;
+        let im2 = rb2.effective_inv_mass;
+        let lin_inv_lhs = (im1 + im2).map(crate::utils::inv);
         let ang_inv_lhs = (ii1 + ii2).inverse();
 
         Self {
@@ -87,9 +87,9 @@ impl PrismaticPositionConstraint {
             err += *axis1 * (limit_err - self.limits[1]);
         }
 
-        let impulse = err * (self.lin_inv_lhs * params.joint_erp);
-        position1.translation.vector += self.im1 * impulse;
-        position2.translation.vector -= self.im2 * impulse;
+        let impulse = err.component_mul(&self.lin_inv_lhs) * params.joint_erp;
+        position1.translation.vector += impulse.component_mul(&self.im1);
+        position2.translation.vector -= impulse.component_mul(&self.im2);
 
         positions[self.position1 as usize] = position1;
         positions[self.position2 as usize] = position2;
@@ -103,6 +103,8 @@ pub(crate) struct PrismaticPositionGroundConstraint {
     local_frame2: Isometry<f32>,
     axis1: Unit<Vector<f32>>,
     local_axis2: Unit<Vector<f32>>,
+    im2: Vector<f32>,
+    lin_inv_lhs: Vector<f32>,
     limits: [f32; 2],
 }
 
@@ -136,6 +138,8 @@ impl PrismaticPositionGroundConstraint {
             axis1,
             local_axis2,
             position2: rb2.active_set_offset,
+            im2: rb2.effective_inv_mass,
+            lin_inv_lhs: rb2.effective_inv_mass.map(crate::utils::inv),
             limits: cparams.limits,
         }
     }
@@ -161,9 +165,8 @@ impl PrismaticPositionGroundConstraint {
             err += *self.axis1 * (limit_err - self.limits[1]);
         }
 
-        // NOTE: no need to divide by im2 just to multiply right after.
-        let impulse = err * params.joint_erp;
-        position2.translation.vector -= impulse;
+        let impulse = err.component_mul(&self.lin_inv_lhs) * params.joint_erp;
+        position2.translation.vector -= impulse.component_mul(&self.im2);
 
         positions[self.position2 as usize] = position2;
     }
@@ -4,13 +4,16 @@ use crate::dynamics::{
 };
 use crate::math::{AngularInertia, Vector};
 use crate::utils::{WAngularInertia, WCross, WCrossMatrix};
-#[cfg(feature = "dim3")]
-use na::{Cholesky, Matrix3x2, Matrix5, Vector5, U2, U3};
 #[cfg(feature = "dim2")]
 use {
     crate::utils::SdpMatrix2,
     na::{Matrix2, Vector2},
 };
+#[cfg(feature = "dim3")]
+use {
+    crate::utils::SdpMatrix3,
+    na::{Cholesky, Matrix3x2, Matrix5, Vector5, U2, U3},
+};
 
 #[cfg(feature = "dim2")]
 type LinImpulseDim = na::U1;
@@ -44,14 +47,22 @@ pub(crate) struct PrismaticVelocityConstraint {
     limits_impulse: f32,
     limits_forcedirs: Option<(Vector<f32>, Vector<f32>)>,
     limits_rhs: f32,
+    limits_inv_lhs: f32,
+
+    motor_impulse: f32,
+    motor_forcedirs: Option<(Vector<f32>, Vector<f32>)>,
+    motor_rhs: f32,
+    motor_inv_lhs: f32,
+    motor_gamma: f32,
+    motor_max_impulse: f32,
 
     #[cfg(feature = "dim2")]
     basis1: Vector2<f32>,
     #[cfg(feature = "dim3")]
     basis1: Matrix3x2<f32>,
 
-    im1: f32,
-    im2: f32,
+    im1: Vector<f32>,
+    im2: Vector<f32>,
 
     ii1_sqrt: AngularInertia<f32>,
     ii2_sqrt: AngularInertia<f32>,
@@ -92,12 +103,12 @@ impl PrismaticVelocityConstraint {
         // simplifications of the computation without introducing
         // much instabilities.
 
-        let im1 = rb1.mass_properties.inv_mass;
+        let im1 = rb1.effective_inv_mass;
         let ii1 = rb1.world_inv_inertia_sqrt.squared();
         let r1 = anchor1 - rb1.world_com;
         let r1_mat = r1.gcross_matrix();
 
-        let im2 = rb2.mass_properties.inv_mass;
+        let im2 = rb2.effective_inv_mass;
         let ii2 = rb2.world_inv_inertia_sqrt.squared();
         let r2 = anchor2 - rb2.world_com;
         let r2_mat = r2.gcross_matrix();
@@ -111,8 +122,9 @@ impl PrismaticVelocityConstraint {
             let r2_mat_b1 = r2_mat * basis1;
 
             lhs = Matrix5::zeros();
-            let lhs00 = ii1.quadform3x2(&r1_mat_b1).add_diagonal(im1)
-                + ii2.quadform3x2(&r2_mat_b1).add_diagonal(im2);
+            let lhs00 = SdpMatrix3::from_diagonal_elements(im1 + im2).quadform3x2(&basis1)
+                + ii1.quadform3x2(&r1_mat_b1)
+                + ii2.quadform3x2(&r2_mat_b1);
             let lhs10 = ii1 * r1_mat_b1 + ii2 * r2_mat_b1;
             let lhs11 = (ii1 + ii2).into_matrix();
             lhs.fixed_slice_mut::<U2, U2>(0, 0)
@@ -125,7 +137,9 @@ impl PrismaticVelocityConstraint {
         {
             let b1r1 = basis1.dot(&r1_mat);
             let b2r2 = basis1.dot(&r2_mat);
-            let m11 = im1 + im2 + b1r1 * ii1 * b1r1 + b2r2 * ii2 * b2r2;
+            let m11 = basis1.dot(&(im1 + im2).component_mul(&basis1))
+                + b1r1 * ii1 * b1r1
+                + b2r2 * ii2 * b2r2;
             let m12 = basis1.dot(&r1_mat) * ii1 + basis1.dot(&r2_mat) * ii2;
             let m22 = ii1 + ii2;
             lhs = SdpMatrix2::new(m11, m12, m22);
@@ -152,6 +166,7 @@ impl PrismaticVelocityConstraint {
         // Setup limit constraint.
         let mut limits_forcedirs = None;
         let mut limits_rhs = 0.0;
+        let mut limits_inv_lhs = 0.0;
         let mut limits_impulse = 0.0;
 
         if cparams.limits_enabled {
@@ -163,14 +178,48 @@ impl PrismaticVelocityConstraint {
             if dist < cparams.limits[0] {
                 limits_forcedirs = Some((-axis1.into_inner(), axis2.into_inner()));
                 limits_rhs = anchor_linvel2.dot(&axis2) - anchor_linvel1.dot(&axis1);
+                limits_inv_lhs = crate::utils::inv(
+                    axis1.dot(&im1.component_mul(&axis1)) + axis2.dot(&im2.component_mul(&axis2)),
+                );
                 limits_impulse = cparams.limits_impulse;
             } else if dist > cparams.limits[1] {
                 limits_forcedirs = Some((axis1.into_inner(), -axis2.into_inner()));
                 limits_rhs = -anchor_linvel2.dot(&axis2) + anchor_linvel1.dot(&axis1);
+                limits_inv_lhs = crate::utils::inv(
+                    axis1.dot(&im1.component_mul(&axis1)) + axis2.dot(&im2.component_mul(&axis2)),
+                );
                 limits_impulse = cparams.limits_impulse;
             }
         }
 
+        // Setup motor.
+        let mut motor_forcedirs = None;
+        let mut motor_rhs = 0.0;
+        let mut motor_inv_lhs = 0.0;
+        let mut motor_gamma = 0.0;
+        let mut motor_impulse = 0.0;
+
+        if cparams.motor_enabled {
+            let dist = (anchor2 - anchor1).dot(&axis1);
+            let (gamma, bias) = super::motor_coefficients(
+                params.dt(),
+                cparams.motor_stiffness,
+                cparams.motor_damping,
+                dist - cparams.target_motor_pos,
+            );
+            let dvel = anchor_linvel2.dot(&axis2) - anchor_linvel1.dot(&axis1);
+
+            motor_forcedirs = Some((-axis1.into_inner(), axis2.into_inner()));
+            motor_rhs = cparams.target_motor_vel - bias - dvel;
+            motor_inv_lhs = crate::utils::inv(
+                axis1.dot(&im1.component_mul(&axis1))
+                    + axis2.dot(&im2.component_mul(&axis2))
+                    + gamma,
+            );
+            motor_gamma = gamma;
+            motor_impulse = cparams.motor_impulse;
+        }
+
         PrismaticVelocityConstraint {
             joint_id,
             mj_lambda1: rb1.active_set_offset,
@@ -183,6 +232,13 @@ impl PrismaticVelocityConstraint {
             limits_impulse: limits_impulse * params.warmstart_coeff,
             limits_forcedirs,
             limits_rhs,
+            limits_inv_lhs,
+            motor_impulse: motor_impulse * params.warmstart_coeff,
+            motor_forcedirs,
+            motor_rhs,
+            motor_inv_lhs,
+            motor_gamma,
+            motor_max_impulse: cparams.max_motor_impulse,
             basis1,
             inv_lhs,
             rhs,
@@ -201,19 +257,24 @@ impl PrismaticVelocityConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = self.impulse.fixed_rows::<U3>(2).into_owned();
 
-        mj_lambda1.linear += self.im1 * lin_impulse;
+        mj_lambda1.linear += lin_impulse.component_mul(&self.im1);
         mj_lambda1.angular += self
             .ii1_sqrt
             .transform_vector(ang_impulse + self.r1.gcross(lin_impulse));
 
-        mj_lambda2.linear -= self.im2 * lin_impulse;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
         if let Some((limits_forcedir1, limits_forcedir2)) = self.limits_forcedirs {
-            mj_lambda1.linear += limits_forcedir1 * (self.im1 * self.limits_impulse);
-            mj_lambda2.linear += limits_forcedir2 * (self.im2 * self.limits_impulse);
+            mj_lambda1.linear += limits_forcedir1.component_mul(&self.im1) * self.limits_impulse;
+            mj_lambda2.linear += limits_forcedir2.component_mul(&self.im2) * self.limits_impulse;
+        }
+
+        if let Some((motor_forcedir1, motor_forcedir2)) = self.motor_forcedirs {
+            mj_lambda1.linear += motor_forcedir1.component_mul(&self.im1) * self.motor_impulse;
+            mj_lambda2.linear += motor_forcedir2.component_mul(&self.im2) * self.motor_impulse;
         }
 
         mj_lambdas[self.mj_lambda1 as usize] = mj_lambda1;
@@ -246,16 +307,37 @@ impl PrismaticVelocityConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = impulse.fixed_rows::<U3>(2).into_owned();
 
-        mj_lambda1.linear += self.im1 * lin_impulse;
+        mj_lambda1.linear += lin_impulse.component_mul(&self.im1);
         mj_lambda1.angular += self
             .ii1_sqrt
             .transform_vector(ang_impulse + self.r1.gcross(lin_impulse));
 
-        mj_lambda2.linear -= self.im2 * lin_impulse;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
+        /*
+         * Joint motor.
+         */
+        if let Some((motor_forcedir1, motor_forcedir2)) = self.motor_forcedirs {
+            let ang_vel1 = self.ii1_sqrt.transform_vector(mj_lambda1.angular);
+            let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+            let lin_dvel = motor_forcedir2.dot(&(mj_lambda2.linear + ang_vel2.gcross(self.r2)))
+                + motor_forcedir1.dot(&(mj_lambda1.linear + ang_vel1.gcross(self.r1)));
+            let new_impulse = (self.motor_impulse
+                + (self.motor_rhs - lin_dvel - self.motor_gamma * self.motor_impulse)
+                    * self.motor_inv_lhs)
+                .max(-self.motor_max_impulse)
+                .min(self.motor_max_impulse);
+            let dimpulse = new_impulse - self.motor_impulse;
+            self.motor_impulse = new_impulse;
+
+            mj_lambda1.linear += motor_forcedir1.component_mul(&self.im1) * dimpulse;
+            mj_lambda2.linear += motor_forcedir2.component_mul(&self.im2) * dimpulse;
+        }
+
         /*
          * Joint limits.
          */
@@ -268,12 +350,12 @@ impl PrismaticVelocityConstraint {
             let lin_dvel = limits_forcedir2.dot(&(mj_lambda2.linear + ang_vel2.gcross(self.r2)))
                 + limits_forcedir1.dot(&(mj_lambda1.linear + ang_vel1.gcross(self.r1)))
                 + self.limits_rhs;
-            let new_impulse = (self.limits_impulse - lin_dvel / (self.im1 + self.im2)).max(0.0);
+            let new_impulse = (self.limits_impulse - lin_dvel * self.limits_inv_lhs).max(0.0);
             let dimpulse = new_impulse - self.limits_impulse;
             self.limits_impulse = new_impulse;
 
-            mj_lambda1.linear += limits_forcedir1 * (self.im1 * dimpulse);
-            mj_lambda2.linear += limits_forcedir2 * (self.im2 * dimpulse);
+            mj_lambda1.linear += limits_forcedir1.component_mul(&self.im1) * dimpulse;
+            mj_lambda2.linear += limits_forcedir2.component_mul(&self.im2) * dimpulse;
         }
 
         mj_lambdas[self.mj_lambda1 as usize] = mj_lambda1;
@@ -285,6 +367,7 @@ impl PrismaticVelocityConstraint {
         if let JointParams::PrismaticJoint(revolute) = &mut joint.params {
             revolute.impulse = self.impulse;
             revolute.limits_impulse = self.limits_impulse;
+            revolute.motor_impulse = self.motor_impulse;
         }
     }
 }
@@ -313,6 +396,14 @@ pub(crate) struct PrismaticVelocityGroundConstraint {
 
     limits_impulse: f32,
     limits_rhs: f32,
+    limits_inv_lhs: f32,
+
+    motor_impulse: f32,
+    motor_forcedir2: Option<Vector<f32>>,
+    motor_rhs: f32,
+    motor_inv_lhs: f32,
+    motor_gamma: f32,
+    motor_max_impulse: f32,
 
     axis2: Vector<f32>,
     #[cfg(feature = "dim2")]
@@ -321,7 +412,7 @@ pub(crate) struct PrismaticVelocityGroundConstraint {
     basis1: Matrix3x2<f32>,
     limits_forcedir2: Option<Vector<f32>>,
 
-    im2: f32,
+    im2: Vector<f32>,
     ii2_sqrt: AngularInertia<f32>,
 }
 
@@ -388,7 +479,7 @@ impl PrismaticVelocityGroundConstraint {
         // simplifications of the computation without introducing
         // much instabilities.
 
-        let im2 = rb2.mass_properties.inv_mass;
+        let im2 = rb2.effective_inv_mass;
         let ii2 = rb2.world_inv_inertia_sqrt.squared();
         let r1 = anchor1 - rb1.world_com;
         let r2 = anchor2 - rb2.world_com;
@@ -402,7 +493,8 @@ impl PrismaticVelocityGroundConstraint {
             let r2_mat_b1 = r2_mat * basis1;
 
             lhs = Matrix5::zeros();
-            let lhs00 = ii2.quadform3x2(&r2_mat_b1).add_diagonal(im2);
+            let lhs00 = SdpMatrix3::from_diagonal_elements(im2).quadform3x2(&basis1)
+                + ii2.quadform3x2(&r2_mat_b1);
             let lhs10 = ii2 * r2_mat_b1;
             let lhs11 = ii2.into_matrix();
             lhs.fixed_slice_mut::<U2, U2>(0, 0)
@@ -414,7 +506,7 @@ impl PrismaticVelocityGroundConstraint {
         #[cfg(feature = "dim2")]
         {
             let b2r2 = basis1.dot(&r2_mat);
-            let m11 = im2 + b2r2 * ii2 * b2r2;
+            let m11 = basis1.dot(&im2.component_mul(&basis1)) + b2r2 * ii2 * b2r2;
             let m12 = basis1.dot(&r2_mat) * ii2;
             let m22 = ii2;
             lhs = SdpMatrix2::new(m11, m12, m22);
@@ -441,6 +533,7 @@ impl PrismaticVelocityGroundConstraint {
         // Setup limit constraint.
         let mut limits_forcedir2 = None;
         let mut limits_rhs = 0.0;
+        let mut limits_inv_lhs = 0.0;
         let mut limits_impulse = 0.0;
 
         if cparams.limits_enabled {
@@ -453,14 +546,43 @@ impl PrismaticVelocityGroundConstraint {
             if dist < cparams.limits[0] {
                 limits_forcedir2 = Some(axis2.into_inner());
                 limits_rhs = anchor_linvel2.dot(&axis2) - anchor_linvel1.dot(&axis1);
+                limits_inv_lhs = crate::utils::inv(axis2.dot(&im2.component_mul(&axis2)));
                 limits_impulse = cparams.limits_impulse;
             } else if dist > cparams.limits[1] {
                 limits_forcedir2 = Some(-axis2.into_inner());
                 limits_rhs = -anchor_linvel2.dot(&axis2) + anchor_linvel1.dot(&axis1);
+                limits_inv_lhs = crate::utils::inv(axis2.dot(&im2.component_mul(&axis2)));
                 limits_impulse = cparams.limits_impulse;
             }
         }
 
+        // Setup motor.
+        let mut motor_forcedir2 = None;
+        let mut motor_rhs = 0.0;
+        let mut motor_inv_lhs = 0.0;
+        let mut motor_gamma = 0.0;
+        let mut motor_impulse = 0.0;
+
+        if cparams.motor_enabled {
+            // The motor quantities are expressed in the joint's frame of reference,
+            // so they have to be negated if the bodies are swapped.
+            let flip_sign = if flipped { -1.0 } else { 1.0 };
+            let dist = (anchor2 - anchor1).dot(&axis1) * flip_sign;
+            let (gamma, bias) = super::motor_coefficients(
+                params.dt(),
+                cparams.motor_stiffness,
+                cparams.motor_damping,
+                dist - cparams.target_motor_pos,
+            );
+            let dvel = (anchor_linvel2.dot(&axis2) - anchor_linvel1.dot(&axis1)) * flip_sign;
+
+            motor_forcedir2 = Some(axis2.into_inner() * flip_sign);
+            motor_rhs = cparams.target_motor_vel - bias - dvel;
+            motor_inv_lhs = crate::utils::inv(axis2.dot(&im2.component_mul(&axis2)) + gamma);
+            motor_gamma = gamma;
+            motor_impulse = cparams.motor_impulse;
+        }
+
         PrismaticVelocityGroundConstraint {
             joint_id,
             mj_lambda2: rb2.active_set_offset,
@@ -475,6 +597,13 @@ impl PrismaticVelocityGroundConstraint {
             axis2: axis2.into_inner(),
             limits_forcedir2,
             limits_rhs,
+            limits_inv_lhs,
+            motor_impulse: motor_impulse * params.warmstart_coeff,
+            motor_forcedir2,
+            motor_rhs,
+            motor_inv_lhs,
+            motor_gamma,
+            motor_max_impulse: cparams.max_motor_impulse,
         }
     }
 
@@ -487,13 +616,17 @@ impl PrismaticVelocityGroundConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = self.impulse.fixed_rows::<U3>(2).into_owned();
 
-        mj_lambda2.linear -= self.im2 * lin_impulse;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
         if let Some(limits_forcedir2) = self.limits_forcedir2 {
-            mj_lambda2.linear += limits_forcedir2 * (self.im2 * self.limits_impulse);
+            mj_lambda2.linear += limits_forcedir2.component_mul(&self.im2) * self.limits_impulse;
+        }
+
+        if let Some(motor_forcedir2) = self.motor_forcedir2 {
+            mj_lambda2.linear += motor_forcedir2.component_mul(&self.im2) * self.motor_impulse;
         }
 
         mj_lambdas[self.mj_lambda2 as usize] = mj_lambda2;
@@ -522,11 +655,29 @@ impl PrismaticVelocityGroundConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = impulse.fixed_rows::<U3>(2).into_owned();
 
-        mj_lambda2.linear -= self.im2 * lin_impulse;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
+        /*
+         * Joint motor.
+         */
+        if let Some(motor_forcedir2) = self.motor_forcedir2 {
+            let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+            let lin_dvel = motor_forcedir2.dot(&(mj_lambda2.linear + ang_vel2.gcross(self.r2)));
+            let new_impulse = (self.motor_impulse
+                + (self.motor_rhs - lin_dvel - self.motor_gamma * self.motor_impulse)
+                    * self.motor_inv_lhs)
+                .max(-self.motor_max_impulse)
+                .min(self.motor_max_impulse);
+            let dimpulse = new_impulse - self.motor_impulse;
+            self.motor_impulse = new_impulse;
+
+            mj_lambda2.linear += motor_forcedir2.component_mul(&self.im2) * dimpulse;
+        }
+
         /*
          * Joint limits.
          */
@@ -537,11 +688,11 @@ impl PrismaticVelocityGroundConstraint {
 
             let lin_dvel = limits_forcedir2.dot(&(mj_lambda2.linear + ang_vel2.gcross(self.r2)))
                 + self.limits_rhs;
-            let new_impulse = (self.limits_impulse - lin_dvel / self.im2).max(0.0);
+            let new_impulse = (self.limits_impulse - lin_dvel * self.limits_inv_lhs).max(0.0);
             let dimpulse = new_impulse - self.limits_impulse;
             self.limits_impulse = new_impulse;
 
-            mj_lambda2.linear += limits_forcedir2 * (self.im2 * dimpulse);
+            mj_lambda2.linear += limits_forcedir2.component_mul(&self.im2) * dimpulse;
         }
 
         mj_lambdas[self.mj_lambda2 as usize] = mj_lambda2;
@@ -553,6 +704,7 @@ impl PrismaticVelocityGroundConstraint {
         if let JointParams::PrismaticJoint(revolute) = &mut joint.params {
             revolute.impulse = self.impulse;
             revolute.limits_impulse = self.limits_impulse;
+            revolute.motor_impulse = self.motor_impulse;
         }
     }
 }
@@ -8,13 +8,16 @@ use crate::math::{
     AngVector, AngularInertia, Isometry, Point, SimdBool, SimdFloat, Vector, SIMD_WIDTH,
 };
 use crate::utils::{WAngularInertia, WCross, WCrossMatrix};
-#[cfg(feature = "dim3")]
-use na::{Cholesky, Matrix3x2, Matrix5, Vector5, U2, U3};
 #[cfg(feature = "dim2")]
 use {
     crate::utils::SdpMatrix2,
     na::{Matrix2, Vector2},
 };
+#[cfg(feature = "dim3")]
+use {
+    crate::utils::SdpMatrix3,
+    na::{Cholesky, Matrix3x2, Matrix5, Vector5, U2, U3},
+};
 
 #[cfg(feature = "dim2")]
 type LinImpulseDim = na::U1;
@@ -48,14 +51,22 @@ pub(crate) struct WPrismaticVelocityConstraint {
     limits_impulse: SimdFloat,
     limits_forcedirs: Option<(Vector<SimdFloat>, Vector<SimdFloat>)>,
     limits_rhs: SimdFloat,
+    limits_inv_lhs: SimdFloat,
+
+    motor_impulse: SimdFloat,
+    motor_forcedirs: Option<(Vector<SimdFloat>, Vector<SimdFloat>)>,
+    motor_rhs: SimdFloat,
+    motor_inv_lhs: SimdFloat,
+    motor_gamma: SimdFloat,
+    motor_max_impulse: SimdFloat,
 
     #[cfg(feature = "dim2")]
     basis1: Vector2<SimdFloat>,
     #[cfg(feature = "dim3")]
     basis1: Matrix3x2<SimdFloat>,
 
-    im1: SimdFloat,
-    im2: SimdFloat,
+    im1: Vector<SimdFloat>,
+    im2: Vector<SimdFloat>,
 
     ii1_sqrt: AngularInertia<SimdFloat>,
     ii2_sqrt: AngularInertia<SimdFloat>,
@@ -73,7 +84,7 @@ impl WPrismaticVelocityConstraint {
         let linvel1 = Vector::from(array![|ii| rbs1[ii].linvel; SIMD_WIDTH]);
         let angvel1 = AngVector::<SimdFloat>::from(array![|ii| rbs1[ii].angvel; SIMD_WIDTH]);
         let world_com1 = Point::from(array![|ii| rbs1[ii].world_com; SIMD_WIDTH]);
-        let im1 = SimdFloat::from(array![|ii| rbs1[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im1 = Vector::from(array![|ii| rbs1[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii1_sqrt = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs1[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         );
@@ -83,7 +94,7 @@ impl WPrismaticVelocityConstraint {
         let linvel2 = Vector::from(array![|ii| rbs2[ii].linvel; SIMD_WIDTH]);
         let angvel2 = AngVector::<SimdFloat>::from(array![|ii| rbs2[ii].angvel; SIMD_WIDTH]);
         let world_com2 = Point::from(array![|ii| rbs2[ii].world_com; SIMD_WIDTH]);
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii2_sqrt = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs2[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         );
@@ -149,8 +160,9 @@ impl WPrismaticVelocityConstraint {
             let r2_mat_b1 = r2_mat * basis1;
 
             lhs = Matrix5::zeros();
-            let lhs00 = ii1.quadform3x2(&r1_mat_b1).add_diagonal(im1)
-                + ii2.quadform3x2(&r2_mat_b1).add_diagonal(im2);
+            let lhs00 = SdpMatrix3::from_diagonal_elements(im1 + im2).quadform3x2(&basis1)
+                + ii1.quadform3x2(&r1_mat_b1)
+                + ii2.quadform3x2(&r2_mat_b1);
             let lhs10 = ii1 * r1_mat_b1 + ii2 * r2_mat_b1;
             let lhs11 = (ii1 + ii2).into_matrix();
             lhs.fixed_slice_mut::<U2, U2>(0, 0)
@@ -163,7 +175,9 @@ impl WPrismaticVelocityConstraint {
         {
             let b1r1 = basis1.dot(&r1_mat);
             let b2r2 = basis1.dot(&r2_mat);
-            let m11 = im1 + im2 + b1r1 * ii1 * b1r1 + b2r2 * ii2 * b2r2;
+            let m11 = basis1.dot(&(im1 + im2).component_mul(&basis1))
+                + b1r1 * ii1 * b1r1
+                + b2r2 * ii2 * b2r2;
             let m12 = basis1.dot(&r1_mat) * ii1 + basis1.dot(&r2_mat) * ii2;
             let m22 = ii1 + ii2;
             lhs = SdpMatrix2::new(m11, m12, m22);
@@ -190,6 +204,7 @@ impl WPrismaticVelocityConstraint {
         // Setup limit constraint.
         let mut limits_forcedirs = None;
         let mut limits_rhs = na::zero();
+        let mut limits_inv_lhs = na::zero();
         let mut limits_impulse = na::zero();
         let limits_enabled = SimdBool::from(array![|ii| cparams[ii].limits_enabled; SIMD_WIDTH]);
 
@@ -212,10 +227,51 @@ impl WPrismaticVelocityConstraint {
             if sign != _0 {
                 limits_forcedirs = Some((axis1 * -sign, axis2 * sign));
                 limits_rhs = (anchor_linvel2.dot(&axis2) - anchor_linvel1.dot(&axis1)) * sign;
+                let lin_lhs =
+                    axis1.dot(&im1.component_mul(&axis1)) + axis2.dot(&im2.component_mul(&axis2));
+                limits_inv_lhs = (_1 / lin_lhs).select(lin_lhs.simd_ne(_0), _0);
                 limits_impulse = lim_impulse.select(min_enabled | max_enabled, _0);
             }
         }
 
+        // Setup motor.
+        let mut motor_forcedirs = None;
+        let mut motor_rhs = na::zero();
+        let mut motor_inv_lhs = na::zero();
+        let mut motor_gamma = na::zero();
+        let mut motor_impulse = na::zero();
+        let mut motor_max_impulse = na::zero();
+        let motor_enabled = SimdBool::from(array![|ii| cparams[ii].motor_enabled; SIMD_WIDTH]);
+
+        if motor_enabled.any() {
+            let _0: SimdFloat = na::zero();
+            let _1: SimdFloat = na::one();
+            let sign = _1.select(motor_enabled, _0);
+            let dist = (anchor2 - anchor1).dot(&axis1);
+            let dvel = anchor_linvel2.dot(&axis2) - anchor_linvel1.dot(&axis1);
+            let coeffs = array![|ii| super::motor_coefficients(
+                params.dt(),
+                cparams[ii].motor_stiffness,
+                cparams[ii].motor_damping,
+                dist.extract(ii) - cparams[ii].target_motor_pos,
+            ); SIMD_WIDTH];
+            let gamma = SimdFloat::from(array![|ii| coeffs[ii].0; SIMD_WIDTH]);
+            let bias = SimdFloat::from(array![|ii| coeffs[ii].1; SIMD_WIDTH]);
+            let target_vel = SimdFloat::from(array![|ii| cparams[ii].target_motor_vel; SIMD_WIDTH]);
+            let impulse = SimdFloat::from(array![|ii| cparams[ii].motor_impulse; SIMD_WIDTH]);
+            let max_impulse =
+                SimdFloat::from(array![|ii| cparams[ii].max_motor_impulse; SIMD_WIDTH]);
+
+            motor_forcedirs = Some((axis1 * -sign, axis2 * sign));
+            motor_rhs = (target_vel - bias - dvel).select(motor_enabled, _0);
+            let lin_lhs =
+                axis1.dot(&im1.component_mul(&axis1)) + axis2.dot(&im2.component_mul(&axis2));
+            motor_inv_lhs = (_1 / (lin_lhs + gamma)).select(motor_enabled, _0);
+            motor_gamma = gamma.select(motor_enabled, _0);
+            motor_impulse = impulse.select(motor_enabled, _0);
+            motor_max_impulse = max_impulse.select(motor_enabled, _0);
+        }
+
         WPrismaticVelocityConstraint {
             joint_id,
             mj_lambda1,
@@ -228,6 +284,13 @@ impl WPrismaticVelocityConstraint {
             limits_impulse: limits_impulse * SimdFloat::splat(params.warmstart_coeff),
             limits_forcedirs,
             limits_rhs,
+            limits_inv_lhs,
+            motor_impulse: motor_impulse * SimdFloat::splat(params.warmstart_coeff),
+            motor_forcedirs,
+            motor_rhs,
+            motor_inv_lhs,
+            motor_gamma,
+            motor_max_impulse,
             basis1,
             inv_lhs,
             rhs,
@@ -260,19 +323,24 @@ impl WPrismaticVelocityConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = self.impulse.fixed_rows::<U3>(2).into_owned();
 
-        mj_lambda1.linear += lin_impulse * self.im1;
+        mj_lambda1.linear += lin_impulse.component_mul(&self.im1);
         mj_lambda1.angular += self
             .ii1_sqrt
             .transform_vector(ang_impulse + self.r1.gcross(lin_impulse));
 
-        mj_lambda2.linear -= lin_impulse * self.im2;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
         if let Some((limits_forcedir1, limits_forcedir2)) = self.limits_forcedirs {
-            mj_lambda1.linear += limits_forcedir1 * (self.im1 * self.limits_impulse);
-            mj_lambda2.linear += limits_forcedir2 * (self.im2 * self.limits_impulse);
+            mj_lambda1.linear += limits_forcedir1.component_mul(&self.im1) * self.limits_impulse;
+            mj_lambda2.linear += limits_forcedir2.component_mul(&self.im2) * self.limits_impulse;
+        }
+
+        if let Some((motor_forcedir1, motor_forcedir2)) = self.motor_forcedirs {
+            mj_lambda1.linear += motor_forcedir1.component_mul(&self.im1) * self.motor_impulse;
+            mj_lambda2.linear += motor_forcedir2.component_mul(&self.im2) * self.motor_impulse;
         }
 
         for ii in 0..SIMD_WIDTH {
@@ -325,16 +393,37 @@ impl WPrismaticVelocityConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = impulse.fixed_rows::<U3>(2).into_owned();
 
-        mj_lambda1.linear += lin_impulse * self.im1;
+        mj_lambda1.linear += lin_impulse.component_mul(&self.im1);
         mj_lambda1.angular += self
             .ii1_sqrt
             .transform_vector(ang_impulse + self.r1.gcross(lin_impulse));
 
-        mj_lambda2.linear -= lin_impulse * self.im2;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
+        /*
+         * Joint motor.
+         */
+        if let Some((motor_forcedir1, motor_forcedir2)) = self.motor_forcedirs {
+            let ang_vel1 = self.ii1_sqrt.transform_vector(mj_lambda1.angular);
+            let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+            let lin_dvel = motor_forcedir2.dot(&(mj_lambda2.linear + ang_vel2.gcross(self.r2)))
+                + motor_forcedir1.dot(&(mj_lambda1.linear + ang_vel1.gcross(self.r1)));
+            let new_impulse = (self.motor_impulse
+                + (self.motor_rhs - lin_dvel - self.motor_gamma * self.motor_impulse)
+                    * self.motor_inv_lhs)
+                .simd_max(-self.motor_max_impulse)
+                .simd_min(self.motor_max_impulse);
+            let dimpulse = new_impulse - self.motor_impulse;
+            self.motor_impulse = new_impulse;
+
+            mj_lambda1.linear += motor_forcedir1.component_mul(&self.im1) * dimpulse;
+            mj_lambda2.linear += motor_forcedir2.component_mul(&self.im2) * dimpulse;
+        }
+
         /*
          * Joint limits.
          */
@@ -348,12 +437,12 @@ impl WPrismaticVelocityConstraint {
                 + limits_forcedir1.dot(&(mj_lambda1.linear + ang_vel1.gcross(self.r1)))
                 + self.limits_rhs;
             let new_impulse =
-                (self.limits_impulse - lin_dvel / (self.im1 + self.im2)).simd_max(na::zero());
+                (self.limits_impulse - lin_dvel * self.limits_inv_lhs).simd_max(na::zero());
             let dimpulse = new_impulse - self.limits_impulse;
             self.limits_impulse = new_impulse;
 
-            mj_lambda1.linear += limits_forcedir1 * (self.im1 * dimpulse);
-            mj_lambda2.linear += limits_forcedir2 * (self.im2 * dimpulse);
+            mj_lambda1.linear += limits_forcedir1.component_mul(&self.im1) * dimpulse;
+            mj_lambda2.linear += limits_forcedir2.component_mul(&self.im2) * dimpulse;
         }
 
         for ii in 0..SIMD_WIDTH {
@@ -372,6 +461,7 @@ impl WPrismaticVelocityConstraint {
             if let JointParams::PrismaticJoint(rev) = &mut joint.params {
                 rev.impulse = self.impulse.extract(ii);
                 rev.limits_impulse = self.limits_impulse.extract(ii);
+                rev.motor_impulse = self.motor_impulse.extract(ii);
             }
         }
     }
@@ -401,6 +491,14 @@ pub(crate) struct WPrismaticVelocityGroundConstraint {
 
     limits_impulse: SimdFloat,
     limits_rhs: SimdFloat,
+    limits_inv_lhs: SimdFloat,
+
+    motor_impulse: SimdFloat,
+    motor_forcedir2: Option<Vector<SimdFloat>>,
+    motor_rhs: SimdFloat,
+    motor_inv_lhs: SimdFloat,
+    motor_gamma: SimdFloat,
+    motor_max_impulse: SimdFloat,
 
     axis2: Vector<SimdFloat>,
     #[cfg(feature = "dim2")]
@@ -409,7 +507,7 @@ pub(crate) struct WPrismaticVelocityGroundConstraint {
     basis1: Matrix3x2<SimdFloat>,
     limits_forcedir2: Option<Vector<SimdFloat>>,
 
-    im2: SimdFloat,
+    im2: Vector<SimdFloat>,
     ii2_sqrt: AngularInertia<SimdFloat>,
 }
 
@@ -431,7 +529,7 @@ impl WPrismaticVelocityGroundConstraint {
         let linvel2 = Vector::from(array![|ii| rbs2[ii].linvel; SIMD_WIDTH]);
         let angvel2 = AngVector::<SimdFloat>::from(array![|ii| rbs2[ii].angvel; SIMD_WIDTH]);
         let world_com2 = Point::from(array![|ii| rbs2[ii].world_com; SIMD_WIDTH]);
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii2_sqrt = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs2[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         );
@@ -503,7 +601,8 @@ impl WPrismaticVelocityGroundConstraint {
             let r2_mat_b1 = r2_mat * basis1;
 
             lhs = Matrix5::zeros();
-            let lhs00 = ii2.quadform3x2(&r2_mat_b1).add_diagonal(im2);
+            let lhs00 = SdpMatrix3::from_diagonal_elements(im2).quadform3x2(&basis1)
+                + ii2.quadform3x2(&r2_mat_b1);
             let lhs10 = ii2 * r2_mat_b1;
             let lhs11 = ii2.into_matrix();
             lhs.fixed_slice_mut::<U2, U2>(0, 0)
@@ -515,7 +614,7 @@ impl WPrismaticVelocityGroundConstraint {
         #[cfg(feature = "dim2")]
         {
             let b2r2 = basis1.dot(&r2_mat);
-            let m11 = im2 + b2r2 * ii2 * b2r2;
+            let m11 = basis1.dot(&im2.component_mul(&basis1)) + b2r2 * ii2 * b2r2;
             let m12 = basis1.dot(&r2_mat) * ii2;
             let m22 = ii2;
             lhs = SdpMatrix2::new(m11, m12, m22);
@@ -542,6 +641,7 @@ impl WPrismaticVelocityGroundConstraint {
         // Setup limit constraint.
         let mut limits_forcedir2 = None;
         let mut limits_rhs = na::zero();
+        let mut limits_inv_lhs = na::zero();
         let mut limits_impulse = na::zero();
         let limits_enabled = SimdBool::from(array![|ii| cparams[ii].limits_enabled; SIMD_WIDTH]);
 
@@ -564,10 +664,53 @@ impl WPrismaticVelocityGroundConstraint {
             if sign != _0 {
                 limits_forcedir2 = Some(axis2 * sign);
                 limits_rhs = anchor_linvel2.dot(&axis2) * sign - anchor_linvel1.dot(&axis1) * sign;
+                let lin_lhs = axis2.dot(&im2.component_mul(&axis2));
+                limits_inv_lhs = (_1 / lin_lhs).select(lin_lhs.simd_ne(_0), _0);
                 limits_impulse = lim_impulse.select(use_min | use_max, _0);
             }
         }
 
+        // Setup motor.
+        let mut motor_forcedir2 = None;
+        let mut motor_rhs = na::zero();
+        let mut motor_inv_lhs = na::zero();
+        let mut motor_gamma = na::zero();
+        let mut motor_impulse = na::zero();
+        let mut motor_max_impulse = na::zero();
+        let motor_enabled = SimdBool::from(array![|ii| cparams[ii].motor_enabled; SIMD_WIDTH]);
+
+        if motor_enabled.any() {
+            let _0: SimdFloat = na::zero();
+            let _1: SimdFloat = na::one();
+            // The motor quantities are expressed in the joint's frame of reference,
+            // so they have to be negated if the bodies are swapped.
+            let flip_sign =
+                SimdFloat::from(array![|ii| if flipped[ii] { -1.0 } else { 1.0 }; SIMD_WIDTH]);
+            let sign = flip_sign.select(motor_enabled, _0);
+            let dist = (anchor2 - anchor1).dot(&axis1) * flip_sign;
+            let dvel = (anchor_linvel2.dot(&axis2) - anchor_linvel1.dot(&axis1)) * flip_sign;
+            let coeffs = array![|ii| super::motor_coefficients(
+                params.dt(),
+                cparams[ii].motor_stiffness,
+                cparams[ii].motor_damping,
+                dist.extract(ii) - cparams[ii].target_motor_pos,
+            ); SIMD_WIDTH];
+            let gamma = SimdFloat::from(array![|ii| coeffs[ii].0; SIMD_WIDTH]);
+            let bias = SimdFloat::from(array![|ii| coeffs[ii].1; SIMD_WIDTH]);
+            let target_vel = SimdFloat::from(array![|ii| cparams[ii].target_motor_vel; SIMD_WIDTH]);
+            let impulse = SimdFloat::from(array![|ii| cparams[ii].motor_impulse; SIMD_WIDTH]);
+            let max_impulse =
+                SimdFloat::from(array![|ii| cparams[ii].max_motor_impulse; SIMD_WIDTH]);
+
+            motor_forcedir2 = Some(axis2 * sign);
+            motor_rhs = (target_vel - bias - dvel).select(motor_enabled, _0);
+            let lin_lhs = axis2.dot(&im2.component_mul(&axis2));
+            motor_inv_lhs = (_1 / (lin_lhs + gamma)).select(motor_enabled, _0);
+            motor_gamma = gamma.select(motor_enabled, _0);
+            motor_impulse = impulse.select(motor_enabled, _0);
+            motor_max_impulse = max_impulse.select(motor_enabled, _0);
+        }
+
         WPrismaticVelocityGroundConstraint {
             joint_id,
             mj_lambda2,
@@ -582,6 +725,13 @@ impl WPrismaticVelocityGroundConstraint {
             axis2,
             limits_forcedir2,
             limits_rhs,
+            limits_inv_lhs,
+            motor_impulse: motor_impulse * SimdFloat::splat(params.warmstart_coeff),
+            motor_forcedir2,
+            motor_rhs,
+            motor_inv_lhs,
+            motor_gamma,
+            motor_max_impulse,
         }
     }
 
@@ -601,13 +751,17 @@ impl WPrismaticVelocityGroundConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = self.impulse.fixed_rows::<U3>(2).into_owned();
 
-        mj_lambda2.linear -= lin_impulse * self.im2;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
         if let Some(limits_forcedir2) = self.limits_forcedir2 {
-            mj_lambda2.linear += limits_forcedir2 * (self.im2 * self.limits_impulse);
+            mj_lambda2.linear += limits_forcedir2.component_mul(&self.im2) * self.limits_impulse;
+        }
+
+        if let Some(motor_forcedir2) = self.motor_forcedir2 {
+            mj_lambda2.linear += motor_forcedir2.component_mul(&self.im2) * self.motor_impulse;
         }
 
         for ii in 0..SIMD_WIDTH {
@@ -646,11 +800,29 @@ impl WPrismaticVelocityGroundConstraint {
         #[cfg(feature = "dim3")]
         let ang_impulse = impulse.fixed_rows::<U3>(2).into_owned();
 
-        mj_lambda2.linear -= lin_impulse * self.im2;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
+        /*
+         * Joint motor.
+         */
+        if let Some(motor_forcedir2) = self.motor_forcedir2 {
+            let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+            let lin_dvel = motor_forcedir2.dot(&(mj_lambda2.linear + ang_vel2.gcross(self.r2)));
+            let new_impulse = (self.motor_impulse
+                + (self.motor_rhs - lin_dvel - self.motor_gamma * self.motor_impulse)
+                    * self.motor_inv_lhs)
+                .simd_max(-self.motor_max_impulse)
+                .simd_min(self.motor_max_impulse);
+            let dimpulse = new_impulse - self.motor_impulse;
+            self.motor_impulse = new_impulse;
+
+            mj_lambda2.linear += motor_forcedir2.component_mul(&self.im2) * dimpulse;
+        }
+
         /*
          * Joint limits.
          */
@@ -661,11 +833,12 @@ impl WPrismaticVelocityGroundConstraint {
 
             let lin_dvel = limits_forcedir2.dot(&(mj_lambda2.linear + ang_vel2.gcross(self.r2)))
                 + self.limits_rhs;
-            let new_impulse = (self.limits_impulse - lin_dvel / self.im2).simd_max(na::zero());
+            let new_impulse =
+                (self.limits_impulse - lin_dvel * self.limits_inv_lhs).simd_max(na::zero());
             let dimpulse = new_impulse - self.limits_impulse;
             self.limits_impulse = new_impulse;
 
-            mj_lambda2.linear += limits_forcedir2 * (self.im2 * dimpulse);
+            mj_lambda2.linear += limits_forcedir2.component_mul(&self.im2) * dimpulse;
         }
 
         for ii in 0..SIMD_WIDTH {
@@ -681,6 +854,7 @@ impl WPrismaticVelocityGroundConstraint {
             if let JointParams::PrismaticJoint(rev) = &mut joint.params {
                 rev.impulse = self.impulse.extract(ii);
                 rev.limits_impulse = self.limits_impulse.extract(ii);
+                rev.motor_impulse = self.motor_impulse.extract(ii);
             }
         }
     }
@@ -1,6 +1,7 @@
 use crate::dynamics::{IntegrationParameters, RevoluteJoint, RigidBody};
 use crate::math::{AngularInertia, Isometry, Point, Rotation, Vector};
-use crate::utils::WAngularInertia;
+use crate::utils::{WAngularInertia, WDot};
+#[cfg(feature = "dim3")]
 use na::Unit;
 
 #[derive(Debug)]
@@ -8,30 +9,42 @@ pub(crate) struct RevolutePositionConstraint {
     position1: usize,
     position2: usize,
 
-    im1: f32,
-    im2: f32,
+    im1: Vector<f32>,
+    im2: Vector<f32>,
 
     ii1: AngularInertia<f32>,
     ii2: AngularInertia<f32>,
 
-    lin_inv_lhs: f32,
+    lin_inv_lhs: Vector<f32>,
+    #[cfg(feature = "dim3")]
     ang_inv_lhs: AngularInertia<f32>,
 
     local_anchor1: Point<f32>,
     local_anchor2: Point<f32>,
 
+    #[cfg(feature = "dim3")]
     local_axis1: Unit<Vector<f32>>,
+    #[cfg(feature = "dim3")]
     local_axis2: Unit<Vector<f32>>,
+
+    #[cfg(feature = "dim2")]
+    reference_angle: f32,
+    #[cfg(feature = "dim3")]
+    local_basis1: Vector<f32>,
+    #[cfg(feature = "dim3")]
+    local_basis2: Vector<f32>,
+
+    limits_enabled: bool,
+    limits: [f32; 2],
 }
 
 impl RevolutePositionConstraint {
     pub fn from_params(rb1: &RigidBody, rb2: &RigidBody, cparams: &RevoluteJoint) -> Self {
         let ii1 = rb1.world_inv_inertia_sqrt.squared();
         let ii2 = rb2.world_inv_inertia_sqrt.squared();
-        let im1 = rb1.mass_properties.inv_mass;
-        let im2 = rb2.mass_properties.inv_mass;
-        let lin_inv_lhs = 1.0 / (im1 + im2);
-        let ang_inv_lhs = (ii1 + ii2).inverse();
+        let im1 = rb1.effective_inv_mass;
+        let im2 = rb2.effective_inv_mass;
+        let lin_inv_lhs = (im1 + im2).map(crate::utils::inv);
 
         Self {
             im1,
@@ -39,11 +52,22 @@ impl RevolutePositionConstraint {
             ii1,
             ii2,
             lin_inv_lhs,
-            ang_inv_lhs,
+            #[cfg(feature = "dim3")]
+            ang_inv_lhs: (ii1 + ii2).inverse(),
             local_anchor1: cparams.local_anchor1,
             local_anchor2: cparams.local_anchor2,
+            #[cfg(feature = "dim3")]
             local_axis1: cparams.local_axis1,
+            #[cfg(feature = "dim3")]
             local_axis2: cparams.local_axis2,
+            #[cfg(feature = "dim2")]
+            reference_angle: cparams.reference_angle,
+            #[cfg(feature = "dim3")]
+            local_basis1: cparams.basis1[0],
+            #[cfg(feature = "dim3")]
+            local_basis2: cparams.basis2[0],
+            limits_enabled: cparams.limits_enabled,
+            limits: cparams.limits,
             position1: rb1.active_set_offset,
             position2: rb2.active_set_offset,
         }
@@ -53,27 +77,64 @@ impl RevolutePositionConstraint {
         let mut position1 = positions[self.position1 as usize];
         let mut position2 = positions[self.position2 as usize];
 
-        let axis1 = position1 * self.local_axis1;
-        let axis2 = position2 * self.local_axis2;
-        let delta_rot =
-            Rotation::rotation_between_axis(&axis1, &axis2).unwrap_or(Rotation::identity());
-        let ang_error = delta_rot.scaled_axis() * params.joint_erp;
-        let ang_impulse = self.ang_inv_lhs.transform_vector(ang_error);
+        // In 2D, the rotation axes are always aligned.
+        #[cfg(feature = "dim3")]
+        {
+            let axis1 = position1 * self.local_axis1;
+            let axis2 = position2 * self.local_axis2;
+            let delta_rot =
+                Rotation::rotation_between_axis(&axis1, &axis2).unwrap_or(Rotation::identity());
+            let ang_error = delta_rot.scaled_axis() * params.joint_erp;
+            let ang_impulse = self.ang_inv_lhs.transform_vector(ang_error);
+
+            position1.rotation =
+                Rotation::new(self.ii1.transform_vector(ang_impulse)) * position1.rotation;
+            position2.rotation =
+                Rotation::new(self.ii2.transform_vector(-ang_impulse)) * position2.rotation;
+        }
+
+        if self.limits_enabled {
+            #[cfg(feature = "dim2")]
+            let (axis1, angle) = {
+                let frame1 = position1.rotation * Rotation::new(self.reference_angle);
+                (1.0, frame1.angle_to(&position2.rotation))
+            };
+            #[cfg(feature = "dim3")]
+            let (axis1, angle) = {
+                let axis1 = position1 * self.local_axis1;
+                let basis1 = position1 * self.local_basis1;
+                let basis2 = position2 * self.local_basis2;
+                let angle = axis1.dot(&basis1.cross(&basis2)).atan2(basis1.dot(&basis2));
+                (axis1.into_inner(), angle)
+            };
+            let limit_err = if angle < self.limits[0] {
+                angle - self.limits[0]
+            } else if angle > self.limits[1] {
+                angle - self.limits[1]
+            } else {
+                0.0
+            };
 
-        position1.rotation =
-            Rotation::new(self.ii1.transform_vector(ang_impulse)) * position1.rotation;
-        position2.rotation =
-            Rotation::new(self.ii2.transform_vector(-ang_impulse)) * position2.rotation;
+            if limit_err != 0.0 {
+                let ii1_axis = self.ii1.transform_vector(axis1);
+                let ii2_axis = self.ii2.transform_vector(axis1);
+                let inv_lhs = crate::utils::inv(axis1.gdot(ii1_axis + ii2_axis));
+                let ang_impulse = limit_err * params.joint_erp * inv_lhs;
+
+                position1.rotation = Rotation::new(ii1_axis * ang_impulse) * position1.rotation;
+                position2.rotation = Rotation::new(ii2_axis * -ang_impulse) * position2.rotation;
+            }
+        }
 
         let anchor1 = position1 * self.local_anchor1;
         let anchor2 = position2 * self.local_anchor2;
 
         let delta_tra = anchor2 - anchor1;
         let lin_error = delta_tra * params.joint_erp;
-        let lin_impulse = self.lin_inv_lhs * lin_error;
+        let lin_impulse = lin_error.component_mul(&self.lin_inv_lhs);
 
-        position1.translation.vector += self.im1 * lin_impulse;
-        position2.translation.vector -= self.im2 * lin_impulse;
+        position1.translation.vector += lin_impulse.component_mul(&self.im1);
+        position2.translation.vector -= lin_impulse.component_mul(&self.im2);
 
         positions[self.position1 as usize] = position1;
         positions[self.position2 as usize] = position2;
@@ -85,8 +146,20 @@ pub(crate) struct RevolutePositionGroundConstraint {
     position2: usize,
     anchor1: Point<f32>,
     local_anchor2: Point<f32>,
+    #[cfg(feature = "dim2")]
+    rotation1: Rotation<f32>,
+    #[cfg(feature = "dim3")]
     axis1: Unit<Vector<f32>>,
+    #[cfg(feature = "dim3")]
     local_axis2: Unit<Vector<f32>>,
+    #[cfg(feature = "dim3")]
+    basis1: Vector<f32>,
+    #[cfg(feature = "dim3")]
+    local_basis2: Vector<f32>,
+    im2: Vector<f32>,
+    lin_inv_lhs: Vector<f32>,
+    limits_enabled: bool,
+    limits: [f32; 2],
 }
 
 impl RevolutePositionGroundConstraint {
@@ -98,26 +171,64 @@ impl RevolutePositionGroundConstraint {
     ) -> Self {
         let anchor1;
         let local_anchor2;
-        let axis1;
-        let local_axis2;
+        #[cfg(feature = "dim3")]
+        let (axis1, local_axis2, basis1, local_basis2);
+        let limits;
+        #[cfg(feature = "dim2")]
+        let rotation1;
 
         if flipped {
             anchor1 = rb1.predicted_position * cparams.local_anchor2;
             local_anchor2 = cparams.local_anchor1;
-            axis1 = rb1.predicted_position * cparams.local_axis2;
-            local_axis2 = cparams.local_axis1;
+            #[cfg(feature = "dim3")]
+            {
+                axis1 = rb1.predicted_position * cparams.local_axis2;
+                local_axis2 = cparams.local_axis1;
+                basis1 = rb1.predicted_position * cparams.basis2[0];
+                local_basis2 = cparams.basis1[0];
+            }
+            // The angle measured from the second body is the opposite of the joint's angle.
+            limits = [-cparams.limits[1], -cparams.limits[0]];
+            #[cfg(feature = "dim2")]
+            {
+                rotation1 =
+                    rb1.predicted_position.rotation * Rotation::new(-cparams.reference_angle);
+            }
         } else {
             anchor1 = rb1.predicted_position * cparams.local_anchor1;
             local_anchor2 = cparams.local_anchor2;
-            axis1 = rb1.predicted_position * cparams.local_axis1;
-            local_axis2 = cparams.local_axis2;
+            #[cfg(feature = "dim3")]
+            {
+                axis1 = rb1.predicted_position * cparams.local_axis1;
+                local_axis2 = cparams.local_axis2;
+                basis1 = rb1.predicted_position * cparams.basis1[0];
+                local_basis2 = cparams.basis2[0];
+            }
+            limits = cparams.limits;
+            #[cfg(feature = "dim2")]
+            {
+                rotation1 =
+                    rb1.predicted_position.rotation * Rotation::new(cparams.reference_angle);
+            }
         };
 
         Self {
             anchor1,
             local_anchor2,
+            #[cfg(feature = "dim2")]
+            rotation1,
+            #[cfg(feature = "dim3")]
             axis1,
+            #[cfg(feature = "dim3")]
             local_axis2,
+            #[cfg(feature = "dim3")]
+            basis1,
+            #[cfg(feature = "dim3")]
+            local_basis2,
+            im2: rb2.effective_inv_mass,
+            lin_inv_lhs: rb2.effective_inv_mass.map(crate::utils::inv),
+            limits_enabled: cparams.limits_enabled,
+            limits,
             position2: rb2.active_set_offset,
         }
     }
@@ -125,17 +236,47 @@ impl RevolutePositionGroundConstraint {
     pub fn solve(&self, params: &IntegrationParameters, positions: &mut [Isometry<f32>]) {
         let mut position2 = positions[self.position2 as usize];
 
-        let axis2 = position2 * self.local_axis2;
+        #[cfg(feature = "dim3")]
+        {
+            let axis2 = position2 * self.local_axis2;
+
+            let delta_rot =
+                Rotation::scaled_rotation_between_axis(&axis2, &self.axis1, params.joint_erp)
+                    .unwrap_or(Rotation::identity());
+            position2.rotation = delta_rot * position2.rotation;
+        }
 
-        let delta_rot =
-            Rotation::scaled_rotation_between_axis(&axis2, &self.axis1, params.joint_erp)
-                .unwrap_or(Rotation::identity());
-        position2.rotation = delta_rot * position2.rotation;
+        if self.limits_enabled {
+            #[cfg(feature = "dim2")]
+            let (axis1, angle) = (1.0, self.rotation1.angle_to(&position2.rotation));
+            #[cfg(feature = "dim3")]
+            let (axis1, angle) = {
+                let basis2 = position2 * self.local_basis2;
+                let angle = self
+                    .axis1
+                    .dot(&self.basis1.cross(&basis2))
+                    .atan2(self.basis1.dot(&basis2));
+                (self.axis1.into_inner(), angle)
+            };
+            let limit_err = if angle < self.limits[0] {
+                angle - self.limits[0]
+            } else if angle > self.limits[1] {
+                angle - self.limits[1]
+            } else {
+                0.0
+            };
+
+            if limit_err != 0.0 {
+                position2.rotation =
+                    Rotation::new(axis1 * (-limit_err * params.joint_erp)) * position2.rotation;
+            }
+        }
 
         let anchor2 = position2 * self.local_anchor2;
         let delta_tra = anchor2 - self.anchor1;
         let lin_error = delta_tra * params.joint_erp;
-        position2.translation.vector -= lin_error;
+        let lin_impulse = lin_error.component_mul(&self.lin_inv_lhs);
+        position2.translation.vector -= lin_impulse.component_mul(&self.im2);
 
         positions[self.position2 as usize] = position2;
     }
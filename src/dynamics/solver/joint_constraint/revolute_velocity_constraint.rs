@@ -2,9 +2,12 @@ use crate::dynamics::solver::DeltaVel;
 use crate::dynamics::{
     IntegrationParameters, JointGraphEdge, JointIndex, JointParams, RevoluteJoint, RigidBody,
 };
-use crate::math::{AngularInertia, Vector};
-use crate::utils::{WAngularInertia, WCross, WCrossMatrix};
+use crate::math::{AngVector, AngularInertia, Vector};
+use crate::utils::{WAngularInertia, WCross, WCrossMatrix, WDot};
+#[cfg(feature = "dim3")]
 use na::{Cholesky, Matrix3x2, Matrix5, Vector5, U2, U3};
+#[cfg(feature = "dim2")]
+use {crate::math::SdpMatrix, na::Vector2};
 
 #[derive(Debug)]
 pub(crate) struct RevoluteVelocityConstraint {
@@ -16,14 +19,37 @@ pub(crate) struct RevoluteVelocityConstraint {
     r1: Vector<f32>,
     r2: Vector<f32>,
 
+    #[cfg(feature = "dim3")]
     inv_lhs: Matrix5<f32>,
+    #[cfg(feature = "dim3")]
     rhs: Vector5<f32>,
+    #[cfg(feature = "dim3")]
     impulse: Vector5<f32>,
 
+    #[cfg(feature = "dim2")]
+    inv_lhs: SdpMatrix<f32>,
+    #[cfg(feature = "dim2")]
+    rhs: Vector2<f32>,
+    #[cfg(feature = "dim2")]
+    impulse: Vector2<f32>,
+
+    #[cfg(feature = "dim3")]
     basis1: Matrix3x2<f32>,
 
-    im1: f32,
-    im2: f32,
+    motor_impulse: f32,
+    motor_axis1: Option<AngVector<f32>>,
+    motor_rhs: f32,
+    motor_inv_lhs: f32,
+    motor_gamma: f32,
+    motor_max_impulse: f32,
+
+    limits_impulse: f32,
+    limits_axis1: Option<AngVector<f32>>,
+    limits_rhs: f32,
+    limits_inv_lhs: f32,
+
+    im1: Vector<f32>,
+    im2: Vector<f32>,
 
     ii1_sqrt: AngularInertia<f32>,
     ii2_sqrt: AngularInertia<f32>,
@@ -40,6 +66,7 @@ impl RevoluteVelocityConstraint {
         // Linear part.
         let anchor1 = rb1.position * cparams.local_anchor1;
         let anchor2 = rb2.position * cparams.local_anchor2;
+        #[cfg(feature = "dim3")]
         let basis1 = Matrix3x2::from_columns(&[
             rb1.position * cparams.basis1[0],
             rb1.position * cparams.basis1[1],
@@ -52,8 +79,8 @@ impl RevoluteVelocityConstraint {
         //        let basis2 = r21 * basis1;
         // NOTE: to simplify, we use basis2 = basis1.
         // Though we may want to test if that does not introduce any instability.
-        let im1 = rb1.mass_properties.inv_mass;
-        let im2 = rb2.mass_properties.inv_mass;
+        let im1 = rb1.effective_inv_mass;
+        let im2 = rb2.effective_inv_mass;
 
         let ii1 = rb1.world_inv_inertia_sqrt.squared();
         let r1 = anchor1 - rb1.world_com;
@@ -63,24 +90,99 @@ impl RevoluteVelocityConstraint {
         let r2 = anchor2 - rb2.world_com;
         let r2_mat = r2.gcross_matrix();
 
-        let mut lhs = Matrix5::zeros();
-        let lhs00 =
-            ii2.quadform(&r2_mat).add_diagonal(im2) + ii1.quadform(&r1_mat).add_diagonal(im1);
-        let lhs10 = basis1.tr_mul(&(ii2 * r2_mat + ii1 * r1_mat));
-        let lhs11 = (ii1 + ii2).quadform3x2(&basis1).into_matrix();
-
-        // Note that cholesky won't read the upper-right part
-        // of lhs so we don't have to fill it.
-        lhs.fixed_slice_mut::<U3, U3>(0, 0)
-            .copy_from(&lhs00.into_matrix());
-        lhs.fixed_slice_mut::<U2, U3>(3, 0).copy_from(&lhs10);
-        lhs.fixed_slice_mut::<U2, U2>(3, 3).copy_from(&lhs11);
+        #[cfg(feature = "dim3")]
+        let inv_lhs = {
+            let mut lhs = Matrix5::zeros();
+            let lhs00 = ii2.quadform(&r2_mat).add_diagonal_elements(im2)
+                + ii1.quadform(&r1_mat).add_diagonal_elements(im1);
+            let lhs10 = basis1.tr_mul(&(ii2 * r2_mat + ii1 * r1_mat));
+            let lhs11 = (ii1 + ii2).quadform3x2(&basis1).into_matrix();
+
+            // Note that cholesky won't read the upper-right part
+            // of lhs so we don't have to fill it.
+            lhs.fixed_slice_mut::<U3, U3>(0, 0)
+                .copy_from(&lhs00.into_matrix());
+            lhs.fixed_slice_mut::<U2, U3>(3, 0).copy_from(&lhs10);
+            lhs.fixed_slice_mut::<U2, U2>(3, 3).copy_from(&lhs11);
+
+            Cholesky::new_unchecked(lhs).inverse()
+        };
 
-        let inv_lhs = Cholesky::new_unchecked(lhs).inverse();
+        // In 2D we only have the linear part, so we just unroll
+        // the computation like for the ball joint.
+        #[cfg(feature = "dim2")]
+        let inv_lhs = {
+            let m11 = im1.x + im2.x + r1_mat.x * r1_mat.x * ii1 + r2_mat.x * r2_mat.x * ii2;
+            let m12 = r1_mat.x * r1_mat.y * ii1 + r2_mat.x * r2_mat.y * ii2;
+            let m22 = im1.y + im2.y + r1_mat.y * r1_mat.y * ii1 + r2_mat.y * r2_mat.y * ii2;
+            SdpMatrix::new(m11, m12, m22).inverse_unchecked()
+        };
 
         let lin_rhs = rb2.linvel + rb2.angvel.gcross(r2) - rb1.linvel - rb1.angvel.gcross(r1);
-        let ang_rhs = basis1.tr_mul(&(rb2.angvel - rb1.angvel));
-        let rhs = Vector5::new(lin_rhs.x, lin_rhs.y, lin_rhs.z, ang_rhs.x, ang_rhs.y);
+        #[cfg(feature = "dim3")]
+        let rhs = {
+            let ang_rhs = basis1.tr_mul(&(rb2.angvel - rb1.angvel));
+            Vector5::new(lin_rhs.x, lin_rhs.y, lin_rhs.z, ang_rhs.x, ang_rhs.y)
+        };
+        #[cfg(feature = "dim2")]
+        let rhs = lin_rhs;
+
+        // Setup motor and limits.
+        let mut motor_axis1 = None;
+        let mut motor_rhs = 0.0;
+        let mut motor_inv_lhs = 0.0;
+        let mut motor_gamma = 0.0;
+        let mut motor_impulse = 0.0;
+        let mut limits_axis1 = None;
+        let mut limits_rhs = 0.0;
+        let mut limits_inv_lhs = 0.0;
+        let mut limits_impulse = 0.0;
+
+        if cparams.motor_enabled || cparams.limits_enabled {
+            #[cfg(feature = "dim2")]
+            let axis1: AngVector<f32> = 1.0;
+            #[cfg(feature = "dim3")]
+            let axis1 = (rb1.position * cparams.local_axis1).into_inner();
+            let angle = cparams.estimate_angle(&rb1.position, &rb2.position);
+            let dvel = axis1.gdot(rb2.angvel - rb1.angvel);
+            let ii1_axis = rb1.world_inv_inertia_sqrt.transform_vector(axis1);
+            let ii2_axis = rb2.world_inv_inertia_sqrt.transform_vector(axis1);
+            let eff_inv = ii1_axis.gdot(ii1_axis) + ii2_axis.gdot(ii2_axis);
+
+            if cparams.motor_enabled {
+                let (gamma, bias) = super::motor_coefficients(
+                    params.dt(),
+                    cparams.motor_stiffness,
+                    cparams.motor_damping,
+                    angle - cparams.target_motor_pos,
+                );
+
+                motor_axis1 = Some(axis1);
+                motor_rhs = cparams.target_motor_vel - bias - dvel;
+                motor_inv_lhs = crate::utils::inv(eff_inv + gamma);
+                motor_gamma = gamma;
+                motor_impulse = cparams.motor_impulse;
+            }
+
+            // FIXME: we should allow both limits to be active at
+            // the same time, and allow predictive constraint activation.
+            if cparams.limits_enabled {
+                let sign = if angle < cparams.limits[0] {
+                    1.0
+                } else if angle > cparams.limits[1] {
+                    -1.0
+                } else {
+                    0.0
+                };
+
+                if sign != 0.0 {
+                    limits_axis1 = Some(axis1 * sign);
+                    limits_rhs = dvel * sign;
+                    limits_inv_lhs = crate::utils::inv(eff_inv);
+                    limits_impulse = cparams.limits_impulse;
+                }
+            }
+        }
 
         RevoluteVelocityConstraint {
             joint_id,
@@ -88,10 +190,21 @@ impl RevoluteVelocityConstraint {
             mj_lambda2: rb2.active_set_offset,
             im1,
             ii1_sqrt: rb1.world_inv_inertia_sqrt,
+            #[cfg(feature = "dim3")]
             basis1,
             im2,
             ii2_sqrt: rb2.world_inv_inertia_sqrt,
             impulse: cparams.impulse * params.warmstart_coeff,
+            motor_impulse: motor_impulse * params.warmstart_coeff,
+            motor_axis1,
+            motor_rhs,
+            motor_inv_lhs,
+            motor_gamma,
+            motor_max_impulse: cparams.max_motor_impulse,
+            limits_impulse: limits_impulse * params.warmstart_coeff,
+            limits_axis1,
+            limits_rhs,
+            limits_inv_lhs,
             inv_lhs,
             rhs,
             r1,
@@ -103,19 +216,37 @@ impl RevoluteVelocityConstraint {
         let mut mj_lambda1 = mj_lambdas[self.mj_lambda1 as usize];
         let mut mj_lambda2 = mj_lambdas[self.mj_lambda2 as usize];
 
+        #[cfg(feature = "dim3")]
         let lin_impulse = self.impulse.fixed_rows::<U3>(0).into_owned();
+        #[cfg(feature = "dim3")]
         let ang_impulse = self.basis1 * self.impulse.fixed_rows::<U2>(3).into_owned();
+        #[cfg(feature = "dim2")]
+        let lin_impulse = self.impulse;
+        #[cfg(feature = "dim2")]
+        let ang_impulse = 0.0;
 
-        mj_lambda1.linear += self.im1 * lin_impulse;
+        mj_lambda1.linear += lin_impulse.component_mul(&self.im1);
         mj_lambda1.angular += self
             .ii1_sqrt
             .transform_vector(ang_impulse + self.r1.gcross(lin_impulse));
 
-        mj_lambda2.linear -= self.im2 * lin_impulse;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
+        if let Some(motor_axis1) = self.motor_axis1 {
+            let ang_impulse = motor_axis1 * self.motor_impulse;
+            mj_lambda1.angular -= self.ii1_sqrt.transform_vector(ang_impulse);
+            mj_lambda2.angular += self.ii2_sqrt.transform_vector(ang_impulse);
+        }
+
+        if let Some(limits_axis1) = self.limits_axis1 {
+            let ang_impulse = limits_axis1 * self.limits_impulse;
+            mj_lambda1.angular -= self.ii1_sqrt.transform_vector(ang_impulse);
+            mj_lambda2.angular += self.ii2_sqrt.transform_vector(ang_impulse);
+        }
+
         mj_lambdas[self.mj_lambda1 as usize] = mj_lambda1;
         mj_lambdas[self.mj_lambda2 as usize] = mj_lambda2;
     }
@@ -129,24 +260,72 @@ impl RevoluteVelocityConstraint {
         let lin_dvel = mj_lambda2.linear + ang_vel2.gcross(self.r2)
             - mj_lambda1.linear
             - ang_vel1.gcross(self.r1);
-        let ang_dvel = self.basis1.tr_mul(&(ang_vel2 - ang_vel1));
-        let rhs =
-            Vector5::new(lin_dvel.x, lin_dvel.y, lin_dvel.z, ang_dvel.x, ang_dvel.y) + self.rhs;
+        #[cfg(feature = "dim3")]
+        let rhs = {
+            let ang_dvel = self.basis1.tr_mul(&(ang_vel2 - ang_vel1));
+            Vector5::new(lin_dvel.x, lin_dvel.y, lin_dvel.z, ang_dvel.x, ang_dvel.y) + self.rhs
+        };
+        #[cfg(feature = "dim2")]
+        let rhs = lin_dvel + self.rhs;
         let impulse = self.inv_lhs * rhs;
         self.impulse += impulse;
+        #[cfg(feature = "dim3")]
         let lin_impulse = impulse.fixed_rows::<U3>(0).into_owned();
+        #[cfg(feature = "dim3")]
         let ang_impulse = self.basis1 * impulse.fixed_rows::<U2>(3).into_owned();
+        #[cfg(feature = "dim2")]
+        let lin_impulse = impulse;
+        #[cfg(feature = "dim2")]
+        let ang_impulse = 0.0;
 
-        mj_lambda1.linear += self.im1 * lin_impulse;
+        mj_lambda1.linear += lin_impulse.component_mul(&self.im1);
         mj_lambda1.angular += self
             .ii1_sqrt
             .transform_vector(ang_impulse + self.r1.gcross(lin_impulse));
 
-        mj_lambda2.linear -= self.im2 * lin_impulse;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
+        /*
+         * Joint motor.
+         */
+        if let Some(motor_axis1) = self.motor_axis1 {
+            let ang_vel1 = self.ii1_sqrt.transform_vector(mj_lambda1.angular);
+            let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+            let ang_dvel = motor_axis1.gdot(ang_vel2 - ang_vel1);
+            let new_impulse = (self.motor_impulse
+                + (self.motor_rhs - ang_dvel - self.motor_gamma * self.motor_impulse)
+                    * self.motor_inv_lhs)
+                .max(-self.motor_max_impulse)
+                .min(self.motor_max_impulse);
+            let dimpulse = new_impulse - self.motor_impulse;
+            self.motor_impulse = new_impulse;
+
+            let ang_impulse = motor_axis1 * dimpulse;
+            mj_lambda1.angular -= self.ii1_sqrt.transform_vector(ang_impulse);
+            mj_lambda2.angular += self.ii2_sqrt.transform_vector(ang_impulse);
+        }
+
+        /*
+         * Joint limits.
+         */
+        if let Some(limits_axis1) = self.limits_axis1 {
+            let ang_vel1 = self.ii1_sqrt.transform_vector(mj_lambda1.angular);
+            let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+            let ang_dvel = limits_axis1.gdot(ang_vel2 - ang_vel1) + self.limits_rhs;
+            let new_impulse = (self.limits_impulse - ang_dvel * self.limits_inv_lhs).max(0.0);
+            let dimpulse = new_impulse - self.limits_impulse;
+            self.limits_impulse = new_impulse;
+
+            let ang_impulse = limits_axis1 * dimpulse;
+            mj_lambda1.angular -= self.ii1_sqrt.transform_vector(ang_impulse);
+            mj_lambda2.angular += self.ii2_sqrt.transform_vector(ang_impulse);
+        }
+
         mj_lambdas[self.mj_lambda1 as usize] = mj_lambda1;
         mj_lambdas[self.mj_lambda2 as usize] = mj_lambda2;
     }
@@ -155,6 +334,8 @@ impl RevoluteVelocityConstraint {
         let joint = &mut joints_all[self.joint_id].weight;
         if let JointParams::RevoluteJoint(revolute) = &mut joint.params {
             revolute.impulse = self.impulse;
+            revolute.motor_impulse = self.motor_impulse;
+            revolute.limits_impulse = self.limits_impulse;
         }
     }
 }
@@ -167,13 +348,36 @@ pub(crate) struct RevoluteVelocityGroundConstraint {
 
     r2: Vector<f32>,
 
+    #[cfg(feature = "dim3")]
     inv_lhs: Matrix5<f32>,
+    #[cfg(feature = "dim3")]
     rhs: Vector5<f32>,
+    #[cfg(feature = "dim3")]
     impulse: Vector5<f32>,
 
+    #[cfg(feature = "dim2")]
+    inv_lhs: SdpMatrix<f32>,
+    #[cfg(feature = "dim2")]
+    rhs: Vector2<f32>,
+    #[cfg(feature = "dim2")]
+    impulse: Vector2<f32>,
+
+    #[cfg(feature = "dim3")]
     basis1: Matrix3x2<f32>,
 
-    im2: f32,
+    motor_impulse: f32,
+    motor_axis2: Option<AngVector<f32>>,
+    motor_rhs: f32,
+    motor_inv_lhs: f32,
+    motor_gamma: f32,
+    motor_max_impulse: f32,
+
+    limits_impulse: f32,
+    limits_axis2: Option<AngVector<f32>>,
+    limits_rhs: f32,
+    limits_inv_lhs: f32,
+
+    im2: Vector<f32>,
 
     ii2_sqrt: AngularInertia<f32>,
 }
@@ -189,22 +393,29 @@ impl RevoluteVelocityGroundConstraint {
     ) -> Self {
         let anchor2;
         let anchor1;
+        #[cfg(feature = "dim3")]
         let basis1;
 
         if flipped {
             anchor1 = rb1.position * cparams.local_anchor2;
             anchor2 = rb2.position * cparams.local_anchor1;
-            basis1 = Matrix3x2::from_columns(&[
-                rb1.position * cparams.basis2[0],
-                rb1.position * cparams.basis2[1],
-            ]);
+            #[cfg(feature = "dim3")]
+            {
+                basis1 = Matrix3x2::from_columns(&[
+                    rb1.position * cparams.basis2[0],
+                    rb1.position * cparams.basis2[1],
+                ]);
+            }
         } else {
             anchor1 = rb1.position * cparams.local_anchor1;
             anchor2 = rb2.position * cparams.local_anchor2;
-            basis1 = Matrix3x2::from_columns(&[
-                rb1.position * cparams.basis1[0],
-                rb1.position * cparams.basis1[1],
-            ]);
+            #[cfg(feature = "dim3")]
+            {
+                basis1 = Matrix3x2::from_columns(&[
+                    rb1.position * cparams.basis1[0],
+                    rb1.position * cparams.basis1[1],
+                ]);
+            }
         };
 
         //        let r21 = Rotation::rotation_between_axis(&axis1, &axis2)
@@ -212,29 +423,116 @@ impl RevoluteVelocityGroundConstraint {
         //            .to_rotation_matrix()
         //            .into_inner();
         //        let basis2 = /*r21 * */ basis1;
-        let im2 = rb2.mass_properties.inv_mass;
+        let im2 = rb2.effective_inv_mass;
         let ii2 = rb2.world_inv_inertia_sqrt.squared();
         let r1 = anchor1 - rb1.world_com;
         let r2 = anchor2 - rb2.world_com;
         let r2_mat = r2.gcross_matrix();
 
-        let mut lhs = Matrix5::zeros();
-        let lhs00 = ii2.quadform(&r2_mat).add_diagonal(im2);
-        let lhs10 = basis1.tr_mul(&(ii2 * r2_mat));
-        let lhs11 = ii2.quadform3x2(&basis1).into_matrix();
-
-        // Note that cholesky won't read the upper-right part
-        // of lhs so we don't have to fill it.
-        lhs.fixed_slice_mut::<U3, U3>(0, 0)
-            .copy_from(&lhs00.into_matrix());
-        lhs.fixed_slice_mut::<U2, U3>(3, 0).copy_from(&lhs10);
-        lhs.fixed_slice_mut::<U2, U2>(3, 3).copy_from(&lhs11);
+        #[cfg(feature = "dim3")]
+        let inv_lhs = {
+            let mut lhs = Matrix5::zeros();
+            let lhs00 = ii2.quadform(&r2_mat).add_diagonal_elements(im2);
+            let lhs10 = basis1.tr_mul(&(ii2 * r2_mat));
+            let lhs11 = ii2.quadform3x2(&basis1).into_matrix();
+
+            // Note that cholesky won't read the upper-right part
+            // of lhs so we don't have to fill it.
+            lhs.fixed_slice_mut::<U3, U3>(0, 0)
+                .copy_from(&lhs00.into_matrix());
+            lhs.fixed_slice_mut::<U2, U3>(3, 0).copy_from(&lhs10);
+            lhs.fixed_slice_mut::<U2, U2>(3, 3).copy_from(&lhs11);
+
+            Cholesky::new_unchecked(lhs).inverse()
+        };
 
-        let inv_lhs = Cholesky::new_unchecked(lhs).inverse();
+        #[cfg(feature = "dim2")]
+        let inv_lhs = {
+            let m11 = im2.x + r2_mat.x * r2_mat.x * ii2;
+            let m12 = r2_mat.x * r2_mat.y * ii2;
+            let m22 = im2.y + r2_mat.y * r2_mat.y * ii2;
+            SdpMatrix::new(m11, m12, m22).inverse_unchecked()
+        };
 
         let lin_rhs = rb2.linvel + rb2.angvel.gcross(r2) - rb1.linvel - rb1.angvel.gcross(r1);
-        let ang_rhs = basis1.tr_mul(&(rb2.angvel - rb1.angvel));
-        let rhs = Vector5::new(lin_rhs.x, lin_rhs.y, lin_rhs.z, ang_rhs.x, ang_rhs.y);
+        #[cfg(feature = "dim3")]
+        let rhs = {
+            let ang_rhs = basis1.tr_mul(&(rb2.angvel - rb1.angvel));
+            Vector5::new(lin_rhs.x, lin_rhs.y, lin_rhs.z, ang_rhs.x, ang_rhs.y)
+        };
+        #[cfg(feature = "dim2")]
+        let rhs = lin_rhs;
+
+        // Setup motor and limits.
+        let mut motor_axis2 = None;
+        let mut motor_rhs = 0.0;
+        let mut motor_inv_lhs = 0.0;
+        let mut motor_gamma = 0.0;
+        let mut motor_impulse = 0.0;
+        let mut limits_axis2 = None;
+        let mut limits_rhs = 0.0;
+        let mut limits_inv_lhs = 0.0;
+        let mut limits_impulse = 0.0;
+
+        if cparams.motor_enabled || cparams.limits_enabled {
+            // The motor and limits quantities are expressed in the joint's frame of
+            // reference, so the axis has to be negated if the bodies are swapped.
+            #[cfg(feature = "dim2")]
+            let (axis2, angle): (AngVector<f32>, _) = if flipped {
+                (-1.0, cparams.estimate_angle(&rb2.position, &rb1.position))
+            } else {
+                (1.0, cparams.estimate_angle(&rb1.position, &rb2.position))
+            };
+            #[cfg(feature = "dim3")]
+            let (axis2, angle) = if flipped {
+                (
+                    -(rb2.position * cparams.local_axis1).into_inner(),
+                    cparams.estimate_angle(&rb2.position, &rb1.position),
+                )
+            } else {
+                (
+                    (rb1.position * cparams.local_axis1).into_inner(),
+                    cparams.estimate_angle(&rb1.position, &rb2.position),
+                )
+            };
+            let dvel = axis2.gdot(rb2.angvel - rb1.angvel);
+            let ii2_axis = rb2.world_inv_inertia_sqrt.transform_vector(axis2);
+            let eff_inv = ii2_axis.gdot(ii2_axis);
+
+            if cparams.motor_enabled {
+                let (gamma, bias) = super::motor_coefficients(
+                    params.dt(),
+                    cparams.motor_stiffness,
+                    cparams.motor_damping,
+                    angle - cparams.target_motor_pos,
+                );
+
+                motor_axis2 = Some(axis2);
+                motor_rhs = cparams.target_motor_vel - bias - dvel;
+                motor_inv_lhs = crate::utils::inv(eff_inv + gamma);
+                motor_gamma = gamma;
+                motor_impulse = cparams.motor_impulse;
+            }
+
+            // FIXME: we should allow both limits to be active at
+            // the same time, and allow predictive constraint activation.
+            if cparams.limits_enabled {
+                let sign = if angle < cparams.limits[0] {
+                    1.0
+                } else if angle > cparams.limits[1] {
+                    -1.0
+                } else {
+                    0.0
+                };
+
+                if sign != 0.0 {
+                    limits_axis2 = Some(axis2 * sign);
+                    limits_rhs = dvel * sign;
+                    limits_inv_lhs = crate::utils::inv(eff_inv);
+                    limits_impulse = cparams.limits_impulse;
+                }
+            }
+        }
 
         RevoluteVelocityGroundConstraint {
             joint_id,
@@ -242,6 +540,17 @@ impl RevoluteVelocityGroundConstraint {
             im2,
             ii2_sqrt: rb2.world_inv_inertia_sqrt,
             impulse: cparams.impulse * params.warmstart_coeff,
+            motor_impulse: motor_impulse * params.warmstart_coeff,
+            motor_axis2,
+            motor_rhs,
+            motor_inv_lhs,
+            motor_gamma,
+            motor_max_impulse: cparams.max_motor_impulse,
+            limits_impulse: limits_impulse * params.warmstart_coeff,
+            limits_axis2,
+            limits_rhs,
+            limits_inv_lhs,
+            #[cfg(feature = "dim3")]
             basis1,
             inv_lhs,
             rhs,
@@ -252,14 +561,32 @@ impl RevoluteVelocityGroundConstraint {
     pub fn warmstart(&self, mj_lambdas: &mut [DeltaVel<f32>]) {
         let mut mj_lambda2 = mj_lambdas[self.mj_lambda2 as usize];
 
+        #[cfg(feature = "dim3")]
         let lin_impulse = self.impulse.fixed_rows::<U3>(0).into_owned();
+        #[cfg(feature = "dim3")]
         let ang_impulse = self.basis1 * self.impulse.fixed_rows::<U2>(3).into_owned();
+        #[cfg(feature = "dim2")]
+        let lin_impulse = self.impulse;
+        #[cfg(feature = "dim2")]
+        let ang_impulse = 0.0;
 
-        mj_lambda2.linear -= self.im2 * lin_impulse;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
+        if let Some(motor_axis2) = self.motor_axis2 {
+            mj_lambda2.angular += self
+                .ii2_sqrt
+                .transform_vector(motor_axis2 * self.motor_impulse);
+        }
+
+        if let Some(limits_axis2) = self.limits_axis2 {
+            mj_lambda2.angular += self
+                .ii2_sqrt
+                .transform_vector(limits_axis2 * self.limits_impulse);
+        }
+
         mj_lambdas[self.mj_lambda2 as usize] = mj_lambda2;
     }
 
@@ -268,19 +595,61 @@ impl RevoluteVelocityGroundConstraint {
 
         let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
         let lin_dvel = mj_lambda2.linear + ang_vel2.gcross(self.r2);
-        let ang_dvel = self.basis1.tr_mul(&ang_vel2);
-        let rhs =
-            Vector5::new(lin_dvel.x, lin_dvel.y, lin_dvel.z, ang_dvel.x, ang_dvel.y) + self.rhs;
+        #[cfg(feature = "dim3")]
+        let rhs = {
+            let ang_dvel = self.basis1.tr_mul(&ang_vel2);
+            Vector5::new(lin_dvel.x, lin_dvel.y, lin_dvel.z, ang_dvel.x, ang_dvel.y) + self.rhs
+        };
+        #[cfg(feature = "dim2")]
+        let rhs = lin_dvel + self.rhs;
         let impulse = self.inv_lhs * rhs;
         self.impulse += impulse;
+        #[cfg(feature = "dim3")]
         let lin_impulse = impulse.fixed_rows::<U3>(0).into_owned();
+        #[cfg(feature = "dim3")]
         let ang_impulse = self.basis1 * impulse.fixed_rows::<U2>(3).into_owned();
+        #[cfg(feature = "dim2")]
+        let lin_impulse = impulse;
+        #[cfg(feature = "dim2")]
+        let ang_impulse = 0.0;
 
-        mj_lambda2.linear -= self.im2 * lin_impulse;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
+        /*
+         * Joint motor.
+         */
+        if let Some(motor_axis2) = self.motor_axis2 {
+            let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+            let ang_dvel = motor_axis2.gdot(ang_vel2);
+            let new_impulse = (self.motor_impulse
+                + (self.motor_rhs - ang_dvel - self.motor_gamma * self.motor_impulse)
+                    * self.motor_inv_lhs)
+                .max(-self.motor_max_impulse)
+                .min(self.motor_max_impulse);
+            let dimpulse = new_impulse - self.motor_impulse;
+            self.motor_impulse = new_impulse;
+
+            mj_lambda2.angular += self.ii2_sqrt.transform_vector(motor_axis2 * dimpulse);
+        }
+
+        /*
+         * Joint limits.
+         */
+        if let Some(limits_axis2) = self.limits_axis2 {
+            let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+            let ang_dvel = limits_axis2.gdot(ang_vel2) + self.limits_rhs;
+            let new_impulse = (self.limits_impulse - ang_dvel * self.limits_inv_lhs).max(0.0);
+            let dimpulse = new_impulse - self.limits_impulse;
+            self.limits_impulse = new_impulse;
+
+            mj_lambda2.angular += self.ii2_sqrt.transform_vector(limits_axis2 * dimpulse);
+        }
+
         mj_lambdas[self.mj_lambda2 as usize] = mj_lambda2;
     }
 
@@ -289,6 +658,8 @@ impl RevoluteVelocityGroundConstraint {
         let joint = &mut joints_all[self.joint_id].weight;
         if let JointParams::RevoluteJoint(revolute) = &mut joint.params {
             revolute.impulse = self.impulse;
+            revolute.motor_impulse = self.motor_impulse;
+            revolute.limits_impulse = self.limits_impulse;
         }
     }
 }
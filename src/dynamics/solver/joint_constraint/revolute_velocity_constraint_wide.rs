@@ -1,12 +1,17 @@
-use simba::simd::SimdValue;
+use simba::simd::{SimdBool as _, SimdPartialOrd, SimdValue};
 
 use crate::dynamics::solver::DeltaVel;
 use crate::dynamics::{
     IntegrationParameters, JointGraphEdge, JointIndex, JointParams, RevoluteJoint, RigidBody,
 };
-use crate::math::{AngVector, AngularInertia, Isometry, Point, SimdFloat, Vector, SIMD_WIDTH};
-use crate::utils::{WAngularInertia, WCross, WCrossMatrix};
+use crate::math::{
+    AngVector, AngularInertia, Isometry, Point, SimdBool, SimdFloat, Vector, SIMD_WIDTH,
+};
+use crate::utils::{WAngularInertia, WCross, WCrossMatrix, WDot};
+#[cfg(feature = "dim3")]
 use na::{Cholesky, Matrix3x2, Matrix5, Vector5, U2, U3};
+#[cfg(feature = "dim2")]
+use {crate::math::SdpMatrix, na::Vector2};
 
 #[derive(Debug)]
 pub(crate) struct WRevoluteVelocityConstraint {
@@ -18,14 +23,37 @@ pub(crate) struct WRevoluteVelocityConstraint {
     r1: Vector<SimdFloat>,
     r2: Vector<SimdFloat>,
 
+    #[cfg(feature = "dim3")]
     inv_lhs: Matrix5<SimdFloat>,
+    #[cfg(feature = "dim3")]
     rhs: Vector5<SimdFloat>,
+    #[cfg(feature = "dim3")]
     impulse: Vector5<SimdFloat>,
 
+    #[cfg(feature = "dim2")]
+    inv_lhs: SdpMatrix<SimdFloat>,
+    #[cfg(feature = "dim2")]
+    rhs: Vector2<SimdFloat>,
+    #[cfg(feature = "dim2")]
+    impulse: Vector2<SimdFloat>,
+
+    #[cfg(feature = "dim3")]
     basis1: Matrix3x2<SimdFloat>,
 
-    im1: SimdFloat,
-    im2: SimdFloat,
+    motor_impulse: SimdFloat,
+    motor_axis1: Option<AngVector<SimdFloat>>,
+    motor_rhs: SimdFloat,
+    motor_inv_lhs: SimdFloat,
+    motor_gamma: SimdFloat,
+    motor_max_impulse: SimdFloat,
+
+    limits_impulse: SimdFloat,
+    limits_axis1: Option<AngVector<SimdFloat>>,
+    limits_rhs: SimdFloat,
+    limits_inv_lhs: SimdFloat,
+
+    im1: Vector<SimdFloat>,
+    im2: Vector<SimdFloat>,
 
     ii1_sqrt: AngularInertia<SimdFloat>,
     ii2_sqrt: AngularInertia<SimdFloat>,
@@ -43,7 +71,7 @@ impl WRevoluteVelocityConstraint {
         let linvel1 = Vector::from(array![|ii| rbs1[ii].linvel; SIMD_WIDTH]);
         let angvel1 = AngVector::<SimdFloat>::from(array![|ii| rbs1[ii].angvel; SIMD_WIDTH]);
         let world_com1 = Point::from(array![|ii| rbs1[ii].world_com; SIMD_WIDTH]);
-        let im1 = SimdFloat::from(array![|ii| rbs1[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im1 = Vector::from(array![|ii| rbs1[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii1_sqrt = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs1[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         );
@@ -53,7 +81,7 @@ impl WRevoluteVelocityConstraint {
         let linvel2 = Vector::from(array![|ii| rbs2[ii].linvel; SIMD_WIDTH]);
         let angvel2 = AngVector::<SimdFloat>::from(array![|ii| rbs2[ii].angvel; SIMD_WIDTH]);
         let world_com2 = Point::from(array![|ii| rbs2[ii].world_com; SIMD_WIDTH]);
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii2_sqrt = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs2[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         );
@@ -61,14 +89,19 @@ impl WRevoluteVelocityConstraint {
 
         let local_anchor1 = Point::from(array![|ii| cparams[ii].local_anchor1; SIMD_WIDTH]);
         let local_anchor2 = Point::from(array![|ii| cparams[ii].local_anchor2; SIMD_WIDTH]);
+        #[cfg(feature = "dim3")]
         let local_basis1 = [
             Vector::from(array![|ii| cparams[ii].basis1[0]; SIMD_WIDTH]),
             Vector::from(array![|ii| cparams[ii].basis1[1]; SIMD_WIDTH]),
         ];
+        #[cfg(feature = "dim3")]
         let impulse = Vector5::from(array![|ii| cparams[ii].impulse; SIMD_WIDTH]);
+        #[cfg(feature = "dim2")]
+        let impulse = Vector2::from(array![|ii| cparams[ii].impulse; SIMD_WIDTH]);
 
         let anchor1 = position1 * local_anchor1;
         let anchor2 = position2 * local_anchor2;
+        #[cfg(feature = "dim3")]
         let basis1 =
             Matrix3x2::from_columns(&[position1 * local_basis1[0], position1 * local_basis1[1]]);
 
@@ -80,31 +113,121 @@ impl WRevoluteVelocityConstraint {
         // NOTE: to simplify, we use basis2 = basis1.
         // Though we may want to test if that does not introduce any instability.
         let ii1 = ii1_sqrt.squared();
-        let r1 = anchor1 - world_com1;
+        let r1: Vector<SimdFloat> = anchor1 - world_com1;
         let r1_mat = r1.gcross_matrix();
 
         let ii2 = ii2_sqrt.squared();
-        let r2 = anchor2 - world_com2;
+        let r2: Vector<SimdFloat> = anchor2 - world_com2;
         let r2_mat = r2.gcross_matrix();
 
-        let mut lhs = Matrix5::zeros();
-        let lhs00 =
-            ii2.quadform(&r2_mat).add_diagonal(im2) + ii1.quadform(&r1_mat).add_diagonal(im1);
-        let lhs10 = basis1.tr_mul(&(ii2 * r2_mat + ii1 * r1_mat));
-        let lhs11 = (ii1 + ii2).quadform3x2(&basis1).into_matrix();
-
-        // Note that cholesky won't read the upper-right part
-        // of lhs so we don't have to fill it.
-        lhs.fixed_slice_mut::<U3, U3>(0, 0)
-            .copy_from(&lhs00.into_matrix());
-        lhs.fixed_slice_mut::<U2, U3>(3, 0).copy_from(&lhs10);
-        lhs.fixed_slice_mut::<U2, U2>(3, 3).copy_from(&lhs11);
+        #[cfg(feature = "dim3")]
+        let inv_lhs = {
+            let mut lhs = Matrix5::zeros();
+            let lhs00 = ii2.quadform(&r2_mat).add_diagonal_elements(im2)
+                + ii1.quadform(&r1_mat).add_diagonal_elements(im1);
+            let lhs10 = basis1.tr_mul(&(ii2 * r2_mat + ii1 * r1_mat));
+            let lhs11 = (ii1 + ii2).quadform3x2(&basis1).into_matrix();
+
+            // Note that cholesky won't read the upper-right part
+            // of lhs so we don't have to fill it.
+            lhs.fixed_slice_mut::<U3, U3>(0, 0)
+                .copy_from(&lhs00.into_matrix());
+            lhs.fixed_slice_mut::<U2, U3>(3, 0).copy_from(&lhs10);
+            lhs.fixed_slice_mut::<U2, U2>(3, 3).copy_from(&lhs11);
+
+            Cholesky::new_unchecked(lhs).inverse()
+        };
 
-        let inv_lhs = Cholesky::new_unchecked(lhs).inverse();
+        // In 2D we only have the linear part, so we just unroll
+        // the computation like for the ball joint.
+        #[cfg(feature = "dim2")]
+        let inv_lhs = {
+            let m11 = im1.x + im2.x + r1_mat.x * r1_mat.x * ii1 + r2_mat.x * r2_mat.x * ii2;
+            let m12 = r1_mat.x * r1_mat.y * ii1 + r2_mat.x * r2_mat.y * ii2;
+            let m22 = im1.y + im2.y + r1_mat.y * r1_mat.y * ii1 + r2_mat.y * r2_mat.y * ii2;
+            SdpMatrix::new(m11, m12, m22).inverse_unchecked()
+        };
 
         let lin_rhs = linvel2 + angvel2.gcross(r2) - linvel1 - angvel1.gcross(r1);
-        let ang_rhs = basis1.tr_mul(&(angvel2 - angvel1));
-        let rhs = Vector5::new(lin_rhs.x, lin_rhs.y, lin_rhs.z, ang_rhs.x, ang_rhs.y);
+        #[cfg(feature = "dim3")]
+        let rhs = {
+            let ang_rhs = basis1.tr_mul(&(angvel2 - angvel1));
+            Vector5::new(lin_rhs.x, lin_rhs.y, lin_rhs.z, ang_rhs.x, ang_rhs.y)
+        };
+        #[cfg(feature = "dim2")]
+        let rhs = lin_rhs;
+
+        // Setup motor and limits.
+        let mut motor_axis1 = None;
+        let mut motor_rhs = na::zero();
+        let mut motor_inv_lhs = na::zero();
+        let mut motor_gamma = na::zero();
+        let mut motor_impulse = na::zero();
+        let mut motor_max_impulse = na::zero();
+        let mut limits_axis1 = None;
+        let mut limits_rhs = na::zero();
+        let mut limits_inv_lhs = na::zero();
+        let mut limits_impulse = na::zero();
+        let motor_enabled = SimdBool::from(array![|ii| cparams[ii].motor_enabled; SIMD_WIDTH]);
+        let limits_enabled = SimdBool::from(array![|ii| cparams[ii].limits_enabled; SIMD_WIDTH]);
+
+        if (motor_enabled | limits_enabled).any() {
+            let _0: SimdFloat = na::zero();
+            let _1: SimdFloat = na::one();
+            #[cfg(feature = "dim2")]
+            let axis1 = _1;
+            #[cfg(feature = "dim3")]
+            let axis1 = position1 * Vector::from(array![|ii| *cparams[ii].local_axis1; SIMD_WIDTH]);
+            let angles = array![|ii| cparams[ii].estimate_angle(&rbs1[ii].position, &rbs2[ii].position); SIMD_WIDTH];
+            let dvel = axis1.gdot(angvel2 - angvel1);
+            let ii1_axis = ii1_sqrt.transform_vector(axis1);
+            let ii2_axis = ii2_sqrt.transform_vector(axis1);
+            let eff_inv = ii1_axis.gdot(ii1_axis) + ii2_axis.gdot(ii2_axis);
+
+            if motor_enabled.any() {
+                let coeffs = array![|ii| super::motor_coefficients(
+                    params.dt(),
+                    cparams[ii].motor_stiffness,
+                    cparams[ii].motor_damping,
+                    angles[ii] - cparams[ii].target_motor_pos,
+                ); SIMD_WIDTH];
+                let gamma = SimdFloat::from(array![|ii| coeffs[ii].0; SIMD_WIDTH]);
+                let bias = SimdFloat::from(array![|ii| coeffs[ii].1; SIMD_WIDTH]);
+                let target_vel =
+                    SimdFloat::from(array![|ii| cparams[ii].target_motor_vel; SIMD_WIDTH]);
+                let impulse = SimdFloat::from(array![|ii| cparams[ii].motor_impulse; SIMD_WIDTH]);
+                let max_impulse =
+                    SimdFloat::from(array![|ii| cparams[ii].max_motor_impulse; SIMD_WIDTH]);
+
+                motor_axis1 = Some(axis1 * _1.select(motor_enabled, _0));
+                motor_rhs = (target_vel - bias - dvel).select(motor_enabled, _0);
+                motor_inv_lhs = (_1 / (eff_inv + gamma)).select(motor_enabled, _0);
+                motor_gamma = gamma.select(motor_enabled, _0);
+                motor_impulse = impulse.select(motor_enabled, _0);
+                motor_max_impulse = max_impulse.select(motor_enabled, _0);
+            }
+
+            // FIXME: we should allow both limits to be active at
+            // the same time + allow predictive constraint activation.
+            if limits_enabled.any() {
+                let angle = SimdFloat::from(angles);
+                let min_limit = SimdFloat::from(array![|ii| cparams[ii].limits[0]; SIMD_WIDTH]);
+                let max_limit = SimdFloat::from(array![|ii| cparams[ii].limits[1]; SIMD_WIDTH]);
+                let lim_impulse =
+                    SimdFloat::from(array![|ii| cparams[ii].limits_impulse; SIMD_WIDTH]);
+
+                let min_enabled = angle.simd_lt(min_limit) & limits_enabled;
+                let max_enabled = angle.simd_gt(max_limit) & limits_enabled;
+                let sign = _1.select(min_enabled, (-_1).select(max_enabled, _0));
+
+                if sign != _0 {
+                    limits_axis1 = Some(axis1 * sign);
+                    limits_rhs = dvel * sign;
+                    limits_inv_lhs = (_1 / eff_inv).select(min_enabled | max_enabled, _0);
+                    limits_impulse = lim_impulse.select(min_enabled | max_enabled, _0);
+                }
+            }
+        }
 
         WRevoluteVelocityConstraint {
             joint_id,
@@ -112,10 +235,21 @@ impl WRevoluteVelocityConstraint {
             mj_lambda2,
             im1,
             ii1_sqrt,
+            #[cfg(feature = "dim3")]
             basis1,
             im2,
             ii2_sqrt,
             impulse: impulse * SimdFloat::splat(params.warmstart_coeff),
+            motor_impulse: motor_impulse * SimdFloat::splat(params.warmstart_coeff),
+            motor_axis1,
+            motor_rhs,
+            motor_inv_lhs,
+            motor_gamma,
+            motor_max_impulse,
+            limits_impulse: limits_impulse * SimdFloat::splat(params.warmstart_coeff),
+            limits_axis1,
+            limits_rhs,
+            limits_inv_lhs,
             inv_lhs,
             rhs,
             r1,
@@ -141,19 +275,37 @@ impl WRevoluteVelocityConstraint {
             ),
         };
 
+        #[cfg(feature = "dim3")]
         let lin_impulse = self.impulse.fixed_rows::<U3>(0).into_owned();
+        #[cfg(feature = "dim3")]
         let ang_impulse = self.basis1 * self.impulse.fixed_rows::<U2>(3).into_owned();
+        #[cfg(feature = "dim2")]
+        let lin_impulse = self.impulse;
+        #[cfg(feature = "dim2")]
+        let ang_impulse = na::zero::<SimdFloat>();
 
-        mj_lambda1.linear += lin_impulse * self.im1;
+        mj_lambda1.linear += lin_impulse.component_mul(&self.im1);
         mj_lambda1.angular += self
             .ii1_sqrt
             .transform_vector(ang_impulse + self.r1.gcross(lin_impulse));
 
-        mj_lambda2.linear -= lin_impulse * self.im2;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
+        if let Some(motor_axis1) = self.motor_axis1 {
+            let ang_impulse = motor_axis1 * self.motor_impulse;
+            mj_lambda1.angular -= self.ii1_sqrt.transform_vector(ang_impulse);
+            mj_lambda2.angular += self.ii2_sqrt.transform_vector(ang_impulse);
+        }
+
+        if let Some(limits_axis1) = self.limits_axis1 {
+            let ang_impulse = limits_axis1 * self.limits_impulse;
+            mj_lambda1.angular -= self.ii1_sqrt.transform_vector(ang_impulse);
+            mj_lambda2.angular += self.ii2_sqrt.transform_vector(ang_impulse);
+        }
+
         for ii in 0..SIMD_WIDTH {
             mj_lambdas[self.mj_lambda1[ii] as usize].linear = mj_lambda1.linear.extract(ii);
             mj_lambdas[self.mj_lambda1[ii] as usize].angular = mj_lambda1.angular.extract(ii);
@@ -187,24 +339,73 @@ impl WRevoluteVelocityConstraint {
         let lin_dvel = mj_lambda2.linear + ang_vel2.gcross(self.r2)
             - mj_lambda1.linear
             - ang_vel1.gcross(self.r1);
-        let ang_dvel = self.basis1.tr_mul(&(ang_vel2 - ang_vel1));
-        let rhs =
-            Vector5::new(lin_dvel.x, lin_dvel.y, lin_dvel.z, ang_dvel.x, ang_dvel.y) + self.rhs;
+        #[cfg(feature = "dim3")]
+        let rhs = {
+            let ang_dvel = self.basis1.tr_mul(&(ang_vel2 - ang_vel1));
+            Vector5::new(lin_dvel.x, lin_dvel.y, lin_dvel.z, ang_dvel.x, ang_dvel.y) + self.rhs
+        };
+        #[cfg(feature = "dim2")]
+        let rhs = lin_dvel + self.rhs;
         let impulse = self.inv_lhs * rhs;
         self.impulse += impulse;
+        #[cfg(feature = "dim3")]
         let lin_impulse = impulse.fixed_rows::<U3>(0).into_owned();
+        #[cfg(feature = "dim3")]
         let ang_impulse = self.basis1 * impulse.fixed_rows::<U2>(3).into_owned();
+        #[cfg(feature = "dim2")]
+        let lin_impulse = impulse;
+        #[cfg(feature = "dim2")]
+        let ang_impulse = na::zero::<SimdFloat>();
 
-        mj_lambda1.linear += lin_impulse * self.im1;
+        mj_lambda1.linear += lin_impulse.component_mul(&self.im1);
         mj_lambda1.angular += self
             .ii1_sqrt
             .transform_vector(ang_impulse + self.r1.gcross(lin_impulse));
 
-        mj_lambda2.linear -= lin_impulse * self.im2;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
+        /*
+         * Joint motor.
+         */
+        if let Some(motor_axis1) = self.motor_axis1 {
+            let ang_vel1 = self.ii1_sqrt.transform_vector(mj_lambda1.angular);
+            let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+            let ang_dvel = motor_axis1.gdot(ang_vel2 - ang_vel1);
+            let new_impulse = (self.motor_impulse
+                + (self.motor_rhs - ang_dvel - self.motor_gamma * self.motor_impulse)
+                    * self.motor_inv_lhs)
+                .simd_max(-self.motor_max_impulse)
+                .simd_min(self.motor_max_impulse);
+            let dimpulse = new_impulse - self.motor_impulse;
+            self.motor_impulse = new_impulse;
+
+            let ang_impulse = motor_axis1 * dimpulse;
+            mj_lambda1.angular -= self.ii1_sqrt.transform_vector(ang_impulse);
+            mj_lambda2.angular += self.ii2_sqrt.transform_vector(ang_impulse);
+        }
+
+        /*
+         * Joint limits.
+         */
+        if let Some(limits_axis1) = self.limits_axis1 {
+            let ang_vel1 = self.ii1_sqrt.transform_vector(mj_lambda1.angular);
+            let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+            let ang_dvel = limits_axis1.gdot(ang_vel2 - ang_vel1) + self.limits_rhs;
+            let new_impulse =
+                (self.limits_impulse - ang_dvel * self.limits_inv_lhs).simd_max(na::zero());
+            let dimpulse = new_impulse - self.limits_impulse;
+            self.limits_impulse = new_impulse;
+
+            let ang_impulse = limits_axis1 * dimpulse;
+            mj_lambda1.angular -= self.ii1_sqrt.transform_vector(ang_impulse);
+            mj_lambda2.angular += self.ii2_sqrt.transform_vector(ang_impulse);
+        }
+
         for ii in 0..SIMD_WIDTH {
             mj_lambdas[self.mj_lambda1[ii] as usize].linear = mj_lambda1.linear.extract(ii);
             mj_lambdas[self.mj_lambda1[ii] as usize].angular = mj_lambda1.angular.extract(ii);
@@ -219,7 +420,9 @@ impl WRevoluteVelocityConstraint {
         for ii in 0..SIMD_WIDTH {
             let joint = &mut joints_all[self.joint_id[ii]].weight;
             if let JointParams::RevoluteJoint(rev) = &mut joint.params {
-                rev.impulse = self.impulse.extract(ii)
+                rev.impulse = self.impulse.extract(ii);
+                rev.motor_impulse = self.motor_impulse.extract(ii);
+                rev.limits_impulse = self.limits_impulse.extract(ii);
             }
         }
     }
@@ -233,13 +436,36 @@ pub(crate) struct WRevoluteVelocityGroundConstraint {
 
     r2: Vector<SimdFloat>,
 
+    #[cfg(feature = "dim3")]
     inv_lhs: Matrix5<SimdFloat>,
+    #[cfg(feature = "dim3")]
     rhs: Vector5<SimdFloat>,
+    #[cfg(feature = "dim3")]
     impulse: Vector5<SimdFloat>,
 
+    #[cfg(feature = "dim2")]
+    inv_lhs: SdpMatrix<SimdFloat>,
+    #[cfg(feature = "dim2")]
+    rhs: Vector2<SimdFloat>,
+    #[cfg(feature = "dim2")]
+    impulse: Vector2<SimdFloat>,
+
+    #[cfg(feature = "dim3")]
     basis1: Matrix3x2<SimdFloat>,
 
-    im2: SimdFloat,
+    motor_impulse: SimdFloat,
+    motor_axis2: Option<AngVector<SimdFloat>>,
+    motor_rhs: SimdFloat,
+    motor_inv_lhs: SimdFloat,
+    motor_gamma: SimdFloat,
+    motor_max_impulse: SimdFloat,
+
+    limits_impulse: SimdFloat,
+    limits_axis2: Option<AngVector<SimdFloat>>,
+    limits_rhs: SimdFloat,
+    limits_inv_lhs: SimdFloat,
+
+    im2: Vector<SimdFloat>,
 
     ii2_sqrt: AngularInertia<SimdFloat>,
 }
@@ -262,12 +488,15 @@ impl WRevoluteVelocityGroundConstraint {
         let linvel2 = Vector::from(array![|ii| rbs2[ii].linvel; SIMD_WIDTH]);
         let angvel2 = AngVector::<SimdFloat>::from(array![|ii| rbs2[ii].angvel; SIMD_WIDTH]);
         let world_com2 = Point::from(array![|ii| rbs2[ii].world_com; SIMD_WIDTH]);
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii2_sqrt = AngularInertia::<SimdFloat>::from(
             array![|ii| rbs2[ii].world_inv_inertia_sqrt; SIMD_WIDTH],
         );
         let mj_lambda2 = array![|ii| rbs2[ii].active_set_offset; SIMD_WIDTH];
+        #[cfg(feature = "dim3")]
         let impulse = Vector5::from(array![|ii| cparams[ii].impulse; SIMD_WIDTH]);
+        #[cfg(feature = "dim2")]
+        let impulse = Vector2::from(array![|ii| cparams[ii].impulse; SIMD_WIDTH]);
 
         let local_anchor1 = Point::from(
             array![|ii| if flipped[ii] { cparams[ii].local_anchor2 } else { cparams[ii].local_anchor1 }; SIMD_WIDTH],
@@ -275,6 +504,7 @@ impl WRevoluteVelocityGroundConstraint {
         let local_anchor2 = Point::from(
             array![|ii| if flipped[ii] { cparams[ii].local_anchor1 } else { cparams[ii].local_anchor2 }; SIMD_WIDTH],
         );
+        #[cfg(feature = "dim3")]
         let basis1 = Matrix3x2::from_columns(&[
             position1
                 * Vector::from(
@@ -295,27 +525,125 @@ impl WRevoluteVelocityGroundConstraint {
         //            .into_inner();
         //        let basis2 = /*r21 * */ basis1;
         let ii2 = ii2_sqrt.squared();
-        let r1 = anchor1 - world_com1;
-        let r2 = anchor2 - world_com2;
+        let r1: Vector<SimdFloat> = anchor1 - world_com1;
+        let r2: Vector<SimdFloat> = anchor2 - world_com2;
         let r2_mat = r2.gcross_matrix();
 
-        let mut lhs = Matrix5::zeros();
-        let lhs00 = ii2.quadform(&r2_mat).add_diagonal(im2);
-        let lhs10 = basis1.tr_mul(&(ii2 * r2_mat));
-        let lhs11 = ii2.quadform3x2(&basis1).into_matrix();
-
-        // Note that cholesky won't read the upper-right part
-        // of lhs so we don't have to fill it.
-        lhs.fixed_slice_mut::<U3, U3>(0, 0)
-            .copy_from(&lhs00.into_matrix());
-        lhs.fixed_slice_mut::<U2, U3>(3, 0).copy_from(&lhs10);
-        lhs.fixed_slice_mut::<U2, U2>(3, 3).copy_from(&lhs11);
+        #[cfg(feature = "dim3")]
+        let inv_lhs = {
+            let mut lhs = Matrix5::zeros();
+            let lhs00 = ii2.quadform(&r2_mat).add_diagonal_elements(im2);
+            let lhs10 = basis1.tr_mul(&(ii2 * r2_mat));
+            let lhs11 = ii2.quadform3x2(&basis1).into_matrix();
+
+            // Note that cholesky won't read the upper-right part
+            // of lhs so we don't have to fill it.
+            lhs.fixed_slice_mut::<U3, U3>(0, 0)
+                .copy_from(&lhs00.into_matrix());
+            lhs.fixed_slice_mut::<U2, U3>(3, 0).copy_from(&lhs10);
+            lhs.fixed_slice_mut::<U2, U2>(3, 3).copy_from(&lhs11);
+
+            Cholesky::new_unchecked(lhs).inverse()
+        };
 
-        let inv_lhs = Cholesky::new_unchecked(lhs).inverse();
+        #[cfg(feature = "dim2")]
+        let inv_lhs = {
+            let m11 = im2.x + r2_mat.x * r2_mat.x * ii2;
+            let m12 = r2_mat.x * r2_mat.y * ii2;
+            let m22 = im2.y + r2_mat.y * r2_mat.y * ii2;
+            SdpMatrix::new(m11, m12, m22).inverse_unchecked()
+        };
 
         let lin_rhs = linvel2 + angvel2.gcross(r2) - linvel1 - angvel1.gcross(r1);
-        let ang_rhs = basis1.tr_mul(&(angvel2 - angvel1));
-        let rhs = Vector5::new(lin_rhs.x, lin_rhs.y, lin_rhs.z, ang_rhs.x, ang_rhs.y);
+        #[cfg(feature = "dim3")]
+        let rhs = {
+            let ang_rhs = basis1.tr_mul(&(angvel2 - angvel1));
+            Vector5::new(lin_rhs.x, lin_rhs.y, lin_rhs.z, ang_rhs.x, ang_rhs.y)
+        };
+        #[cfg(feature = "dim2")]
+        let rhs = lin_rhs;
+
+        // Setup motor and limits.
+        let mut motor_axis2 = None;
+        let mut motor_rhs = na::zero();
+        let mut motor_inv_lhs = na::zero();
+        let mut motor_gamma = na::zero();
+        let mut motor_impulse = na::zero();
+        let mut motor_max_impulse = na::zero();
+        let mut limits_axis2 = None;
+        let mut limits_rhs = na::zero();
+        let mut limits_inv_lhs = na::zero();
+        let mut limits_impulse = na::zero();
+        let motor_enabled = SimdBool::from(array![|ii| cparams[ii].motor_enabled; SIMD_WIDTH]);
+        let limits_enabled = SimdBool::from(array![|ii| cparams[ii].limits_enabled; SIMD_WIDTH]);
+
+        if (motor_enabled | limits_enabled).any() {
+            let _0: SimdFloat = na::zero();
+            let _1: SimdFloat = na::one();
+            // The motor and limits quantities are expressed in the joint's frame of
+            // reference, so the axis has to be negated if the bodies are swapped.
+            #[cfg(feature = "dim2")]
+            let axis2 =
+                SimdFloat::from(array![|ii| if flipped[ii] { -1.0 } else { 1.0 }; SIMD_WIDTH]);
+            #[cfg(feature = "dim3")]
+            let axis2 = Vector::from(array![|ii| if flipped[ii] {
+                -(rbs2[ii].position * cparams[ii].local_axis1).into_inner()
+            } else {
+                (rbs1[ii].position * cparams[ii].local_axis1).into_inner()
+            }; SIMD_WIDTH]);
+            let angles = array![|ii| if flipped[ii] {
+                cparams[ii].estimate_angle(&rbs2[ii].position, &rbs1[ii].position)
+            } else {
+                cparams[ii].estimate_angle(&rbs1[ii].position, &rbs2[ii].position)
+            }; SIMD_WIDTH];
+            let dvel = axis2.gdot(angvel2 - angvel1);
+            let ii2_axis = ii2_sqrt.transform_vector(axis2);
+            let eff_inv = ii2_axis.gdot(ii2_axis);
+
+            if motor_enabled.any() {
+                let coeffs = array![|ii| super::motor_coefficients(
+                    params.dt(),
+                    cparams[ii].motor_stiffness,
+                    cparams[ii].motor_damping,
+                    angles[ii] - cparams[ii].target_motor_pos,
+                ); SIMD_WIDTH];
+                let gamma = SimdFloat::from(array![|ii| coeffs[ii].0; SIMD_WIDTH]);
+                let bias = SimdFloat::from(array![|ii| coeffs[ii].1; SIMD_WIDTH]);
+                let target_vel =
+                    SimdFloat::from(array![|ii| cparams[ii].target_motor_vel; SIMD_WIDTH]);
+                let impulse = SimdFloat::from(array![|ii| cparams[ii].motor_impulse; SIMD_WIDTH]);
+                let max_impulse =
+                    SimdFloat::from(array![|ii| cparams[ii].max_motor_impulse; SIMD_WIDTH]);
+
+                motor_axis2 = Some(axis2 * _1.select(motor_enabled, _0));
+                motor_rhs = (target_vel - bias - dvel).select(motor_enabled, _0);
+                motor_inv_lhs = (_1 / (eff_inv + gamma)).select(motor_enabled, _0);
+                motor_gamma = gamma.select(motor_enabled, _0);
+                motor_impulse = impulse.select(motor_enabled, _0);
+                motor_max_impulse = max_impulse.select(motor_enabled, _0);
+            }
+
+            // FIXME: we should allow both limits to be active at
+            // the same time + allow predictive constraint activation.
+            if limits_enabled.any() {
+                let angle = SimdFloat::from(angles);
+                let min_limit = SimdFloat::from(array![|ii| cparams[ii].limits[0]; SIMD_WIDTH]);
+                let max_limit = SimdFloat::from(array![|ii| cparams[ii].limits[1]; SIMD_WIDTH]);
+                let lim_impulse =
+                    SimdFloat::from(array![|ii| cparams[ii].limits_impulse; SIMD_WIDTH]);
+
+                let min_enabled = angle.simd_lt(min_limit) & limits_enabled;
+                let max_enabled = angle.simd_gt(max_limit) & limits_enabled;
+                let sign = _1.select(min_enabled, (-_1).select(max_enabled, _0));
+
+                if sign != _0 {
+                    limits_axis2 = Some(axis2 * sign);
+                    limits_rhs = dvel * sign;
+                    limits_inv_lhs = (_1 / eff_inv).select(min_enabled | max_enabled, _0);
+                    limits_impulse = lim_impulse.select(min_enabled | max_enabled, _0);
+                }
+            }
+        }
 
         WRevoluteVelocityGroundConstraint {
             joint_id,
@@ -323,6 +651,17 @@ impl WRevoluteVelocityGroundConstraint {
             im2,
             ii2_sqrt,
             impulse: impulse * SimdFloat::splat(params.warmstart_coeff),
+            motor_impulse: motor_impulse * SimdFloat::splat(params.warmstart_coeff),
+            motor_axis2,
+            motor_rhs,
+            motor_inv_lhs,
+            motor_gamma,
+            motor_max_impulse,
+            limits_impulse: limits_impulse * SimdFloat::splat(params.warmstart_coeff),
+            limits_axis2,
+            limits_rhs,
+            limits_inv_lhs,
+            #[cfg(feature = "dim3")]
             basis1,
             inv_lhs,
             rhs,
@@ -340,14 +679,32 @@ impl WRevoluteVelocityGroundConstraint {
             ),
         };
 
+        #[cfg(feature = "dim3")]
         let lin_impulse = self.impulse.fixed_rows::<U3>(0).into_owned();
+        #[cfg(feature = "dim3")]
         let ang_impulse = self.basis1 * self.impulse.fixed_rows::<U2>(3).into_owned();
+        #[cfg(feature = "dim2")]
+        let lin_impulse = self.impulse;
+        #[cfg(feature = "dim2")]
+        let ang_impulse = na::zero::<SimdFloat>();
 
-        mj_lambda2.linear -= lin_impulse * self.im2;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
+        if let Some(motor_axis2) = self.motor_axis2 {
+            mj_lambda2.angular += self
+                .ii2_sqrt
+                .transform_vector(motor_axis2 * self.motor_impulse);
+        }
+
+        if let Some(limits_axis2) = self.limits_axis2 {
+            mj_lambda2.angular += self
+                .ii2_sqrt
+                .transform_vector(limits_axis2 * self.limits_impulse);
+        }
+
         for ii in 0..SIMD_WIDTH {
             mj_lambdas[self.mj_lambda2[ii] as usize].linear = mj_lambda2.linear.extract(ii);
             mj_lambdas[self.mj_lambda2[ii] as usize].angular = mj_lambda2.angular.extract(ii);
@@ -366,19 +723,62 @@ impl WRevoluteVelocityGroundConstraint {
 
         let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
         let lin_dvel = mj_lambda2.linear + ang_vel2.gcross(self.r2);
-        let ang_dvel = self.basis1.tr_mul(&ang_vel2);
-        let rhs =
-            Vector5::new(lin_dvel.x, lin_dvel.y, lin_dvel.z, ang_dvel.x, ang_dvel.y) + self.rhs;
+        #[cfg(feature = "dim3")]
+        let rhs = {
+            let ang_dvel = self.basis1.tr_mul(&ang_vel2);
+            Vector5::new(lin_dvel.x, lin_dvel.y, lin_dvel.z, ang_dvel.x, ang_dvel.y) + self.rhs
+        };
+        #[cfg(feature = "dim2")]
+        let rhs = lin_dvel + self.rhs;
         let impulse = self.inv_lhs * rhs;
         self.impulse += impulse;
+        #[cfg(feature = "dim3")]
         let lin_impulse = impulse.fixed_rows::<U3>(0).into_owned();
+        #[cfg(feature = "dim3")]
         let ang_impulse = self.basis1 * impulse.fixed_rows::<U2>(3).into_owned();
+        #[cfg(feature = "dim2")]
+        let lin_impulse = impulse;
+        #[cfg(feature = "dim2")]
+        let ang_impulse = na::zero::<SimdFloat>();
 
-        mj_lambda2.linear -= lin_impulse * self.im2;
+        mj_lambda2.linear -= lin_impulse.component_mul(&self.im2);
         mj_lambda2.angular -= self
             .ii2_sqrt
             .transform_vector(ang_impulse + self.r2.gcross(lin_impulse));
 
+        /*
+         * Joint motor.
+         */
+        if let Some(motor_axis2) = self.motor_axis2 {
+            let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+            let ang_dvel = motor_axis2.gdot(ang_vel2);
+            let new_impulse = (self.motor_impulse
+                + (self.motor_rhs - ang_dvel - self.motor_gamma * self.motor_impulse)
+                    * self.motor_inv_lhs)
+                .simd_max(-self.motor_max_impulse)
+                .simd_min(self.motor_max_impulse);
+            let dimpulse = new_impulse - self.motor_impulse;
+            self.motor_impulse = new_impulse;
+
+            mj_lambda2.angular += self.ii2_sqrt.transform_vector(motor_axis2 * dimpulse);
+        }
+
+        /*
+         * Joint limits.
+         */
+        if let Some(limits_axis2) = self.limits_axis2 {
+            let ang_vel2 = self.ii2_sqrt.transform_vector(mj_lambda2.angular);
+
+            let ang_dvel = limits_axis2.gdot(ang_vel2) + self.limits_rhs;
+            let new_impulse =
+                (self.limits_impulse - ang_dvel * self.limits_inv_lhs).simd_max(na::zero());
+            let dimpulse = new_impulse - self.limits_impulse;
+            self.limits_impulse = new_impulse;
+
+            mj_lambda2.angular += self.ii2_sqrt.transform_vector(limits_axis2 * dimpulse);
+        }
+
         for ii in 0..SIMD_WIDTH {
             mj_lambdas[self.mj_lambda2[ii] as usize].linear = mj_lambda2.linear.extract(ii);
             mj_lambdas[self.mj_lambda2[ii] as usize].angular = mj_lambda2.angular.extract(ii);
@@ -390,7 +790,9 @@ impl WRevoluteVelocityGroundConstraint {
         for ii in 0..SIMD_WIDTH {
             let joint = &mut joints_all[self.joint_id[ii]].weight;
             if let JointParams::RevoluteJoint(rev) = &mut joint.params {
-                rev.impulse = self.impulse.extract(ii)
+                rev.impulse = self.impulse.extract(ii);
+                rev.motor_impulse = self.motor_impulse.extract(ii);
+                rev.limits_impulse = self.limits_impulse.extract(ii);
             }
         }
     }
@@ -234,6 +234,7 @@ impl ParallelIslandSolver {
                         let dvel = mj_lambdas[rb.active_set_offset];
                         rb.linvel += dvel.linear;
                         rb.angvel += rb.world_inv_inertia_sqrt.transform_vector(dvel.angular);
+                        rb.project_velocities_on_free_axes();
                         rb.integrate(params.dt());
                         positions[rb.active_set_offset] = rb.position;
                     }
@@ -250,7 +251,7 @@ impl ParallelIslandSolver {
                     let batch_size = thread.batch_size;
                     for handle in active_bodies[thread.position_writeback_index] {
                         let rb = &mut bodies[*handle];
-                        rb.set_position(positions[rb.active_set_offset], false);
+                        rb.set_solver_position(positions[rb.active_set_offset]);
                     }
                 }
             })
@@ -66,8 +66,8 @@ pub(crate) struct PositionConstraint {
     pub local_n1: Vector<f32>,
     pub num_contacts: u8,
     pub radius: f32,
-    pub im1: f32,
-    pub im2: f32,
+    pub im1: Vector<f32>,
+    pub im2: Vector<f32>,
     pub ii1: AngularInertia<f32>,
     pub ii2: AngularInertia<f32>,
     pub erp: f32,
@@ -115,8 +115,8 @@ impl PositionConstraint {
                 local_p2,
                 local_n1: manifold.local_n1,
                 radius,
-                im1: rb1.mass_properties.inv_mass,
-                im2: rb2.mass_properties.inv_mass,
+                im1: rb1.effective_inv_mass,
+                im2: rb2.effective_inv_mass,
                 ii1: rb1.world_inv_inertia_sqrt.squared(),
                 ii2: rb2.world_inv_inertia_sqrt.squared(),
                 num_contacts: manifold_points.len() as u8,
@@ -175,13 +175,14 @@ impl PositionConstraint {
                 let ii_gcross2 = self.ii2.transform_vector(gcross2);
 
                 // Compute impulse.
-                let inv_r =
-                    self.im1 + self.im2 + gcross1.gdot(ii_gcross1) + gcross2.gdot(ii_gcross2);
-                let impulse = err / inv_r;
+                let inv_r = n.dot(&(self.im1 + self.im2).component_mul(&n))
+                    + gcross1.gdot(ii_gcross1)
+                    + gcross2.gdot(ii_gcross2);
+                let impulse = err * crate::utils::inv(inv_r);
 
                 // Apply impulse.
-                let tra1 = Translation::from(n * (impulse * self.im1));
-                let tra2 = Translation::from(n * (-impulse * self.im2));
+                let tra1 = Translation::from(n.component_mul(&self.im1) * impulse);
+                let tra2 = Translation::from(n.component_mul(&self.im2) * -impulse);
                 let rot1 = Rotation::new(ii_gcross1 * impulse);
                 let rot2 = Rotation::new(ii_gcross2 * impulse);
 
@@ -225,13 +226,14 @@ impl PositionConstraint {
                 let ii_gcross2 = self.ii2.transform_vector(gcross2);
 
                 // Compute impulse.
-                let inv_r =
-                    self.im1 + self.im2 + gcross1.gdot(ii_gcross1) + gcross2.gdot(ii_gcross2);
-                let impulse = err / inv_r;
+                let inv_r = n1.dot(&(self.im1 + self.im2).component_mul(&n1))
+                    + gcross1.gdot(ii_gcross1)
+                    + gcross2.gdot(ii_gcross2);
+                let impulse = err * crate::utils::inv(inv_r);
 
                 // Apply impulse.
-                let tra1 = Translation::from(n1 * (impulse * self.im1));
-                let tra2 = Translation::from(n1 * (-impulse * self.im2));
+                let tra1 = Translation::from(n1.component_mul(&self.im1) * impulse);
+                let tra2 = Translation::from(n1.component_mul(&self.im2) * -impulse);
                 let rot1 = Rotation::new(ii_gcross1 * impulse);
                 let rot2 = Rotation::new(ii_gcross2 * impulse);
 
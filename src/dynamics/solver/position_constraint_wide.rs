@@ -18,8 +18,8 @@ pub(crate) struct WPositionConstraint {
     pub local_p2: [Point<SimdFloat>; MAX_MANIFOLD_POINTS],
     pub local_n1: Vector<SimdFloat>,
     pub radius: SimdFloat,
-    pub im1: SimdFloat,
-    pub im2: SimdFloat,
+    pub im1: Vector<SimdFloat>,
+    pub im2: Vector<SimdFloat>,
     pub ii1: AngularInertia<SimdFloat>,
     pub ii2: AngularInertia<SimdFloat>,
     pub erp: SimdFloat,
@@ -38,10 +38,10 @@ impl WPositionConstraint {
         let rbs1 = array![|ii| bodies.get(manifolds[ii].body_pair.body1).unwrap(); SIMD_WIDTH];
         let rbs2 = array![|ii| bodies.get(manifolds[ii].body_pair.body2).unwrap(); SIMD_WIDTH];
 
-        let im1 = SimdFloat::from(array![|ii| rbs1[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im1 = Vector::from(array![|ii| rbs1[ii].effective_inv_mass; SIMD_WIDTH]);
         let sqrt_ii1: AngularInertia<SimdFloat> =
             AngularInertia::from(array![|ii| rbs1[ii].world_inv_inertia_sqrt; SIMD_WIDTH]);
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let sqrt_ii2: AngularInertia<SimdFloat> =
             AngularInertia::from(array![|ii| rbs2[ii].world_inv_inertia_sqrt; SIMD_WIDTH]);
 
@@ -143,14 +143,18 @@ impl WPositionConstraint {
                 let ii_gcross2 = self.ii2.transform_vector(gcross2);
 
                 // Compute impulse.
-                let inv_r =
-                    self.im1 + self.im2 + gcross1.gdot(ii_gcross1) + gcross2.gdot(ii_gcross2);
-                let impulse = err / inv_r;
+                let inv_r = n.dot(&(self.im1 + self.im2).component_mul(&n))
+                    + gcross1.gdot(ii_gcross1)
+                    + gcross2.gdot(ii_gcross2);
+                let impulse =
+                    (err / inv_r).select(inv_r.simd_gt(SimdFloat::zero()), SimdFloat::zero());
 
                 // Apply impulse.
-                pos1.translation = Translation::from(n * (impulse * self.im1)) * pos1.translation;
+                pos1.translation =
+                    Translation::from(n.component_mul(&self.im1) * impulse) * pos1.translation;
                 pos1.rotation = Rotation::new(ii_gcross1 * impulse) * pos1.rotation;
-                pos2.translation = Translation::from(n * (-impulse * self.im2)) * pos2.translation;
+                pos2.translation =
+                    Translation::from(n.component_mul(&self.im2) * -impulse) * pos2.translation;
                 pos2.rotation = Rotation::new(ii_gcross2 * impulse) * pos2.rotation;
             }
         }
@@ -198,14 +202,18 @@ impl WPositionConstraint {
                 let ii_gcross2 = self.ii2.transform_vector(gcross2);
 
                 // Compute impulse.
-                let inv_r =
-                    self.im1 + self.im2 + gcross1.gdot(ii_gcross1) + gcross2.gdot(ii_gcross2);
-                let impulse = err / inv_r;
+                let inv_r = n1.dot(&(self.im1 + self.im2).component_mul(&n1))
+                    + gcross1.gdot(ii_gcross1)
+                    + gcross2.gdot(ii_gcross2);
+                let impulse =
+                    (err / inv_r).select(inv_r.simd_gt(SimdFloat::zero()), SimdFloat::zero());
 
                 // Apply impulse.
-                pos1.translation = Translation::from(n1 * (impulse * self.im1)) * pos1.translation;
+                pos1.translation =
+                    Translation::from(n1.component_mul(&self.im1) * impulse) * pos1.translation;
                 pos1.rotation = Rotation::new(ii_gcross1 * impulse) * pos1.rotation;
-                pos2.translation = Translation::from(n1 * (-impulse * self.im2)) * pos2.translation;
+                pos2.translation =
+                    Translation::from(n1.component_mul(&self.im2) * -impulse) * pos2.translation;
                 pos2.rotation = Rotation::new(ii_gcross2 * impulse) * pos2.rotation;
             }
         }
@@ -14,7 +14,7 @@ pub(crate) struct PositionGroundConstraint {
     pub n1: Vector<f32>,
     pub num_contacts: u8,
     pub radius: f32,
-    pub im2: f32,
+    pub im2: Vector<f32>,
     pub ii2: AngularInertia<f32>,
     pub erp: f32,
     pub max_linear_correction: f32,
@@ -86,7 +86,7 @@ impl PositionGroundConstraint {
                 local_p2,
                 n1,
                 radius,
-                im2: rb2.mass_properties.inv_mass,
+                im2: rb2.effective_inv_mass,
                 ii2: rb2.world_inv_inertia_sqrt.squared(),
                 num_contacts: manifold_contacts.len() as u8,
                 erp: params.erp,
@@ -143,11 +143,11 @@ impl PositionGroundConstraint {
                 let ii_gcross2 = self.ii2.transform_vector(gcross2);
 
                 // Compute impulse.
-                let inv_r = self.im2 + gcross2.gdot(ii_gcross2);
-                let impulse = err / inv_r;
+                let inv_r = n.dot(&self.im2.component_mul(&n)) + gcross2.gdot(ii_gcross2);
+                let impulse = err * crate::utils::inv(inv_r);
 
                 // Apply impulse.
-                let tra2 = Translation::from(n * (-impulse * self.im2));
+                let tra2 = Translation::from(n.component_mul(&self.im2) * -impulse);
                 let rot2 = Rotation::new(ii_gcross2 * impulse);
                 pos2 = Isometry::from_parts(tra2 * pos2.translation, rot2 * pos2.rotation);
             }
@@ -182,11 +182,11 @@ impl PositionGroundConstraint {
                 let ii_gcross2 = self.ii2.transform_vector(gcross2);
 
                 // Compute impulse.
-                let inv_r = self.im2 + gcross2.gdot(ii_gcross2);
-                let impulse = err / inv_r;
+                let inv_r = n1.dot(&self.im2.component_mul(&n1)) + gcross2.gdot(ii_gcross2);
+                let impulse = err * crate::utils::inv(inv_r);
 
                 // Apply impulse.
-                let tra2 = Translation::from(n1 * (-impulse * self.im2));
+                let tra2 = Translation::from(n1.component_mul(&self.im2) * -impulse);
                 let rot2 = Rotation::new(ii_gcross2 * impulse);
                 pos2 = Isometry::from_parts(tra2 * pos2.translation, rot2 * pos2.rotation);
             }
@@ -17,7 +17,7 @@ pub(crate) struct WPositionGroundConstraint {
     pub local_p2: [Point<SimdFloat>; MAX_MANIFOLD_POINTS],
     pub n1: Vector<SimdFloat>,
     pub radius: SimdFloat,
-    pub im2: SimdFloat,
+    pub im2: Vector<SimdFloat>,
     pub ii2: AngularInertia<SimdFloat>,
     pub erp: SimdFloat,
     pub max_linear_correction: SimdFloat,
@@ -43,7 +43,7 @@ impl WPositionGroundConstraint {
             }
         }
 
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let sqrt_ii2: AngularInertia<SimdFloat> =
             AngularInertia::from(array![|ii| rbs2[ii].world_inv_inertia_sqrt; SIMD_WIDTH]);
 
@@ -149,11 +149,13 @@ impl WPositionGroundConstraint {
                 let ii_gcross2 = self.ii2.transform_vector(gcross2);
 
                 // Compute impulse.
-                let inv_r = self.im2 + gcross2.gdot(ii_gcross2);
-                let impulse = err / inv_r;
+                let inv_r = n.dot(&self.im2.component_mul(&n)) + gcross2.gdot(ii_gcross2);
+                let impulse =
+                    (err / inv_r).select(inv_r.simd_gt(SimdFloat::zero()), SimdFloat::zero());
 
                 // Apply impulse.
-                pos2.translation = Translation::from(n * (-impulse * self.im2)) * pos2.translation;
+                pos2.translation =
+                    Translation::from(n.component_mul(&self.im2) * -impulse) * pos2.translation;
                 pos2.rotation = Rotation::new(ii_gcross2 * impulse) * pos2.rotation;
             }
         }
@@ -191,11 +193,13 @@ impl WPositionGroundConstraint {
                 let ii_gcross2 = self.ii2.transform_vector(gcross2);
 
                 // Compute impulse.
-                let inv_r = self.im2 + gcross2.gdot(ii_gcross2);
-                let impulse = err / inv_r;
+                let inv_r = n1.dot(&self.im2.component_mul(&n1)) + gcross2.gdot(ii_gcross2);
+                let impulse =
+                    (err / inv_r).select(inv_r.simd_gt(SimdFloat::zero()), SimdFloat::zero());
 
                 // Apply impulse.
-                pos2.translation = Translation::from(n1 * (-impulse * self.im2)) * pos2.translation;
+                pos2.translation =
+                    Translation::from(n1.component_mul(&self.im2) * -impulse) * pos2.translation;
                 pos2.rotation = Rotation::new(ii_gcross2 * impulse) * pos2.rotation;
             }
         }
@@ -4,9 +4,11 @@ use crate::dynamics::solver::VelocityGroundConstraint;
 use crate::dynamics::solver::{WVelocityConstraint, WVelocityGroundConstraint};
 use crate::dynamics::{IntegrationParameters, RigidBodySet};
 use crate::geometry::{ContactManifold, ContactManifoldIndex};
-use crate::math::{AngVector, Vector, DIM, MAX_MANIFOLD_POINTS};
+use crate::math::{AngVector, Vector, ANG_DIM, DIM, MAX_MANIFOLD_POINTS};
 use crate::utils::{WAngularInertia, WBasis, WCross, WDot};
 use simba::simd::SimdPartialOrd;
+#[cfg(feature = "dim3")]
+use {crate::utils::WSign, na::SimdRealField};
 
 //#[repr(align(64))]
 #[derive(Copy, Clone, Debug)]
@@ -115,18 +117,38 @@ impl VelocityConstraintElement {
     }
 }
 
+/// Computes the friction directions of a contact with the normal force direction `force_dir1`.
+///
+/// The first friction direction is aligned with the projection of `friction_dir` on the plane
+/// orthogonal to `force_dir1`, unless this projection is close to zero.
+#[cfg(feature = "dim3")]
+pub(crate) fn tangent_basis<N: SimdRealField<Element = f32> + WSign<N>>(
+    force_dir1: &Vector<N>,
+    friction_dir: &Vector<N>,
+) -> [Vector<N>; DIM - 1] {
+    let default_tangent = force_dir1.orthonormal_basis()[0];
+    let projected = friction_dir - force_dir1 * force_dir1.dot(friction_dir);
+    let norm = projected.norm();
+    let use_projected = norm.simd_gt(N::splat(1.0e-5));
+    let tangent = (projected / norm).zip_map(&default_tangent, |a, b| a.select(use_projected, b));
+    [tangent, force_dir1.cross(&tangent)]
+}
+
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct VelocityConstraint {
     pub dir1: Vector<f32>, // Non-penetration force direction for the first body.
-    pub im1: f32,
-    pub im2: f32,
-    pub limit: f32,
+    pub tangents1: [Vector<f32>; DIM - 1], // Friction force directions for the first body.
+    pub im1: Vector<f32>,
+    pub im2: Vector<f32>,
+    pub limit: [f32; DIM - 1],
+    pub rolling_limit: [f32; ANG_DIM],
     pub mj_lambda1: usize,
     pub mj_lambda2: usize,
     pub manifold_id: ContactManifoldIndex,
     pub manifold_contact_id: usize,
     pub num_contacts: u8,
     pub elements: [VelocityConstraintElement; MAX_MANIFOLD_POINTS],
+    pub rolling_parts: [VelocityConstraintElementPart; ANG_DIM],
 }
 
 impl VelocityConstraint {
@@ -148,10 +170,22 @@ impl VelocityConstraint {
         let rb2 = &bodies[manifold.body_pair.body2];
         let mj_lambda1 = rb1.active_set_offset;
         let mj_lambda2 = rb2.active_set_offset;
+        let im = rb1.effective_inv_mass + rb2.effective_inv_mass;
         let pos_coll1 = rb1.position * manifold.delta1;
         let pos_coll2 = rb2.position * manifold.delta2;
         let force_dir1 = pos_coll1 * (-manifold.local_n1);
         let warmstart_coeff = manifold.warmstart_multiplier * params.warmstart_coeff;
+        #[cfg(feature = "dim2")]
+        let tangents1 = force_dir1.orthonormal_basis();
+        #[cfg(feature = "dim3")]
+        let tangents1 = tangent_basis(
+            &force_dir1,
+            &manifold.world_friction_dir(&pos_coll1, &pos_coll2),
+        );
+        #[cfg(feature = "dim2")]
+        let rolling_axes = [1.0];
+        #[cfg(feature = "dim3")]
+        let rolling_axes = [force_dir1, tangents1[0], tangents1[1]];
 
         for (l, manifold_points) in manifold
             .active_contacts()
@@ -161,10 +195,13 @@ impl VelocityConstraint {
             #[cfg(not(target_arch = "wasm32"))]
             let mut constraint = VelocityConstraint {
                 dir1: force_dir1,
+                tangents1,
                 elements: [VelocityConstraintElement::zero(); MAX_MANIFOLD_POINTS],
-                im1: rb1.mass_properties.inv_mass,
-                im2: rb2.mass_properties.inv_mass,
-                limit: manifold.friction,
+                rolling_parts: [VelocityConstraintElementPart::zero(); ANG_DIM],
+                im1: rb1.effective_inv_mass,
+                im2: rb2.effective_inv_mass,
+                limit: manifold.friction_limits(),
+                rolling_limit: manifold.rolling_limits(),
                 mj_lambda1,
                 mj_lambda2,
                 manifold_id,
@@ -205,9 +242,11 @@ impl VelocityConstraint {
             #[cfg(target_arch = "wasm32")]
             {
                 constraint.dir1 = force_dir1;
-                constraint.im1 = rb1.mass_properties.inv_mass;
-                constraint.im2 = rb2.mass_properties.inv_mass;
-                constraint.limit = manifold.friction;
+                constraint.tangents1 = tangents1;
+                constraint.im1 = rb1.effective_inv_mass;
+                constraint.im2 = rb2.effective_inv_mass;
+                constraint.limit = manifold.friction_limits();
+                constraint.rolling_limit = manifold.rolling_limits();
                 constraint.mj_lambda1 = mj_lambda1;
                 constraint.mj_lambda2 = mj_lambda2;
                 constraint.manifold_id = manifold_id;
@@ -232,11 +271,11 @@ impl VelocityConstraint {
                         .world_inv_inertia_sqrt
                         .transform_vector(dp2.gcross(-force_dir1));
 
-                    let r = 1.0
-                        / (rb1.mass_properties.inv_mass
-                            + rb2.mass_properties.inv_mass
+                    let r = crate::utils::inv(
+                        force_dir1.dot(&im.component_mul(&force_dir1))
                             + gcross1.gdot(gcross1)
-                            + gcross2.gdot(gcross2));
+                            + gcross2.gdot(gcross2),
+                    );
 
                     let mut rhs = (vel1 - vel2).dot(&force_dir1);
 
@@ -259,8 +298,6 @@ impl VelocityConstraint {
 
                 // Tangent parts.
                 {
-                    let tangents1 = force_dir1.orthonormal_basis();
-
                     for j in 0..DIM - 1 {
                         let gcross1 = rb1
                             .world_inv_inertia_sqrt
@@ -268,12 +305,12 @@ impl VelocityConstraint {
                         let gcross2 = rb2
                             .world_inv_inertia_sqrt
                             .transform_vector(dp2.gcross(-tangents1[j]));
-                        let r = 1.0
-                            / (rb1.mass_properties.inv_mass
-                                + rb2.mass_properties.inv_mass
+                        let r = crate::utils::inv(
+                            tangents1[j].dot(&im.component_mul(&tangents1[j]))
                                 + gcross1.gdot(gcross1)
-                                + gcross2.gdot(gcross2));
-                        let rhs = (vel1 - vel2).dot(&tangents1[j]);
+                                + gcross2.gdot(gcross2),
+                        );
+                        let rhs = (vel1 - vel2 + manifold.tangent_velocity).dot(&tangents1[j]);
                         #[cfg(feature = "dim2")]
                         let impulse = manifold_points[k].tangent_impulse * warmstart_coeff;
                         #[cfg(feature = "dim3")]
@@ -290,6 +327,24 @@ impl VelocityConstraint {
                 }
             }
 
+            // Rolling and spinning resistance parts.
+            for j in 0..ANG_DIM {
+                let gcross1 = rb1.world_inv_inertia_sqrt.transform_vector(rolling_axes[j]);
+                let gcross2 = rb2
+                    .world_inv_inertia_sqrt
+                    .transform_vector(-rolling_axes[j]);
+                let r = crate::utils::inv(gcross1.gdot(gcross1) + gcross2.gdot(gcross2));
+                let rhs = rolling_axes[j].gdot(rb1.angvel) - rolling_axes[j].gdot(rb2.angvel);
+
+                constraint.rolling_parts[j] = VelocityConstraintElementPart {
+                    gcross1,
+                    gcross2,
+                    rhs,
+                    impulse: 0.0,
+                    r,
+                };
+            }
+
             #[cfg(not(target_arch = "wasm32"))]
             if push {
                 out_constraints.push(AnyVelocityConstraint::Nongrouped(constraint));
@@ -306,21 +361,18 @@ impl VelocityConstraint {
 
         for i in 0..self.num_contacts as usize {
             let elt = &self.elements[i].normal_part;
-            mj_lambda1.linear += self.dir1 * (self.im1 * elt.impulse);
+            mj_lambda1.linear += self.dir1.component_mul(&self.im1) * elt.impulse;
             mj_lambda1.angular += elt.gcross1 * elt.impulse;
 
-            mj_lambda2.linear += self.dir1 * (-self.im2 * elt.impulse);
+            mj_lambda2.linear += self.dir1.component_mul(&self.im2) * -elt.impulse;
             mj_lambda2.angular += elt.gcross2 * elt.impulse;
 
-            // FIXME: move this out of the for loop?
-            let tangents1 = self.dir1.orthonormal_basis();
-
             for j in 0..DIM - 1 {
                 let elt = &self.elements[i].tangent_part[j];
-                mj_lambda1.linear += tangents1[j] * (self.im1 * elt.impulse);
+                mj_lambda1.linear += self.tangents1[j].component_mul(&self.im1) * elt.impulse;
                 mj_lambda1.angular += elt.gcross1 * elt.impulse;
 
-                mj_lambda2.linear += tangents1[j] * (-self.im2 * elt.impulse);
+                mj_lambda2.linear += self.tangents1[j].component_mul(&self.im2) * -elt.impulse;
                 mj_lambda2.angular += elt.gcross2 * elt.impulse;
             }
         }
@@ -337,29 +389,51 @@ impl VelocityConstraint {
 
         // Solve friction.
         for i in 0..self.num_contacts as usize {
-            let tangents1 = self.dir1.orthonormal_basis();
-
             for j in 0..DIM - 1 {
                 let normal_elt = &self.elements[i].normal_part;
                 let elt = &mut self.elements[i].tangent_part[j];
-                let dimpulse = tangents1[j].dot(&mj_lambda1.linear)
+                let dimpulse = self.tangents1[j].dot(&mj_lambda1.linear)
                     + elt.gcross1.gdot(mj_lambda1.angular)
-                    - tangents1[j].dot(&mj_lambda2.linear)
+                    - self.tangents1[j].dot(&mj_lambda2.linear)
                     + elt.gcross2.gdot(mj_lambda2.angular)
                     + elt.rhs;
-                let limit = self.limit * normal_elt.impulse;
+                let limit = self.limit[j] * normal_elt.impulse;
                 let new_impulse = (elt.impulse - elt.r * dimpulse).simd_clamp(-limit, limit);
                 let dlambda = new_impulse - elt.impulse;
                 elt.impulse = new_impulse;
 
-                mj_lambda1.linear += tangents1[j] * (self.im1 * dlambda);
+                mj_lambda1.linear += self.tangents1[j].component_mul(&self.im1) * dlambda;
                 mj_lambda1.angular += elt.gcross1 * dlambda;
 
-                mj_lambda2.linear += tangents1[j] * (-self.im2 * dlambda);
+                mj_lambda2.linear += self.tangents1[j].component_mul(&self.im2) * -dlambda;
                 mj_lambda2.angular += elt.gcross2 * dlambda;
             }
         }
 
+        // Solve rolling and spinning resistance.
+        let total_impulse: f32 = self.elements[..self.num_contacts as usize]
+            .iter()
+            .map(|elt| elt.normal_part.impulse)
+            .sum();
+
+        for j in 0..ANG_DIM {
+            if self.rolling_limit[j] == 0.0 {
+                continue;
+            }
+
+            let elt = &mut self.rolling_parts[j];
+            let dimpulse = elt.gcross1.gdot(mj_lambda1.angular)
+                + elt.gcross2.gdot(mj_lambda2.angular)
+                + elt.rhs;
+            let limit = self.rolling_limit[j] * total_impulse;
+            let new_impulse = (elt.impulse - elt.r * dimpulse).simd_clamp(-limit, limit);
+            let dlambda = new_impulse - elt.impulse;
+            elt.impulse = new_impulse;
+
+            mj_lambda1.angular += elt.gcross1 * dlambda;
+            mj_lambda2.angular += elt.gcross2 * dlambda;
+        }
+
         // Solve non-penetration.
         for i in 0..self.num_contacts as usize {
             let elt = &mut self.elements[i].normal_part;
@@ -371,10 +445,10 @@ impl VelocityConstraint {
             let dlambda = new_impulse - elt.impulse;
             elt.impulse = new_impulse;
 
-            mj_lambda1.linear += self.dir1 * (self.im1 * dlambda);
+            mj_lambda1.linear += self.dir1.component_mul(&self.im1) * dlambda;
             mj_lambda1.angular += elt.gcross1 * dlambda;
 
-            mj_lambda2.linear += self.dir1 * (-self.im2 * dlambda);
+            mj_lambda2.linear += self.dir1.component_mul(&self.im2) * -dlambda;
             mj_lambda2.angular += elt.gcross2 * dlambda;
         }
 
@@ -1,6 +1,8 @@
 use super::{AnyVelocityConstraint, DeltaVel};
-use crate::math::{AngVector, Vector, DIM, MAX_MANIFOLD_POINTS};
-use crate::utils::{WAngularInertia, WBasis, WCross, WDot};
+use crate::math::{AngVector, Vector, ANG_DIM, DIM, MAX_MANIFOLD_POINTS};
+#[cfg(feature = "dim2")]
+use crate::utils::WBasis;
+use crate::utils::{WAngularInertia, WCross, WDot};
 
 use crate::dynamics::{IntegrationParameters, RigidBodySet};
 use crate::geometry::{ContactManifold, ContactManifoldIndex};
@@ -45,13 +47,16 @@ impl VelocityGroundConstraintElement {
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct VelocityGroundConstraint {
     pub dir1: Vector<f32>, // Non-penetration force direction for the first body.
-    pub im2: f32,
-    pub limit: f32,
+    pub tangents1: [Vector<f32>; DIM - 1], // Friction force directions for the first body.
+    pub im2: Vector<f32>,
+    pub limit: [f32; DIM - 1],
+    pub rolling_limit: [f32; ANG_DIM],
     pub mj_lambda2: usize,
     pub manifold_id: ContactManifoldIndex,
     pub manifold_contact_id: usize,
     pub num_contacts: u8,
     pub elements: [VelocityGroundConstraintElement; MAX_MANIFOLD_POINTS],
+    pub rolling_parts: [VelocityGroundConstraintElementPart; ANG_DIM],
 }
 
 impl VelocityGroundConstraint {
@@ -69,20 +74,38 @@ impl VelocityGroundConstraint {
         let force_dir1;
         let coll_pos1;
         let coll_pos2;
+        let tangent_velocity;
 
         if flipped {
             coll_pos1 = rb2.position * manifold.delta2;
             coll_pos2 = rb1.position * manifold.delta1;
             force_dir1 = coll_pos1 * (-manifold.local_n2);
+            tangent_velocity = -manifold.tangent_velocity;
             std::mem::swap(&mut rb1, &mut rb2);
         } else {
             coll_pos1 = rb1.position * manifold.delta1;
             coll_pos2 = rb2.position * manifold.delta2;
             force_dir1 = coll_pos1 * (-manifold.local_n1);
+            tangent_velocity = manifold.tangent_velocity;
         }
 
         let mj_lambda2 = rb2.active_set_offset;
         let warmstart_coeff = manifold.warmstart_multiplier * params.warmstart_coeff;
+        #[cfg(feature = "dim2")]
+        let tangents1 = force_dir1.orthonormal_basis();
+        #[cfg(feature = "dim3")]
+        let tangents1 = {
+            let friction_dir = if flipped {
+                manifold.world_friction_dir(&coll_pos2, &coll_pos1)
+            } else {
+                manifold.world_friction_dir(&coll_pos1, &coll_pos2)
+            };
+            super::tangent_basis(&force_dir1, &friction_dir)
+        };
+        #[cfg(feature = "dim2")]
+        let rolling_axes = [1.0];
+        #[cfg(feature = "dim3")]
+        let rolling_axes = [force_dir1, tangents1[0], tangents1[1]];
 
         for (l, manifold_points) in manifold
             .active_contacts()
@@ -92,9 +115,12 @@ impl VelocityGroundConstraint {
             #[cfg(not(target_arch = "wasm32"))]
             let mut constraint = VelocityGroundConstraint {
                 dir1: force_dir1,
+                tangents1,
                 elements: [VelocityGroundConstraintElement::zero(); MAX_MANIFOLD_POINTS],
-                im2: rb2.mass_properties.inv_mass,
-                limit: manifold.friction,
+                rolling_parts: [VelocityGroundConstraintElementPart::zero(); ANG_DIM],
+                im2: rb2.effective_inv_mass,
+                limit: manifold.friction_limits(),
+                rolling_limit: manifold.rolling_limits(),
                 mj_lambda2,
                 manifold_id,
                 manifold_contact_id: l * MAX_MANIFOLD_POINTS,
@@ -134,8 +160,10 @@ impl VelocityGroundConstraint {
             #[cfg(target_arch = "wasm32")]
             {
                 constraint.dir1 = force_dir1;
-                constraint.im2 = rb2.mass_properties.inv_mass;
-                constraint.limit = manifold.friction;
+                constraint.tangents1 = tangents1;
+                constraint.im2 = rb2.effective_inv_mass;
+                constraint.limit = manifold.friction_limits();
+                constraint.rolling_limit = manifold.rolling_limits();
                 constraint.mj_lambda2 = mj_lambda2;
                 constraint.manifold_id = manifold_id;
                 constraint.manifold_contact_id = l * MAX_MANIFOLD_POINTS;
@@ -168,7 +196,10 @@ impl VelocityGroundConstraint {
                         .world_inv_inertia_sqrt
                         .transform_vector(dp2.gcross(-force_dir1));
 
-                    let r = 1.0 / (rb2.mass_properties.inv_mass + gcross2.gdot(gcross2));
+                    let r = crate::utils::inv(
+                        force_dir1.dot(&rb2.effective_inv_mass.component_mul(&force_dir1))
+                            + gcross2.gdot(gcross2),
+                    );
 
                     let mut rhs = (vel1 - vel2).dot(&force_dir1);
 
@@ -190,14 +221,15 @@ impl VelocityGroundConstraint {
 
                 // Tangent parts.
                 {
-                    let tangents1 = force_dir1.orthonormal_basis();
-
                     for j in 0..DIM - 1 {
                         let gcross2 = rb2
                             .world_inv_inertia_sqrt
                             .transform_vector(dp2.gcross(-tangents1[j]));
-                        let r = 1.0 / (rb2.mass_properties.inv_mass + gcross2.gdot(gcross2));
-                        let rhs = -vel2.dot(&tangents1[j]) + vel1.dot(&tangents1[j]);
+                        let r = crate::utils::inv(
+                            tangents1[j].dot(&rb2.effective_inv_mass.component_mul(&tangents1[j]))
+                                + gcross2.gdot(gcross2),
+                        );
+                        let rhs = (vel1 - vel2 + tangent_velocity).dot(&tangents1[j]);
                         #[cfg(feature = "dim2")]
                         let impulse = manifold_points[k].tangent_impulse * warmstart_coeff;
                         #[cfg(feature = "dim3")]
@@ -214,6 +246,22 @@ impl VelocityGroundConstraint {
                 }
             }
 
+            // Rolling and spinning resistance parts.
+            for j in 0..ANG_DIM {
+                let gcross2 = rb2
+                    .world_inv_inertia_sqrt
+                    .transform_vector(-rolling_axes[j]);
+                let r = crate::utils::inv(gcross2.gdot(gcross2));
+                let rhs = rolling_axes[j].gdot(rb1.angvel) - rolling_axes[j].gdot(rb2.angvel);
+
+                constraint.rolling_parts[j] = VelocityGroundConstraintElementPart {
+                    gcross2,
+                    rhs,
+                    impulse: 0.0,
+                    r,
+                };
+            }
+
             #[cfg(not(target_arch = "wasm32"))]
             if push {
                 out_constraints.push(AnyVelocityConstraint::NongroupedGround(constraint));
@@ -226,16 +274,15 @@ impl VelocityGroundConstraint {
 
     pub fn warmstart(&self, mj_lambdas: &mut [DeltaVel<f32>]) {
         let mut mj_lambda2 = DeltaVel::zero();
-        let tangents1 = self.dir1.orthonormal_basis();
 
         for i in 0..self.num_contacts as usize {
             let elt = &self.elements[i].normal_part;
-            mj_lambda2.linear += self.dir1 * (-self.im2 * elt.impulse);
+            mj_lambda2.linear += self.dir1.component_mul(&self.im2) * -elt.impulse;
             mj_lambda2.angular += elt.gcross2 * elt.impulse;
 
             for j in 0..DIM - 1 {
                 let elt = &self.elements[i].tangent_part[j];
-                mj_lambda2.linear += tangents1[j] * (-self.im2 * elt.impulse);
+                mj_lambda2.linear += self.tangents1[j].component_mul(&self.im2) * -elt.impulse;
                 mj_lambda2.angular += elt.gcross2 * elt.impulse;
             }
         }
@@ -248,25 +295,44 @@ impl VelocityGroundConstraint {
         let mut mj_lambda2 = mj_lambdas[self.mj_lambda2 as usize];
 
         // Solve friction.
-        let tangents1 = self.dir1.orthonormal_basis();
-
         for i in 0..self.num_contacts as usize {
             for j in 0..DIM - 1 {
                 let normal_elt = &self.elements[i].normal_part;
                 let elt = &mut self.elements[i].tangent_part[j];
-                let dimpulse = -tangents1[j].dot(&mj_lambda2.linear)
+                let dimpulse = -self.tangents1[j].dot(&mj_lambda2.linear)
                     + elt.gcross2.gdot(mj_lambda2.angular)
                     + elt.rhs;
-                let limit = self.limit * normal_elt.impulse;
+                let limit = self.limit[j] * normal_elt.impulse;
                 let new_impulse = (elt.impulse - elt.r * dimpulse).simd_clamp(-limit, limit);
                 let dlambda = new_impulse - elt.impulse;
                 elt.impulse = new_impulse;
 
-                mj_lambda2.linear += tangents1[j] * (-self.im2 * dlambda);
+                mj_lambda2.linear += self.tangents1[j].component_mul(&self.im2) * -dlambda;
                 mj_lambda2.angular += elt.gcross2 * dlambda;
             }
         }
 
+        // Solve rolling and spinning resistance.
+        let total_impulse: f32 = self.elements[..self.num_contacts as usize]
+            .iter()
+            .map(|elt| elt.normal_part.impulse)
+            .sum();
+
+        for j in 0..ANG_DIM {
+            if self.rolling_limit[j] == 0.0 {
+                continue;
+            }
+
+            let elt = &mut self.rolling_parts[j];
+            let dimpulse = elt.gcross2.gdot(mj_lambda2.angular) + elt.rhs;
+            let limit = self.rolling_limit[j] * total_impulse;
+            let new_impulse = (elt.impulse - elt.r * dimpulse).simd_clamp(-limit, limit);
+            let dlambda = new_impulse - elt.impulse;
+            elt.impulse = new_impulse;
+
+            mj_lambda2.angular += elt.gcross2 * dlambda;
+        }
+
         // Solve penetration.
         for i in 0..self.num_contacts as usize {
             let elt = &mut self.elements[i].normal_part;
@@ -276,7 +342,7 @@ impl VelocityGroundConstraint {
             let dlambda = new_impulse - elt.impulse;
             elt.impulse = new_impulse;
 
-            mj_lambda2.linear += self.dir1 * (-self.im2 * dlambda);
+            mj_lambda2.linear += self.dir1.component_mul(&self.im2) * -dlambda;
             mj_lambda2.angular += elt.gcross2 * dlambda;
         }
 
@@ -2,12 +2,14 @@ use super::{AnyVelocityConstraint, DeltaVel};
 use crate::dynamics::{IntegrationParameters, RigidBodySet};
 use crate::geometry::{ContactManifold, ContactManifoldIndex};
 use crate::math::{
-    AngVector, AngularInertia, Isometry, Point, SimdFloat, Vector, DIM, MAX_MANIFOLD_POINTS,
-    SIMD_WIDTH,
+    AngVector, AngularInertia, Isometry, Point, SimdFloat, Vector, ANG_DIM, DIM,
+    MAX_MANIFOLD_POINTS, SIMD_WIDTH,
 };
-use crate::utils::{WAngularInertia, WBasis, WCross, WDot};
+#[cfg(feature = "dim2")]
+use crate::utils::WBasis;
+use crate::utils::{WAngularInertia, WCross, WDot};
 use num::Zero;
-use simba::simd::{SimdPartialOrd, SimdValue};
+use simba::simd::{SimdBool, SimdPartialOrd, SimdValue};
 
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct WVelocityGroundConstraintElementPart {
@@ -46,10 +48,13 @@ impl WVelocityGroundConstraintElement {
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct WVelocityGroundConstraint {
     pub dir1: Vector<SimdFloat>, // Non-penetration force direction for the first body.
+    pub tangents1: [Vector<SimdFloat>; DIM - 1], // Friction force directions for the first body.
     pub elements: [WVelocityGroundConstraintElement; MAX_MANIFOLD_POINTS],
+    pub rolling_parts: [WVelocityGroundConstraintElementPart; ANG_DIM],
     pub num_contacts: u8,
-    pub im2: SimdFloat,
-    pub limit: SimdFloat,
+    pub im2: Vector<SimdFloat>,
+    pub limit: [SimdFloat; DIM - 1],
+    pub rolling_limit: [SimdFloat; ANG_DIM],
     pub mj_lambda2: [usize; SIMD_WIDTH],
     pub manifold_id: [ContactManifoldIndex; SIMD_WIDTH],
     pub manifold_contact_id: usize,
@@ -76,7 +81,7 @@ impl WVelocityGroundConstraint {
             }
         }
 
-        let im2 = SimdFloat::from(array![|ii| rbs2[ii].mass_properties.inv_mass; SIMD_WIDTH]);
+        let im2 = Vector::from(array![|ii| rbs2[ii].effective_inv_mass; SIMD_WIDTH]);
         let ii2: AngularInertia<SimdFloat> =
             AngularInertia::from(array![|ii| rbs2[ii].world_inv_inertia_sqrt; SIMD_WIDTH]);
 
@@ -98,6 +103,9 @@ impl WVelocityGroundConstraint {
 
         let coll_pos1 = pos1 * delta1;
         let coll_pos2 = pos2 * delta2;
+        let tangent_velocity = Vector::from(
+            array![|ii| if flipped[ii] { -manifolds[ii].tangent_velocity } else { manifolds[ii].tangent_velocity }; SIMD_WIDTH],
+        );
 
         let world_com1 = Point::from(array![|ii| rbs1[ii].world_com; SIMD_WIDTH]);
         let world_com2 = Point::from(array![|ii| rbs2[ii].world_com; SIMD_WIDTH]);
@@ -109,7 +117,33 @@ impl WVelocityGroundConstraint {
 
         let mj_lambda2 = array![|ii| rbs2[ii].active_set_offset; SIMD_WIDTH];
 
-        let friction = SimdFloat::from(array![|ii| manifolds[ii].friction; SIMD_WIDTH]);
+        #[cfg(feature = "dim2")]
+        let tangents1 = force_dir1.orthonormal_basis();
+        #[cfg(feature = "dim3")]
+        let tangents1 = {
+            // NOTE: the friction direction is computed from the colliders in the manifold order.
+            let friction_dir = Vector::from(array![|ii| manifolds[ii].world_friction_dir(
+                    &(bodies[manifolds[ii].body_pair.body1].position * manifolds[ii].delta1),
+                    &(bodies[manifolds[ii].body_pair.body2].position * manifolds[ii].delta2),
+                ); SIMD_WIDTH]);
+            super::tangent_basis(&force_dir1, &friction_dir)
+        };
+        #[cfg(feature = "dim2")]
+        let rolling_axes = [SimdFloat::splat(1.0)];
+        #[cfg(feature = "dim3")]
+        let rolling_axes = [force_dir1, tangents1[0], tangents1[1]];
+
+        let mut limit = [SimdFloat::zero(); DIM - 1];
+        for j in 0..DIM - 1 {
+            limit[j] = SimdFloat::from(array![|ii| manifolds[ii].friction_limits()[j]; SIMD_WIDTH]);
+        }
+
+        let mut rolling_limit = [SimdFloat::zero(); ANG_DIM];
+        for j in 0..ANG_DIM {
+            rolling_limit[j] =
+                SimdFloat::from(array![|ii| manifolds[ii].rolling_limits()[j]; SIMD_WIDTH]);
+        }
+
         let restitution = SimdFloat::from(array![|ii| manifolds[ii].restitution; SIMD_WIDTH]);
         let restitution_velocity_threshold =
             SimdFloat::splat(params.restitution_velocity_threshold);
@@ -124,9 +158,12 @@ impl WVelocityGroundConstraint {
 
             let mut constraint = WVelocityGroundConstraint {
                 dir1: force_dir1,
+                tangents1,
                 elements: [WVelocityGroundConstraintElement::zero(); MAX_MANIFOLD_POINTS],
+                rolling_parts: [WVelocityGroundConstraintElementPart::zero(); ANG_DIM],
                 im2,
-                limit: friction,
+                limit,
+                rolling_limit,
                 mj_lambda2,
                 manifold_id,
                 manifold_contact_id: l,
@@ -157,7 +194,10 @@ impl WVelocityGroundConstraint {
                 {
                     let gcross2 = ii2.transform_vector(dp2.gcross(-force_dir1));
 
-                    let r = SimdFloat::splat(1.0) / (im2 + gcross2.gdot(gcross2));
+                    let lhs =
+                        force_dir1.dot(&im2.component_mul(&force_dir1)) + gcross2.gdot(gcross2);
+                    let r = (SimdFloat::splat(1.0) / lhs)
+                        .select(lhs.simd_gt(SimdFloat::zero()), SimdFloat::zero());
                     let mut rhs = (vel1 - vel2).dot(&force_dir1);
                     let use_restitution = rhs.simd_le(-restitution_velocity_threshold);
                     let rhs_with_restitution = rhs + rhs * restitution;
@@ -173,8 +213,6 @@ impl WVelocityGroundConstraint {
                 }
 
                 // tangent parts.
-                let tangents1 = force_dir1.orthonormal_basis();
-
                 for j in 0..DIM - 1 {
                     #[cfg(feature = "dim2")]
                     let impulse = SimdFloat::from(
@@ -186,8 +224,11 @@ impl WVelocityGroundConstraint {
                     );
 
                     let gcross2 = ii2.transform_vector(dp2.gcross(-tangents1[j]));
-                    let r = SimdFloat::splat(1.0) / (im2 + gcross2.gdot(gcross2));
-                    let rhs = -vel2.dot(&tangents1[j]) + vel1.dot(&tangents1[j]);
+                    let lhs =
+                        tangents1[j].dot(&im2.component_mul(&tangents1[j])) + gcross2.gdot(gcross2);
+                    let r = (SimdFloat::splat(1.0) / lhs)
+                        .select(lhs.simd_gt(SimdFloat::zero()), SimdFloat::zero());
+                    let rhs = (vel1 - vel2 + tangent_velocity).dot(&tangents1[j]);
 
                     constraint.elements[k].tangent_parts[j] =
                         WVelocityGroundConstraintElementPart {
@@ -199,6 +240,22 @@ impl WVelocityGroundConstraint {
                 }
             }
 
+            // Rolling and spinning resistance parts.
+            for j in 0..ANG_DIM {
+                let gcross2 = ii2.transform_vector(-rolling_axes[j]);
+                let lhs = gcross2.gdot(gcross2);
+                let r = (SimdFloat::splat(1.0) / lhs)
+                    .select(lhs.simd_gt(SimdFloat::zero()), SimdFloat::zero());
+                let rhs = rolling_axes[j].gdot(angvel1) - rolling_axes[j].gdot(angvel2);
+
+                constraint.rolling_parts[j] = WVelocityGroundConstraintElementPart {
+                    gcross2,
+                    rhs,
+                    impulse: SimdFloat::zero(),
+                    r,
+                };
+            }
+
             if push {
                 out_constraints.push(AnyVelocityConstraint::GroupedGround(constraint));
             } else {
@@ -218,16 +275,14 @@ impl WVelocityGroundConstraint {
             ),
         };
 
-        let tangents1 = self.dir1.orthonormal_basis();
-
         for i in 0..self.num_contacts as usize {
             let elt = &self.elements[i].normal_part;
-            mj_lambda2.linear += self.dir1 * (-self.im2 * elt.impulse);
+            mj_lambda2.linear += self.dir1.component_mul(&self.im2) * -elt.impulse;
             mj_lambda2.angular += elt.gcross2 * elt.impulse;
 
             for j in 0..DIM - 1 {
                 let elt = &self.elements[i].tangent_parts[j];
-                mj_lambda2.linear += tangents1[j] * (-self.im2 * elt.impulse);
+                mj_lambda2.linear += self.tangents1[j].component_mul(&self.im2) * -elt.impulse;
                 mj_lambda2.angular += elt.gcross2 * elt.impulse;
             }
         }
@@ -249,26 +304,45 @@ impl WVelocityGroundConstraint {
         };
 
         // Solve friction first.
-        let tangents1 = self.dir1.orthonormal_basis();
-
         for i in 0..self.num_contacts as usize {
             let normal_elt = &self.elements[i].normal_part;
 
             for j in 0..DIM - 1 {
                 let elt = &mut self.elements[i].tangent_parts[j];
-                let dimpulse = -tangents1[j].dot(&mj_lambda2.linear)
+                let dimpulse = -self.tangents1[j].dot(&mj_lambda2.linear)
                     + elt.gcross2.gdot(mj_lambda2.angular)
                     + elt.rhs;
-                let limit = self.limit * normal_elt.impulse;
+                let limit = self.limit[j] * normal_elt.impulse;
                 let new_impulse = (elt.impulse - elt.r * dimpulse).simd_clamp(-limit, limit);
                 let dlambda = new_impulse - elt.impulse;
                 elt.impulse = new_impulse;
 
-                mj_lambda2.linear += tangents1[j] * (-self.im2 * dlambda);
+                mj_lambda2.linear += self.tangents1[j].component_mul(&self.im2) * -dlambda;
                 mj_lambda2.angular += elt.gcross2 * dlambda;
             }
         }
 
+        // Solve rolling and spinning resistance.
+        let mut total_impulse = SimdFloat::zero();
+        for i in 0..self.num_contacts as usize {
+            total_impulse += self.elements[i].normal_part.impulse;
+        }
+
+        for j in 0..ANG_DIM {
+            if self.rolling_limit[j].simd_eq(SimdFloat::zero()).all() {
+                continue;
+            }
+
+            let elt = &mut self.rolling_parts[j];
+            let dimpulse = elt.gcross2.gdot(mj_lambda2.angular) + elt.rhs;
+            let limit = self.rolling_limit[j] * total_impulse;
+            let new_impulse = (elt.impulse - elt.r * dimpulse).simd_clamp(-limit, limit);
+            let dlambda = new_impulse - elt.impulse;
+            elt.impulse = new_impulse;
+
+            mj_lambda2.angular += elt.gcross2 * dlambda;
+        }
+
         // Solve non-penetration after friction.
         for i in 0..self.num_contacts as usize {
             let elt = &mut self.elements[i].normal_part;
@@ -278,7 +352,7 @@ impl WVelocityGroundConstraint {
             let dlambda = new_impulse - elt.impulse;
             elt.impulse = new_impulse;
 
-            mj_lambda2.linear += self.dir1 * (-self.im2 * dlambda);
+            mj_lambda2.linear += self.dir1.component_mul(&self.im2) * -dlambda;
             mj_lambda2.angular += elt.gcross2 * dlambda;
         }
 
@@ -90,6 +90,7 @@ impl VelocitySolver {
             let dvel = self.mj_lambdas[rb.active_set_offset];
             rb.linvel += dvel.linear;
             rb.angvel += rb.world_inv_inertia_sqrt.transform_vector(dvel.angular);
+            rb.project_velocities_on_free_axes();
         });
 
         // Write impulses back into the manifold structures.
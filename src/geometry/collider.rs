@@ -1,12 +1,17 @@
-use crate::dynamics::{MassProperties, RigidBodyHandle, RigidBodySet};
+use crate::dynamics::{CoefficientCombineRule, MassProperties, RigidBodyHandle, RigidBodySet};
+#[cfg(feature = "dim2")]
+use crate::geometry::Polygon;
 use crate::geometry::{
-    Ball, Capsule, ColliderGraphIndex, Contact, Cuboid, HeightField, InteractionGraph,
-    InteractionGroups, Proximity, Segment, Shape, ShapeType, Triangle, Trimesh,
+    Ball, Capsule, ColliderGraphIndex, Compound, Contact, Cuboid, HeightField, InteractionGraph,
+    InteractionGroups, PartMaterial, Proximity, Segment, Shape, ShapeType, Triangle, Trimesh,
+    VHACDParameters, Voxels,
 };
 #[cfg(feature = "dim3")]
-use crate::geometry::{Cone, Cylinder, RoundCylinder};
+use crate::geometry::{Cone, ConvexPolyhedron, Cylinder, RoundCylinder, TrimeshFlags};
 use crate::math::{AngVector, Isometry, Point, Rotation, Vector};
 use na::Point3;
+#[cfg(feature = "dim3")]
+use na::Unit;
 use ncollide::bounding_volume::AABB;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -79,6 +84,17 @@ impl ColliderShape {
         ColliderShape(Arc::new(Trimesh::new(vertices, indices)))
     }
 
+    /// Initializes a triangle mesh shape defined by its vertex and index buffers, and flags
+    /// controlling its behavior.
+    #[cfg(feature = "dim3")]
+    pub fn trimesh_with_flags(
+        vertices: Vec<Point<f32>>,
+        indices: Vec<Point3<u32>>,
+        flags: TrimeshFlags,
+    ) -> Self {
+        ColliderShape(Arc::new(Trimesh::with_flags(vertices, indices, flags)))
+    }
+
     /// Initializes an heightfield shape defined by its set of height and a scale
     /// factor along each coordinate axis.
     #[cfg(feature = "dim2")]
@@ -92,6 +108,97 @@ impl ColliderShape {
     pub fn heightfield(heights: na::DMatrix<f32>, scale: Vector<f32>) -> Self {
         ColliderShape(Arc::new(HeightField::new(heights, scale)))
     }
+
+    /// Initializes a compound shape defined by its parts and their positions relative to the
+    /// compound.
+    ///
+    /// The parts must not be triangle meshes, heightfields, voxel shapes, or compound shapes.
+    pub fn compound(shapes: Vec<(Isometry<f32>, ColliderShape)>) -> Self {
+        ColliderShape(Arc::new(Compound::new(shapes)))
+    }
+
+    /// Initializes a compound shape defined by its parts, their positions relative to the
+    /// compound, and the materials overriding the friction and restitution of the collider
+    /// for each part.
+    ///
+    /// The parts must not be triangle meshes, heightfields, voxel shapes, or compound shapes.
+    pub fn compound_with_part_materials(
+        parts: Vec<(Isometry<f32>, ColliderShape, Option<PartMaterial>)>,
+    ) -> Self {
+        ColliderShape(Arc::new(Compound::with_part_materials(parts)))
+    }
+
+    /// Initializes a voxel shape defined by the size of its voxels and the grid coordinates of
+    /// its filled voxels.
+    pub fn voxels(voxel_size: Vector<f32>, voxels: &[Point<i32>]) -> Self {
+        ColliderShape(Arc::new(Voxels::new(voxel_size, voxels)))
+    }
+
+    /// Initializes a compound shape made of the convex parts of an approximate convex
+    /// decomposition of the given triangle mesh.
+    ///
+    /// Unlike a triangle mesh, the resulting shape has a volume and can be attached to a dynamic
+    /// rigid-body. The decomposition is deterministic: the same mesh and parameters always give
+    /// the same parts. Returns `None` if the mesh has no triangle or if no convex part could be
+    /// computed, e.g., if all the vertices of the mesh are equal.
+    ///
+    /// This is an expensive operation that should not be performed at each frame. Its cost
+    /// grows faster than the number of voxels, i.e., the square (in 2D) or the cube (in 3D) of
+    /// `params.resolution`: doubling the resolution can make it more than ten times slower.
+    /// With the default parameters, decomposing a 3D mesh of a thousand triangles can take
+    /// a couple of seconds.
+    pub fn convex_decomposition(
+        vertices: &[Point<f32>],
+        indices: &[Point3<u32>],
+        params: &VHACDParameters,
+    ) -> Option<Self> {
+        let parts: Vec<_> =
+            crate::geometry::convex_decomposition::convex_decomposition(vertices, indices, params)
+                .into_iter()
+                .map(|part| (Isometry::identity(), part))
+                .collect();
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(Self::compound(parts))
+        }
+    }
+
+    /// Initializes a convex polygon shape defined as the convex hull of the given set of points.
+    ///
+    /// Returns `None` if the convex hull has less than three vertices.
+    #[cfg(feature = "dim2")]
+    pub fn convex_hull(points: &[Point<f32>]) -> Option<Self> {
+        Polygon::from_convex_hull(points).map(|ch| ColliderShape(Arc::new(ch)))
+    }
+
+    /// Initializes a convex polygon shape from its vertices given in counter-clockwise order.
+    ///
+    /// No convexity check is performed. Returns `None` if there are less than three vertices or
+    /// if two consecutive vertices are equal.
+    #[cfg(feature = "dim2")]
+    pub fn convex_polyline(vertices: Vec<Point<f32>>) -> Option<Self> {
+        Polygon::from_convex_polyline(vertices).map(|ch| ColliderShape(Arc::new(ch)))
+    }
+
+    /// Initializes a convex polyhedron shape defined as the convex hull of the given set of points.
+    ///
+    /// Returns `None` if the convex hull computation failed.
+    #[cfg(feature = "dim3")]
+    pub fn convex_hull(points: &[Point<f32>]) -> Option<Self> {
+        ConvexPolyhedron::from_convex_hull(points).map(|ch| ColliderShape(Arc::new(ch)))
+    }
+
+    /// Initializes a convex polyhedron shape defined by the vertex and index buffers of a convex
+    /// triangle mesh.
+    ///
+    /// No convexity check is performed. Returns `None` if the mesh is not closed or has
+    /// degenerate triangles.
+    #[cfg(feature = "dim3")]
+    pub fn convex_mesh(vertices: Vec<Point<f32>>, indices: Vec<Point3<u32>>) -> Option<Self> {
+        ConvexPolyhedron::from_convex_mesh(vertices, indices).map(|ch| ColliderShape(Arc::new(ch)))
+    }
 }
 
 #[cfg(feature = "serde-serialize")]
@@ -153,25 +260,30 @@ impl<'de> serde::Deserialize<'de> for ColliderShape {
 
                 let shape = match ShapeType::from_i32(tag) {
                     Some(ShapeType::Ball) => deser::<A, Ball>(&mut seq)?,
-                    Some(ShapeType::Polygon) => {
-                        unimplemented!()
-                        // let shape: Polygon = seq
-                        //     .next_element()?
-                        //     .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
-                        // Arc::new(shape) as Arc<dyn Shape>
-                    }
+                    #[cfg(feature = "dim2")]
+                    Some(ShapeType::Polygon) => deser::<A, Polygon>(&mut seq)?,
                     Some(ShapeType::Cuboid) => deser::<A, Cuboid>(&mut seq)?,
                     Some(ShapeType::Capsule) => deser::<A, Capsule>(&mut seq)?,
                     Some(ShapeType::Triangle) => deser::<A, Triangle>(&mut seq)?,
                     Some(ShapeType::Segment) => deser::<A, Segment>(&mut seq)?,
                     Some(ShapeType::Trimesh) => deser::<A, Trimesh>(&mut seq)?,
                     Some(ShapeType::HeightField) => deser::<A, HeightField>(&mut seq)?,
+                    Some(ShapeType::Compound) => deser::<A, Compound>(&mut seq)?,
+                    Some(ShapeType::Voxels) => deser::<A, Voxels>(&mut seq)?,
                     #[cfg(feature = "dim3")]
                     Some(ShapeType::Cylinder) => deser::<A, Cylinder>(&mut seq)?,
                     #[cfg(feature = "dim3")]
                     Some(ShapeType::Cone) => deser::<A, Cone>(&mut seq)?,
                     #[cfg(feature = "dim3")]
                     Some(ShapeType::RoundCylinder) => deser::<A, RoundCylinder>(&mut seq)?,
+                    #[cfg(feature = "dim3")]
+                    Some(ShapeType::ConvexPolyhedron) => deser::<A, ConvexPolyhedron>(&mut seq)?,
+                    #[cfg(feature = "dim3")]
+                    Some(ShapeType::Polygon) => {
+                        return Err(serde::de::Error::custom(
+                            "found invalid shape type to deserialize",
+                        ))
+                    }
                     None => {
                         return Err(serde::de::Error::custom(
                             "found invalid shape type to deserialize",
@@ -204,6 +316,33 @@ pub struct Collider {
     pub friction: f32,
     /// The restitution coefficient of this collider.
     pub restitution: f32,
+    /// The direction, expressed in the local-space of this collider, along which
+    /// `directional_friction` applies instead of `friction`.
+    #[cfg(feature = "dim3")]
+    pub friction_direction: Option<Unit<Vector<f32>>>,
+    /// The friction coefficient of this collider along its `friction_direction`.
+    #[cfg(feature = "dim3")]
+    pub directional_friction: f32,
+    /// The rolling resistance coefficient of this collider.
+    ///
+    /// This is the ratio between the maximum torque opposing the rolling of the bodies in
+    /// contact and the normal contact force.
+    pub rolling_resistance: f32,
+    /// The spinning resistance coefficient of this collider.
+    ///
+    /// This is the ratio between the maximum torque opposing the rotation of the bodies in
+    /// contact around the contact normal and the normal contact force.
+    #[cfg(feature = "dim3")]
+    pub spinning_resistance: f32,
+    /// The rule used to combine the friction coefficients of this collider and
+    /// of the colliders it is in contact with.
+    pub friction_combine_rule: CoefficientCombineRule,
+    /// The rule used to combine the restitution coefficients of this collider and
+    /// of the colliders it is in contact with.
+    pub restitution_combine_rule: CoefficientCombineRule,
+    /// The total contact force magnitude above which a contact force event is emitted
+    /// for the contact pairs involving this collider.
+    pub contact_force_event_threshold: f32,
     pub(crate) collision_groups: InteractionGroups,
     pub(crate) solver_groups: InteractionGroups,
     pub(crate) contact_graph_index: ColliderGraphIndex,
@@ -272,6 +411,19 @@ impl Collider {
         &*self.shape.0
     }
 
+    /// The voxels of this collider, if its shape is a voxel shape.
+    ///
+    /// If this shape is shared with other colliders, it is cloned first so the other colliders
+    /// are not affected by the modifications.
+    pub(crate) fn voxels_mut(&mut self) -> Option<&mut Voxels> {
+        if Arc::get_mut(&mut self.shape.0).is_none() {
+            let voxels = self.shape.as_voxels()?.clone();
+            self.shape = ColliderShape(Arc::new(voxels));
+        }
+
+        Arc::get_mut(&mut self.shape.0)?.downcast_mut()
+    }
+
     /// Compute the axis-aligned bounding box of this collider.
     pub fn compute_aabb(&self) -> AABB<f32> {
         self.shape.compute_aabb(&self.position)
@@ -301,6 +453,24 @@ pub struct ColliderBuilder {
     pub friction: f32,
     /// The restitution coefficient of the collider to be built.
     pub restitution: f32,
+    /// The direction along which `directional_friction` applies instead of `friction`, if any.
+    #[cfg(feature = "dim3")]
+    pub friction_direction: Option<Unit<Vector<f32>>>,
+    /// The friction coefficient along `friction_direction` of the collider to be built.
+    #[cfg(feature = "dim3")]
+    pub directional_friction: f32,
+    /// The rolling resistance coefficient of the collider to be built.
+    pub rolling_resistance: f32,
+    /// The spinning resistance coefficient of the collider to be built.
+    #[cfg(feature = "dim3")]
+    pub spinning_resistance: f32,
+    /// The rule used to combine two friction coefficients.
+    pub friction_combine_rule: CoefficientCombineRule,
+    /// The rule used to combine two restitution coefficients.
+    pub restitution_combine_rule: CoefficientCombineRule,
+    /// The total contact force magnitude above which a contact force event is emitted
+    /// for the contact pairs involving the collider to be built.
+    pub contact_force_event_threshold: f32,
     /// The position of this collider relative to the local frame of the rigid-body it is attached to.
     pub delta: Isometry<f32>,
     /// Is this collider a sensor?
@@ -321,6 +491,16 @@ impl ColliderBuilder {
             density: None,
             friction: Self::default_friction(),
             restitution: 0.0,
+            #[cfg(feature = "dim3")]
+            friction_direction: None,
+            #[cfg(feature = "dim3")]
+            directional_friction: Self::default_friction(),
+            rolling_resistance: 0.0,
+            #[cfg(feature = "dim3")]
+            spinning_resistance: 0.0,
+            friction_combine_rule: CoefficientCombineRule::Average,
+            restitution_combine_rule: CoefficientCombineRule::Average,
+            contact_force_event_threshold: f32::MAX,
             delta: Isometry::identity(),
             is_sensor: false,
             user_data: 0,
@@ -412,6 +592,17 @@ impl ColliderBuilder {
         Self::new(ColliderShape::trimesh(vertices, indices))
     }
 
+    /// Initializes a collider builder with a triangle mesh shape defined by its vertex and index buffers,
+    /// and flags controlling its behavior.
+    #[cfg(feature = "dim3")]
+    pub fn trimesh_with_flags(
+        vertices: Vec<Point<f32>>,
+        indices: Vec<Point3<u32>>,
+        flags: TrimeshFlags,
+    ) -> Self {
+        Self::new(ColliderShape::trimesh_with_flags(vertices, indices, flags))
+    }
+
     /// Initializes a collider builder with a heightfield shape defined by its set of height and a scale
     /// factor along each coordinate axis.
     #[cfg(feature = "dim2")]
@@ -426,6 +617,78 @@ impl ColliderBuilder {
         Self::new(ColliderShape::heightfield(heights, scale))
     }
 
+    /// Initializes a collider builder with a compound shape defined by its parts and their
+    /// positions relative to the compound.
+    ///
+    /// The parts must not be triangle meshes, heightfields, voxel shapes, or compound shapes.
+    pub fn compound(shapes: Vec<(Isometry<f32>, ColliderShape)>) -> Self {
+        Self::new(ColliderShape::compound(shapes))
+    }
+
+    /// Initializes a collider builder with a compound shape defined by its parts, their
+    /// positions relative to the compound, and their materials.
+    ///
+    /// See [`ColliderShape::compound_with_part_materials`] for details.
+    pub fn compound_with_part_materials(
+        parts: Vec<(Isometry<f32>, ColliderShape, Option<PartMaterial>)>,
+    ) -> Self {
+        Self::new(ColliderShape::compound_with_part_materials(parts))
+    }
+
+    /// Initializes a collider builder with a voxel shape defined by the size of its voxels and
+    /// the grid coordinates of its filled voxels.
+    pub fn voxels(voxel_size: Vector<f32>, voxels: &[Point<i32>]) -> Self {
+        Self::new(ColliderShape::voxels(voxel_size, voxels))
+    }
+
+    /// Initializes a collider builder with a compound shape made of the convex parts of an
+    /// approximate convex decomposition of the given triangle mesh.
+    ///
+    /// See [`ColliderShape::convex_decomposition`] for details.
+    pub fn convex_decomposition(
+        vertices: &[Point<f32>],
+        indices: &[Point3<u32>],
+        params: &VHACDParameters,
+    ) -> Option<Self> {
+        ColliderShape::convex_decomposition(vertices, indices, params).map(Self::new)
+    }
+
+    /// Initializes a collider builder with a convex polygon shape defined as the convex hull
+    /// of the given set of points.
+    ///
+    /// Returns `None` if the convex hull has less than three vertices.
+    #[cfg(feature = "dim2")]
+    pub fn convex_hull(points: &[Point<f32>]) -> Option<Self> {
+        ColliderShape::convex_hull(points).map(Self::new)
+    }
+
+    /// Initializes a collider builder with a convex polygon shape defined by its vertices given
+    /// in counter-clockwise order.
+    ///
+    /// Returns `None` if there are less than three vertices or if two consecutive vertices are equal.
+    #[cfg(feature = "dim2")]
+    pub fn convex_polyline(vertices: Vec<Point<f32>>) -> Option<Self> {
+        ColliderShape::convex_polyline(vertices).map(Self::new)
+    }
+
+    /// Initializes a collider builder with a convex polyhedron shape defined as the convex hull
+    /// of the given set of points.
+    ///
+    /// Returns `None` if the convex hull computation failed.
+    #[cfg(feature = "dim3")]
+    pub fn convex_hull(points: &[Point<f32>]) -> Option<Self> {
+        ColliderShape::convex_hull(points).map(Self::new)
+    }
+
+    /// Initializes a collider builder with a convex polyhedron shape defined by the vertex and
+    /// index buffers of a convex triangle mesh.
+    ///
+    /// Returns `None` if the mesh is not closed or has degenerate triangles.
+    #[cfg(feature = "dim3")]
+    pub fn convex_mesh(vertices: Vec<Point<f32>>, indices: Vec<Point3<u32>>) -> Option<Self> {
+        ColliderShape::convex_mesh(vertices, indices).map(Self::new)
+    }
+
     /// The default friction coefficient used by the collider builder.
     pub fn default_friction() -> f32 {
         0.5
@@ -467,12 +730,62 @@ impl ColliderBuilder {
         self
     }
 
+    /// Sets a friction coefficient that applies along the given direction instead of `friction`.
+    ///
+    /// The direction is expressed in the local-space of the collider this builder will build.
+    /// The friction coefficient set by `.friction(...)` applies along the tangent direction
+    /// orthogonal to it.
+    #[cfg(feature = "dim3")]
+    pub fn anisotropic_friction(
+        mut self,
+        direction: Unit<Vector<f32>>,
+        directional_friction: f32,
+    ) -> Self {
+        self.friction_direction = Some(direction);
+        self.directional_friction = directional_friction;
+        self
+    }
+
+    /// Sets the rolling resistance coefficient of the collider this builder will build.
+    pub fn rolling_resistance(mut self, rolling_resistance: f32) -> Self {
+        self.rolling_resistance = rolling_resistance;
+        self
+    }
+
+    /// Sets the spinning resistance coefficient of the collider this builder will build.
+    #[cfg(feature = "dim3")]
+    pub fn spinning_resistance(mut self, spinning_resistance: f32) -> Self {
+        self.spinning_resistance = spinning_resistance;
+        self
+    }
+
+    /// Sets the rule to be used to combine two friction coefficients in a contact.
+    pub fn friction_combine_rule(mut self, rule: CoefficientCombineRule) -> Self {
+        self.friction_combine_rule = rule;
+        self
+    }
+
     /// Sets the restitution coefficient of the collider this builder will build.
     pub fn restitution(mut self, restitution: f32) -> Self {
         self.restitution = restitution;
         self
     }
 
+    /// Sets the rule to be used to combine two restitution coefficients in a contact.
+    pub fn restitution_combine_rule(mut self, rule: CoefficientCombineRule) -> Self {
+        self.restitution_combine_rule = rule;
+        self
+    }
+
+    /// Sets the total contact force magnitude above which a contact force event is emitted
+    /// for the contact pairs involving the collider this builder will build.
+    ///
+    /// By default, this is set to `f32::MAX` so no contact force event is ever emitted.
+    pub fn contact_force_event_threshold(mut self, threshold: f32) -> Self {
+        self.contact_force_event_threshold = threshold;
+        self
+    }
+
     /// Sets the density of the collider this builder will build.
     pub fn density(mut self, density: f32) -> Self {
         self.density = Some(density);
@@ -528,6 +841,16 @@ impl ColliderBuilder {
             density,
             friction: self.friction,
             restitution: self.restitution,
+            #[cfg(feature = "dim3")]
+            friction_direction: self.friction_direction,
+            #[cfg(feature = "dim3")]
+            directional_friction: self.directional_friction,
+            rolling_resistance: self.rolling_resistance,
+            #[cfg(feature = "dim3")]
+            spinning_resistance: self.spinning_resistance,
+            friction_combine_rule: self.friction_combine_rule,
+            restitution_combine_rule: self.restitution_combine_rule,
+            contact_force_event_threshold: self.contact_force_event_threshold,
             delta: self.delta,
             is_sensor: self.is_sensor,
             parent: RigidBodySet::invalid_handle(),
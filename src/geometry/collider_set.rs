@@ -1,8 +1,60 @@
 use crate::data::arena::Arena;
 use crate::data::pubsub::PubSub;
-use crate::dynamics::{RigidBodyHandle, RigidBodySet};
-use crate::geometry::{Collider, ColliderGraphIndex};
-use std::ops::{Index, IndexMut};
+use crate::dynamics::{MassProperties, RigidBody, RigidBodyHandle, RigidBodySet};
+use crate::geometry::{Collider, ColliderGraphIndex, Voxels};
+use std::ops::{Deref, DerefMut, Index, IndexMut};
+
+/// A mutable reference to the voxels of a collider.
+///
+/// The mass properties of the rigid-body the collider is attached to are updated when this
+/// reference is dropped.
+pub struct VoxelsMut<'a> {
+    collider: &'a mut Collider,
+    parent: &'a mut RigidBody,
+    // The mass properties of the voxels before modification, relative to the parent.
+    mass_properties: MassProperties,
+}
+
+impl<'a> VoxelsMut<'a> {
+    fn new(collider: &'a mut Collider, parent: &'a mut RigidBody) -> Option<Self> {
+        // Clone the voxels now if they are shared so `deref_mut` never has to.
+        let _ = collider.voxels_mut()?;
+        let mass_properties = collider
+            .mass_properties()
+            .transform_by(collider.position_wrt_parent());
+
+        Some(Self {
+            collider,
+            parent,
+            mass_properties,
+        })
+    }
+}
+
+impl<'a> Deref for VoxelsMut<'a> {
+    type Target = Voxels;
+    fn deref(&self) -> &Voxels {
+        self.collider.shape().as_voxels().unwrap()
+    }
+}
+
+impl<'a> DerefMut for VoxelsMut<'a> {
+    fn deref_mut(&mut self) -> &mut Voxels {
+        self.collider.voxels_mut().unwrap()
+    }
+}
+
+impl<'a> Drop for VoxelsMut<'a> {
+    fn drop(&mut self) {
+        let mass_properties = self
+            .collider
+            .mass_properties()
+            .transform_by(self.collider.position_wrt_parent());
+        self.parent.mass_properties -= self.mass_properties;
+        self.parent.mass_properties += mass_properties;
+        self.parent.update_world_mass_properties();
+    }
+}
 
 /// The unique identifier of a collider added to a collider set.
 pub type ColliderHandle = crate::data::arena::Index;
@@ -21,6 +73,9 @@ pub(crate) struct RemovedCollider {
 /// A set of colliders that can be handled by a physics `World`.
 pub struct ColliderSet {
     pub(crate) removed_colliders: PubSub<RemovedCollider>,
+    // The colliders inserted into this set, or which shape was modified.
+    pub(crate) modified_colliders: PubSub<ColliderHandle>,
+    pub(crate) modified_shapes: Vec<ColliderHandle>,
     pub(crate) colliders: Arena<Collider>,
 }
 
@@ -29,6 +84,8 @@ impl ColliderSet {
     pub fn new() -> Self {
         ColliderSet {
             removed_colliders: PubSub::new(),
+            modified_colliders: PubSub::new(),
+            modified_shapes: Vec::new(),
             colliders: Arena::new(),
         }
     }
@@ -74,6 +131,7 @@ impl ColliderSet {
         let coll = self.colliders.get(handle).unwrap();
         parent.add_collider_internal(handle, &coll);
         bodies.activate(parent_handle);
+        self.modified_colliders.publish(handle);
         handle
     }
 
@@ -151,6 +209,27 @@ impl ColliderSet {
         self.colliders.get_mut(handle)
     }
 
+    /// Gets a mutable reference to the voxels of the collider with the given handle, if its
+    /// shape is a voxel shape.
+    ///
+    /// The rigid-body this collider is attached to is activated so the modified voxels are taken
+    /// into account by the next timestep, and the bodies in contact with this collider are woken
+    /// up. If the voxel shape is shared with other colliders, it is cloned first. The mass
+    /// properties of the rigid-body are updated when the returned reference is dropped.
+    pub fn voxels_mut<'a>(
+        &'a mut self,
+        handle: ColliderHandle,
+        bodies: &'a mut RigidBodySet,
+    ) -> Option<VoxelsMut<'a>> {
+        let collider = self.colliders.get_mut(handle)?;
+        let _ = collider.shape().as_voxels()?;
+        bodies.activate(collider.parent);
+        self.modified_shapes.push(handle);
+        self.modified_colliders.publish(handle);
+        let parent = bodies.get_mut_internal(collider.parent)?;
+        VoxelsMut::new(collider, parent)
+    }
+
     pub(crate) fn get2_mut_internal(
         &mut self,
         h1: ColliderHandle,
@@ -0,0 +1,294 @@
+use crate::geometry::{ColliderShape, PointProjection, Ray, RayIntersection, ShapeType, WQuadtree};
+use crate::math::{Isometry, Point};
+use ncollide::bounding_volume::{BoundingVolume, AABB};
+use ncollide::query::{PointQuery, RayCast};
+use ncollide::shape::FeatureId;
+
+/// The friction and restitution coefficients of a part of a compound shape.
+///
+/// They replace the coefficients of the collider for the contacts involving this part.
+/// The combine rules of the collider are still used.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct PartMaterial {
+    /// The friction coefficient of the part.
+    pub friction: f32,
+    /// The restitution coefficient of the part.
+    pub restitution: f32,
+}
+
+/// A compound shape, i.e., a set of shapes, each with its own position relative to the compound.
+///
+/// Using a single collider with a compound shape is more efficient than attaching several
+/// colliders to the same rigid-body because the compound appears only once in the broad-phase.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct Compound {
+    shapes: Vec<(Isometry<f32>, ColliderShape)>,
+    materials: Vec<Option<PartMaterial>>,
+    wquadtree: WQuadtree<usize>,
+    aabbs: Vec<AABB<f32>>,
+    aabb: AABB<f32>,
+}
+
+impl Compound {
+    /// Builds a new compound shape from its parts and their positions relative to the compound.
+    ///
+    /// Panics if no part is given, or if one of the parts is a triangle mesh, a heightfield, a
+    /// voxel shape, or another compound shape.
+    pub fn new(shapes: Vec<(Isometry<f32>, ColliderShape)>) -> Self {
+        assert!(
+            !shapes.is_empty(),
+            "A compound shape must contain at least one part."
+        );
+
+        let mut aabb = AABB::new_invalid();
+        let mut aabbs = Vec::with_capacity(shapes.len());
+
+        for (pos, shape) in &shapes {
+            assert!(
+                !matches!(
+                    shape.shape_type(),
+                    ShapeType::Trimesh
+                        | ShapeType::HeightField
+                        | ShapeType::Compound
+                        | ShapeType::Voxels
+                ),
+                "The parts of a compound shape must not be composite shapes."
+            );
+
+            let part_aabb = shape.compute_aabb(pos);
+            aabb.merge(&part_aabb);
+            aabbs.push(part_aabb);
+        }
+
+        let mut wquadtree = WQuadtree::new();
+        // NOTE: we apply no dilation factor because we won't
+        // update this tree dynamically.
+        wquadtree.clear_and_rebuild(aabbs.iter().cloned().enumerate(), 0.0);
+
+        Self {
+            materials: vec![None; shapes.len()],
+            shapes,
+            wquadtree,
+            aabbs,
+            aabb,
+        }
+    }
+
+    /// Builds a new compound shape from its parts, their positions relative to the compound,
+    /// and their materials.
+    ///
+    /// A part with a `None` material uses the friction and restitution coefficients of the
+    /// collider. Panics in the same cases as [`Compound::new`].
+    pub fn with_part_materials(
+        parts: Vec<(Isometry<f32>, ColliderShape, Option<PartMaterial>)>,
+    ) -> Self {
+        let (shapes, materials): (Vec<_>, Vec<_>) = parts
+            .into_iter()
+            .map(|(pos, shape, material)| ((pos, shape), material))
+            .unzip();
+        let mut compound = Self::new(shapes);
+        compound.materials = materials;
+        compound
+    }
+
+    /// Compute the axis-aligned bounding box of this compound shape.
+    pub fn aabb(&self, pos: &Isometry<f32>) -> AABB<f32> {
+        self.aabb.transform_by(pos)
+    }
+
+    /// The parts of this compound shape and their positions relative to the compound.
+    pub fn shapes(&self) -> &[(Isometry<f32>, ColliderShape)] {
+        &self.shapes
+    }
+
+    /// The AABB of each part of this compound shape, in the local-space of the compound.
+    pub fn aabbs(&self) -> &[AABB<f32>] {
+        &self.aabbs
+    }
+
+    /// The material of the `i`-th part of this compound shape, if it overrides the collider's.
+    pub fn part_material(&self, i: usize) -> Option<&PartMaterial> {
+        self.materials[i].as_ref()
+    }
+
+    /// Sets the material of the `i`-th part of this compound shape.
+    ///
+    /// If `None`, the friction and restitution coefficients of the collider are used.
+    pub fn set_part_material(&mut self, i: usize, material: Option<PartMaterial>) {
+        self.materials[i] = material;
+    }
+
+    pub(crate) fn quadtree(&self) -> &WQuadtree<usize> {
+        &self.wquadtree
+    }
+}
+
+impl PointQuery<f32> for Compound {
+    fn project_point(&self, m: &Isometry<f32>, pt: &Point<f32>, solid: bool) -> PointProjection {
+        let proj = self.project_point_with_feature(m, pt).0;
+
+        if solid && proj.is_inside {
+            PointProjection::new(true, *pt)
+        } else {
+            proj
+        }
+    }
+
+    fn project_point_with_feature(
+        &self,
+        m: &Isometry<f32>,
+        pt: &Point<f32>,
+    ) -> (PointProjection, FeatureId) {
+        let mut best: Option<(f32, PointProjection)> = None;
+
+        for (pos, shape) in &self.shapes {
+            let proj = shape.project_point(&(m * pos), pt, false);
+            let dist = if proj.is_inside {
+                -f32::MAX
+            } else {
+                na::distance_squared(pt, &proj.point)
+            };
+
+            if best.as_ref().map(|b| dist < b.0).unwrap_or(true) {
+                best = Some((dist, proj));
+            }
+        }
+
+        // NOTE: a compound built with `Compound::new` always has at least one part, but
+        // an empty one may still be obtained through deserialization.
+        let proj = best
+            .map(|b| b.1)
+            .unwrap_or(PointProjection::new(false, *pt));
+        (proj, FeatureId::Unknown)
+    }
+}
+
+impl RayCast<f32> for Compound {
+    fn toi_and_normal_with_ray(
+        &self,
+        m: &Isometry<f32>,
+        ray: &Ray,
+        max_toi: f32,
+        solid: bool,
+    ) -> Option<RayIntersection> {
+        let mut intersections = Vec::new();
+        let ls_ray = ray.inverse_transform_by(m);
+        self.wquadtree
+            .cast_ray(&ls_ray, max_toi, &mut intersections);
+        let mut best: Option<RayIntersection> = None;
+
+        // NOTE: because of SIMD padding, the quadtree may return indices greater than the max.
+        for (pos, shape) in intersections.into_iter().filter_map(|i| self.shapes.get(i)) {
+            if let Some(inter) = shape.toi_and_normal_with_ray(&(m * pos), ray, max_toi, solid) {
+                if best.as_ref().map(|b| inter.toi < b.toi).unwrap_or(true) {
+                    best = Some(inter);
+                }
+            }
+        }
+
+        best
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PartMaterial;
+    use crate::dynamics::{CoefficientCombineRule, RigidBodyBuilder};
+    use crate::geometry::{ColliderBuilder, ColliderShape};
+    use crate::math::{Isometry, Translation, Vector};
+    use crate::pipeline::test_world::TestWorld;
+
+    #[test]
+    fn compound_resting_on_ground() {
+        let mut world = TestWorld::with_gravity();
+        let ground = world.insert(RigidBodyBuilder::new_static().build(), None);
+        let co = ColliderBuilder::new(ColliderShape::cuboid(Vector::repeat(10.0)))
+            .position(Translation::from(Vector::y() * -10.0).into())
+            .build();
+        world.insert_collider(co, ground);
+
+        // A U-shaped compound whose vertical parts are rotated horizontal bars.
+        let mut half_extents = Vector::repeat(1.0);
+        half_extents.y = 0.1;
+        let bar = ColliderShape::cuboid(half_extents);
+        #[cfg(feature = "dim2")]
+        let rot = std::f32::consts::FRAC_PI_2;
+        #[cfg(feature = "dim3")]
+        let rot = Vector::z() * std::f32::consts::FRAC_PI_2;
+        let mut left = Vector::y() * 1.0;
+        left.x = -0.9;
+        let mut right = left;
+        right.x = 0.9;
+        let shapes = vec![
+            (Isometry::identity(), bar.clone()),
+            (Isometry::new(left, rot), bar.clone()),
+            (Isometry::new(right, rot), bar),
+        ];
+
+        let rb = RigidBodyBuilder::new_dynamic()
+            .position(Translation::from(Vector::y() * 0.5).into())
+            .build();
+        let handle = world.insert(rb, Some(ColliderBuilder::compound(shapes).build()));
+
+        world.run(60);
+
+        // The compound rests on its horizontal part, whose bottom is 0.1 below the body origin.
+        let rb = &world.bodies[handle];
+        assert!((rb.position().translation.vector.y - 0.1).abs() < 0.02);
+        assert!(rb.position().rotation.angle() < 0.01);
+        assert!(rb.linvel().norm() < 0.1);
+    }
+
+    #[test]
+    fn compound_part_materials() {
+        let mut world = TestWorld::with_gravity();
+
+        // A ground made of two parts. Only the second one is bouncy.
+        let part = ColliderShape::cuboid(Vector::repeat(2.0));
+        let bouncy = PartMaterial {
+            friction: 0.5,
+            restitution: 1.0,
+        };
+        let parts = vec![
+            (
+                Translation::from(Vector::x() * -3.0).into(),
+                part.clone(),
+                None,
+            ),
+            (
+                Translation::from(Vector::x() * 3.0).into(),
+                part,
+                Some(bouncy),
+            ),
+        ];
+
+        let co = ColliderBuilder::compound_with_part_materials(parts)
+            .restitution_combine_rule(CoefficientCombineRule::Max)
+            .build();
+        world.insert(RigidBodyBuilder::new_static().build(), Some(co));
+
+        let handles: Vec<_> = [-3.0, 3.0]
+            .iter()
+            .map(|x| {
+                let mut translation = Vector::y() * 4.0;
+                translation.x = *x;
+                let rb = RigidBodyBuilder::new_dynamic()
+                    .position(Translation::from(translation).into())
+                    .build();
+                world.insert(rb, Some(ColliderBuilder::ball(0.5).build()))
+            })
+            .collect();
+
+        world.run(45);
+
+        // Both balls hit the ground, but only the second one bounced back.
+        let rb1 = &world.bodies[handles[0]];
+        let rb2 = &world.bodies[handles[1]];
+        assert!((rb1.position().translation.vector.y - 2.5).abs() < 0.05);
+        assert!(rb1.linvel().y.abs() < 0.1);
+        assert!(rb2.linvel().y > 2.0);
+        assert!(rb2.linvel().x.abs() < 0.1);
+    }
+}
@@ -1,8 +1,8 @@
 use crate::data::MaybeSerializableData;
-use crate::dynamics::BodyPair;
+use crate::dynamics::{BodyPair, CoefficientCombineRule};
 use crate::geometry::contact_generator::{ContactGeneratorWorkspace, ContactPhase};
-use crate::geometry::{Collider, ColliderPair, ColliderSet};
-use crate::math::{Isometry, Point, Vector};
+use crate::geometry::{Collider, ColliderHandle, ColliderPair, ColliderSet};
+use crate::math::{Isometry, Point, Vector, ANG_DIM, DIM};
 #[cfg(feature = "simd-is-enabled")]
 use {
     crate::math::{SimdFloat, SIMD_WIDTH},
@@ -59,8 +59,8 @@ pub(crate) struct WContact {
     pub local_n1: Vector<SimdFloat>,
     pub local_n2: Vector<SimdFloat>,
     pub dist: SimdFloat,
-    pub fid1: [u8; SIMD_WIDTH],
-    pub fid2: [u8; SIMD_WIDTH],
+    pub fid1: [u32; SIMD_WIDTH],
+    pub fid2: [u32; SIMD_WIDTH],
 }
 
 #[cfg(feature = "simd-is-enabled")]
@@ -105,13 +105,13 @@ pub struct Contact {
     /// For primitive shapes like cuboid, ball, etc., this is 0.
     /// For shapes like trimesh and heightfield this identifies the specific triangle
     /// involved in the contact.
-    pub fid1: u8,
+    pub fid1: u32,
     /// The identifier of the subshape of the second collider involved in this contact.
     ///
     /// For primitive shapes like cuboid, ball, etc., this is 0.
     /// For shapes like trimesh and heightfield this identifies the specific triangle
     /// involved in the contact.
-    pub fid2: u8,
+    pub fid2: u32,
     /// The distance between the two colliders along the contact normal.
     ///
     /// If this is negative, the colliders are penetrating.
@@ -122,8 +122,8 @@ impl Contact {
     pub(crate) fn new(
         local_p1: Point<f32>,
         local_p2: Point<f32>,
-        fid1: u8,
-        fid2: u8,
+        fid1: u32,
+        fid2: u32,
         dist: f32,
     ) -> Self {
         Self {
@@ -171,6 +171,28 @@ impl Contact {
     // }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Event triggered when the contact forces between two colliders exceed the contact force
+/// event threshold of one of them.
+pub struct ContactForceEvent {
+    /// The first collider involved in the contact.
+    pub collider1: ColliderHandle,
+    /// The second collider involved in the contact.
+    pub collider2: ColliderHandle,
+    /// The sum of all the contact forces applied to the first collider.
+    ///
+    /// The sum of all the contact forces applied to the second collider is given by `-total_force`.
+    pub total_force: Vector<f32>,
+    /// The sum of the magnitudes of all the contact forces between the two colliders.
+    ///
+    /// This is the value compared to the colliders' contact force event thresholds.
+    pub total_force_magnitude: f32,
+    /// The direction of the contact force with the greatest magnitude, applied to the first collider.
+    pub max_force_direction: Vector<f32>,
+    /// The magnitude of the contact force with the greatest magnitude.
+    pub max_force_magnitude: f32,
+}
+
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 #[derive(Clone)]
 /// The description of all the contacts between a pair of colliders.
@@ -293,11 +315,43 @@ pub struct ContactManifold {
     pub friction: f32,
     /// The restitution coefficient for all the contacts on this contact manifold.
     pub restitution: f32,
+    /// The target velocity of the surface of the first collider at the contact points,
+    /// relative to the surface of the second collider, expressed in world-space.
+    ///
+    /// Friction will drive the relative tangential velocity of the colliders toward this value.
+    /// Only the component of this vector orthogonal to the contact normal is taken into account.
+    /// This is typically set by a `ContactModifier` to simulate conveyor belts, and is reset to
+    /// zero at each timestep before the `ContactModifier` is called.
+    pub tangent_velocity: Vector<f32>,
+    /// The direction, expressed in the local-space of the first collider, along which
+    /// `directional_friction` applies instead of `friction`.
+    #[cfg(feature = "dim3")]
+    pub local_friction_dir1: Option<Vector<f32>>,
+    /// The direction, expressed in the local-space of the second collider, along which
+    /// `directional_friction` applies instead of `friction`.
+    ///
+    /// This is ignored if `local_friction_dir1` is set.
+    #[cfg(feature = "dim3")]
+    pub local_friction_dir2: Option<Vector<f32>>,
+    /// The friction coefficient along the friction direction of this contact manifold, if any.
+    #[cfg(feature = "dim3")]
+    pub directional_friction: f32,
+    /// The rolling resistance coefficient for all the contacts on this contact manifold.
+    pub rolling_resistance: f32,
+    /// The spinning resistance coefficient for all the contacts on this contact manifold.
+    #[cfg(feature = "dim3")]
+    pub spinning_resistance: f32,
     /// The relative position between the first collider and its parent at the time the
     /// contact points were generated.
+    ///
+    /// If the contact points involve a part of a compound shape, this also includes the
+    /// position of the part relative to the collider.
     pub delta1: Isometry<f32>,
     /// The relative position between the second collider and its parent at the time the
     /// contact points were generated.
+    ///
+    /// If the contact points involve a part of a compound shape, this also includes the
+    /// position of the part relative to the collider.
     pub delta2: Isometry<f32>,
     /// Flags used to control some aspects of the constraints solver for this contact manifold.
     pub solver_flags: SolverFlags,
@@ -329,6 +383,16 @@ impl ContactManifold {
             warmstart_multiplier: Self::min_warmstart_multiplier(),
             friction,
             restitution,
+            tangent_velocity: Vector::zeros(),
+            #[cfg(feature = "dim3")]
+            local_friction_dir1: None,
+            #[cfg(feature = "dim3")]
+            local_friction_dir2: None,
+            #[cfg(feature = "dim3")]
+            directional_friction: friction,
+            rolling_resistance: 0.0,
+            #[cfg(feature = "dim3")]
+            spinning_resistance: 0.0,
             delta1,
             delta2,
             constraint_index: 0,
@@ -353,6 +417,16 @@ impl ContactManifold {
             warmstart_multiplier: self.warmstart_multiplier,
             friction: self.friction,
             restitution: self.restitution,
+            tangent_velocity: self.tangent_velocity,
+            #[cfg(feature = "dim3")]
+            local_friction_dir1: self.local_friction_dir1,
+            #[cfg(feature = "dim3")]
+            local_friction_dir2: self.local_friction_dir2,
+            #[cfg(feature = "dim3")]
+            directional_friction: self.directional_friction,
+            rolling_resistance: self.rolling_resistance,
+            #[cfg(feature = "dim3")]
+            spinning_resistance: self.spinning_resistance,
             delta1: self.delta1,
             delta2: self.delta2,
             constraint_index: self.constraint_index,
@@ -378,16 +452,113 @@ impl ContactManifold {
         subshape2: usize,
         solver_flags: SolverFlags,
     ) -> Self {
-        Self::new(
+        let mut manifold = Self::new(
             pair,
             (subshape1, subshape2),
             BodyPair::new(coll1.parent, coll2.parent),
             *coll1.position_wrt_parent(),
             *coll2.position_wrt_parent(),
-            (coll1.friction + coll2.friction) * 0.5,
-            (coll1.restitution + coll2.restitution) * 0.5,
+            CoefficientCombineRule::combine(
+                coll1.friction,
+                coll2.friction,
+                coll1.friction_combine_rule,
+                coll2.friction_combine_rule,
+            ),
+            CoefficientCombineRule::combine(
+                coll1.restitution,
+                coll2.restitution,
+                coll1.restitution_combine_rule,
+                coll2.restitution_combine_rule,
+            ),
             solver_flags,
-        )
+        );
+
+        manifold.rolling_resistance = CoefficientCombineRule::combine(
+            coll1.rolling_resistance,
+            coll2.rolling_resistance,
+            coll1.friction_combine_rule,
+            coll2.friction_combine_rule,
+        );
+
+        #[cfg(feature = "dim3")]
+        {
+            manifold.spinning_resistance = CoefficientCombineRule::combine(
+                coll1.spinning_resistance,
+                coll2.spinning_resistance,
+                coll1.friction_combine_rule,
+                coll2.friction_combine_rule,
+            );
+
+            // If only one of the colliders has a friction direction, its directional friction
+            // is combined with the isotropic friction of the other collider.
+            let directional_friction = |coll: &Collider| {
+                if coll.friction_direction.is_some() {
+                    coll.directional_friction
+                } else {
+                    coll.friction
+                }
+            };
+
+            manifold.local_friction_dir1 = coll1.friction_direction.map(|dir| dir.into_inner());
+            manifold.local_friction_dir2 = coll2.friction_direction.map(|dir| dir.into_inner());
+            manifold.directional_friction = CoefficientCombineRule::combine(
+                directional_friction(coll1),
+                directional_friction(coll2),
+                coll1.friction_combine_rule,
+                coll2.friction_combine_rule,
+            );
+        }
+
+        manifold
+    }
+
+    /// The friction coefficients along each tangent direction of the contacts of this manifold.
+    ///
+    /// In 3D, the first tangent direction is aligned with the friction direction, if any.
+    pub(crate) fn friction_limits(&self) -> [f32; DIM - 1] {
+        #[cfg(feature = "dim2")]
+        return [self.friction];
+
+        #[cfg(feature = "dim3")]
+        if self.local_friction_dir1.is_some() || self.local_friction_dir2.is_some() {
+            [self.directional_friction, self.friction]
+        } else {
+            [self.friction, self.friction]
+        }
+    }
+
+    /// The resistance coefficients opposing the relative rotation of the bodies.
+    ///
+    /// In 3D, they are the spinning resistance followed by the rolling resistance along
+    /// each tangent direction.
+    pub(crate) fn rolling_limits(&self) -> [f32; ANG_DIM] {
+        #[cfg(feature = "dim2")]
+        return [self.rolling_resistance];
+
+        #[cfg(feature = "dim3")]
+        [
+            self.spinning_resistance,
+            self.rolling_resistance,
+            self.rolling_resistance,
+        ]
+    }
+
+    /// The world-space direction along which the directional friction of this manifold applies.
+    ///
+    /// Returns a zero vector if none of the colliders has a friction direction.
+    #[cfg(feature = "dim3")]
+    pub(crate) fn world_friction_dir(
+        &self,
+        coll_pos1: &Isometry<f32>,
+        coll_pos2: &Isometry<f32>,
+    ) -> Vector<f32> {
+        if let Some(dir1) = self.local_friction_dir1 {
+            coll_pos1 * dir1
+        } else if let Some(dir2) = self.local_friction_dir2 {
+            coll_pos2 * dir2
+        } else {
+            Vector::zeros()
+        }
     }
 
     pub(crate) fn min_warmstart_multiplier() -> f32 {
@@ -423,11 +594,34 @@ impl ContactManifold {
         &self.points
     }
 
+    /// Deactivates all the active contacts for which `f` returns `false`.
+    ///
+    /// Deactivated contacts are ignored by the constraints solver during this timestep only:
+    /// they will be considered again at the next timestep.
+    pub fn retain_active_contacts(&mut self, mut f: impl FnMut(&Contact) -> bool) {
+        let mut i = 0;
+
+        while i != self.num_active_contacts {
+            if f(&self.points[i]) {
+                i += 1;
+            } else {
+                // Swap with the last active contact.
+                self.num_active_contacts -= 1;
+                self.points.swap(i, self.num_active_contacts);
+                let removed = &mut self.points[self.num_active_contacts];
+                removed.impulse = 0.0;
+                removed.tangent_impulse = Contact::zero_tangent_impulse();
+            }
+        }
+    }
+
     pub(crate) fn swap_identifiers(&mut self) {
         self.pair = self.pair.swap();
         self.body_pair = self.body_pair.swap();
         self.subshape_index_pair = (self.subshape_index_pair.1, self.subshape_index_pair.0);
         std::mem::swap(&mut self.delta1, &mut self.delta2);
+        #[cfg(feature = "dim3")]
+        std::mem::swap(&mut self.local_friction_dir1, &mut self.local_friction_dir2);
     }
 
     pub(crate) fn update_warmstart_multiplier(&mut self) {
@@ -529,3 +723,76 @@ impl ContactManifold {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "dim3")]
+    use crate::dynamics::CoefficientCombineRule;
+    use crate::dynamics::RigidBodyBuilder;
+    use crate::geometry::ColliderBuilder;
+    #[cfg(feature = "dim3")]
+    use crate::math::Vector;
+    use crate::pipeline::test_world::TestWorld;
+
+    #[test]
+    fn rolling_resistance_slows_down_balls() {
+        let mut world = TestWorld::with_gravity();
+        let (ground, _) = world.insert_ground(50.0);
+
+        // Two balls rolling on the ground, far enough from each other not to collide.
+        let mut balls = Vec::new();
+        for (i, rolling_resistance) in [0.0, 0.1].iter().enumerate() {
+            #[cfg(feature = "dim2")]
+            let rb = RigidBodyBuilder::new_dynamic()
+                .translation(0.0, 0.6 + i as f32 * 5.0)
+                .linvel(5.0, 0.0);
+            #[cfg(feature = "dim3")]
+            let rb = RigidBodyBuilder::new_dynamic()
+                .translation(0.0, 0.6, i as f32 * 5.0)
+                .linvel(5.0, 0.0, 0.0);
+            let co = ColliderBuilder::ball(0.5).rolling_resistance(*rolling_resistance);
+            balls.push(world.insert(rb.build(), Some(co.build())));
+        }
+
+        // In 2D, the second ball lands on a platform of its own.
+        #[cfg(feature = "dim2")]
+        {
+            let co = ColliderBuilder::cuboid(50.0, 0.1).translation(0.0, 5.0);
+            let _ = world.insert_collider(co.build(), ground);
+        }
+        #[cfg(feature = "dim3")]
+        let _ = ground;
+
+        world.run(60);
+
+        let free_speed = world.bodies[balls[0]].linvel().x;
+        let damped_speed = world.bodies[balls[1]].linvel().x;
+        // Without rolling resistance, the ball keeps the speed it has once it stops slipping.
+        assert!(free_speed > 3.0);
+        assert!(damped_speed < free_speed - 0.3);
+        assert!(damped_speed > 0.0);
+    }
+
+    #[cfg(feature = "dim3")]
+    #[test]
+    fn anisotropic_friction() {
+        let mut world = TestWorld::with_gravity();
+        let _ = world.insert_ground(50.0);
+
+        // A box sliding diagonally, without friction along its local `x` axis.
+        let rb = RigidBodyBuilder::new_dynamic()
+            .translation(0.0, 0.6, 0.0)
+            .linvel(3.0, 0.0, 3.0);
+        let co = ColliderBuilder::cuboid(0.5, 0.5, 0.5)
+            .friction(1.0)
+            .anisotropic_friction(Vector::x_axis(), 0.0)
+            .friction_combine_rule(CoefficientCombineRule::Min);
+        let body = world.insert(rb.build(), Some(co.build()));
+
+        world.run(60);
+
+        let linvel = world.bodies[body].linvel();
+        assert!(linvel.x > 2.9);
+        assert!(linvel.z.abs() < 0.1);
+    }
+}
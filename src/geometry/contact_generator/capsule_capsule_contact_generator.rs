@@ -54,12 +54,12 @@ pub fn generate_contacts<'a>(
     let swapped = false;
 
     let fid1 = if let SegmentPointLocation::OnVertex(v1) = loc1 {
-        v1 as u8 * 2
+        v1 as u32 * 2
     } else {
         1
     };
     let fid2 = if let SegmentPointLocation::OnVertex(v2) = loc2 {
-        v2 as u8 * 2
+        v2 as u32 * 2
     } else {
         1
     };
@@ -105,8 +105,8 @@ pub fn generate_contacts<'a>(
                         Contact::new(
                             clip_a.0,
                             pos21 * clip_a.1,
-                            clip_a.2 as u8,
-                            clip_a.3 as u8,
+                            clip_a.2 as u32,
+                            clip_a.3 as u32,
                             (clip_a.1 - clip_a.0).dot(&local_n1),
                         )
                     } else {
@@ -114,8 +114,8 @@ pub fn generate_contacts<'a>(
                         Contact::new(
                             clip_b.0,
                             pos21 * clip_b.1,
-                            clip_b.2 as u8,
-                            clip_b.3 as u8,
+                            clip_b.2 as u32,
+                            clip_b.3 as u32,
                             (clip_b.1 - clip_b.0).dot(&local_n1),
                         )
                     };
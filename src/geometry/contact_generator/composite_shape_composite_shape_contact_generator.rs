@@ -0,0 +1,323 @@
+use crate::data::hashmap::{Entry, HashMap};
+use crate::data::MaybeSerializableData;
+use crate::geometry::contact_generator::{
+    ContactGenerationContext, ContactGeneratorWorkspace, PrimitiveContactGenerationContext,
+    PrimitiveContactGenerator,
+};
+use crate::geometry::ContactManifold;
+use crate::ncollide::bounding_volume::BoundingVolume;
+#[cfg(feature = "serde-serialize")]
+use erased_serde::Serialize;
+
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Clone)]
+struct SubDetector {
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    generator: Option<PrimitiveContactGenerator>,
+    manifold_id: usize,
+    timestamp: bool,
+    workspace: Option<ContactGeneratorWorkspace>,
+}
+
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Clone)]
+pub struct CompositeShapeCompositeShapeContactGeneratorWorkspace {
+    timestamp: bool,
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    old_manifolds: Vec<ContactManifold>,
+    sub_detectors: HashMap<(usize, usize), SubDetector>,
+}
+
+impl CompositeShapeCompositeShapeContactGeneratorWorkspace {
+    pub fn new() -> Self {
+        Self {
+            timestamp: false,
+            old_manifolds: Vec::new(),
+            sub_detectors: HashMap::default(),
+        }
+    }
+}
+
+/// Generates contacts between two composite shapes.
+///
+/// One contact manifold is generated for each pair of parts with intersecting AABBs. Its
+/// subshape indices are the indices of the two parts.
+pub fn generate_contacts_composite_shape_composite_shape(ctxt: &mut ContactGenerationContext) {
+    let collider1 = &ctxt.colliders[ctxt.pair.pair.collider1];
+    let collider2 = &ctxt.colliders[ctxt.pair.pair.collider2];
+    let shape1 = collider1.shape();
+    let shape2 = collider2.shape();
+
+    let workspace: &mut CompositeShapeCompositeShapeContactGeneratorWorkspace = ctxt
+        .pair
+        .generator_workspace
+        .as_mut()
+        .expect("The CompositeShapeCompositeShapeContactGeneratorWorkspace is missing.")
+        .0
+        .downcast_mut()
+        .expect(
+            "Invalid workspace type, expected a CompositeShapeCompositeShapeContactGeneratorWorkspace.",
+        );
+    let new_timestamp = !workspace.timestamp;
+    workspace.timestamp = new_timestamp;
+
+    /*
+     * Compute interferences.
+     */
+    let pos12 = collider1.position.inverse() * collider2.position;
+    // TODO: somehow precompute the AABB and reuse it?
+    let ls_aabb2 = shape2
+        .compute_aabb(&pos12)
+        .loosened(ctxt.prediction_distance);
+
+    std::mem::swap(&mut workspace.old_manifolds, &mut ctxt.pair.manifolds);
+    ctxt.pair.manifolds.clear();
+    let coll_pair = ctxt.pair.pair;
+    let manifolds = &mut ctxt.pair.manifolds;
+    let prediction_distance = ctxt.prediction_distance;
+    let dispatcher = ctxt.dispatcher;
+    let solver_flags = ctxt.solver_flags;
+
+    let is_composite = shape1.map_parts_in_local_aabb(&ls_aabb2, &mut |i1, part_pos1, part1| {
+        let position1 = collider1.position() * part_pos1;
+        let pos21 = collider2.position.inverse() * position1;
+        let ls_part_aabb1 = part1.compute_aabb(&pos21).loosened(prediction_distance);
+
+        let _ = shape2.map_parts_in_local_aabb(&ls_part_aabb1, &mut |i2, part_pos2, part2| {
+            let position2 = collider2.position() * part_pos2;
+
+            let sub_detector = match workspace.sub_detectors.entry((i1, i2)) {
+                Entry::Occupied(entry) => {
+                    let sub_detector = entry.into_mut();
+                    let manifold = workspace.old_manifolds[sub_detector.manifold_id].take();
+                    sub_detector.manifold_id = manifolds.len();
+                    sub_detector.timestamp = new_timestamp;
+                    manifolds.push(manifold);
+                    sub_detector
+                }
+                Entry::Vacant(entry) => {
+                    let (generator, workspace2) =
+                        dispatcher.dispatch_primitives(part1.shape_type(), part2.shape_type());
+                    let sub_detector = SubDetector {
+                        generator: Some(generator),
+                        manifold_id: manifolds.len(),
+                        timestamp: new_timestamp,
+                        workspace: workspace2,
+                    };
+                    let manifold = ContactManifold::with_subshape_indices(
+                        coll_pair,
+                        collider1,
+                        collider2,
+                        i1,
+                        i2,
+                        solver_flags,
+                    );
+                    manifolds.push(manifold);
+
+                    entry.insert(sub_detector)
+                }
+            };
+
+            if sub_detector.generator.is_none() {
+                // We probably lost the generator after deserialization.
+                // So we need to dispatch again.
+                let (generator, workspace2) =
+                    dispatcher.dispatch_primitives(part1.shape_type(), part2.shape_type());
+                sub_detector.generator = Some(generator);
+
+                // Don't overwrite the workspace if we already deserialized one.
+                if sub_detector.workspace.is_none() {
+                    sub_detector.workspace = workspace2;
+                }
+            }
+
+            let manifold = &mut manifolds[sub_detector.manifold_id];
+            let delta1 = collider1.position_wrt_parent() * part_pos1;
+            let delta2 = collider2.position_wrt_parent() * part_pos2;
+
+            // The contact points are expressed in the local-space of the parts. The deltas are
+            // reset at each update because the voxel identifiers are reused after a removal.
+            let mut ctxt2 = if coll_pair.collider1 != manifold.pair.collider1 {
+                manifold.delta1 = delta2;
+                manifold.delta2 = delta1;
+                PrimitiveContactGenerationContext {
+                    prediction_distance,
+                    collider1: collider2,
+                    collider2: collider1,
+                    shape1: part2,
+                    shape2: part1,
+                    position1: &position2,
+                    position2: &position1,
+                    manifold,
+                    workspace: sub_detector.workspace.as_mut().map(|w| &mut *w.0),
+                }
+            } else {
+                manifold.delta1 = delta1;
+                manifold.delta2 = delta2;
+                PrimitiveContactGenerationContext {
+                    prediction_distance,
+                    collider1,
+                    collider2,
+                    shape1: part1,
+                    shape2: part2,
+                    position1: &position1,
+                    position2: &position2,
+                    manifold,
+                    workspace: sub_detector.workspace.as_mut().map(|w| &mut *w.0),
+                }
+            };
+
+            (sub_detector.generator.unwrap().generate_contacts)(&mut ctxt2)
+        });
+    });
+
+    assert!(is_composite, "Invalid shape types provided.");
+
+    workspace
+        .sub_detectors
+        .retain(|_, detector| detector.timestamp == new_timestamp)
+}
+
+impl MaybeSerializableData for CompositeShapeCompositeShapeContactGeneratorWorkspace {
+    #[cfg(feature = "serde-serialize")]
+    fn as_serialize(&self) -> Option<(u32, &dyn Serialize)> {
+        Some((
+            super::WorkspaceSerializationTag::CompositeShapeCompositeShapeContactGeneratorWorkspace
+                as u32,
+            self,
+        ))
+    }
+
+    fn clone_dyn(&self) -> Box<dyn MaybeSerializableData> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dynamics::{MassProperties, RigidBodyBuilder};
+    use crate::geometry::{ColliderBuilder, ColliderShape};
+    use crate::math::{Point, Translation, Vector};
+    use crate::pipeline::test_world::TestWorld;
+    use na::Point3;
+
+    #[test]
+    fn trimesh_resting_on_heightfield_and_trimesh() {
+        // A unit box made of triangles.
+        #[cfg(feature = "dim2")]
+        let (box_vertices, box_indices) = (
+            vec![
+                Point::new(-0.5, -0.5),
+                Point::new(0.5, -0.5),
+                Point::new(0.5, 0.5),
+                Point::new(-0.5, 0.5),
+            ],
+            vec![Point3::new(0, 1, 2), Point3::new(0, 2, 3)],
+        );
+        #[cfg(feature = "dim3")]
+        let (box_vertices, box_indices) = (
+            (0..8)
+                .map(|i| {
+                    Point::new(
+                        if i & 1 != 0 { 0.5 } else { -0.5 },
+                        if i & 2 != 0 { 0.5 } else { -0.5 },
+                        if i & 4 != 0 { 0.5 } else { -0.5 },
+                    )
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                Point3::new(0, 1, 3),
+                Point3::new(0, 3, 2),
+                Point3::new(4, 6, 7),
+                Point3::new(4, 7, 5),
+                Point3::new(0, 4, 5),
+                Point3::new(0, 5, 1),
+                Point3::new(2, 3, 7),
+                Point3::new(2, 7, 6),
+                Point3::new(0, 2, 6),
+                Point3::new(0, 6, 4),
+                Point3::new(1, 5, 7),
+                Point3::new(1, 7, 3),
+            ],
+        );
+
+        // A flat ground with its top at y = 0, either as a heightfield or as a triangle mesh.
+        #[cfg(feature = "dim2")]
+        let grounds = vec![
+            ColliderShape::heightfield(na::DVector::zeros(11), Vector::new(20.0, 1.0)),
+            ColliderShape::trimesh(
+                vec![
+                    Point::new(-10.0, -1.0),
+                    Point::new(10.0, -1.0),
+                    Point::new(10.0, 0.0),
+                    Point::new(-10.0, 0.0),
+                ],
+                vec![Point3::new(0, 1, 2), Point3::new(0, 2, 3)],
+            ),
+        ];
+        #[cfg(feature = "dim3")]
+        let grounds = vec![
+            ColliderShape::heightfield(na::DMatrix::zeros(11, 11), Vector::new(20.0, 1.0, 20.0)),
+            ColliderShape::trimesh(
+                vec![
+                    Point::new(-10.0, 0.0, -10.0),
+                    Point::new(10.0, 0.0, -10.0),
+                    Point::new(10.0, 0.0, 10.0),
+                    Point::new(-10.0, 0.0, 10.0),
+                ],
+                vec![Point3::new(0, 2, 1), Point3::new(0, 3, 2)],
+            ),
+        ];
+
+        for ground in grounds {
+            let mut world = TestWorld::with_gravity();
+            let num_ground_parts = ground
+                .as_trimesh()
+                .map(|trimesh| trimesh.num_triangles())
+                .unwrap_or(usize::MAX);
+            let ground_body = world.insert(RigidBodyBuilder::new_static().build(), None);
+            let co = ColliderBuilder::new(ground).build();
+            let ground_handle = world.insert_collider(co, ground_body);
+
+            // A triangle mesh has no volume so we give its body the mass of a unit box.
+            let rb = RigidBodyBuilder::new_dynamic()
+                .position(Translation::from(Vector::y() * 1.0).into())
+                .mass_properties(MassProperties::from_cuboid(1.0, Vector::repeat(0.5)))
+                .build();
+            let handle = world.insert(rb, None);
+            let co = ColliderBuilder::trimesh(box_vertices.clone(), box_indices.clone()).build();
+            let box_handle = world.insert_collider(co, handle);
+            world.run(100);
+
+            let rb = &world.bodies[handle];
+            assert!((rb.position().translation.vector.y - 0.5).abs() < 0.05);
+            assert!(rb.linvel().norm() < 0.1);
+
+            // The manifolds identify the triangles in contact.
+            let (_, _, pair) = world
+                .narrow_phase
+                .contact_graph()
+                .interaction_pairs()
+                .find(|(h1, h2, _)| {
+                    (*h1, *h2) == (ground_handle, box_handle)
+                        || (*h1, *h2) == (box_handle, ground_handle)
+                })
+                .unwrap();
+            let mut num_contacts = 0;
+
+            for manifold in &pair.manifolds {
+                let (ground_part, box_part) = if manifold.pair.collider1 == ground_handle {
+                    manifold.subshape_index_pair
+                } else {
+                    let (box_part, ground_part) = manifold.subshape_index_pair;
+                    (ground_part, box_part)
+                };
+                assert!(ground_part < num_ground_parts);
+                assert!(box_part < box_indices.len());
+                num_contacts += manifold.num_active_contacts();
+            }
+
+            assert!(num_contacts > 0);
+        }
+    }
+}
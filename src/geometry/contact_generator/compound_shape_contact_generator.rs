@@ -0,0 +1,225 @@
+use crate::data::hashmap::{Entry, HashMap};
+use crate::data::MaybeSerializableData;
+use crate::dynamics::CoefficientCombineRule;
+use crate::geometry::contact_generator::{
+    ContactGenerationContext, ContactGeneratorWorkspace, PrimitiveContactGenerationContext,
+    PrimitiveContactGenerator,
+};
+use crate::geometry::{Collider, Compound, ContactManifold};
+use crate::ncollide::bounding_volume::BoundingVolume;
+#[cfg(feature = "serde-serialize")]
+use erased_serde::Serialize;
+
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Clone)]
+struct SubDetector {
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    generator: Option<PrimitiveContactGenerator>,
+    manifold_id: usize,
+    timestamp: bool,
+    workspace: Option<ContactGeneratorWorkspace>,
+}
+
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Clone)]
+pub struct CompoundShapeContactGeneratorWorkspace {
+    timestamp: bool,
+    interferences: Vec<usize>,
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    old_manifolds: Vec<ContactManifold>,
+    sub_detectors: HashMap<usize, SubDetector>,
+}
+
+impl CompoundShapeContactGeneratorWorkspace {
+    pub fn new() -> Self {
+        Self {
+            timestamp: false,
+            interferences: Vec::new(),
+            old_manifolds: Vec::new(),
+            sub_detectors: HashMap::default(),
+        }
+    }
+}
+
+pub fn generate_contacts_compound_shape(ctxt: &mut ContactGenerationContext) {
+    let collider1 = &ctxt.colliders[ctxt.pair.pair.collider1];
+    let collider2 = &ctxt.colliders[ctxt.pair.pair.collider2];
+
+    if let Some(compound1) = collider1.shape().as_compound() {
+        do_generate_contacts(compound1, collider1, collider2, ctxt, false)
+    } else if let Some(compound2) = collider2.shape().as_compound() {
+        do_generate_contacts(compound2, collider2, collider1, ctxt, true)
+    }
+}
+
+fn do_generate_contacts(
+    compound1: &Compound,
+    collider1: &Collider,
+    collider2: &Collider,
+    ctxt: &mut ContactGenerationContext,
+    flipped: bool,
+) {
+    let ctxt_pair_pair = if flipped {
+        ctxt.pair.pair.swap()
+    } else {
+        ctxt.pair.pair
+    };
+
+    let workspace: &mut CompoundShapeContactGeneratorWorkspace = ctxt
+        .pair
+        .generator_workspace
+        .as_mut()
+        .expect("The CompoundShapeContactGeneratorWorkspace is missing.")
+        .0
+        .downcast_mut()
+        .expect("Invalid workspace type, expected a CompoundShapeContactGeneratorWorkspace.");
+    let new_timestamp = !workspace.timestamp;
+    workspace.timestamp = new_timestamp;
+
+    /*
+     * Compute interferences.
+     */
+    let pos12 = collider1.position.inverse() * collider2.position;
+    // TODO: somehow precompute the AABB and reuse it?
+    let ls_aabb2 = collider2
+        .shape()
+        .compute_aabb(&pos12)
+        .loosened(ctxt.prediction_distance);
+
+    workspace.interferences.clear();
+    compound1
+        .quadtree()
+        .intersect_aabb(&ls_aabb2, &mut workspace.interferences);
+
+    std::mem::swap(&mut workspace.old_manifolds, &mut ctxt.pair.manifolds);
+    ctxt.pair.manifolds.clear();
+    let manifolds = &mut ctxt.pair.manifolds;
+    let shape_type2 = collider2.shape().shape_type();
+
+    for part_id in &workspace.interferences {
+        // Because of SIMD padding, the quadtree may return part indices greater than the max.
+        let (part_pos1, part_shape1) = match compound1.shapes().get(*part_id) {
+            Some(part) => part,
+            None => continue,
+        };
+
+        let sub_detector = match workspace.sub_detectors.entry(*part_id) {
+            Entry::Occupied(entry) => {
+                let sub_detector = entry.into_mut();
+                let manifold = workspace.old_manifolds[sub_detector.manifold_id].take();
+                sub_detector.manifold_id = manifolds.len();
+                sub_detector.timestamp = new_timestamp;
+                manifolds.push(manifold);
+                sub_detector
+            }
+            Entry::Vacant(entry) => {
+                let (generator, workspace2) = ctxt
+                    .dispatcher
+                    .dispatch_primitives(part_shape1.shape_type(), shape_type2);
+                let sub_detector = SubDetector {
+                    generator: Some(generator),
+                    manifold_id: manifolds.len(),
+                    timestamp: new_timestamp,
+                    workspace: workspace2,
+                };
+                let mut manifold = ContactManifold::with_subshape_indices(
+                    ctxt_pair_pair,
+                    collider1,
+                    collider2,
+                    *part_id,
+                    0,
+                    ctxt.solver_flags,
+                );
+                // The contact points will be expressed in the local-space of the part.
+                manifold.delta1 *= part_pos1;
+                #[cfg(feature = "dim3")]
+                {
+                    manifold.local_friction_dir1 = manifold
+                        .local_friction_dir1
+                        .map(|dir| part_pos1.inverse_transform_vector(&dir));
+                }
+
+                if let Some(material) = compound1.part_material(*part_id) {
+                    manifold.friction = CoefficientCombineRule::combine(
+                        material.friction,
+                        collider2.friction,
+                        collider1.friction_combine_rule,
+                        collider2.friction_combine_rule,
+                    );
+                    manifold.restitution = CoefficientCombineRule::combine(
+                        material.restitution,
+                        collider2.restitution,
+                        collider1.restitution_combine_rule,
+                        collider2.restitution_combine_rule,
+                    );
+                }
+
+                manifolds.push(manifold);
+                entry.insert(sub_detector)
+            }
+        };
+
+        if sub_detector.generator.is_none() {
+            // We probably lost the generator after deserialization.
+            // So we need to dispatch again.
+            let (generator, workspace2) = ctxt
+                .dispatcher
+                .dispatch_primitives(part_shape1.shape_type(), shape_type2);
+            sub_detector.generator = Some(generator);
+
+            // Don't overwrite the workspace if we already deserialized one.
+            if sub_detector.workspace.is_none() {
+                sub_detector.workspace = workspace2;
+            }
+        }
+
+        let manifold = &mut manifolds[sub_detector.manifold_id];
+        let position1 = collider1.position() * part_pos1;
+
+        let mut ctxt2 = if ctxt_pair_pair.collider1 != manifold.pair.collider1 {
+            PrimitiveContactGenerationContext {
+                prediction_distance: ctxt.prediction_distance,
+                collider1: collider2,
+                collider2: collider1,
+                shape1: collider2.shape(),
+                shape2: &**part_shape1,
+                position1: collider2.position(),
+                position2: &position1,
+                manifold,
+                workspace: sub_detector.workspace.as_mut().map(|w| &mut *w.0),
+            }
+        } else {
+            PrimitiveContactGenerationContext {
+                prediction_distance: ctxt.prediction_distance,
+                collider1,
+                collider2,
+                shape1: &**part_shape1,
+                shape2: collider2.shape(),
+                position1: &position1,
+                position2: collider2.position(),
+                manifold,
+                workspace: sub_detector.workspace.as_mut().map(|w| &mut *w.0),
+            }
+        };
+
+        (sub_detector.generator.unwrap().generate_contacts)(&mut ctxt2)
+    }
+
+    workspace
+        .sub_detectors
+        .retain(|_, detector| detector.timestamp == new_timestamp)
+}
+
+impl MaybeSerializableData for CompoundShapeContactGeneratorWorkspace {
+    #[cfg(feature = "serde-serialize")]
+    fn as_serialize(&self) -> Option<(u32, &dyn Serialize)> {
+        Some((
+            super::WorkspaceSerializationTag::CompoundShapeContactGeneratorWorkspace as u32,
+            self,
+        ))
+    }
+
+    fn clone_dyn(&self) -> Box<dyn MaybeSerializableData> {
+        Box::new(self.clone())
+    }
+}
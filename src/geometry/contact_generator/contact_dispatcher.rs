@@ -1,15 +1,16 @@
 #[cfg(feature = "dim3")]
 use crate::geometry::contact_generator::PfmPfmContactManifoldGeneratorWorkspace;
 use crate::geometry::contact_generator::{
+    CompositeShapeCompositeShapeContactGeneratorWorkspace, CompoundShapeContactGeneratorWorkspace,
     ContactGenerator, ContactGeneratorWorkspace, ContactPhase,
     HeightFieldShapeContactGeneratorWorkspace, PrimitiveContactGenerator,
-    TrimeshShapeContactGeneratorWorkspace,
+    TrimeshShapeContactGeneratorWorkspace, VoxelsShapeContactGeneratorWorkspace,
 };
 use crate::geometry::ShapeType;
 
 /// Trait implemented by structures responsible for selecting a collision-detection algorithm
 /// for a given pair of shapes.
-pub trait ContactDispatcher {
+pub trait ContactDispatcher: Send + Sync {
     /// Select the collision-detection algorithm for the given pair of primitive shapes.
     fn dispatch_primitives(
         &self,
@@ -50,13 +51,6 @@ impl ContactDispatcher for DefaultContactDispatcher {
                 },
                 None,
             ),
-            // (ShapeType::Polygon, ShapeType::Polygon) => (
-            //     PrimitiveContactGenerator {
-            //         generate_contacts: super::generate_contacts_polygon_polygon,
-            //         ..PrimitiveContactGenerator::default()
-            //     },
-            //     None,
-            // ),
             (ShapeType::Capsule, ShapeType::Capsule) => (
                 PrimitiveContactGenerator {
                     generate_contacts: super::generate_contacts_capsule_capsule,
@@ -71,6 +65,14 @@ impl ContactDispatcher for DefaultContactDispatcher {
                 },
                 None,
             ),
+            #[cfg(feature = "dim2")]
+            (ShapeType::Polygon, _) | (_, ShapeType::Polygon) => (
+                PrimitiveContactGenerator {
+                    generate_contacts: super::generate_contacts_polygon_polygon,
+                    ..PrimitiveContactGenerator::default()
+                },
+                None,
+            ),
             (ShapeType::Capsule, ShapeType::Cuboid) | (ShapeType::Cuboid, ShapeType::Capsule) => (
                 PrimitiveContactGenerator {
                     generate_contacts: super::generate_contacts_cuboid_capsule,
@@ -87,6 +89,17 @@ impl ContactDispatcher for DefaultContactDispatcher {
                     None,
                 )
             }
+            #[cfg(feature = "dim2")]
+            (ShapeType::Triangle, _)
+            | (_, ShapeType::Triangle)
+            | (ShapeType::Segment, _)
+            | (_, ShapeType::Segment) => (
+                PrimitiveContactGenerator {
+                    generate_contacts: super::generate_contacts_polygon_polygon,
+                    ..PrimitiveContactGenerator::default()
+                },
+                None,
+            ),
             #[cfg(feature = "dim3")]
             (ShapeType::Cylinder, _)
             | (_, ShapeType::Cylinder)
@@ -95,7 +108,13 @@ impl ContactDispatcher for DefaultContactDispatcher {
             | (ShapeType::RoundCylinder, _)
             | (_, ShapeType::RoundCylinder)
             | (ShapeType::Capsule, _)
-            | (_, ShapeType::Capsule) => (
+            | (_, ShapeType::Capsule)
+            | (ShapeType::ConvexPolyhedron, _)
+            | (_, ShapeType::ConvexPolyhedron)
+            | (ShapeType::Triangle, _)
+            | (_, ShapeType::Triangle)
+            | (ShapeType::Segment, _)
+            | (_, ShapeType::Segment) => (
                 PrimitiveContactGenerator {
                     generate_contacts: super::generate_contacts_pfm_pfm,
                     ..PrimitiveContactGenerator::default()
@@ -114,6 +133,15 @@ impl ContactDispatcher for DefaultContactDispatcher {
         shape2: ShapeType,
     ) -> (ContactPhase, Option<ContactGeneratorWorkspace>) {
         match (shape1, shape2) {
+            (_, _) if shape1.is_composite() && shape2.is_composite() => (
+                ContactPhase::NearPhase(ContactGenerator {
+                    generate_contacts: super::generate_contacts_composite_shape_composite_shape,
+                    ..ContactGenerator::default()
+                }),
+                Some(ContactGeneratorWorkspace::from(
+                    CompositeShapeCompositeShapeContactGeneratorWorkspace::new(),
+                )),
+            ),
             (ShapeType::Trimesh, _) | (_, ShapeType::Trimesh) => (
                 ContactPhase::NearPhase(ContactGenerator {
                     generate_contacts: super::generate_contacts_trimesh_shape,
@@ -132,6 +160,24 @@ impl ContactDispatcher for DefaultContactDispatcher {
                     HeightFieldShapeContactGeneratorWorkspace::new(),
                 )),
             ),
+            (ShapeType::Compound, _) | (_, ShapeType::Compound) => (
+                ContactPhase::NearPhase(ContactGenerator {
+                    generate_contacts: super::generate_contacts_compound_shape,
+                    ..ContactGenerator::default()
+                }),
+                Some(ContactGeneratorWorkspace::from(
+                    CompoundShapeContactGeneratorWorkspace::new(),
+                )),
+            ),
+            (ShapeType::Voxels, _) | (_, ShapeType::Voxels) => (
+                ContactPhase::NearPhase(ContactGenerator {
+                    generate_contacts: super::generate_contacts_voxels_shape,
+                    ..ContactGenerator::default()
+                }),
+                Some(ContactGeneratorWorkspace::from(
+                    VoxelsShapeContactGeneratorWorkspace::new(),
+                )),
+            ),
             _ => {
                 let (gen, workspace) = self.dispatch_primitives(shape1, shape2);
                 (ContactPhase::ExactPhase(gen), workspace)
@@ -8,13 +8,17 @@ use crate::math::Isometry;
 use crate::math::{SimdFloat, SIMD_WIDTH};
 use crate::pipeline::EventHandler;
 
+/// The contact generation algorithm selected by a `ContactDispatcher` for a pair of colliders.
 #[derive(Copy, Clone)]
 pub enum ContactPhase {
+    /// A contact generator working on the whole contact pair, e.g., for composite shapes.
     NearPhase(ContactGenerator),
+    /// A contact generator working on a single manifold between two primitive shapes.
     ExactPhase(PrimitiveContactGenerator),
 }
 
 impl ContactPhase {
+    /// Runs the contact generator and emits contact events if the pair started or stopped touching.
     #[inline]
     pub fn generate_contacts(
         self,
@@ -61,6 +65,7 @@ impl ContactPhase {
         }
     }
 
+    /// Runs the contact generator on `SIMD_WIDTH` pairs at once.
     #[cfg(feature = "simd-is-enabled")]
     #[inline]
     pub fn generate_contacts_simd(
@@ -139,34 +144,59 @@ impl ContactPhase {
     }
 }
 
+/// The data available to a primitive contact generator.
 pub struct PrimitiveContactGenerationContext<'a> {
+    /// The distance below which contacts are generated even if the shapes do not touch.
     pub prediction_distance: f32,
+    /// The first collider involved in the contact.
     pub collider1: &'a Collider,
+    /// The second collider involved in the contact.
     pub collider2: &'a Collider,
+    /// The shape of the first collider, or a sub-shape of it.
     pub shape1: &'a dyn Shape,
+    /// The shape of the second collider, or a sub-shape of it.
     pub shape2: &'a dyn Shape,
+    /// The world-space position of `shape1`.
     pub position1: &'a Isometry<f32>,
+    /// The world-space position of `shape2`.
     pub position2: &'a Isometry<f32>,
+    /// The contact manifold to update.
     pub manifold: &'a mut ContactManifold,
+    /// The workspace returned by the dispatcher alongside this generator, if any.
     pub workspace: Option<&'a mut (dyn MaybeSerializableData)>,
 }
 
 #[cfg(feature = "simd-is-enabled")]
+/// The data available to a primitive contact generator working on `SIMD_WIDTH` pairs at once.
 pub struct PrimitiveContactGenerationContextSimd<'a, 'b> {
+    /// The distance below which contacts are generated even if the shapes do not touch.
     pub prediction_distance: f32,
+    /// The first collider of each pair.
     pub colliders1: [&'a Collider; SIMD_WIDTH],
+    /// The second collider of each pair.
     pub colliders2: [&'a Collider; SIMD_WIDTH],
+    /// The first shape of each pair.
     pub shapes1: [&'a dyn Shape; SIMD_WIDTH],
+    /// The second shape of each pair.
     pub shapes2: [&'a dyn Shape; SIMD_WIDTH],
+    /// The world-space positions of `shapes1`.
     pub positions1: &'a Isometry<SimdFloat>,
+    /// The world-space positions of `shapes2`.
     pub positions2: &'a Isometry<SimdFloat>,
+    /// The contact manifolds to update.
     pub manifolds: &'a mut [&'b mut ContactManifold],
+    /// The workspace of each pair, if any.
     pub workspaces: &'a mut [Option<&'b mut (dyn MaybeSerializableData)>],
 }
 
+/// A contact generator for a pair of primitive shapes.
+///
+/// Unset functions default to a generator that does nothing.
 #[derive(Copy, Clone)]
 pub struct PrimitiveContactGenerator {
+    /// Generates the contacts of a single pair of shapes.
     pub generate_contacts: fn(&mut PrimitiveContactGenerationContext),
+    /// Generates the contacts of `SIMD_WIDTH` pairs of shapes at once.
     #[cfg(feature = "simd-is-enabled")]
     pub generate_contacts_simd: fn(&mut PrimitiveContactGenerationContextSimd),
 }
@@ -187,26 +217,43 @@ impl Default for PrimitiveContactGenerator {
     }
 }
 
+/// The data available to a contact generator.
 pub struct ContactGenerationContext<'a> {
+    /// The dispatcher to use for selecting algorithms for sub-shapes.
     pub dispatcher: &'a dyn ContactDispatcher,
+    /// The distance below which contacts are generated even if the shapes do not touch.
     pub prediction_distance: f32,
+    /// The set of all colliders.
     pub colliders: &'a ColliderSet,
+    /// The contact pair to update.
     pub pair: &'a mut ContactPair,
+    /// The solver flags computed by the contact pair filter.
     pub solver_flags: SolverFlags,
 }
 
 #[cfg(feature = "simd-is-enabled")]
+/// The data available to a contact generator working on `SIMD_WIDTH` pairs at once.
 pub struct ContactGenerationContextSimd<'a, 'b> {
+    /// The dispatcher to use for selecting algorithms for sub-shapes.
     pub dispatcher: &'a dyn ContactDispatcher,
+    /// The distance below which contacts are generated even if the shapes do not touch.
     pub prediction_distance: f32,
+    /// The set of all colliders.
     pub colliders: &'a ColliderSet,
+    /// The contact pairs to update.
     pub pairs: &'a mut [&'b mut ContactPair],
+    /// The solver flags of each pair.
     pub solver_flags: &'a [SolverFlags],
 }
 
+/// A contact generator for a pair of colliders, at least one of which is not a primitive shape.
+///
+/// Unset functions default to a generator that does nothing.
 #[derive(Copy, Clone)]
 pub struct ContactGenerator {
+    /// Generates the contacts of a single contact pair.
     pub generate_contacts: fn(&mut ContactGenerationContext),
+    /// Generates the contacts of `SIMD_WIDTH` contact pairs at once.
     #[cfg(feature = "simd-is-enabled")]
     pub generate_contacts_simd: fn(&mut ContactGenerationContextSimd),
 }
@@ -2,11 +2,15 @@ use crate::data::MaybeSerializableData;
 #[cfg(feature = "dim3")]
 use crate::geometry::contact_generator::PfmPfmContactManifoldGeneratorWorkspace;
 use crate::geometry::contact_generator::{
+    CompositeShapeCompositeShapeContactGeneratorWorkspace, CompoundShapeContactGeneratorWorkspace,
     HeightFieldShapeContactGeneratorWorkspace, TrimeshShapeContactGeneratorWorkspace,
-    WorkspaceSerializationTag,
+    VoxelsShapeContactGeneratorWorkspace, WorkspaceSerializationTag,
 };
 
 // Note we have this newtype because it simplifies the serialization/deserialization code.
+/// The persistent data of a contact generator, stored on the contact pair or manifold.
+///
+/// Only the workspaces of the built-in contact generators can be serialized.
 pub struct ContactGeneratorWorkspace(pub Box<dyn MaybeSerializableData>);
 
 impl Clone for ContactGeneratorWorkspace {
@@ -84,6 +88,15 @@ impl<'de> serde::Deserialize<'de> for ContactGeneratorWorkspace {
                     Some(WorkspaceSerializationTag::TrimeshShapeContactGeneratorWorkspace) => {
                         deser::<A, TrimeshShapeContactGeneratorWorkspace>(&mut seq)?
                     }
+                    Some(WorkspaceSerializationTag::CompoundShapeContactGeneratorWorkspace) => {
+                        deser::<A, CompoundShapeContactGeneratorWorkspace>(&mut seq)?
+                    }
+                    Some(WorkspaceSerializationTag::VoxelsShapeContactGeneratorWorkspace) => {
+                        deser::<A, VoxelsShapeContactGeneratorWorkspace>(&mut seq)?
+                    }
+                    Some(
+                        WorkspaceSerializationTag::CompositeShapeCompositeShapeContactGeneratorWorkspace,
+                    ) => deser::<A, CompositeShapeCompositeShapeContactGeneratorWorkspace>(&mut seq)?,
                     #[cfg(feature = "dim3")]
                     Some(WorkspaceSerializationTag::PfmPfmContactGeneratorWorkspace) => {
                         deser::<A, PfmPfmContactManifoldGeneratorWorkspace>(&mut seq)?
@@ -1,11 +1,13 @@
 use crate::data::hashmap::{Entry, HashMap};
 use crate::data::MaybeSerializableData;
 use crate::geometry::contact_generator::{
-    ContactGenerationContext, ContactGeneratorWorkspace, PrimitiveContactGenerationContext,
-    PrimitiveContactGenerator,
+    internal_edges, ContactGenerationContext, ContactGeneratorWorkspace,
+    PrimitiveContactGenerationContext, PrimitiveContactGenerator,
 };
+#[cfg(feature = "dim3")]
+use crate::geometry::Triangle as Facet;
 #[cfg(feature = "dim2")]
-use crate::geometry::Capsule;
+use crate::geometry::{Capsule, Segment as Facet};
 use crate::geometry::{Collider, ContactManifold, HeightField, Shape};
 use crate::ncollide::bounding_volume::BoundingVolume;
 #[cfg(feature = "serde-serialize")]
@@ -28,6 +30,9 @@ pub struct HeightFieldShapeContactGeneratorWorkspace {
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
     old_manifolds: Vec<ContactManifold>,
     sub_detectors: HashMap<usize, SubDetector>,
+    // The facets surrounding the facet being processed.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    neighbors: Vec<Facet>,
 }
 
 impl HeightFieldShapeContactGeneratorWorkspace {
@@ -36,6 +41,7 @@ impl HeightFieldShapeContactGeneratorWorkspace {
             timestamp: false,
             old_manifolds: Vec::new(),
             sub_detectors: HashMap::default(),
+            neighbors: Vec::new(),
         }
     }
 }
@@ -56,8 +62,14 @@ fn do_generate_contacts(
     collider1: &Collider,
     collider2: &Collider,
     ctxt: &mut ContactGenerationContext,
-    _flipped: bool,
+    flipped: bool,
 ) {
+    let ctxt_pair_pair = if flipped {
+        ctxt.pair.pair.swap()
+    } else {
+        ctxt.pair.pair
+    };
+
     let workspace: &mut HeightFieldShapeContactGeneratorWorkspace = ctxt
         .pair
         .generator_workspace
@@ -81,7 +93,7 @@ fn do_generate_contacts(
 
     std::mem::swap(&mut workspace.old_manifolds, &mut ctxt.pair.manifolds);
     ctxt.pair.manifolds.clear();
-    let coll_pair = ctxt.pair.pair;
+    let coll_pair = ctxt_pair_pair;
     let manifolds = &mut ctxt.pair.manifolds;
     let prediction_distance = ctxt.prediction_distance;
     let dispatcher = ctxt.dispatcher;
@@ -168,7 +180,21 @@ fn do_generate_contacts(
             }
         };
 
-        (sub_detector.generator.unwrap().generate_contacts)(&mut ctxt2)
+        (sub_detector.generator.unwrap().generate_contacts)(&mut ctxt2);
+
+        // The generator may have swapped the manifold identifiers.
+        let manifold = ctxt2.manifold;
+        let swapped = coll_pair.collider1 != manifold.pair.collider1;
+        neighbor_facets(heightfield1, i, &mut workspace.neighbors);
+        internal_edges::correct_internal_edge_contacts(
+            part1,
+            &workspace.neighbors,
+            position1,
+            collider2.position(),
+            manifold,
+            swapped,
+            prediction_distance,
+        );
     });
 
     workspace
@@ -176,6 +202,32 @@ fn do_generate_contacts(
         .retain(|_, detector| detector.timestamp == new_timestamp)
 }
 
+// Collects the segments preceding and following the `i`-th segment.
+#[cfg(feature = "dim2")]
+fn neighbor_facets(heightfield: &HeightField, i: usize, out: &mut Vec<Facet>) {
+    out.clear();
+    let prev = i.checked_sub(1).and_then(|i| heightfield.segment_at(i));
+    out.extend(prev);
+    out.extend(heightfield.segment_at(i + 1));
+}
+
+// Collects the triangles of the cells surrounding the cell containing the `id`-th triangle.
+#[cfg(feature = "dim3")]
+fn neighbor_facets(heightfield: &HeightField, id: usize, out: &mut Vec<Facet>) {
+    let (nrows, ncols) = (heightfield.nrows(), heightfield.ncols());
+    let cell = id % (nrows * ncols);
+    let (i, j) = (cell % nrows, cell / nrows);
+    out.clear();
+
+    for ii in i.saturating_sub(1)..(i + 2).min(nrows) {
+        for jj in j.saturating_sub(1)..(j + 2).min(ncols) {
+            let (left, right) = heightfield.triangles_at(ii, jj);
+            out.extend(left);
+            out.extend(right);
+        }
+    }
+}
+
 impl MaybeSerializableData for HeightFieldShapeContactGeneratorWorkspace {
     #[cfg(feature = "serde-serialize")]
     fn as_serialize(&self) -> Option<(u32, &dyn Serialize)> {
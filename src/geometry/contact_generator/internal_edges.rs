@@ -0,0 +1,216 @@
+use crate::geometry::ContactManifold;
+#[cfg(feature = "dim2")]
+use crate::geometry::Segment as Facet;
+#[cfg(feature = "dim3")]
+use crate::geometry::Triangle as Facet;
+use crate::math::{Isometry, Point, Vector, DIM};
+use ncollide::query::PointQuery;
+
+/// Fixes the contacts generated against a facet of a triangle mesh or heightfield.
+///
+/// The facet is a triangle in 3D and a segment in 2D. A contact normal is valid only if no
+/// facet touching the contact point lies above the plane orthogonal to this normal. Otherwise
+/// the contact was generated on an edge or vertex shared with a neighbor facet, and its normal is
+/// replaced by the normal of the facet. Without this, objects sliding on a flat mesh would bump
+/// on the edges between its facets.
+///
+/// In 2D, this is only applied to heightfields: triangle meshes are made of solid triangles
+/// instead of segments, and their internal edges are not corrected.
+///
+/// The facet, its neighbors, and the contact points on the facet side are expressed in the
+/// local-space of the mesh, positioned at `pos1`.
+pub(crate) fn correct_internal_edge_contacts(
+    facet: &Facet,
+    neighbors: &[Facet],
+    pos1: &Isometry<f32>,
+    pos2: &Isometry<f32>,
+    manifold: &mut ContactManifold,
+    swapped: bool,
+    prediction_distance: f32,
+) {
+    if manifold.points.is_empty() {
+        return;
+    }
+
+    let facet_normal = match facet.normal() {
+        Some(normal) => normal.into_inner(),
+        None => return,
+    };
+    let normal = if swapped {
+        manifold.local_n2
+    } else {
+        manifold.local_n1
+    };
+
+    let vertices = facet_vertices(facet);
+    let mut size = 0.0f32;
+    for i in 0..DIM {
+        size = size.max(na::distance(&vertices[i], &vertices[(i + 1) % DIM]));
+    }
+    let eps = size * 1.0e-3;
+
+    let is_valid = manifold.points.iter().all(|contact| {
+        let pt = if swapped {
+            contact.local_p2
+        } else {
+            contact.local_p1
+        };
+
+        std::iter::once(facet).chain(neighbors).all(|neighbor| {
+            neighbor.distance_to_point(&Isometry::identity(), &pt, true) > eps
+                || facet_vertices(neighbor)
+                    .iter()
+                    .all(|vtx| (vtx - pt).dot(&normal) <= eps)
+        })
+    });
+
+    if !is_valid {
+        let facet_normal = if facet_normal.dot(&normal) < 0.0 {
+            -facet_normal
+        } else {
+            facet_normal
+        };
+        set_manifold_normal(
+            manifold,
+            facet_normal,
+            pos1,
+            pos2,
+            swapped,
+            prediction_distance,
+        );
+    }
+}
+
+/// Replaces the normal of a contact manifold and updates the distances of its contacts.
+///
+/// The `normal` is expressed in the local-space of the shape positioned at `pos1`.
+pub(crate) fn set_manifold_normal(
+    manifold: &mut ContactManifold,
+    normal: Vector<f32>,
+    pos1: &Isometry<f32>,
+    pos2: &Isometry<f32>,
+    swapped: bool,
+    prediction_distance: f32,
+) {
+    let world_normal = pos1 * normal;
+    let other_normal = pos2.inverse_transform_vector(&-world_normal);
+
+    for contact in &mut manifold.points {
+        let (pt1, pt2) = if swapped {
+            (pos1 * contact.local_p2, pos2 * contact.local_p1)
+        } else {
+            (pos1 * contact.local_p1, pos2 * contact.local_p2)
+        };
+        contact.dist = (pt2 - pt1).dot(&world_normal);
+    }
+
+    if swapped {
+        manifold.local_n1 = other_normal;
+        manifold.local_n2 = normal;
+    } else {
+        manifold.local_n1 = normal;
+        manifold.local_n2 = other_normal;
+    }
+
+    manifold.sort_contacts(prediction_distance);
+}
+
+#[cfg(feature = "dim2")]
+fn facet_vertices(facet: &Facet) -> [Point<f32>; DIM] {
+    [facet.a, facet.b]
+}
+
+#[cfg(feature = "dim3")]
+fn facet_vertices(facet: &Facet) -> [Point<f32>; DIM] {
+    *facet.vertices()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dynamics::RigidBodyBuilder;
+    use crate::geometry::{ColliderBuilder, ColliderShape};
+    use crate::math::{Translation, Vector};
+    use crate::pipeline::test_world::TestWorld;
+
+    // Slides a shape, resting at the given height, on a ground with its top at y = 0.
+    fn slide(ground: ColliderBuilder, shape: ColliderBuilder, height: f32) {
+        let mut world = TestWorld::with_gravity();
+        let ground_body = world.insert(RigidBodyBuilder::new_static().build(), None);
+        world.insert_collider(ground.friction(0.0).build(), ground_body);
+
+        let mut rb = RigidBodyBuilder::new_dynamic()
+            .position(Translation::from(Vector::x() * -20.0 + Vector::y() * height).into())
+            .build();
+        rb.set_linvel(Vector::x() * 10.0, true);
+        let handle = world.insert(rb, Some(shape.friction(0.0).build()));
+
+        for _ in 0..120 {
+            world.step();
+
+            // The shape crosses many internal edges without being deflected.
+            let rb = &world.bodies[handle];
+            assert!(rb.linvel().y.abs() < 0.1);
+            assert!((rb.linvel().x - 10.0).abs() < 0.1);
+            assert!((rb.position().translation.vector.y - height).abs() < 0.02);
+        }
+    }
+
+    fn sliding_shapes() -> Vec<(ColliderBuilder, f32)> {
+        vec![
+            (
+                ColliderBuilder::new(ColliderShape::cuboid(Vector::repeat(0.5))),
+                0.5,
+            ),
+            (ColliderBuilder::ball(0.5), 0.5),
+            (ColliderBuilder::capsule_x(0.5, 0.25), 0.25),
+        ]
+    }
+
+    // A flat heightfield with its top at y = 0, made of cells of unit width.
+    fn heightfield() -> ColliderBuilder {
+        #[cfg(feature = "dim2")]
+        return ColliderBuilder::heightfield(na::DVector::zeros(61), Vector::new(60.0, 1.0));
+        #[cfg(feature = "dim3")]
+        return ColliderBuilder::heightfield(
+            na::DMatrix::zeros(11, 61),
+            Vector::new(60.0, 1.0, 10.0),
+        );
+    }
+
+    #[test]
+    fn sliding_on_heightfield_without_bumps() {
+        for (shape, height) in sliding_shapes() {
+            slide(heightfield(), shape, height);
+        }
+    }
+
+    #[cfg(feature = "dim3")]
+    #[test]
+    fn sliding_on_trimesh_without_bumps() {
+        use crate::geometry::TrimeshFlags;
+        use crate::math::Point;
+        use na::Point3;
+
+        // A flat mesh with its top at y = 0, made of triangles of unit width.
+        let vertices: Vec<_> = (0..61 * 11)
+            .map(|i| Point::new((i % 61) as f32 - 30.0, 0.0, (i / 61) as f32 - 5.0))
+            .collect();
+        let mut indices = Vec::new();
+        for k in 0..10 {
+            for i in 0..60 {
+                let id = k * 61 + i;
+                indices.push(Point3::new(id, id + 61, id + 1));
+                indices.push(Point3::new(id + 1, id + 61, id + 62));
+            }
+        }
+
+        for (shape, height) in sliding_shapes() {
+            let ground = ColliderBuilder::trimesh_with_flags(
+                vertices.clone(),
+                indices.clone(),
+                TrimeshFlags::FIX_INTERNAL_EDGES,
+            );
+            slide(ground, shape, height);
+        }
+    }
+}
@@ -3,6 +3,13 @@ pub use self::ball_ball_contact_generator::generate_contacts_ball_ball;
 pub use self::ball_ball_contact_generator::generate_contacts_ball_ball_simd;
 pub use self::ball_convex_contact_generator::generate_contacts_ball_convex;
 pub use self::capsule_capsule_contact_generator::generate_contacts_capsule_capsule;
+pub use self::composite_shape_composite_shape_contact_generator::{
+    generate_contacts_composite_shape_composite_shape,
+    CompositeShapeCompositeShapeContactGeneratorWorkspace,
+};
+pub use self::compound_shape_contact_generator::{
+    generate_contacts_compound_shape, CompoundShapeContactGeneratorWorkspace,
+};
 pub use self::contact_dispatcher::{ContactDispatcher, DefaultContactDispatcher};
 pub use self::contact_generator::{
     ContactGenerationContext, ContactGenerator, ContactPhase, PrimitiveContactGenerationContext,
@@ -12,6 +19,7 @@ pub use self::contact_generator::{
 pub use self::contact_generator::{
     ContactGenerationContextSimd, PrimitiveContactGenerationContextSimd,
 };
+pub use self::contact_generator_workspace::ContactGeneratorWorkspace;
 pub use self::cuboid_capsule_contact_generator::generate_contacts_cuboid_capsule;
 pub use self::cuboid_cuboid_contact_generator::generate_contacts_cuboid_cuboid;
 pub use self::cuboid_triangle_contact_generator::generate_contacts_cuboid_triangle;
@@ -22,11 +30,14 @@ pub use self::heightfield_shape_contact_generator::{
 pub use self::pfm_pfm_contact_generator::{
     generate_contacts_pfm_pfm, PfmPfmContactManifoldGeneratorWorkspace,
 };
-// pub use self::polygon_polygon_contact_generator::generate_contacts_polygon_polygon;
-pub use self::contact_generator_workspace::ContactGeneratorWorkspace;
+#[cfg(feature = "dim2")]
+pub use self::polygon_polygon_contact_generator::generate_contacts_polygon_polygon;
 pub use self::trimesh_shape_contact_generator::{
     generate_contacts_trimesh_shape, TrimeshShapeContactGeneratorWorkspace,
 };
+pub use self::voxels_shape_contact_generator::{
+    generate_contacts_voxels_shape, VoxelsShapeContactGeneratorWorkspace,
+};
 
 pub(crate) use self::polygon_polygon_contact_generator::clip_segments;
 #[cfg(feature = "dim2")]
@@ -38,6 +49,8 @@ mod ball_ball_contact_generator;
 mod ball_convex_contact_generator;
 mod ball_polygon_contact_generator;
 mod capsule_capsule_contact_generator;
+mod composite_shape_composite_shape_contact_generator;
+mod compound_shape_contact_generator;
 mod contact_dispatcher;
 mod contact_generator;
 mod contact_generator_workspace;
@@ -46,11 +59,13 @@ mod cuboid_cuboid_contact_generator;
 mod cuboid_polygon_contact_generator;
 mod cuboid_triangle_contact_generator;
 mod heightfield_shape_contact_generator;
+mod internal_edges;
 #[cfg(feature = "dim3")]
 mod pfm_pfm_contact_generator;
 mod polygon_polygon_contact_generator;
 mod serializable_workspace_tag;
 mod trimesh_shape_contact_generator;
+mod voxels_shape_contact_generator;
 
 use crate::geometry::{Contact, ContactManifold};
 
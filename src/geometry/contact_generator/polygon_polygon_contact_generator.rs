@@ -1,33 +1,50 @@
-#![allow(dead_code)] // TODO: remove this once we support polygons.
-
+#[cfg(feature = "dim2")]
 use crate::geometry::contact_generator::PrimitiveContactGenerationContext;
-use crate::geometry::{sat, Contact, ContactManifold, KinematicsCategory, Polygon};
-use crate::math::{Isometry, Point};
 #[cfg(feature = "dim2")]
-use crate::{math::Vector, utils};
-
-pub fn generate_contacts_polygon_polygon(_ctxt: &mut PrimitiveContactGenerationContext) {
-    unimplemented!()
-    // if let (Shape::Polygon(polygon1), Shape::Polygon(polygon2)) = (ctxt.shape1, ctxt.shape2) {
-    //     generate_contacts(
-    //         polygon1,
-    //         &ctxt.position1,
-    //         polygon2,
-    //         &ctxt.position2,
-    //         ctxt.manifold,
-    //     );
-    //     ctxt.manifold.update_warmstart_multiplier();
-    // } else {
-    //     unreachable!()
-    // }
-    //
-    // ctxt.manifold.sort_contacts(ctxt.prediction_distance);
+use crate::geometry::{sat, Contact, ContactManifold, KinematicsCategory, Polygon, ShapePolygon};
+use crate::math::Point;
+#[cfg(feature = "dim2")]
+use crate::{
+    math::{Isometry, Vector},
+    utils,
+};
+
+/// Generates contacts between two 2D shapes with a polygonal representation.
+///
+/// This supports polygons, cuboids, triangles, segments, and capsules.
+#[cfg(feature = "dim2")]
+pub fn generate_contacts_polygon_polygon(ctxt: &mut PrimitiveContactGenerationContext) {
+    if let (Some((polygon1, radius1)), Some((polygon2, radius2))) = (
+        Polygon::from_shape(ctxt.shape1),
+        Polygon::from_shape(ctxt.shape2),
+    ) {
+        generate_contacts(
+            ctxt.prediction_distance,
+            &polygon1,
+            radius1,
+            ctxt.position1,
+            &polygon2,
+            radius2,
+            ctxt.position2,
+            ctxt.manifold,
+        );
+        ctxt.manifold.update_warmstart_multiplier();
+    } else {
+        // One of the shapes is degenerate, e.g., a segment with two identical vertices.
+        ctxt.manifold.points.clear();
+    }
+
+    ctxt.manifold.sort_contacts(ctxt.prediction_distance);
 }
 
+#[cfg(feature = "dim2")]
 fn generate_contacts<'a>(
-    mut p1: &'a Polygon,
+    prediction_distance: f32,
+    mut p1: &'a ShapePolygon<'a>,
+    mut radius1: f32,
     mut m1: &'a Isometry<f32>,
-    mut p2: &'a Polygon,
+    mut p2: &'a ShapePolygon<'a>,
+    mut radius2: f32,
     mut m2: &'a Isometry<f32>,
     manifold: &'a mut ContactManifold,
 ) {
@@ -38,14 +55,16 @@ fn generate_contacts<'a>(
         return;
     }
 
+    let total_prediction = prediction_distance + radius1 + radius2;
+
     let mut sep1 = sat::polygon_polygon_compute_separation_features(p1, p2, &m12);
-    if sep1.0 > 0.0 {
+    if sep1.0 > total_prediction {
         manifold.points.clear();
         return;
     }
 
     let mut sep2 = sat::polygon_polygon_compute_separation_features(p2, p1, &m21);
-    if sep2.0 > 0.0 {
+    if sep2.0 > total_prediction {
         manifold.points.clear();
         return;
     }
@@ -55,46 +74,45 @@ fn generate_contacts<'a>(
         std::mem::swap(&mut sep1, &mut sep2);
         std::mem::swap(&mut m1, &mut m2);
         std::mem::swap(&mut p1, &mut p2);
+        std::mem::swap(&mut radius1, &mut radius2);
         std::mem::swap(&mut m12, &mut m21);
         manifold.swap_identifiers();
         swapped = true;
     }
 
     let support_face1 = sep1.1;
-    let local_n1 = p1.normals[support_face1];
+    let local_n1 = p1.normals()[support_face1];
     let local_n2 = m21 * -local_n1;
     let support_face2 = p2.support_face(&local_n2);
-    let len1 = p1.vertices.len();
-    let len2 = p2.vertices.len();
+    let len1 = p1.vertices().len();
+    let len2 = p2.vertices().len();
 
     let seg1 = (
-        p1.vertices[support_face1],
-        p1.vertices[(support_face1 + 1) % len1],
+        p1.vertices()[support_face1],
+        p1.vertices()[(support_face1 + 1) % len1],
     );
     let seg2 = (
-        m12 * p2.vertices[support_face2],
-        m12 * p2.vertices[(support_face2 + 1) % len2],
+        m12 * p2.vertices()[support_face2],
+        m12 * p2.vertices()[(support_face2 + 1) % len2],
     );
     if let Some((clip_a, clip_b)) = clip_segments(seg1, seg2) {
-        let dist_a = (clip_a.1 - clip_a.0).dot(&local_n1);
-        let dist_b = (clip_b.1 - clip_b.0).dot(&local_n1);
+        let dist_a = (clip_a.1 - clip_a.0).dot(&local_n1) - radius1 - radius2;
+        let dist_b = (clip_b.1 - clip_b.0).dot(&local_n1) - radius1 - radius2;
 
         let mut impulses_a = (0.0, Contact::zero_tangent_impulse());
         let mut impulses_b = (0.0, Contact::zero_tangent_impulse());
 
         let fids_a = (
-            ((support_face1 * 2 + clip_a.2) % (len1 * 2)) as u8,
-            ((support_face2 * 2 + clip_a.3) % (len2 * 2)) as u8,
+            ((support_face1 * 2 + clip_a.2) % (len1 * 2)) as u32,
+            ((support_face2 * 2 + clip_a.3) % (len2 * 2)) as u32,
         );
 
         let fids_b = (
-            ((support_face1 * 2 + clip_b.2) % (len1 * 2)) as u8,
-            ((support_face2 * 2 + clip_b.3) % (len2 * 2)) as u8,
+            ((support_face1 * 2 + clip_b.2) % (len1 * 2)) as u32,
+            ((support_face2 * 2 + clip_b.3) % (len2 * 2)) as u32,
         );
 
-        if manifold.points.len() != 0 {
-            assert_eq!(manifold.points.len(), 2);
-
+        if manifold.points.len() == 2 {
             // We already had 2 points in the previous iteration.
             // Match the features to see if we keep the cached impulse.
             let original_fids_a;
@@ -103,11 +121,11 @@ fn generate_contacts<'a>(
             // NOTE: the previous manifold may have its bodies swapped wrt. our new manifold.
             // So we have to adjust accordingly the features we will be comparing.
             if swapped {
-                original_fids_a = (manifold.points[0].fid1, manifold.points[0].fid2);
-                original_fids_b = (manifold.points[1].fid1, manifold.points[1].fid2);
-            } else {
                 original_fids_a = (manifold.points[0].fid2, manifold.points[0].fid1);
                 original_fids_b = (manifold.points[1].fid2, manifold.points[1].fid1);
+            } else {
+                original_fids_a = (manifold.points[0].fid1, manifold.points[0].fid2);
+                original_fids_b = (manifold.points[1].fid1, manifold.points[1].fid2);
             }
 
             if fids_a == original_fids_a {
@@ -136,8 +154,8 @@ fn generate_contacts<'a>(
 
         manifold.points.clear();
         manifold.points.push(Contact {
-            local_p1: clip_a.0,
-            local_p2: m21 * clip_a.1,
+            local_p1: clip_a.0 + local_n1 * radius1,
+            local_p2: m21 * clip_a.1 + local_n2 * radius2,
             impulse: impulses_a.0,
             tangent_impulse: impulses_a.1,
             fid1: fids_a.0,
@@ -146,8 +164,8 @@ fn generate_contacts<'a>(
         });
 
         manifold.points.push(Contact {
-            local_p1: clip_b.0,
-            local_p2: m21 * clip_b.1,
+            local_p1: clip_b.0 + local_n1 * radius1,
+            local_p2: m21 * clip_b.1 + local_n2 * radius2,
             impulse: impulses_b.0,
             tangent_impulse: impulses_b.1,
             fid1: fids_b.0,
@@ -266,17 +284,20 @@ pub(crate) fn clip_segments(
         return None;
     }
 
+    // NOTE: a segment orthogonal to the other one has a zero length once projected.
+    // All its points then have the same projection, so any barycentric coordinate works.
     let length1 = range1[1] - range1[0];
     let length2 = range2[1] - range2[0];
+    let ratio = |num: f32, length: f32| if length != 0.0 { num / length } else { 0.0 };
 
     let ca = if range2[0] > range1[0] {
-        let bcoord = (range2[0] - range1[0]) / length1;
+        let bcoord = ratio(range2[0] - range1[0], length1);
         let p1 = seg1.0 + tangent1 * bcoord;
         let p2 = seg2.0;
 
         (p1, p2, 1, features2[0])
     } else {
-        let bcoord = (range1[0] - range2[0]) / length2;
+        let bcoord = ratio(range1[0] - range2[0], length2);
         let p1 = seg1.0;
         let p2 = seg2.0 + (seg2.1 - seg2.0) * bcoord;
 
@@ -284,13 +305,13 @@ pub(crate) fn clip_segments(
     };
 
     let cb = if range2[1] < range1[1] {
-        let bcoord = (range2[1] - range1[0]) / length1;
+        let bcoord = ratio(range2[1] - range1[0], length1);
         let p1 = seg1.0 + tangent1 * bcoord;
         let p2 = seg2.1;
 
         (p1, p2, 1, features2[1])
     } else {
-        let bcoord = (range1[1] - range2[0]) / length2;
+        let bcoord = ratio(range1[1] - range2[0], length2);
         let p1 = seg1.1;
         let p2 = seg2.0 + (seg2.1 - seg2.0) * bcoord;
 
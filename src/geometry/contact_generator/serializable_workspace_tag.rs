@@ -6,4 +6,7 @@ pub(super) enum WorkspaceSerializationTag {
     #[cfg(feature = "dim3")]
     PfmPfmContactGeneratorWorkspace,
     HeightfieldShapeContactGeneratorWorkspace,
+    CompoundShapeContactGeneratorWorkspace,
+    VoxelsShapeContactGeneratorWorkspace,
+    CompositeShapeCompositeShapeContactGeneratorWorkspace,
 }
@@ -1,8 +1,12 @@
 use crate::data::MaybeSerializableData;
+#[cfg(feature = "dim3")]
+use crate::geometry::contact_generator::internal_edges;
 use crate::geometry::contact_generator::{
     ContactGenerationContext, PrimitiveContactGenerationContext,
 };
 use crate::geometry::{Collider, ContactManifold, ShapeType, Trimesh};
+#[cfg(feature = "dim3")]
+use crate::geometry::{Triangle, TrimeshFlags};
 use crate::ncollide::bounding_volume::{BoundingVolume, AABB};
 #[cfg(feature = "serde-serialize")]
 use erased_serde::Serialize;
@@ -15,6 +19,10 @@ pub struct TrimeshShapeContactGeneratorWorkspace {
     old_interferences: Vec<usize>,
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
     old_manifolds: Vec<ContactManifold>,
+    // The triangles adjacent to the triangle being processed.
+    #[cfg(feature = "dim3")]
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    neighbors: Vec<Triangle>,
 }
 
 impl TrimeshShapeContactGeneratorWorkspace {
@@ -24,6 +32,8 @@ impl TrimeshShapeContactGeneratorWorkspace {
             local_aabb2: AABB::new_invalid(),
             old_interferences: Vec::new(),
             old_manifolds: Vec::new(),
+            #[cfg(feature = "dim3")]
+            neighbors: Vec::new(),
         }
     }
 }
@@ -129,6 +139,8 @@ fn do_generate_contacts(
     let new_interferences = &workspace.interferences;
     let mut old_inter_it = workspace.old_interferences.drain(..).peekable();
     let mut old_manifolds_it = workspace.old_manifolds.drain(..);
+    #[cfg(feature = "dim3")]
+    let neighbors = &mut workspace.neighbors;
     let shape_type2 = collider2.shape().shape_type();
 
     // TODO: don't redispatch at each frame (we should probably do the same as
@@ -203,6 +215,31 @@ fn do_generate_contacts(
         };
 
         (generator.generate_contacts)(&mut ctxt2);
+
+        #[cfg(feature = "dim3")]
+        {
+            if trimesh1.flags().contains(TrimeshFlags::FIX_INTERNAL_EDGES) {
+                // The generator may have swapped the manifold identifiers.
+                let manifold = ctxt2.manifold;
+                let swapped = ctxt_pair_pair.collider1 != manifold.pair.collider1;
+                neighbors.clear();
+                neighbors.extend(
+                    trimesh1
+                        .adjacent_triangles(*triangle_id)
+                        .iter()
+                        .map(|j| trimesh1.triangle(*j as usize)),
+                );
+                internal_edges::correct_internal_edge_contacts(
+                    &triangle1,
+                    neighbors,
+                    collider1.position(),
+                    collider2.position(),
+                    manifold,
+                    swapped,
+                    ctxt.prediction_distance,
+                );
+            }
+        }
     }
 }
 
@@ -0,0 +1,281 @@
+use crate::data::hashmap::{Entry, HashMap};
+use crate::data::MaybeSerializableData;
+use crate::geometry::contact_generator::internal_edges::set_manifold_normal;
+use crate::geometry::contact_generator::{
+    ContactGenerationContext, ContactGeneratorWorkspace, PrimitiveContactGenerationContext,
+    PrimitiveContactGenerator,
+};
+use crate::geometry::{Collider, ContactManifold, Shape, Voxels};
+use crate::math::{Isometry, Point, DIM};
+use crate::ncollide::bounding_volume::BoundingVolume;
+#[cfg(feature = "serde-serialize")]
+use erased_serde::Serialize;
+
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Clone)]
+struct SubDetector {
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    generator: Option<PrimitiveContactGenerator>,
+    // The voxel identifiers are reused after a removal so we keep track of the voxel itself.
+    voxel: Point<i32>,
+    manifold_id: usize,
+    timestamp: bool,
+    workspace: Option<ContactGeneratorWorkspace>,
+}
+
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Clone)]
+pub struct VoxelsShapeContactGeneratorWorkspace {
+    timestamp: bool,
+    interferences: Vec<(usize, Point<i32>)>,
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    old_manifolds: Vec<ContactManifold>,
+    sub_detectors: HashMap<usize, SubDetector>,
+}
+
+impl VoxelsShapeContactGeneratorWorkspace {
+    pub fn new() -> Self {
+        Self {
+            timestamp: false,
+            interferences: Vec::new(),
+            old_manifolds: Vec::new(),
+            sub_detectors: HashMap::default(),
+        }
+    }
+}
+
+pub fn generate_contacts_voxels_shape(ctxt: &mut ContactGenerationContext) {
+    let collider1 = &ctxt.colliders[ctxt.pair.pair.collider1];
+    let collider2 = &ctxt.colliders[ctxt.pair.pair.collider2];
+
+    if let Some(voxels1) = collider1.shape().as_voxels() {
+        do_generate_contacts(voxels1, collider1, collider2, ctxt, false)
+    } else if let Some(voxels2) = collider2.shape().as_voxels() {
+        do_generate_contacts(voxels2, collider2, collider1, ctxt, true)
+    }
+}
+
+fn do_generate_contacts(
+    voxels1: &Voxels,
+    collider1: &Collider,
+    collider2: &Collider,
+    ctxt: &mut ContactGenerationContext,
+    flipped: bool,
+) {
+    let ctxt_pair_pair = if flipped {
+        ctxt.pair.pair.swap()
+    } else {
+        ctxt.pair.pair
+    };
+
+    let workspace: &mut VoxelsShapeContactGeneratorWorkspace = ctxt
+        .pair
+        .generator_workspace
+        .as_mut()
+        .expect("The VoxelsShapeContactGeneratorWorkspace is missing.")
+        .0
+        .downcast_mut()
+        .expect("Invalid workspace type, expected a VoxelsShapeContactGeneratorWorkspace.");
+    let new_timestamp = !workspace.timestamp;
+    workspace.timestamp = new_timestamp;
+
+    /*
+     * Compute interferences.
+     */
+    let pos12 = collider1.position.inverse() * collider2.position;
+    // TODO: somehow precompute the AABB and reuse it?
+    let ls_aabb2 = collider2
+        .shape()
+        .compute_aabb(&pos12)
+        .loosened(ctxt.prediction_distance);
+
+    workspace.interferences.clear();
+    voxels1.voxels_intersecting_local_aabb(&ls_aabb2, &mut workspace.interferences);
+
+    std::mem::swap(&mut workspace.old_manifolds, &mut ctxt.pair.manifolds);
+    ctxt.pair.manifolds.clear();
+    let manifolds = &mut ctxt.pair.manifolds;
+    let shape_type2 = collider2.shape().shape_type();
+    let voxel_shape1 = voxels1.voxel_shape();
+
+    for (voxel_id, voxel) in &workspace.interferences {
+        if workspace
+            .sub_detectors
+            .get(voxel_id)
+            .map(|sub_detector| sub_detector.voxel != *voxel)
+            .unwrap_or(false)
+        {
+            // The voxel this detector was created for has been removed.
+            let _ = workspace.sub_detectors.remove(voxel_id);
+        }
+
+        let voxel_pos1 = Isometry::new(voxels1.voxel_center(voxel).coords, na::zero());
+
+        let sub_detector = match workspace.sub_detectors.entry(*voxel_id) {
+            Entry::Occupied(entry) => {
+                let sub_detector = entry.into_mut();
+                let manifold = workspace.old_manifolds[sub_detector.manifold_id].take();
+                sub_detector.manifold_id = manifolds.len();
+                sub_detector.timestamp = new_timestamp;
+                manifolds.push(manifold);
+                sub_detector
+            }
+            Entry::Vacant(entry) => {
+                let (generator, workspace2) = ctxt
+                    .dispatcher
+                    .dispatch_primitives(voxel_shape1.shape_type(), shape_type2);
+                let sub_detector = SubDetector {
+                    generator: Some(generator),
+                    voxel: *voxel,
+                    manifold_id: manifolds.len(),
+                    timestamp: new_timestamp,
+                    workspace: workspace2,
+                };
+                let mut manifold = ContactManifold::with_subshape_indices(
+                    ctxt_pair_pair,
+                    collider1,
+                    collider2,
+                    *voxel_id,
+                    0,
+                    ctxt.solver_flags,
+                );
+                // The contact points will be expressed in the local-space of the voxel.
+                manifold.delta1 *= voxel_pos1;
+                manifolds.push(manifold);
+                entry.insert(sub_detector)
+            }
+        };
+
+        if sub_detector.generator.is_none() {
+            // We probably lost the generator after deserialization.
+            // So we need to dispatch again.
+            let (generator, workspace2) = ctxt
+                .dispatcher
+                .dispatch_primitives(voxel_shape1.shape_type(), shape_type2);
+            sub_detector.generator = Some(generator);
+
+            // Don't overwrite the workspace if we already deserialized one.
+            if sub_detector.workspace.is_none() {
+                sub_detector.workspace = workspace2;
+            }
+        }
+
+        let manifold = &mut manifolds[sub_detector.manifold_id];
+        let position1 = collider1.position() * voxel_pos1;
+
+        let mut ctxt2 = if ctxt_pair_pair.collider1 != manifold.pair.collider1 {
+            PrimitiveContactGenerationContext {
+                prediction_distance: ctxt.prediction_distance,
+                collider1: collider2,
+                collider2: collider1,
+                shape1: collider2.shape(),
+                shape2: &voxel_shape1,
+                position1: collider2.position(),
+                position2: &position1,
+                manifold,
+                workspace: sub_detector.workspace.as_mut().map(|w| &mut *w.0),
+            }
+        } else {
+            PrimitiveContactGenerationContext {
+                prediction_distance: ctxt.prediction_distance,
+                collider1,
+                collider2,
+                shape1: &voxel_shape1,
+                shape2: collider2.shape(),
+                position1: &position1,
+                position2: collider2.position(),
+                manifold,
+                workspace: sub_detector.workspace.as_mut().map(|w| &mut *w.0),
+            }
+        };
+
+        (sub_detector.generator.unwrap().generate_contacts)(&mut ctxt2);
+
+        // The generator may have swapped the manifold identifiers.
+        let swapped = ctxt_pair_pair.collider1 != ctxt2.manifold.pair.collider1;
+        correct_internal_contacts(
+            voxels1,
+            voxel,
+            &position1,
+            collider2.position(),
+            ctxt2.manifold,
+            swapped,
+            ctxt.prediction_distance,
+        );
+    }
+
+    workspace
+        .sub_detectors
+        .retain(|_, detector| detector.timestamp == new_timestamp)
+}
+
+/// Fixes the contacts generated on the faces of a voxel shared with a filled neighbor.
+///
+/// The components of the contact normal pointing toward a filled neighbor are removed, and the
+/// contacts are dropped if nothing remains. Without this, objects sliding on a flat set of voxels
+/// would bump on the edges between voxels.
+fn correct_internal_contacts(
+    voxels: &Voxels,
+    voxel: &Point<i32>,
+    voxel_pos: &Isometry<f32>,
+    pos2: &Isometry<f32>,
+    manifold: &mut ContactManifold,
+    swapped: bool,
+    prediction_distance: f32,
+) {
+    if manifold.points.is_empty() {
+        return;
+    }
+
+    let voxel_normal = if swapped {
+        manifold.local_n2
+    } else {
+        manifold.local_n1
+    };
+    let mut normal = voxel_normal;
+
+    for i in 0..DIM {
+        if normal[i] != 0.0 {
+            let mut neighbor = *voxel;
+            neighbor[i] += normal[i].signum() as i32;
+
+            if voxels.contains(&neighbor) {
+                normal[i] = 0.0;
+            }
+        }
+    }
+
+    if normal == voxel_normal {
+        return;
+    }
+
+    if normal.norm_squared() < 1.0e-6 {
+        manifold.points.clear();
+        manifold.sort_contacts(prediction_distance);
+        return;
+    }
+
+    normal.normalize_mut();
+    set_manifold_normal(
+        manifold,
+        normal,
+        voxel_pos,
+        pos2,
+        swapped,
+        prediction_distance,
+    );
+}
+
+impl MaybeSerializableData for VoxelsShapeContactGeneratorWorkspace {
+    #[cfg(feature = "serde-serialize")]
+    fn as_serialize(&self) -> Option<(u32, &dyn Serialize)> {
+        Some((
+            super::WorkspaceSerializationTag::VoxelsShapeContactGeneratorWorkspace as u32,
+            self,
+        ))
+    }
+
+    fn clone_dyn(&self) -> Box<dyn MaybeSerializableData> {
+        Box::new(self.clone())
+    }
+}
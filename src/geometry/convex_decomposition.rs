@@ -0,0 +1,1019 @@
+use crate::geometry::ColliderShape;
+use crate::math::{Point, Vector, DIM};
+use na::Point3;
+use std::ops::{Mul, Sub};
+
+/// Parameters of the approximate convex decomposition of a triangle mesh.
+///
+/// The mesh is first voxelized. The set of voxels is then recursively split by axis-aligned
+/// planes until each part is close enough to its convex hull. The result only depends on the
+/// input mesh and on these parameters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct VHACDParameters {
+    /// Maximum concavity of each convex part (default: `0.01`).
+    ///
+    /// The concavity of a part is the volume between the part and its convex hull, divided by
+    /// the volume of the convex hull of the whole mesh.
+    pub concavity: f32,
+    /// Number of voxels along the largest side of the AABB of the mesh
+    /// (default: `64` in 3D, `256` in 2D).
+    ///
+    /// Higher resolutions give more accurate parts, but the decomposition time grows quickly
+    /// with the resolution. See [`ColliderShape::convex_decomposition`].
+    pub resolution: u32,
+    /// Only one voxel boundary out of `plane_downsampling` is tested as a splitting plane
+    /// (default: `4`).
+    pub plane_downsampling: u32,
+    /// Maximum number of convex parts generated (default: `1024`).
+    pub max_convex_hulls: u32,
+}
+
+impl Default for VHACDParameters {
+    fn default() -> Self {
+        Self {
+            concavity: 0.01,
+            #[cfg(feature = "dim2")]
+            resolution: 256,
+            #[cfg(feature = "dim3")]
+            resolution: 64,
+            plane_downsampling: 4,
+            max_convex_hulls: 1024,
+        }
+    }
+}
+
+type Cell = [i32; DIM];
+/// The vertices and triangles of a convex hull with integer coordinates.
+#[cfg(feature = "dim3")]
+type IntegerHull3 = (Vec<[i64; 3]>, Vec<Point3<u32>>);
+
+const EMPTY: u8 = 0;
+const SURFACE: u8 = 1;
+const OUTSIDE: u8 = 2;
+
+/// Computes an approximate convex decomposition of the given triangle mesh.
+pub(crate) fn convex_decomposition(
+    vertices: &[Point<f32>],
+    indices: &[Point3<u32>],
+    params: &VHACDParameters,
+) -> Vec<ColliderShape> {
+    let (origin, voxel_size, voxels) = match voxelize(vertices, indices, params.resolution) {
+        Some(voxelization) => voxelization,
+        None => return Vec::new(),
+    };
+
+    let root = Part::new(voxels);
+    let max_concavity = params.concavity * root.hull_volume as f32;
+    let plane_downsampling = params.plane_downsampling.max(1) as usize;
+    let mut parts = vec![root];
+
+    while parts.len() < params.max_convex_hulls as usize {
+        // Split the most concave part first.
+        let mut worst = None;
+        let mut worst_concavity = max_concavity;
+
+        for (i, part) in parts.iter().enumerate() {
+            if part.concavity as f32 > worst_concavity {
+                worst = Some(i);
+                worst_concavity = part.concavity as f32;
+            }
+        }
+
+        let i = match worst {
+            Some(i) => i,
+            None => break,
+        };
+
+        if let Some((left, right)) = parts[i].best_split(plane_downsampling) {
+            parts[i] = left;
+            parts.push(right);
+        } else {
+            parts[i].concavity = 0;
+        }
+    }
+
+    // The voxel corners are given in half-voxel units.
+    let to_world =
+        |pt: &[i64; DIM]| origin + Vector::from_fn(|i, _| pt[i] as f32) * (voxel_size / 2.0);
+
+    parts
+        .iter()
+        .filter_map(|part| {
+            let corners = row_end_points(&part.voxels, false);
+
+            #[cfg(feature = "dim2")]
+            {
+                let vertices = convex_hull2(&corners)
+                    .into_iter()
+                    .map(|i| to_world(&corners[i]))
+                    .collect();
+                ColliderShape::convex_polyline(vertices)
+            }
+
+            #[cfg(feature = "dim3")]
+            {
+                convex_polyhedron(&corners, to_world)
+            }
+        })
+        .collect()
+}
+
+struct Part {
+    // Sorted by linear index, i.e., rows of voxels along the `x` axis are contiguous.
+    voxels: Vec<Cell>,
+    mins: Cell,
+    maxs: Cell,
+    // The number of voxel centers inside of the convex hull of the voxel centers of this part.
+    hull_volume: u64,
+    // The number of voxel centers inside of the convex hull of this part that are not part of it.
+    concavity: u64,
+}
+
+impl Part {
+    fn new(voxels: Vec<Cell>) -> Self {
+        let mut mins = voxels[0];
+        let mut maxs = voxels[0];
+
+        for voxel in &voxels {
+            for i in 0..DIM {
+                mins[i] = mins[i].min(voxel[i]);
+                maxs[i] = maxs[i].max(voxel[i]);
+            }
+        }
+
+        // Counting the missing voxels, instead of comparing volumes, ensures the staircase
+        // effect of the voxelization of a convex shape is not seen as concave.
+        let hull_volume =
+            count_centers_in_hull(&row_end_points(&voxels, true)).unwrap_or(voxels.len() as u64);
+        let concavity = hull_volume.saturating_sub(voxels.len() as u64);
+
+        Self {
+            voxels,
+            mins,
+            maxs,
+            hull_volume,
+            concavity,
+        }
+    }
+
+    /// Splits this part with the axis-aligned plane minimizing the concavity of the result.
+    fn best_split(&self, plane_downsampling: usize) -> Option<(Part, Part)> {
+        let mut best: Option<(u64, Part, Part)> = None;
+
+        for axis in 0..DIM {
+            for plane in (self.mins[axis] + 1..=self.maxs[axis]).step_by(plane_downsampling) {
+                let (left, right): (Vec<Cell>, Vec<Cell>) =
+                    self.voxels.iter().partition(|voxel| voxel[axis] < plane);
+
+                if left.is_empty() || right.is_empty() {
+                    continue;
+                }
+
+                let left = Part::new(left);
+                let right = Part::new(right);
+                let cost = left.concavity + right.concavity;
+
+                if best.as_ref().map(|b| cost < b.0).unwrap_or(true) {
+                    best = Some((cost, left, right));
+                }
+            }
+        }
+
+        best.map(|(_, left, right)| (left, right))
+    }
+}
+
+/// Voxelizes a triangle mesh.
+///
+/// Returns the origin of the voxel grid, the size of each voxel, and the voxels covering the
+/// mesh sorted by their linear index.
+fn voxelize(
+    vertices: &[Point<f32>],
+    indices: &[Point3<u32>],
+    resolution: u32,
+) -> Option<(Point<f32>, f32, Vec<Cell>)> {
+    if indices.is_empty() {
+        return None;
+    }
+
+    let mut mins = Point::from(Vector::repeat(f32::MAX));
+    let mut maxs = Point::from(Vector::repeat(-f32::MAX));
+
+    for idx in indices {
+        for k in 0..3 {
+            let pt = vertices[idx[k] as usize];
+            mins = mins.inf(&pt);
+            maxs = maxs.sup(&pt);
+        }
+    }
+
+    let extents = maxs - mins;
+    let voxel_size = extents.max() / resolution.max(1) as f32;
+
+    if voxel_size.is_nan() || voxel_size <= 0.0 {
+        return None;
+    }
+
+    // Add one layer of empty voxels on each side so the outside of the mesh is connected.
+    let origin = mins - Vector::repeat(voxel_size);
+    let mut dims = [0; DIM];
+
+    for i in 0..DIM {
+        dims[i] = (extents[i] / voxel_size).ceil() as i32 + 3;
+    }
+
+    let mut cells = vec![EMPTY; dims.iter().product::<i32>() as usize];
+
+    for idx in indices {
+        let tri = [
+            Point::from((vertices[idx[0] as usize] - origin) / voxel_size),
+            Point::from((vertices[idx[1] as usize] - origin) / voxel_size),
+            Point::from((vertices[idx[2] as usize] - origin) / voxel_size),
+        ];
+        let mut first = [0; DIM];
+        let mut last = [0; DIM];
+
+        for i in 0..DIM {
+            let min = tri[0][i].min(tri[1][i]).min(tri[2][i]);
+            let max = tri[0][i].max(tri[1][i]).max(tri[2][i]);
+            first[i] = (min.floor() as i32).max(0).min(dims[i] - 1);
+            last[i] = (max.floor() as i32).max(0).min(dims[i] - 1);
+        }
+
+        let mut cell = first;
+
+        loop {
+            let center = Point::from(Vector::from_fn(|i, _| cell[i] as f32 + 0.5));
+
+            if triangle_voxel_overlap(&tri, &center) {
+                cells[linear_index(&cell, &dims)] = SURFACE;
+            }
+
+            if !next_cell(&mut cell, &first, &last) {
+                break;
+            }
+        }
+    }
+
+    mark_outside(&mut cells, &dims);
+
+    let voxels = cells
+        .iter()
+        .enumerate()
+        .filter(|(_, state)| **state != OUTSIDE)
+        .map(|(id, _)| {
+            let mut cell = [0; DIM];
+            let mut id = id as i32;
+
+            for i in 0..DIM {
+                cell[i] = id % dims[i];
+                id /= dims[i];
+            }
+
+            cell
+        })
+        .collect();
+
+    Some((origin, voxel_size, voxels))
+}
+
+fn linear_index(cell: &Cell, dims: &Cell) -> usize {
+    let mut id = 0;
+
+    for i in (0..DIM).rev() {
+        id = id * dims[i] + cell[i];
+    }
+
+    id as usize
+}
+
+/// Moves `cell` to the next cell of the box `[first, last]`, with `x` varying fastest.
+///
+/// Returns `false` if `cell` was the last cell of the box.
+fn next_cell(cell: &mut Cell, first: &Cell, last: &Cell) -> bool {
+    for i in 0..DIM {
+        if cell[i] < last[i] {
+            cell[i] += 1;
+            return true;
+        }
+
+        cell[i] = first[i];
+    }
+
+    false
+}
+
+/// Marks as `OUTSIDE` the empty cells that are not enclosed by the mesh.
+#[cfg(feature = "dim2")]
+fn mark_outside(cells: &mut [u8], _dims: &Cell) {
+    // The triangles of a 2D mesh cover its whole area.
+    for state in cells.iter_mut() {
+        if *state == EMPTY {
+            *state = OUTSIDE;
+        }
+    }
+}
+
+/// Marks as `OUTSIDE` the empty cells that are not enclosed by the mesh.
+#[cfg(feature = "dim3")]
+fn mark_outside(cells: &mut [u8], dims: &Cell) {
+    // The first cell is part of the padding so it is always outside of the mesh.
+    let mut stack = vec![[0; DIM]];
+    cells[0] = OUTSIDE;
+
+    while let Some(cell) = stack.pop() {
+        for i in 0..DIM {
+            for &shift in &[-1, 1] {
+                let mut neighbor = cell;
+                neighbor[i] += shift;
+
+                if neighbor[i] >= 0 && neighbor[i] < dims[i] {
+                    let state = &mut cells[linear_index(&neighbor, dims)];
+
+                    if *state == EMPTY {
+                        *state = OUTSIDE;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Tests if a triangle intersects the voxel of unit size centered at `center`.
+///
+/// The AABB of the triangle is assumed to intersect the voxel.
+fn triangle_voxel_overlap(tri: &[Point<f32>; 3], center: &Point<f32>) -> bool {
+    let v = [tri[0] - center, tri[1] - center, tri[2] - center];
+    let edges = [v[1] - v[0], v[2] - v[1], v[0] - v[2]];
+    let separated = |axis: Vector<f32>| {
+        // Slightly enlarge the voxel so triangles lying on a voxel boundary are not missed.
+        let radius = axis.abs().sum() * (0.5 + 1.0e-4);
+        let p0 = v[0].dot(&axis);
+        let p1 = v[1].dot(&axis);
+        let p2 = v[2].dot(&axis);
+        p0.min(p1).min(p2) > radius || p0.max(p1).max(p2) < -radius
+    };
+
+    #[cfg(feature = "dim2")]
+    {
+        edges
+            .iter()
+            .all(|edge| !separated(Vector::new(-edge.y, edge.x)))
+    }
+
+    #[cfg(feature = "dim3")]
+    {
+        !separated(edges[0].cross(&edges[1]))
+            && edges
+                .iter()
+                .all(|edge| (0..3).all(|i| !separated(edge.cross(&Vector::ith(i, 1.0)))))
+    }
+}
+
+/// Builds the convex polyhedron with the convex hull of the given voxel corners.
+#[cfg(feature = "dim3")]
+fn convex_polyhedron(
+    corners: &[[i64; 3]],
+    to_world: impl Fn(&[i64; 3]) -> Point<f32>,
+) -> Option<ColliderShape> {
+    let (vertices, indices) = convex_hull3(corners)?;
+    let world_vertices = vertices.iter().map(&to_world).collect();
+
+    if let Some(shape) = ColliderShape::convex_mesh(world_vertices, indices) {
+        return Some(shape);
+    }
+
+    // The convex polyhedron merges adjacent faces with almost parallel normals, which fails on
+    // finely tessellated convex hulls. In that case, only keep the support points of the convex
+    // hull along the directions `[-k; k]^3`, with smaller and smaller `k`.
+    for &k in &[2, 1] {
+        let mut support_points = Vec::new();
+
+        for x in -k..=k {
+            for y in -k..=k {
+                for z in -k..=k {
+                    let dir = [x, y, z];
+
+                    if dir != [0; 3] {
+                        support_points.extend(vertices.iter().max_by_key(|v| dot3(v, &dir)));
+                    }
+                }
+            }
+        }
+
+        support_points.sort_unstable();
+        support_points.dedup();
+
+        let (vertices, indices) = convex_hull3(&support_points)?;
+        let world_vertices = vertices.iter().map(&to_world).collect();
+
+        if let Some(shape) = ColliderShape::convex_mesh(world_vertices, indices) {
+            return Some(shape);
+        }
+    }
+
+    None
+}
+
+/// The first and last voxels of each row of voxels along the `x` axis, in half-voxel units.
+///
+/// If `centers` is `true`, the centers of these voxels are returned. Otherwise, their corners
+/// are returned. In both cases, the convex hull of the result is the convex hull of the centers
+/// (resp. of the corners) of all the voxels.
+fn row_end_points(voxels: &[Cell], centers: bool) -> Vec<[i64; DIM]> {
+    let mut points = Vec::new();
+    let mut start = 0;
+
+    while start < voxels.len() {
+        let mut end = start;
+
+        while end + 1 < voxels.len() && voxels[end + 1][1..] == voxels[start][1..] {
+            end += 1;
+        }
+
+        for (voxel, side) in [(voxels[start], 0), (voxels[end], 1)].iter() {
+            if centers {
+                let mut center = [0; DIM];
+
+                for i in 0..DIM {
+                    center[i] = voxel[i] as i64 * 2 + 1;
+                }
+
+                points.push(center);
+            } else {
+                // Only the corners on the outer side of the voxel are needed.
+                for k in 0..1 << (DIM - 1) {
+                    let mut corner = [(voxel[0] + side) as i64 * 2; DIM];
+
+                    for i in 1..DIM {
+                        corner[i] = (voxel[i] + ((k >> (i - 1)) & 1)) as i64 * 2;
+                    }
+
+                    points.push(corner);
+                }
+            }
+        }
+
+        start = end + 1;
+    }
+
+    points.sort_unstable();
+    points.dedup();
+    points
+}
+
+/// The number of voxel centers inside of the convex hull of the given voxel centers, all in
+/// half-voxel units.
+#[cfg(feature = "dim2")]
+fn count_centers_in_hull(centers: &[[i64; 2]]) -> Option<u64> {
+    let polygon: Vec<_> = convex_hull2(centers)
+        .into_iter()
+        .map(|i| [centers[i][0] as f64, centers[i][1] as f64])
+        .collect();
+    Some(count_centers_in_polygon(&polygon))
+}
+
+/// The number of voxel centers inside of the convex hull of the given voxel centers, all in
+/// half-voxel units.
+///
+/// Returns `None` if the centers are coplanar but not on an axis-aligned plane.
+#[cfg(feature = "dim3")]
+fn count_centers_in_hull(centers: &[[i64; 3]]) -> Option<u64> {
+    for axis in 0..3 {
+        if centers.iter().all(|c| c[axis] == centers[0][axis]) {
+            let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+            let polygon: Vec<_> = centers.iter().map(|c| [c[u], c[v]]).collect();
+            let polygon: Vec<_> = convex_hull2(&polygon)
+                .into_iter()
+                .map(|i| [polygon[i][0] as f64, polygon[i][1] as f64])
+                .collect();
+            return Some(count_centers_in_polygon(&polygon));
+        }
+    }
+
+    let (vertices, indices) = convex_hull3(centers)?;
+    let min_z = vertices.iter().map(|v| v[2]).min()?;
+    let max_z = vertices.iter().map(|v| v[2]).max()?;
+    let mut count = 0;
+    let mut slice = Vec::new();
+
+    // Count the voxel centers of each slice of the convex hull with constant `z`.
+    for z in (min_z..=max_z).step_by(2) {
+        slice.clear();
+
+        for idx in &indices {
+            for k in 0..3 {
+                let a = vertices[idx[k] as usize];
+                let b = vertices[idx[(k + 1) % 3] as usize];
+
+                if a[2] == z {
+                    slice.push([a[0] as f64, a[1] as f64]);
+                } else if (a[2] < z) != (b[2] < z) && b[2] != z {
+                    let t = (z - a[2]) as f64 / (b[2] - a[2]) as f64;
+                    slice.push([
+                        a[0] as f64 + (b[0] - a[0]) as f64 * t,
+                        a[1] as f64 + (b[1] - a[1]) as f64 * t,
+                    ]);
+                }
+            }
+        }
+
+        let polygon: Vec<_> = convex_hull2(&slice).into_iter().map(|i| slice[i]).collect();
+        count += count_centers_in_polygon(&polygon);
+    }
+
+    Some(count)
+}
+
+/// The number of voxel centers, i.e., points with odd coordinates in half-voxel units, inside
+/// of a convex polygon.
+fn count_centers_in_polygon(polygon: &[[f64; 2]]) -> u64 {
+    let min_y = polygon.iter().map(|pt| pt[1]).fold(f64::MAX, f64::min);
+    let max_y = polygon.iter().map(|pt| pt[1]).fold(-f64::MAX, f64::max);
+    let mut count = 0;
+
+    if let Some((first, last)) = centers_in_range(min_y, max_y) {
+        for y in (first..=last).step_by(2) {
+            let y = y as f64;
+            let mut min_x = f64::MAX;
+            let mut max_x = -f64::MAX;
+
+            for (i, a) in polygon.iter().enumerate() {
+                let b = polygon[(i + 1) % polygon.len()];
+
+                if a[1] == b[1] {
+                    if a[1] == y {
+                        min_x = min_x.min(a[0]).min(b[0]);
+                        max_x = max_x.max(a[0]).max(b[0]);
+                    }
+                } else if (a[1] - y) * (b[1] - y) <= 0.0 {
+                    let x = a[0] + (b[0] - a[0]) * (y - a[1]) / (b[1] - a[1]);
+                    min_x = min_x.min(x);
+                    max_x = max_x.max(x);
+                }
+            }
+
+            if let Some((first, last)) = centers_in_range(min_x, max_x) {
+                count += ((last - first) / 2 + 1) as u64;
+            }
+        }
+    }
+
+    count
+}
+
+/// The first and last odd integers in `[min, max]`, up to a small tolerance.
+fn centers_in_range(min: f64, max: f64) -> Option<(i64, i64)> {
+    let eps = 1.0e-6;
+    let first = ((min - 1.0) / 2.0 - eps).ceil() as i64 * 2 + 1;
+    let last = ((max - 1.0) / 2.0 + eps).floor() as i64 * 2 + 1;
+
+    if first <= last {
+        Some((first, last))
+    } else {
+        None
+    }
+}
+
+/// The indices of the vertices, in counter-clockwise order, of the convex hull of a set of 2D
+/// points.
+///
+/// Points lying on an edge of the convex hull are not included.
+fn convex_hull2<T>(points: &[[T; 2]]) -> Vec<usize>
+where
+    T: Copy + Default + PartialOrd + Sub<Output = T> + Mul<Output = T>,
+{
+    let mut sorted: Vec<usize> = (0..points.len()).collect();
+    sorted.sort_by(|a, b| points[*a].partial_cmp(&points[*b]).unwrap());
+    sorted.dedup_by(|a, b| points[*a] == points[*b]);
+
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let cross = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (points[a], points[b], points[c]);
+        (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+    };
+
+    // Andrew's monotone chain.
+    let mut hull: Vec<usize> = Vec::with_capacity(sorted.len() + 1);
+
+    for &i in &sorted {
+        while hull.len() >= 2
+            && cross(hull[hull.len() - 2], hull[hull.len() - 1], i) <= T::default()
+        {
+            let _ = hull.pop();
+        }
+        hull.push(i);
+    }
+
+    let lower_len = hull.len() + 1;
+
+    for &i in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower_len
+            && cross(hull[hull.len() - 2], hull[hull.len() - 1], i) <= T::default()
+        {
+            let _ = hull.pop();
+        }
+        hull.push(i);
+    }
+
+    let _ = hull.pop();
+    hull
+}
+
+#[cfg(feature = "dim3")]
+fn orient3(a: &[i64; 3], b: &[i64; 3], c: &[i64; 3], d: &[i64; 3]) -> i64 {
+    let ab = sub3(b, a);
+    let ac = sub3(c, a);
+    let ad = sub3(d, a);
+    dot3(&cross3(&ab, &ac), &ad)
+}
+
+#[cfg(feature = "dim3")]
+fn sub3(a: &[i64; 3], b: &[i64; 3]) -> [i64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+#[cfg(feature = "dim3")]
+fn cross3(a: &[i64; 3], b: &[i64; 3]) -> [i64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[cfg(feature = "dim3")]
+fn dot3(a: &[i64; 3], b: &[i64; 3]) -> i64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Computes the convex hull of a set of 3D points with integer coordinates.
+///
+/// Returns the vertices of the convex hull and its triangles oriented counter-clockwise when
+/// seen from the outside. Points lying on a face or an edge of the convex hull are not
+/// included. Returns `None` if all the points are coplanar.
+#[cfg(feature = "dim3")]
+fn convex_hull3(points: &[[i64; 3]]) -> Option<IntegerHull3> {
+    // The vertices of the convex hull are necessarily vertices of the 2D convex hull of each
+    // axis-aligned slice they belong to. Discarding the other points first is much cheaper than
+    // feeding them to the incremental algorithm.
+    let mut keep = vec![true; points.len()];
+
+    for axis in 0..3 {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        let mut sorted: Vec<usize> = (0..points.len()).collect();
+        sorted.sort_unstable_by_key(|i| (points[*i][axis], points[*i][u], points[*i][v]));
+        let mut slice_hull = vec![false; points.len()];
+        let mut start = 0;
+
+        while start < sorted.len() {
+            let coord = points[sorted[start]][axis];
+            let mut end = start;
+
+            while end < sorted.len() && points[sorted[end]][axis] == coord {
+                end += 1;
+            }
+
+            let slice: Vec<_> = sorted[start..end]
+                .iter()
+                .map(|i| [points[*i][u], points[*i][v]])
+                .collect();
+
+            for i in convex_hull2(&slice) {
+                slice_hull[sorted[start + i]] = true;
+            }
+
+            start = end;
+        }
+
+        for (keep, slice_hull) in keep.iter_mut().zip(slice_hull.iter()) {
+            *keep = *keep && *slice_hull;
+        }
+    }
+
+    let candidates: Vec<_> = points
+        .iter()
+        .zip(keep.iter())
+        .filter(|(_, keep)| **keep)
+        .map(|(pt, _)| *pt)
+        .collect();
+    let faces = incremental_convex_hull3(&candidates)?;
+
+    // Points lying on a face or an edge may still have been inserted. A vertex of the convex
+    // hull is adjacent to faces with at least three linearly independent normals.
+    let mut normals = vec![Vec::new(); candidates.len()];
+
+    for face in &faces {
+        let normal = cross3(
+            &sub3(&candidates[face[1]], &candidates[face[0]]),
+            &sub3(&candidates[face[2]], &candidates[face[0]]),
+        );
+
+        for i in face {
+            normals[*i].push(normal);
+        }
+    }
+
+    let vertices: Vec<_> = candidates
+        .iter()
+        .zip(normals.iter())
+        .filter(|(_, normals)| spans_3d(normals))
+        .map(|(pt, _)| *pt)
+        .collect();
+    let indices = incremental_convex_hull3(&vertices)?
+        .into_iter()
+        .map(|face| Point3::new(face[0] as u32, face[1] as u32, face[2] as u32))
+        .collect();
+
+    Some((vertices, indices))
+}
+
+#[cfg(feature = "dim3")]
+fn spans_3d(normals: &[[i64; 3]]) -> bool {
+    for (i, n1) in normals.iter().enumerate() {
+        for n2 in &normals[i + 1..] {
+            let n12 = cross3(n1, n2);
+
+            if n12 != [0; 3] {
+                return normals.iter().any(|n3| {
+                    // Use 128 bits to avoid overflows.
+                    n12[0] as i128 * n3[0] as i128
+                        + n12[1] as i128 * n3[1] as i128
+                        + n12[2] as i128 * n3[2] as i128
+                        != 0
+                });
+            }
+        }
+    }
+
+    false
+}
+
+/// The triangles of the convex hull of a set of 3D points with integer coordinates, oriented
+/// counter-clockwise when seen from the outside.
+///
+/// Returns `None` if all the points are coplanar.
+#[cfg(feature = "dim3")]
+fn incremental_convex_hull3(points: &[[i64; 3]]) -> Option<Vec<[usize; 3]>> {
+    let i0 = 0;
+    let i1 = (0..points.len()).find(|i| points[*i] != points[i0])?;
+    let i2 = (0..points.len()).find(|i| {
+        cross3(
+            &sub3(&points[i1], &points[i0]),
+            &sub3(&points[*i], &points[i0]),
+        ) != [0; 3]
+    })?;
+    let i3 = (0..points.len())
+        .find(|i| orient3(&points[i0], &points[i1], &points[i2], &points[*i]) != 0)?;
+
+    // Each face of the initial tetrahedron is oriented such that the opposite vertex is below it.
+    let tetrahedron = [
+        [i0, i1, i2, i3],
+        [i0, i3, i1, i2],
+        [i1, i3, i2, i0],
+        [i2, i3, i0, i1],
+    ];
+    let mut faces: Vec<[usize; 3]> = tetrahedron
+        .iter()
+        .map(|[a, b, c, opposite]| {
+            if orient3(&points[*a], &points[*b], &points[*c], &points[*opposite]) > 0 {
+                [*a, *c, *b]
+            } else {
+                [*a, *b, *c]
+            }
+        })
+        .collect();
+    let mut visible_edges = std::collections::HashSet::new();
+    let mut horizon = Vec::new();
+
+    for (i, pt) in points.iter().enumerate() {
+        if i == i0 || i == i1 || i == i2 || i == i3 {
+            continue;
+        }
+
+        let visible: Vec<bool> = faces
+            .iter()
+            .map(|f| orient3(&points[f[0]], &points[f[1]], &points[f[2]], pt) > 0)
+            .collect();
+
+        if !visible.iter().any(|v| *v) {
+            continue;
+        }
+
+        visible_edges.clear();
+        horizon.clear();
+
+        for (face, _) in faces.iter().zip(visible.iter()).filter(|(_, v)| **v) {
+            for k in 0..3 {
+                let _ = visible_edges.insert((face[k], face[(k + 1) % 3]));
+            }
+        }
+
+        // The horizon is made of the edges of the visible faces that are adjacent to a
+        // face that is not visible. The faces are traversed in order to keep the result
+        // deterministic.
+        for (face, _) in faces.iter().zip(visible.iter()).filter(|(_, v)| **v) {
+            for k in 0..3 {
+                let (a, b) = (face[k], face[(k + 1) % 3]);
+
+                if !visible_edges.contains(&(b, a)) {
+                    horizon.push((a, b));
+                }
+            }
+        }
+
+        let mut visible = visible.into_iter();
+        faces.retain(|_| !visible.next().unwrap());
+        faces.extend(horizon.iter().map(|(a, b)| [*a, *b, i]));
+    }
+
+    Some(faces)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{convex_decomposition, VHACDParameters};
+    use crate::geometry::ColliderShape;
+    use crate::math::Point;
+    use na::Point3;
+
+    fn part_vertices(part: &ColliderShape) -> Vec<Point<f32>> {
+        #[cfg(feature = "dim2")]
+        return part.as_polygon().unwrap().vertices().to_vec();
+        #[cfg(feature = "dim3")]
+        return part.as_convex_polyhedron().unwrap().points().to_vec();
+    }
+
+    // A U-shaped mesh: a thin bar along `x` with a prong at each end, pointing up along `y`.
+    #[cfg(feature = "dim2")]
+    fn u_shape() -> (Vec<Point<f32>>, Vec<Point3<u32>>) {
+        let boxes = [
+            ([0.0, 0.0], [3.0, 1.0]),
+            ([0.0, 1.0], [1.0, 3.0]),
+            ([2.0, 1.0], [3.0, 3.0]),
+        ];
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        for (mins, maxs) in &boxes {
+            let base = vertices.len() as u32;
+            vertices.push(Point::new(mins[0], mins[1]));
+            vertices.push(Point::new(maxs[0], mins[1]));
+            vertices.push(Point::new(maxs[0], maxs[1]));
+            vertices.push(Point::new(mins[0], maxs[1]));
+            indices.push(Point3::new(base, base + 1, base + 2));
+            indices.push(Point3::new(base, base + 2, base + 3));
+        }
+
+        (vertices, indices)
+    }
+
+    // A U-shaped mesh: a thin bar along `x` with a prong at each end, pointing up along `y`.
+    #[cfg(feature = "dim3")]
+    fn u_shape() -> (Vec<Point<f32>>, Vec<Point3<u32>>) {
+        let outline = [
+            [0.0, 0.0],
+            [3.0, 0.0],
+            [3.0, 3.0],
+            [2.0, 3.0],
+            [2.0, 1.0],
+            [1.0, 1.0],
+            [1.0, 3.0],
+            [0.0, 3.0],
+        ];
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        for z in &[0.0, 1.0] {
+            for pt in &outline {
+                vertices.push(Point::new(pt[0], pt[1], *z));
+            }
+        }
+
+        // Triangulation of the outline, for the bottom and top caps.
+        let cap = [
+            [0, 1, 4],
+            [0, 4, 5],
+            [1, 2, 3],
+            [1, 3, 4],
+            [0, 5, 6],
+            [0, 6, 7],
+        ];
+
+        for tri in &cap {
+            indices.push(Point3::new(tri[0], tri[2], tri[1]));
+            indices.push(Point3::new(tri[0] + 8, tri[1] + 8, tri[2] + 8));
+        }
+
+        // Sides.
+        for i in 0..8 {
+            let j = (i + 1) % 8;
+            indices.push(Point3::new(i, j, j + 8));
+            indices.push(Point3::new(i, j + 8, i + 8));
+        }
+
+        (vertices, indices)
+    }
+
+    #[test]
+    fn convex_decomposition_of_convex_mesh() {
+        // A single square or cube, centered at the origin.
+        #[cfg(feature = "dim2")]
+        let (vertices, indices) = {
+            let vertices = vec![
+                Point::new(-0.5, -0.5),
+                Point::new(0.5, -0.5),
+                Point::new(0.5, 0.5),
+                Point::new(-0.5, 0.5),
+            ];
+            (vertices, vec![Point3::new(0, 1, 2), Point3::new(0, 2, 3)])
+        };
+        #[cfg(feature = "dim3")]
+        let (vertices, indices) = {
+            let trimesh = ncollide::procedural::cuboid(&na::Vector3::repeat(1.0));
+            let indices = trimesh.flat_indices();
+            let indices: Vec<_> = indices
+                .chunks(3)
+                .map(|idx| Point3::new(idx[0], idx[1], idx[2]))
+                .collect();
+            (trimesh.coords, indices)
+        };
+
+        let params = VHACDParameters {
+            resolution: 16,
+            ..VHACDParameters::default()
+        };
+        let shape = ColliderShape::convex_decomposition(&vertices, &indices, &params).unwrap();
+        let parts = shape.as_compound().unwrap().shapes();
+        assert_eq!(parts.len(), 1);
+
+        // The part covers the voxels intersecting the boundary of the mesh.
+        let voxel_size = 1.0 / 16.0;
+        for pt in part_vertices(&parts[0].1) {
+            assert!(pt.coords.iter().all(|x| x.abs() <= 0.5 + voxel_size * 1.5));
+        }
+    }
+
+    #[test]
+    fn convex_decomposition_of_degenerate_meshes() {
+        let params = VHACDParameters::default();
+
+        // No triangle.
+        assert!(ColliderShape::convex_decomposition(&[], &[], &params).is_none());
+
+        // All the vertices are equal.
+        let vertices = vec![Point::origin(); 3];
+        let indices = vec![Point3::new(0, 1, 2)];
+        assert!(ColliderShape::convex_decomposition(&vertices, &indices, &params).is_none());
+
+        // All the vertices are on a line (in 2D) or a plane (in 3D).
+        #[cfg(feature = "dim2")]
+        let vertices = vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(2.0, 0.0),
+        ];
+        #[cfg(feature = "dim3")]
+        let vertices = vec![
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 1.0),
+        ];
+        // The result is a single thin part covering the voxels intersecting the mesh.
+        let shape = ColliderShape::convex_decomposition(&vertices, &indices, &params).unwrap();
+        assert_eq!(shape.as_compound().unwrap().shapes().len(), 1);
+    }
+
+    #[test]
+    fn convex_decomposition_of_concave_mesh() {
+        let (vertices, indices) = u_shape();
+        let params = VHACDParameters {
+            resolution: 24,
+            ..VHACDParameters::default()
+        };
+        let parts = convex_decomposition(&vertices, &indices, &params);
+
+        // The U cannot be approximated by less than three convex parts.
+        assert!(parts.len() >= 3, "found {} parts", parts.len());
+
+        // No part may fill the gap between the two prongs.
+        for part in parts.iter().map(part_vertices) {
+            let min_x = part.iter().map(|pt| pt.x).fold(f32::MAX, f32::min);
+            let max_x = part.iter().map(|pt| pt.x).fold(-f32::MAX, f32::max);
+            let max_y = part.iter().map(|pt| pt.y).fold(-f32::MAX, f32::max);
+            assert!(max_y < 1.5 || max_x < 1.5 || min_x > 1.5);
+        }
+
+        // The decomposition is deterministic.
+        let parts2 = convex_decomposition(&vertices, &indices, &params);
+        assert_eq!(
+            parts.iter().map(part_vertices).collect::<Vec<_>>(),
+            parts2.iter().map(part_vertices).collect::<Vec<_>>()
+        );
+    }
+}
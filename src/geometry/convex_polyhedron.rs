@@ -0,0 +1,219 @@
+use crate::geometry::{PolygonalFeatureMap, PolyhedronFace};
+use crate::math::{Isometry, Point, Vector};
+use na::{Point3, Unit};
+use ncollide::query::{PointProjection, PointQuery, Ray, RayCast, RayIntersection};
+use ncollide::shape::{
+    ConvexHull, ConvexPolygonalFeature, ConvexPolyhedron as _, FeatureId, SupportMap,
+};
+
+/// A convex polyhedron.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct ConvexPolyhedron {
+    hull: ConvexHull<f32>,
+    // A triangulation of the boundary of the polyhedron, used for
+    // computing its mass properties and for rendering.
+    indices: Vec<Point3<u32>>,
+}
+
+impl ConvexPolyhedron {
+    /// Computes the convex hull of the given set of points.
+    ///
+    /// Returns `None` if the convex hull computation failed, e.g., if all the points are coplanar.
+    pub fn from_convex_hull(points: &[Point<f32>]) -> Option<Self> {
+        let hull = ncollide::transformation::convex_hull(points);
+        let indices: Vec<_> = hull
+            .flat_indices()
+            .chunks(3)
+            .map(|idx| Point3::new(idx[0], idx[1], idx[2]))
+            .collect();
+        Self::from_convex_mesh(hull.coords, indices)
+    }
+
+    /// Creates a convex polyhedron from the vertex and index buffers of a triangle mesh.
+    ///
+    /// The triangle mesh is assumed to be convex with its triangles oriented counter-clockwise
+    /// when seen from the outside. No convexity check is performed.
+    /// Returns `None` if the mesh is not closed or contains degenerate triangles.
+    pub fn from_convex_mesh(vertices: Vec<Point<f32>>, indices: Vec<Point3<u32>>) -> Option<Self> {
+        let flat_indices: Vec<usize> = indices
+            .iter()
+            .flat_map(|idx| idx.iter().map(|i| *i as usize))
+            .collect();
+        let hull = ConvexHull::try_new(vertices, &flat_indices)?;
+
+        Some(Self { hull, indices })
+    }
+
+    /// The vertices of this convex polyhedron.
+    pub fn points(&self) -> &[Point<f32>] {
+        self.hull.points()
+    }
+
+    /// The index buffer of the triangles on the boundary of this convex polyhedron.
+    pub fn indices(&self) -> &[Point3<u32>] {
+        &self.indices
+    }
+}
+
+impl SupportMap<f32> for ConvexPolyhedron {
+    fn local_support_point(&self, dir: &Vector<f32>) -> Point<f32> {
+        self.hull.local_support_point(dir)
+    }
+}
+
+impl PolygonalFeatureMap for ConvexPolyhedron {
+    fn local_support_feature(&self, dir: &Unit<Vector<f32>>, out_feature: &mut PolyhedronFace) {
+        let mut face = ConvexPolygonalFeature::new();
+        self.hull
+            .support_face_toward(&Isometry::identity(), dir, &mut face);
+
+        out_feature.clear();
+
+        for i in 0..face.vertices.len() {
+            out_feature.push(
+                face.vertices[i],
+                feature_id(face.vertices_id[i]),
+                feature_id(face.edges_id[i]),
+            );
+        }
+
+        out_feature.fid = feature_id(face.feature_id);
+    }
+}
+
+fn feature_id(id: FeatureId) -> u32 {
+    match id {
+        FeatureId::Vertex(i) => i as u32 * 3,
+        FeatureId::Edge(i) => i as u32 * 3 + 1,
+        FeatureId::Face(i) => i as u32 * 3 + 2,
+        FeatureId::Unknown => 0,
+    }
+}
+
+impl RayCast<f32> for ConvexPolyhedron {
+    fn toi_and_normal_with_ray(
+        &self,
+        m: &Isometry<f32>,
+        ray: &Ray<f32>,
+        max_toi: f32,
+        solid: bool,
+    ) -> Option<RayIntersection<f32>> {
+        self.hull.toi_and_normal_with_ray(m, ray, max_toi, solid)
+    }
+}
+
+impl PointQuery<f32> for ConvexPolyhedron {
+    fn project_point(
+        &self,
+        m: &Isometry<f32>,
+        pt: &Point<f32>,
+        solid: bool,
+    ) -> PointProjection<f32> {
+        self.hull.project_point(m, pt, solid)
+    }
+
+    fn project_point_with_feature(
+        &self,
+        m: &Isometry<f32>,
+        pt: &Point<f32>,
+    ) -> (PointProjection<f32>, FeatureId) {
+        self.hull.project_point_with_feature(m, pt)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ConvexPolyhedron;
+    use crate::dynamics::RigidBodyBuilder;
+    use crate::geometry::{ColliderBuilder, PolygonalFeatureMap, PolyhedronFace};
+    use crate::math::{Point, Vector};
+    use crate::pipeline::test_world::TestWorld;
+    use na::Unit;
+
+    // A prism with a regular polygon of `n` vertices as its top and bottom faces.
+    fn prism_points(n: usize, radius: f32, bottom: f32, top: f32) -> Vec<Point<f32>> {
+        let mut points = Vec::new();
+        for i in 0..n {
+            let angle = i as f32 * std::f32::consts::PI * 2.0 / n as f32;
+            points.push(Point::new(
+                angle.cos() * radius,
+                bottom,
+                angle.sin() * radius,
+            ));
+            points.push(Point::new(angle.cos() * radius, top, angle.sin() * radius));
+        }
+        points
+    }
+
+    #[test]
+    fn support_face_keeps_all_vertices() {
+        let poly = ConvexPolyhedron::from_convex_hull(&prism_points(100, 1.0, 0.0, 1.0)).unwrap();
+        let mut face = PolyhedronFace::new();
+        poly.local_support_feature(&Unit::new_normalize(Vector::y()), &mut face);
+
+        assert_eq!(face.num_vertices, 100);
+        assert!(face.points().iter().all(|pt| relative_eq!(pt.y, 1.0)));
+
+        // The polyhedron has more than 256 features, but their IDs must not collide.
+        let mut ids: Vec<_> = face
+            .vertex_ids()
+            .iter()
+            .chain(face.edge_ids())
+            .chain(std::iter::once(&face.fid))
+            .collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 201);
+    }
+
+    #[test]
+    fn convex_polyhedron_resting_on_ground() {
+        let mut world = TestWorld::with_gravity();
+        world.insert_ground(10.0);
+
+        // A hexagonal prism, so that its support faces have more than four vertices.
+        let points = prism_points(6, 1.0, -0.5, 0.5);
+        let rb = RigidBodyBuilder::new_dynamic()
+            .translation(0.0, 1.0, 0.0)
+            .build();
+        let co = ColliderBuilder::convex_hull(&points).unwrap().build();
+        let handle = world.insert(rb, Some(co));
+
+        world.run(120);
+
+        let rb = &world.bodies[handle];
+        let y = rb.position().translation.vector.y;
+        assert!(y > 0.55 && y < 0.65);
+        assert!(rb.linvel().norm() < 0.1);
+        assert!(rb.angvel().norm() < 0.1);
+    }
+
+    #[test]
+    fn cuboid_resting_near_the_boundary_of_a_large_face() {
+        let mut world = TestWorld::with_gravity();
+
+        // The top face of this prism has twelve vertices. The cuboid below rests on a part of
+        // that face that is not covered by any quadrilateral made of four of its vertices
+        // evenly distributed along its boundary.
+        let ground = RigidBodyBuilder::new_static().build();
+        let points = prism_points(12, 2.0, -0.2, 0.0);
+        let co = ColliderBuilder::convex_hull(&points).unwrap().build();
+        world.insert(ground, Some(co));
+
+        let offset = 1.7 * std::f32::consts::FRAC_1_SQRT_2;
+        let rb = RigidBodyBuilder::new_dynamic()
+            .translation(offset, 0.2, offset)
+            .build();
+        let co = ColliderBuilder::cuboid(0.1, 0.1, 0.1).build();
+        let handle = world.insert(rb, Some(co));
+
+        world.run(120);
+
+        let rb = &world.bodies[handle];
+        let y = rb.position().translation.vector.y;
+        assert!(y > 0.05 && y < 0.15);
+        assert!(rb.linvel().norm() < 0.1);
+        assert!(rb.angvel().norm() < 0.1);
+    }
+}
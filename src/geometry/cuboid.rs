@@ -29,8 +29,8 @@ pub fn local_support_point(cube: &Cuboid, local_dir: Vector<f32>) -> Point<f32>
 // }
 
 #[cfg(feature = "dim2")]
-pub fn vertex_feature_id(vertex: Point<f32>) -> u8 {
-    ((vertex.x.to_bits() >> 31) & 0b001 | (vertex.y.to_bits() >> 30) & 0b010) as u8
+pub fn vertex_feature_id(vertex: Point<f32>) -> u32 {
+    (vertex.x.to_bits() >> 31) & 0b001 | (vertex.y.to_bits() >> 30) & 0b010
 }
 
 // #[cfg(feature = "dim3")]
@@ -175,7 +175,7 @@ pub(crate) fn support_face(cube: &Cuboid, local_dir: Vector<f32>) -> CuboidFeatu
         _ => unreachable!(),
     };
 
-    pub fn vid(i: u8) -> u8 {
+    pub fn vid(i: u32) -> u32 {
         // Each vertex has an even feature id.
         i * 2
     }
@@ -229,6 +229,6 @@ pub(crate) fn support_face(cube: &Cuboid, local_dir: Vector<f32>) -> CuboidFeatu
         vertices,
         vids,
         eids,
-        fid: fid as u8,
+        fid: fid as u32,
     }
 }
@@ -12,7 +12,7 @@ pub enum CuboidFeature {
 #[derive(Debug)]
 pub struct CuboidFeatureVertex {
     pub vertex: Point<f32>,
-    pub vid: u8,
+    pub vid: u32,
 }
 
 impl CuboidFeatureVertex {
@@ -24,8 +24,8 @@ impl CuboidFeatureVertex {
 #[derive(Debug)]
 pub struct CuboidFeatureFace {
     pub vertices: [Point<f32>; 2],
-    pub vids: [u8; 2],
-    pub fid: u8,
+    pub vids: [u32; 2],
+    pub fid: u32,
 }
 
 impl From<Segment<f32>> for CuboidFeatureFace {
@@ -14,7 +14,7 @@ pub(crate) enum CuboidFeature {
 #[derive(Debug)]
 pub(crate) struct CuboidFeatureVertex {
     pub vertex: Point<f32>,
-    pub vid: u8,
+    pub vid: u32,
 }
 
 impl CuboidFeatureVertex {
@@ -26,8 +26,8 @@ impl CuboidFeatureVertex {
 #[derive(Debug)]
 pub(crate) struct CuboidFeatureEdge {
     pub vertices: [Point<f32>; 2],
-    pub vids: [u8; 2],
-    pub eid: u8,
+    pub vids: [u32; 2],
+    pub eid: u32,
 }
 
 impl CuboidFeatureEdge {
@@ -40,9 +40,9 @@ impl CuboidFeatureEdge {
 #[derive(Debug)]
 pub(crate) struct CuboidFeatureFace {
     pub vertices: [Point<f32>; 4],
-    pub vids: [u8; 4], // Feature ID of the vertices.
-    pub eids: [u8; 4], // Feature ID of the edges.
-    pub fid: u8,       // Feature ID of the face.
+    pub vids: [u32; 4], // Feature ID of the vertices.
+    pub eids: [u32; 4], // Feature ID of the edges.
+    pub fid: u32,       // Feature ID of the face.
 }
 
 impl CuboidFeatureFace {
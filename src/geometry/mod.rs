@@ -3,11 +3,24 @@
 pub use self::broad_phase_multi_sap::BroadPhase;
 pub use self::capsule::Capsule;
 pub use self::collider::{Collider, ColliderBuilder, ColliderShape};
-pub use self::collider_set::{ColliderHandle, ColliderSet};
+pub use self::collider_set::{ColliderHandle, ColliderSet, VoxelsMut};
+pub use self::compound::{Compound, PartMaterial};
 pub use self::contact::{
-    Contact, ContactKinematics, ContactManifold, ContactPair, KinematicsCategory, SolverFlags,
+    Contact, ContactForceEvent, ContactKinematics, ContactManifold, ContactPair,
+    KinematicsCategory, SolverFlags,
 };
-pub use self::contact_generator::{ContactDispatcher, DefaultContactDispatcher};
+pub use self::contact_generator::{
+    ContactDispatcher, ContactGenerationContext, ContactGenerator, ContactGeneratorWorkspace,
+    ContactPhase, DefaultContactDispatcher, PrimitiveContactGenerationContext,
+    PrimitiveContactGenerator,
+};
+#[cfg(feature = "simd-is-enabled")]
+pub use self::contact_generator::{
+    ContactGenerationContextSimd, PrimitiveContactGenerationContextSimd,
+};
+pub use self::convex_decomposition::VHACDParameters;
+#[cfg(feature = "dim3")]
+pub use self::convex_polyhedron::ConvexPolyhedron;
 #[cfg(feature = "dim2")]
 pub(crate) use self::cuboid_feature2d::{CuboidFeature, CuboidFeatureFace};
 #[cfg(feature = "dim3")]
@@ -18,12 +31,27 @@ pub use self::interaction_graph::{
 pub use self::narrow_phase::NarrowPhase;
 pub use self::polygon::Polygon;
 pub use self::proximity::ProximityPair;
-pub use self::proximity_detector::{DefaultProximityDispatcher, ProximityDispatcher};
+pub use self::proximity_detector::{
+    DefaultProximityDispatcher, PrimitiveProximityDetectionContext, PrimitiveProximityDetector,
+    ProximityDetectionContext, ProximityDetector, ProximityDispatcher, ProximityPhase,
+};
+#[cfg(feature = "simd-is-enabled")]
+pub use self::proximity_detector::{
+    PrimitiveProximityDetectionContextSimd, ProximityDetectionContextSimd,
+};
+pub use self::query::{intersection_test, nonlinear_time_of_impact, time_of_impact, TOI};
 #[cfg(feature = "dim3")]
 pub use self::round_cylinder::RoundCylinder;
 pub use self::trimesh::Trimesh;
-pub use self::user_callbacks::{ContactPairFilter, PairFilterContext, ProximityPairFilter};
-pub use ncollide::query::Proximity;
+#[cfg(feature = "dim3")]
+pub use self::trimesh::TrimeshFlags;
+pub use self::user_callbacks::{
+    ContactModificationContext, ContactModifier, ContactPairFilter, PairFilterContext,
+    ProximityPairFilter,
+};
+pub use self::voxels::Voxels;
+pub use ncollide::interpolation::RigidMotion;
+pub use ncollide::query::{Proximity, TOIStatus};
 
 /// A segment shape.
 pub type Segment = ncollide::shape::Segment<f32>;
@@ -53,6 +81,8 @@ pub type Ray = ncollide::query::Ray<f32>;
 pub type RayIntersection = ncollide::query::RayIntersection<f32>;
 /// The the projection of a point on a collider.
 pub type PointProjection = ncollide::query::PointProjection<f32>;
+/// A rigid motion interpolating linearly between two positions.
+pub type InterpolatedRigidMotion = ncollide::interpolation::InterpolatedRigidMotion<f32>;
 
 #[cfg(feature = "simd-is-enabled")]
 pub(crate) use self::ball::WBall;
@@ -64,10 +94,13 @@ pub(crate) use self::contact_generator::clip_segments;
 #[cfg(feature = "dim2")]
 pub(crate) use self::contact_generator::clip_segments_with_normal;
 pub(crate) use self::narrow_phase::ContactManifoldIndex;
+#[cfg(feature = "dim2")]
+pub(crate) use self::polygon::ShapePolygon;
 #[cfg(feature = "dim3")]
 pub(crate) use self::polygonal_feature_map::PolygonalFeatureMap;
 #[cfg(feature = "dim3")]
 pub(crate) use self::polyhedron_feature3d::PolyhedronFace;
+pub(crate) use self::query::contact;
 pub(crate) use self::waabb::{WRay, WAABB};
 pub(crate) use self::wquadtree::WQuadtree;
 //pub(crate) use self::z_order::z_cmp_floats;
@@ -78,8 +111,12 @@ mod ball;
 mod broad_phase_multi_sap;
 mod collider;
 mod collider_set;
+mod compound;
 mod contact;
 mod contact_generator;
+mod convex_decomposition;
+#[cfg(feature = "dim3")]
+mod convex_polyhedron;
 pub(crate) mod cuboid;
 #[cfg(feature = "dim2")]
 mod cuboid_feature2d;
@@ -92,9 +129,11 @@ mod polygon;
 mod polyhedron_feature3d;
 mod proximity;
 mod proximity_detector;
+mod query;
 pub(crate) mod sat;
 pub(crate) mod triangle;
 mod trimesh;
+mod voxels;
 mod waabb;
 mod wquadtree;
 //mod z_order;
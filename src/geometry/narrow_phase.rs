@@ -1,7 +1,7 @@
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
-use crate::dynamics::RigidBodySet;
+use crate::dynamics::{CoefficientCombineRule, RigidBodySet};
 use crate::geometry::contact_generator::{
     ContactDispatcher, ContactGenerationContext, DefaultContactDispatcher,
 };
@@ -14,17 +14,19 @@ use crate::geometry::proximity_detector::{
 //    proximity_detector::ProximityDetectionContextSimd, WBall,
 //};
 use crate::geometry::{
-    BroadPhasePairEvent, ColliderGraphIndex, ColliderHandle, ContactEvent, ContactPairFilter,
-    PairFilterContext, ProximityEvent, ProximityPair, ProximityPairFilter, RemovedCollider,
-    SolverFlags,
+    BroadPhasePairEvent, ColliderGraphIndex, ColliderHandle, ContactEvent, ContactForceEvent,
+    ContactModificationContext, ContactModifier, ContactPairFilter, PairFilterContext,
+    ProximityEvent, ProximityPair, ProximityPairFilter, RemovedCollider, SolverFlags,
 };
 use crate::geometry::{ColliderSet, ContactManifold, ContactPair, InteractionGraph};
 //#[cfg(feature = "simd-is-enabled")]
 //use crate::math::{SimdFloat, SIMD_WIDTH};
 use crate::data::pubsub::Subscription;
+use crate::math::Vector;
 use crate::ncollide::query::Proximity;
 use crate::pipeline::EventHandler;
 use std::collections::HashMap;
+use std::sync::Arc;
 //use simba::simd::SimdValue;
 
 /// The narrow-phase responsible for computing precise contact information between colliders.
@@ -34,6 +36,16 @@ pub struct NarrowPhase {
     contact_graph: InteractionGraph<ContactPair>,
     proximity_graph: InteractionGraph<ProximityPair>,
     removed_colliders: Option<Subscription<RemovedCollider>>,
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip, default = "default_contact_dispatcher")
+    )]
+    contact_dispatcher: Arc<dyn ContactDispatcher>,
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip, default = "default_proximity_dispatcher")
+    )]
+    proximity_dispatcher: Arc<dyn ProximityDispatcher>,
     //    ball_ball: Vec<usize>,        // Workspace: Vec<*mut ContactPair>,
     //    shape_shape: Vec<usize>,      // Workspace: Vec<*mut ContactPair>,
     //    ball_ball_prox: Vec<usize>,   // Workspace: Vec<*mut ProximityPair>,
@@ -42,13 +54,34 @@ pub struct NarrowPhase {
 
 pub(crate) type ContactManifoldIndex = usize;
 
+fn default_contact_dispatcher() -> Arc<dyn ContactDispatcher> {
+    Arc::new(DefaultContactDispatcher)
+}
+
+fn default_proximity_dispatcher() -> Arc<dyn ProximityDispatcher> {
+    Arc::new(DefaultProximityDispatcher)
+}
+
 impl NarrowPhase {
     /// Creates a new empty narrow-phase.
     pub fn new() -> Self {
+        Self::with_dispatchers(DefaultContactDispatcher, DefaultProximityDispatcher)
+    }
+
+    /// Creates a new empty narrow-phase with custom contact and proximity dispatchers.
+    ///
+    /// The dispatchers select the contact generator and proximity detector used for each
+    /// pair of colliders, based on their shape types.
+    pub fn with_dispatchers(
+        contact_dispatcher: impl ContactDispatcher + 'static,
+        proximity_dispatcher: impl ProximityDispatcher + 'static,
+    ) -> Self {
         Self {
             contact_graph: InteractionGraph::new(),
             proximity_graph: InteractionGraph::new(),
             removed_colliders: None,
+            contact_dispatcher: Arc::new(contact_dispatcher),
+            proximity_dispatcher: Arc::new(proximity_dispatcher),
             //            ball_ball: Vec::new(),
             //            shape_shape: Vec::new(),
             //            ball_ball_prox: Vec::new(),
@@ -66,6 +99,38 @@ impl NarrowPhase {
         &self.proximity_graph
     }
 
+    /// The dispatcher selecting the contact generator of each contact pair.
+    pub fn contact_dispatcher(&self) -> &dyn ContactDispatcher {
+        &*self.contact_dispatcher
+    }
+
+    /// The dispatcher selecting the proximity detector of each proximity pair.
+    pub fn proximity_dispatcher(&self) -> &dyn ProximityDispatcher {
+        &*self.proximity_dispatcher
+    }
+
+    /// Replaces the contact and proximity dispatchers of this narrow-phase.
+    ///
+    /// All the existing pairs will be re-dispatched through the new dispatchers the next time
+    /// their contacts or proximities are computed. Dispatchers are not serialized, so this must
+    /// also be called after deserializing a narrow-phase that relied on custom dispatchers.
+    pub fn set_dispatchers(
+        &mut self,
+        contact_dispatcher: impl ContactDispatcher + 'static,
+        proximity_dispatcher: impl ProximityDispatcher + 'static,
+    ) {
+        self.contact_dispatcher = Arc::new(contact_dispatcher);
+        self.proximity_dispatcher = Arc::new(proximity_dispatcher);
+
+        for edge in &mut self.contact_graph.graph.edges {
+            edge.weight.generator = None;
+        }
+
+        for edge in &mut self.proximity_graph.graph.edges {
+            edge.weight.detector = None;
+        }
+    }
+
     // #[cfg(feature = "parallel")]
     // pub fn contact_pairs(&self) -> &[ContactPair] {
     //     &self.contact_graph.interactions
@@ -80,7 +145,8 @@ impl NarrowPhase {
     //     &mut self.contact_graph.interactions
     // }
 
-    /// Maintain the narrow-phase internal state by taking collider removal into account.
+    /// Maintain the narrow-phase internal state by taking collider removal and shape
+    /// modifications into account.
     pub fn maintain(&mut self, colliders: &mut ColliderSet, bodies: &mut RigidBodySet) {
         // Ensure we already subscribed.
         if self.removed_colliders.is_none() {
@@ -121,6 +187,21 @@ impl NarrowPhase {
 
         colliders.removed_colliders.ack(&mut cursor);
         self.removed_colliders = Some(cursor);
+
+        // Wake up the bodies in contact with the colliders whose shape was modified.
+        for handle in std::mem::replace(&mut colliders.modified_shapes, Vec::new()) {
+            if let Some(collider) = colliders.get(handle) {
+                for (a, b, pair) in self
+                    .contact_graph
+                    .interactions_with(collider.contact_graph_index)
+                {
+                    if pair.has_any_active_contact() {
+                        bodies.wake_up(colliders[a].parent, true);
+                        bodies.wake_up(colliders[b].parent, true);
+                    }
+                }
+            }
+        }
     }
 
     pub(crate) fn remove_collider<'a>(
@@ -198,8 +279,8 @@ impl NarrowPhase {
                             }
 
                             if self.proximity_graph.graph.find_edge(gid1, gid2).is_none() {
-                                let dispatcher = DefaultProximityDispatcher;
-                                let generator = dispatcher
+                                let generator = self
+                                    .proximity_dispatcher
                                     .dispatch(co1.shape().shape_type(), co2.shape().shape_type());
                                 let interaction =
                                     ProximityPair::new(*pair, generator.0, generator.1);
@@ -228,8 +309,8 @@ impl NarrowPhase {
                             }
 
                             if self.contact_graph.graph.find_edge(gid1, gid2).is_none() {
-                                let dispatcher = DefaultContactDispatcher;
-                                let generator = dispatcher
+                                let generator = self
+                                    .contact_dispatcher
                                     .dispatch(co1.shape().shape_type(), co2.shape().shape_type());
                                 let interaction = ContactPair::new(*pair, generator.0, generator.1);
                                 let _ = self.contact_graph.add_edge(
@@ -295,6 +376,8 @@ impl NarrowPhase {
         pair_filter: Option<&dyn ProximityPairFilter>,
         events: &dyn EventHandler,
     ) {
+        let dispatcher = &*self.proximity_dispatcher;
+
         par_iter_mut!(&mut self.proximity_graph.graph.edges).for_each(|edge| {
             let pair = &mut edge.weight;
             let co1 = &colliders[pair.pair.collider1];
@@ -336,7 +419,6 @@ impl NarrowPhase {
                 }
             }
 
-            let dispatcher = DefaultProximityDispatcher;
             if pair.detector.is_none() {
                 // We need a redispatch for this detector.
                 // This can happen, e.g., after restoring a snapshot of the narrow-phase.
@@ -347,7 +429,7 @@ impl NarrowPhase {
             }
 
             let context = ProximityDetectionContext {
-                dispatcher: &dispatcher,
+                dispatcher,
                 prediction_distance,
                 colliders,
                 pair,
@@ -367,8 +449,11 @@ impl NarrowPhase {
         bodies: &RigidBodySet,
         colliders: &ColliderSet,
         pair_filter: Option<&dyn ContactPairFilter>,
+        contact_modifier: Option<&dyn ContactModifier>,
         events: &dyn EventHandler,
     ) {
+        let dispatcher = &*self.contact_dispatcher;
+
         par_iter_mut!(&mut self.contact_graph.graph.edges).for_each(|edge| {
             let pair = &mut edge.weight;
             let co1 = &colliders[pair.pair.collider1];
@@ -418,7 +503,6 @@ impl NarrowPhase {
                 solver_flags.remove(SolverFlags::COMPUTE_IMPULSES);
             }
 
-            let dispatcher = DefaultContactDispatcher;
             if pair.generator.is_none() {
                 // We need a redispatch for this generator.
                 // This can happen, e.g., after restoring a snapshot of the narrow-phase.
@@ -433,7 +517,7 @@ impl NarrowPhase {
             }
 
             let context = ContactGenerationContext {
-                dispatcher: &dispatcher,
+                dispatcher,
                 prediction_distance,
                 colliders,
                 pair,
@@ -445,9 +529,134 @@ impl NarrowPhase {
                 .generator
                 .unwrap()
                 .generate_contacts(context, events);
+
+            // Undo the modifications of the previous timestep, even if there is no contact
+            // modifier anymore: the contact generators may have kept the contacts of this
+            // manifold without sorting them again, nor updating its coefficients.
+            let friction = CoefficientCombineRule::combine(
+                co1.friction,
+                co2.friction,
+                co1.friction_combine_rule,
+                co2.friction_combine_rule,
+            );
+            let restitution = CoefficientCombineRule::combine(
+                co1.restitution,
+                co2.restitution,
+                co1.restitution_combine_rule,
+                co2.restitution_combine_rule,
+            );
+
+            for manifold in &mut pair.manifolds {
+                manifold.sort_contacts(prediction_distance);
+                manifold.tangent_velocity = Vector::zeros();
+                manifold.friction = friction;
+                manifold.restitution = restitution;
+            }
+
+            if let Some(modifier) = contact_modifier {
+                for manifold in &mut pair.manifolds {
+                    if manifold.num_active_contacts() == 0 {
+                        continue;
+                    }
+
+                    // The contact determination algorithm may have swapped the colliders.
+                    let (co1, co2, rb1, rb2) = if manifold.pair.collider1 == pair.pair.collider1 {
+                        (co1, co2, rb1, rb2)
+                    } else {
+                        (co2, co1, rb2, rb1)
+                    };
+
+                    let mut context = ContactModificationContext {
+                        rigid_body1: rb1,
+                        rigid_body2: rb2,
+                        collider1: co1,
+                        collider2: co2,
+                        manifold,
+                    };
+
+                    modifier.modify_contacts(&mut context);
+                }
+            }
         });
     }
 
+    /// Emits a contact force event for each contact pair with contact forces exceeding the
+    /// contact force event threshold of one of its colliders.
+    ///
+    /// This must be called after the constraints solver so that the contact impulses are up to date.
+    pub(crate) fn emit_contact_force_events(
+        &self,
+        dt: f32,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        events: &dyn EventHandler,
+    ) {
+        let inv_dt = crate::utils::inv(dt);
+
+        for inter in self.contact_graph.graph.edges.iter() {
+            let pair = &inter.weight;
+            let co1 = &colliders[pair.pair.collider1];
+            let co2 = &colliders[pair.pair.collider2];
+            let threshold = co1
+                .contact_force_event_threshold
+                .min(co2.contact_force_event_threshold);
+
+            if threshold == f32::MAX {
+                continue;
+            }
+
+            let rb1 = &bodies[co1.parent];
+            let rb2 = &bodies[co2.parent];
+
+            if (rb1.is_sleeping() || !rb1.is_dynamic()) && (rb2.is_sleeping() || !rb2.is_dynamic())
+            {
+                // The contact impulses were not updated by the solver.
+                continue;
+            }
+
+            let mut event = ContactForceEvent {
+                collider1: pair.pair.collider1,
+                collider2: pair.pair.collider2,
+                total_force: Vector::zeros(),
+                total_force_magnitude: 0.0,
+                max_force_direction: Vector::zeros(),
+                max_force_magnitude: 0.0,
+            };
+
+            for manifold in &pair.manifolds {
+                if !manifold
+                    .solver_flags
+                    .contains(SolverFlags::COMPUTE_IMPULSES)
+                {
+                    continue;
+                }
+
+                // The direction of the contact forces applied to the first collider of the pair.
+                // The contact determination algorithm may have swapped the colliders.
+                let force_dir = if manifold.pair.collider1 == pair.pair.collider1 {
+                    rb1.position() * manifold.delta1 * -manifold.local_n1
+                } else {
+                    rb1.position() * manifold.delta2 * -manifold.local_n2
+                };
+
+                for contact in manifold.active_contacts() {
+                    let force = contact.impulse * inv_dt;
+                    event.total_force += force_dir * force;
+                    event.total_force_magnitude += force.abs();
+
+                    if force.abs() > event.max_force_magnitude {
+                        event.max_force_magnitude = force.abs();
+                        event.max_force_direction = force_dir * force.signum();
+                    }
+                }
+            }
+
+            if event.total_force_magnitude > threshold {
+                events.handle_contact_force_event(event);
+            }
+        }
+    }
+
     /// Retrieve all the interactions with at least one contact point, happening between two active bodies.
     // NOTE: this is very similar to the code from JointSet::select_active_interactions.
     pub(crate) fn sort_and_select_active_contacts<'a>(
@@ -486,3 +695,359 @@ impl NarrowPhase {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::dynamics::{CoefficientCombineRule, RigidBodyBuilder};
+    use crate::geometry::{
+        ColliderBuilder, ColliderShape, ContactDispatcher, ContactGeneratorWorkspace,
+        ContactModificationContext, ContactModifier, ContactPhase, DefaultContactDispatcher,
+        DefaultProximityDispatcher, NarrowPhase, PrimitiveContactGenerationContext,
+        PrimitiveContactGenerator, PrimitiveProximityDetectionContext, PrimitiveProximityDetector,
+        Proximity, ProximityDispatcher, ProximityPhase, ShapeType,
+    };
+    use crate::math::{Isometry, Vector};
+    use crate::pipeline::test_world::TestWorld;
+    use crate::pipeline::ChannelEventCollector;
+    use std::any::Any;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    static NUM_CONTACT_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static NUM_PROXIMITY_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    // Dispatchers ignoring all the contacts and proximities between cuboids.
+    struct IgnoreCuboids;
+
+    fn ignore_contacts(_: &mut PrimitiveContactGenerationContext) {
+        let _ = NUM_CONTACT_CALLS.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn ignore_proximity(_: &mut PrimitiveProximityDetectionContext) -> Proximity {
+        let _ = NUM_PROXIMITY_CALLS.fetch_add(1, Ordering::SeqCst);
+        Proximity::Disjoint
+    }
+
+    impl ContactDispatcher for IgnoreCuboids {
+        fn dispatch_primitives(
+            &self,
+            shape1: ShapeType,
+            shape2: ShapeType,
+        ) -> (PrimitiveContactGenerator, Option<ContactGeneratorWorkspace>) {
+            match (shape1, shape2) {
+                (ShapeType::Cuboid, ShapeType::Cuboid) => (
+                    PrimitiveContactGenerator {
+                        generate_contacts: ignore_contacts,
+                        ..PrimitiveContactGenerator::default()
+                    },
+                    None,
+                ),
+                _ => DefaultContactDispatcher.dispatch_primitives(shape1, shape2),
+            }
+        }
+
+        fn dispatch(
+            &self,
+            shape1: ShapeType,
+            shape2: ShapeType,
+        ) -> (ContactPhase, Option<ContactGeneratorWorkspace>) {
+            match (shape1, shape2) {
+                (ShapeType::Cuboid, ShapeType::Cuboid) => {
+                    let (generator, workspace) =
+                        ContactDispatcher::dispatch_primitives(self, shape1, shape2);
+                    (ContactPhase::ExactPhase(generator), workspace)
+                }
+                _ => DefaultContactDispatcher.dispatch(shape1, shape2),
+            }
+        }
+    }
+
+    impl ProximityDispatcher for IgnoreCuboids {
+        fn dispatch_primitives(
+            &self,
+            shape1: ShapeType,
+            shape2: ShapeType,
+        ) -> (
+            PrimitiveProximityDetector,
+            Option<Box<dyn Any + Send + Sync>>,
+        ) {
+            match (shape1, shape2) {
+                (ShapeType::Cuboid, ShapeType::Cuboid) => (
+                    PrimitiveProximityDetector {
+                        detect_proximity: ignore_proximity,
+                        ..PrimitiveProximityDetector::default()
+                    },
+                    None,
+                ),
+                _ => DefaultProximityDispatcher.dispatch_primitives(shape1, shape2),
+            }
+        }
+
+        fn dispatch(
+            &self,
+            shape1: ShapeType,
+            shape2: ShapeType,
+        ) -> (ProximityPhase, Option<Box<dyn Any + Send + Sync>>) {
+            match (shape1, shape2) {
+                (ShapeType::Cuboid, ShapeType::Cuboid) => {
+                    let (detector, workspace) =
+                        ProximityDispatcher::dispatch_primitives(self, shape1, shape2);
+                    (ProximityPhase::ExactPhase(detector), workspace)
+                }
+                _ => DefaultProximityDispatcher.dispatch(shape1, shape2),
+            }
+        }
+    }
+
+    #[test]
+    fn custom_dispatchers_override_pairs() {
+        let mut world = TestWorld::new();
+        world.narrow_phase = NarrowPhase::with_dispatchers(IgnoreCuboids, IgnoreCuboids);
+
+        // Overlapping cuboids and balls, and a cuboid sensor overlapping a cuboid.
+        let cuboid = || ColliderBuilder::new(ColliderShape::cuboid(Vector::repeat(0.5)));
+        let mut insert = |x: f32, collider: ColliderBuilder| {
+            let rb = RigidBodyBuilder::new_dynamic()
+                .position(Isometry::new(Vector::x() * x, na::zero()))
+                .build();
+            world.insert(rb, vec![collider.build()])
+        };
+        let _ = insert(0.0, cuboid());
+        let _ = insert(0.5, cuboid());
+        let _ = insert(10.0, ColliderBuilder::ball(0.5));
+        let _ = insert(10.5, ColliderBuilder::ball(0.5));
+        let _ = insert(20.0, cuboid());
+        let _ = insert(20.5, cuboid().sensor(true));
+
+        world.step();
+
+        assert!(NUM_CONTACT_CALLS.load(Ordering::SeqCst) > 0);
+        assert!(NUM_PROXIMITY_CALLS.load(Ordering::SeqCst) > 0);
+
+        // Only the balls, handled by the default dispatcher, are in contact.
+        let contact_graph = world.narrow_phase.contact_graph();
+        for (h1, h2, pair) in contact_graph.interaction_pairs() {
+            let is_ball = world.colliders[h1].shape().as_ball().is_some();
+            assert_eq!(is_ball, world.colliders[h2].shape().as_ball().is_some());
+            let num_contacts: usize = pair.manifolds.iter().map(|m| m.num_active_contacts()).sum();
+            assert_eq!(num_contacts > 0, is_ball);
+        }
+
+        let proximity_graph = world.narrow_phase.proximity_graph();
+        assert_eq!(proximity_graph.interaction_pairs().count(), 1);
+        for (_, _, pair) in proximity_graph.interaction_pairs() {
+            assert_eq!(pair.proximity, Proximity::Disjoint);
+        }
+    }
+
+    struct OneWayPlatform;
+
+    impl ContactModifier for OneWayPlatform {
+        fn modify_contacts(&self, context: &mut ContactModificationContext) {
+            // Outward normal of the platform, pointing toward the other collider.
+            let normal = if context.rigid_body1.is_static() {
+                context.collider1.position() * context.manifold.local_n1
+            } else {
+                context.collider2.position() * context.manifold.local_n2
+            };
+
+            if normal.y <= 0.0 {
+                context.manifold.retain_active_contacts(|_| false);
+            }
+        }
+    }
+
+    #[test]
+    fn contact_modifier_one_way_platform() {
+        let mut world = TestWorld::with_gravity();
+        let _ = world.insert_ground(5.0);
+
+        // A ball thrown upward from below the platform.
+        #[cfg(feature = "dim2")]
+        let rb = RigidBodyBuilder::new_dynamic()
+            .translation(0.0, -2.0)
+            .linvel(0.0, 10.0)
+            .build();
+        #[cfg(feature = "dim3")]
+        let rb = RigidBodyBuilder::new_dynamic()
+            .translation(0.0, -2.0, 0.0)
+            .linvel(0.0, 10.0, 0.0)
+            .build();
+        let body = world.insert(rb, Some(ColliderBuilder::ball(0.5).build()));
+
+        for _ in 0..300 {
+            world.step_with(Some(&OneWayPlatform), &());
+        }
+
+        // The ball went through the platform and landed on top of it.
+        let y = world.bodies[body].position().translation.vector.y;
+        assert!(y > 0.5 && y < 0.7);
+    }
+
+    // Ignores all the contacts and sets a tangent velocity while `reject` is set.
+    struct RejectOnce {
+        reject: AtomicBool,
+    }
+
+    impl ContactModifier for RejectOnce {
+        fn modify_contacts(&self, context: &mut ContactModificationContext) {
+            if self.reject.swap(false, Ordering::SeqCst) {
+                context.manifold.retain_active_contacts(|_| false);
+                context.manifold.tangent_velocity = Vector::x() * 10.0;
+            }
+        }
+    }
+
+    #[test]
+    fn contact_modifications_only_last_one_timestep() {
+        let mut world = TestWorld::with_gravity();
+        let _ = world.insert_ground(5.0);
+
+        #[cfg(feature = "dim2")]
+        let (rb, collider) = (
+            RigidBodyBuilder::new_dynamic().translation(0.0, 0.6),
+            ColliderBuilder::cuboid(0.5, 0.5),
+        );
+        #[cfg(feature = "dim3")]
+        let (rb, collider) = (
+            RigidBodyBuilder::new_dynamic().translation(0.0, 0.6, 0.0),
+            ColliderBuilder::cuboid(0.5, 0.5, 0.5),
+        );
+        let body = world.insert(rb.build(), Some(collider.build()));
+
+        let modifier = RejectOnce {
+            reject: AtomicBool::new(false),
+        };
+
+        // Let the cube settle on the ground.
+        for _ in 0..60 {
+            world.step_with(Some(&modifier), &());
+        }
+
+        modifier.reject.store(true, Ordering::SeqCst);
+
+        for _ in 0..60 {
+            world.step_with(Some(&modifier), &());
+        }
+
+        // The cube is resting on the ground again, without sliding.
+        let body = &world.bodies[body];
+        assert!((body.position().translation.vector.y - 0.6).abs() < 1.0e-2);
+        assert!(body.linvel().norm() < 1.0e-2);
+
+        for edge in world.narrow_phase.contact_graph().raw_graph().raw_edges() {
+            for manifold in &edge.weight.manifolds {
+                assert!(manifold.num_active_contacts() > 0);
+                assert_eq!(manifold.tangent_velocity, Vector::zeros());
+            }
+        }
+    }
+
+    // Ignores all the contacts and modifies all the coefficients of the manifolds.
+    struct ModifyEverything;
+
+    impl ContactModifier for ModifyEverything {
+        fn modify_contacts(&self, context: &mut ContactModificationContext) {
+            context.manifold.retain_active_contacts(|_| false);
+            context.manifold.tangent_velocity = Vector::x() * 10.0;
+            context.manifold.friction = 0.0;
+            context.manifold.restitution = 1.0;
+        }
+    }
+
+    #[test]
+    fn contact_modifications_are_reset_without_modifier() {
+        let mut world = TestWorld::with_gravity();
+        let (_, ground) = world.insert_ground(5.0);
+
+        #[cfg(feature = "dim2")]
+        let (rb, collider) = (
+            RigidBodyBuilder::new_dynamic().translation(0.0, 0.6),
+            ColliderBuilder::cuboid(0.5, 0.5),
+        );
+        #[cfg(feature = "dim3")]
+        let (rb, collider) = (
+            RigidBodyBuilder::new_dynamic().translation(0.0, 0.6, 0.0),
+            ColliderBuilder::cuboid(0.5, 0.5, 0.5),
+        );
+        let body = world.insert(rb.build(), None);
+        let cube = world.insert_collider(collider.friction(0.3).build(), body);
+
+        // Let the cube settle on the ground.
+        world.run(60);
+        world.step_with(Some(&ModifyEverything), &());
+        world.step_with(None, &());
+
+        let (co1, co2) = (&world.colliders[ground], &world.colliders[cube]);
+        let friction = CoefficientCombineRule::combine(
+            co1.friction,
+            co2.friction,
+            co1.friction_combine_rule,
+            co2.friction_combine_rule,
+        );
+        let restitution = CoefficientCombineRule::combine(
+            co1.restitution,
+            co2.restitution,
+            co1.restitution_combine_rule,
+            co2.restitution_combine_rule,
+        );
+
+        let mut num_manifolds = 0;
+
+        for edge in world.narrow_phase.contact_graph().raw_graph().raw_edges() {
+            for manifold in &edge.weight.manifolds {
+                assert!(manifold.num_active_contacts() > 0);
+                assert_eq!(manifold.tangent_velocity, Vector::zeros());
+                assert_eq!(manifold.friction, friction);
+                assert_eq!(manifold.restitution, restitution);
+                num_manifolds += 1;
+            }
+        }
+
+        assert!(num_manifolds > 0);
+    }
+
+    #[test]
+    fn contact_force_events() {
+        let mut world = TestWorld::with_gravity();
+        let (_, ground) = world.insert_ground(5.0);
+
+        // A ball falling on the ground.
+        #[cfg(feature = "dim2")]
+        let rb = RigidBodyBuilder::new_dynamic().translation(0.0, 1.0);
+        #[cfg(feature = "dim3")]
+        let rb = RigidBodyBuilder::new_dynamic().translation(0.0, 1.0, 0.0);
+        let co = ColliderBuilder::ball(0.5).contact_force_event_threshold(1.0);
+        let body = world.insert(rb.build(), None);
+        let ball = world.insert_collider(co.build(), body);
+
+        let proximity_channel = crossbeam::channel::unbounded();
+        let contact_channel = crossbeam::channel::unbounded();
+        let contact_force_channel = crossbeam::channel::unbounded();
+        let events = ChannelEventCollector::new(proximity_channel.0, contact_channel.0)
+            .with_contact_force_event_sender(contact_force_channel.0);
+
+        for _ in 0..60 {
+            world.step_with(None, &events);
+        }
+
+        let mut num_events = 0;
+
+        while let Ok(event) = contact_force_channel.1.try_recv() {
+            num_events += 1;
+            assert!(event.total_force_magnitude > 1.0);
+            assert!(event.max_force_magnitude <= event.total_force_magnitude + 1.0e-5);
+
+            // The ground pushes the ball upward.
+            let force_on_ball = if event.collider1 == ball {
+                assert_eq!(event.collider2, ground);
+                event.total_force
+            } else {
+                assert_eq!(event.collider1, ground);
+                -event.total_force
+            };
+            assert!(force_on_ball.y > 0.0);
+        }
+
+        assert!(num_events > 0);
+    }
+}
@@ -1,9 +1,19 @@
-#![allow(dead_code)] // TODO: remove this once we support polygons.
+#![cfg_attr(feature = "dim3", allow(dead_code))] // Polygons are only used as shapes in 2D.
 
 use crate::math::{Isometry, Point, Vector};
 use ncollide::bounding_volume::AABB;
+#[cfg(feature = "dim2")]
+use {
+    crate::geometry::Shape,
+    arrayvec::ArrayVec,
+    na::Unit,
+    ncollide::query::{
+        algorithms::VoronoiSimplex, PointProjection, PointQuery, Ray, RayCast, RayIntersection,
+    },
+    ncollide::shape::{FeatureId, SupportMap},
+};
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 /// A convex planar polygon.
 pub struct Polygon {
@@ -26,6 +36,88 @@ impl Polygon {
         Self { vertices, normals }
     }
 
+    /// Computes the convex hull of the given set of points.
+    ///
+    /// Returns `None` if the convex hull has less than three vertices, e.g., if all the
+    /// points are collinear.
+    #[cfg(feature = "dim2")]
+    pub fn from_convex_hull(points: &[Point<f32>]) -> Option<Self> {
+        if points.len() < 3 {
+            return None;
+        }
+
+        // NOTE: ncollide outputs the hull vertices in clockwise order.
+        let vertices = ncollide::transformation::convex_hull_idx(points)
+            .into_iter()
+            .rev()
+            .map(|i| points[i])
+            .collect();
+        Self::from_convex_polyline(vertices)
+    }
+
+    /// Builds a new polygon from its vertices and computes its normals.
+    ///
+    /// The vertices must be given in counter-clockwise order and form a convex polygon.
+    /// Returns `None` if there are less than three vertices or if an edge is degenerate.
+    #[cfg(feature = "dim2")]
+    pub fn from_convex_polyline(vertices: Vec<Point<f32>>) -> Option<Self> {
+        if vertices.len() < 3 {
+            return None;
+        }
+
+        let normals = polyline_normals(&vertices)?;
+        Some(Self { vertices, normals })
+    }
+
+    /// Converts a 2D shape to a polygon and a border radius, if it has a polygonal representation.
+    ///
+    /// Segments are represented as polygons with two vertices and two opposite normals.
+    /// Returns `None` if the shape has no polygonal representation, or if it is degenerate,
+    /// e.g., a segment with two identical vertices or a flat triangle.
+    #[cfg(feature = "dim2")]
+    pub(crate) fn from_shape(shape: &dyn Shape) -> Option<(ShapePolygon<'_>, f32)> {
+        if let Some(polygon) = shape.as_polygon() {
+            return Some((ShapePolygon::Borrowed(polygon), 0.0));
+        }
+
+        let mut vertices = ArrayVec::new();
+        let radius = if let Some(cuboid) = shape.as_cuboid() {
+            let he = cuboid.half_extents;
+            vertices.push(Point::new(-he.x, -he.y));
+            vertices.push(Point::new(he.x, -he.y));
+            vertices.push(Point::new(he.x, he.y));
+            vertices.push(Point::new(-he.x, he.y));
+            0.0
+        } else if let Some(triangle) = shape.as_triangle() {
+            vertices.push(triangle.a);
+            if (triangle.b - triangle.a).perp(&(triangle.c - triangle.a)) >= 0.0 {
+                vertices.push(triangle.b);
+                vertices.push(triangle.c);
+            } else {
+                vertices.push(triangle.c);
+                vertices.push(triangle.b);
+            }
+            0.0
+        } else if let Some(segment) = shape.as_segment() {
+            vertices.push(segment.a);
+            vertices.push(segment.b);
+            0.0
+        } else if let Some(capsule) = shape.as_capsule() {
+            vertices.push(capsule.segment.a);
+            vertices.push(capsule.segment.b);
+            capsule.radius
+        } else {
+            return None;
+        };
+
+        let mut normals = ArrayVec::new();
+        for i in 0..vertices.len() {
+            normals.push(edge_normal(&vertices, i)?);
+        }
+
+        Some((ShapePolygon::Local { vertices, normals }, radius))
+    }
+
     /// Compute the axis-aligned bounding box of the polygon.
     pub fn aabb(&self, pos: &Isometry<f32>) -> AABB<f32> {
         let p0 = pos * self.vertices[0];
@@ -46,33 +138,263 @@ impl Polygon {
         &self.vertices
     }
 
+    /// The outward normals of the edges of this polygon.
+    pub fn normals(&self) -> &[Vector<f32>] {
+        &self.normals
+    }
+
     pub(crate) fn support_point(&self, dir: &Vector<f32>) -> usize {
-        let mut best_dot = -f32::MAX;
-        let mut best_i = 0;
-
-        for (i, pt) in self.vertices.iter().enumerate() {
-            let dot = pt.coords.dot(&dir);
-            if dot > best_dot {
-                best_dot = dot;
-                best_i = i;
-            }
+        support_point(&self.vertices, dir)
+    }
+}
+
+/// The polygonal representation of a 2D shape, as returned by `Polygon::from_shape`.
+///
+/// The shapes other than polygons have at most four vertices, so they are stored
+/// inline to avoid allocating a new polygon each time contacts are computed.
+#[cfg(feature = "dim2")]
+pub(crate) enum ShapePolygon<'a> {
+    Borrowed(&'a Polygon),
+    Local {
+        vertices: ArrayVec<[Point<f32>; 4]>,
+        normals: ArrayVec<[Vector<f32>; 4]>,
+    },
+}
+
+#[cfg(feature = "dim2")]
+impl<'a> ShapePolygon<'a> {
+    /// The vertices of this polygon.
+    pub fn vertices(&self) -> &[Point<f32>] {
+        match self {
+            ShapePolygon::Borrowed(polygon) => &polygon.vertices,
+            ShapePolygon::Local { vertices, .. } => vertices,
+        }
+    }
+
+    /// The outward normals of the edges of this polygon.
+    pub fn normals(&self) -> &[Vector<f32>] {
+        match self {
+            ShapePolygon::Borrowed(polygon) => &polygon.normals,
+            ShapePolygon::Local { normals, .. } => normals,
         }
+    }
+
+    pub fn support_point(&self, dir: &Vector<f32>) -> usize {
+        support_point(self.vertices(), dir)
+    }
 
-        best_i
+    pub fn support_face(&self, dir: &Vector<f32>) -> usize {
+        support_face(self.normals(), dir)
     }
+}
 
-    pub(crate) fn support_face(&self, dir: &Vector<f32>) -> usize {
-        let mut max_dot = -f32::MAX;
-        let mut max_dot_i = 0;
+fn support_point(vertices: &[Point<f32>], dir: &Vector<f32>) -> usize {
+    let mut best_dot = -f32::MAX;
+    let mut best_i = 0;
 
-        for (i, normal) in self.normals.iter().enumerate() {
-            let dot = normal.dot(dir);
-            if dot > max_dot {
-                max_dot = dot;
-                max_dot_i = i;
+    for (i, pt) in vertices.iter().enumerate() {
+        let dot = pt.coords.dot(&dir);
+        if dot > best_dot {
+            best_dot = dot;
+            best_i = i;
+        }
+    }
+
+    best_i
+}
+
+fn support_face(normals: &[Vector<f32>], dir: &Vector<f32>) -> usize {
+    let mut max_dot = -f32::MAX;
+    let mut max_dot_i = 0;
+
+    for (i, normal) in normals.iter().enumerate() {
+        let dot = normal.dot(dir);
+        if dot > max_dot {
+            max_dot = dot;
+            max_dot_i = i;
+        }
+    }
+
+    max_dot_i
+}
+
+// The outward normals of the edges of a polyline given in counter-clockwise order.
+#[cfg(feature = "dim2")]
+fn polyline_normals(vertices: &[Point<f32>]) -> Option<Vec<Vector<f32>>> {
+    (0..vertices.len())
+        .map(|i| edge_normal(vertices, i))
+        .collect()
+}
+
+// The outward normal of the `i`-th edge of a polyline given in counter-clockwise order.
+// Returns `None` if the edge is degenerate.
+#[cfg(feature = "dim2")]
+fn edge_normal(vertices: &[Point<f32>], i: usize) -> Option<Vector<f32>> {
+    let edge = vertices[(i + 1) % vertices.len()] - vertices[i];
+    Unit::try_new(Vector::new(edge.y, -edge.x), f32::EPSILON).map(|n| n.into_inner())
+}
+
+#[cfg(feature = "dim2")]
+impl SupportMap<f32> for Polygon {
+    fn local_support_point(&self, dir: &Vector<f32>) -> Point<f32> {
+        self.vertices[self.support_point(dir)]
+    }
+}
+
+#[cfg(feature = "dim2")]
+impl RayCast<f32> for Polygon {
+    fn toi_and_normal_with_ray(
+        &self,
+        m: &Isometry<f32>,
+        ray: &Ray<f32>,
+        max_toi: f32,
+        solid: bool,
+    ) -> Option<RayIntersection<f32>> {
+        let ls_ray = ray.inverse_transform_by(m);
+
+        ncollide::query::ray_intersection_with_support_map_with_params(
+            &Isometry::identity(),
+            self,
+            &mut VoronoiSimplex::new(),
+            &ls_ray,
+            max_toi,
+            solid,
+        )
+        .map(|mut res| {
+            res.normal = m * res.normal;
+            res
+        })
+    }
+}
+
+#[cfg(feature = "dim2")]
+impl PointQuery<f32> for Polygon {
+    fn project_point(
+        &self,
+        m: &Isometry<f32>,
+        pt: &Point<f32>,
+        solid: bool,
+    ) -> PointProjection<f32> {
+        ncollide::query::point_projection_on_support_map(
+            m,
+            self,
+            &mut VoronoiSimplex::new(),
+            pt,
+            solid,
+        )
+    }
+
+    fn project_point_with_feature(
+        &self,
+        m: &Isometry<f32>,
+        pt: &Point<f32>,
+    ) -> (PointProjection<f32>, FeatureId) {
+        (self.project_point(m, pt, false), FeatureId::Unknown)
+    }
+}
+
+#[cfg(all(test, feature = "dim2"))]
+mod test {
+    use crate::dynamics::RigidBodyBuilder;
+    use crate::geometry::{ColliderBuilder, Proximity};
+    use crate::math::Point;
+    use crate::pipeline::test_world::TestWorld;
+
+    #[test]
+    fn convex_polygons_resting_on_ground() {
+        let mut world = TestWorld::with_gravity();
+
+        // A cuboid ground on the left, and a rounded ground on the right.
+        let ground = world.insert(RigidBodyBuilder::new_static().build(), None);
+        let co = ColliderBuilder::cuboid(5.0, 0.1)
+            .translation(-5.0, 0.0)
+            .build();
+        world.insert_collider(co, ground);
+        let co = ColliderBuilder::capsule_x(5.0, 0.1)
+            .translation(5.0, 0.0)
+            .build();
+        world.insert_collider(co, ground);
+
+        let points: Vec<_> = (0..6)
+            .map(|i| {
+                let angle = i as f32 * std::f32::consts::PI / 3.0;
+                Point::new(angle.cos(), angle.sin())
+            })
+            .collect();
+
+        let handles: Vec<_> = [-5.0, 5.0]
+            .iter()
+            .map(|x| {
+                let rb = RigidBodyBuilder::new_dynamic().translation(*x, 1.5).build();
+                let co = ColliderBuilder::convex_hull(&points).unwrap().build();
+                world.insert(rb, Some(co))
+            })
+            .collect();
+
+        world.run(120);
+
+        // The hexagon rests on one of its edges, at a distance of sqrt(3) / 2 from its center.
+        let half_height = 3.0f32.sqrt() / 2.0;
+        for h in handles {
+            let rb = &world.bodies[h];
+            let y = rb.position().translation.vector.y;
+            assert!((y - 0.1 - half_height).abs() < 0.05);
+            assert!(rb.linvel().norm() < 0.1);
+            assert!(rb.angvel().abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn degenerate_shapes_against_polygons() {
+        let mut world = TestWorld::new();
+
+        let square = vec![
+            Point::new(-1.0, -1.0),
+            Point::new(1.0, -1.0),
+            Point::new(1.0, 1.0),
+            Point::new(-1.0, 1.0),
+        ];
+        let ground = world.insert(RigidBodyBuilder::new_static().build(), None);
+        let co = ColliderBuilder::convex_polyline(square.clone())
+            .unwrap()
+            .build();
+        world.insert_collider(co, ground);
+        let co = ColliderBuilder::convex_polyline(square)
+            .unwrap()
+            .sensor(true)
+            .build();
+        world.insert_collider(co, ground);
+
+        // Shapes without any valid polygonal representation, overlapping the polygons
+        // but not each other.
+        let p = Point::new(0.0, 0.5);
+        let degenerate_shapes = vec![
+            ColliderBuilder::segment(p, p),
+            ColliderBuilder::triangle(p, p, Point::origin()),
+            ColliderBuilder::capsule_y(0.0, 0.1),
+        ];
+
+        for (i, co) in degenerate_shapes.into_iter().enumerate() {
+            let rb = RigidBodyBuilder::new_dynamic()
+                .translation(i as f32 - 1.0, 0.0)
+                .build();
+            world.insert(rb, Some(co.build()));
+        }
+
+        world.run(10);
+
+        let contacts = world.narrow_phase.contact_graph().raw_graph().raw_edges();
+        assert_eq!(contacts.len(), 3);
+        for edge in contacts {
+            for manifold in &edge.weight.manifolds {
+                assert_eq!(manifold.num_active_contacts(), 0);
             }
         }
 
-        max_dot_i
+        let proximities = world.narrow_phase.proximity_graph().raw_graph().raw_edges();
+        assert_eq!(proximities.len(), 3);
+        for edge in proximities {
+            assert_eq!(edge.weight.proximity, Proximity::Disjoint);
+        }
     }
 }
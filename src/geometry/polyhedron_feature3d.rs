@@ -8,10 +8,15 @@ use ncollide::shape::Segment;
 #[derive(Debug, Clone)]
 pub struct PolyhedronFace {
     pub vertices: [Point<f32>; 4],
-    pub vids: [u8; 4], // Feature ID of the vertices.
-    pub eids: [u8; 4], // Feature ID of the edges.
-    pub fid: u8,       // Feature ID of the face.
+    pub vids: [u32; 4], // Feature ID of the vertices.
+    pub eids: [u32; 4], // Feature ID of the edges.
+    pub fid: u32,       // Feature ID of the face.
     pub num_vertices: usize,
+    // Storage used instead of the three arrays above when the face has more than four
+    // vertices, e.g., for the faces of a `ConvexPolyhedron`. Reused to avoid allocations.
+    large_vertices: Vec<Point<f32>>,
+    large_vids: Vec<u32>,
+    large_eids: Vec<u32>,
 }
 
 impl Default for PolyhedronFace {
@@ -22,6 +27,9 @@ impl Default for PolyhedronFace {
             eids: [0; 4],
             fid: 0,
             num_vertices: 0,
+            large_vertices: Vec::new(),
+            large_vids: Vec::new(),
+            large_eids: Vec::new(),
         }
     }
 }
@@ -34,6 +42,9 @@ impl From<CuboidFeatureFace> for PolyhedronFace {
             eids: face.eids,
             fid: face.fid,
             num_vertices: 4,
+            large_vertices: Vec::new(),
+            large_vids: Vec::new(),
+            large_eids: Vec::new(),
         }
     }
 }
@@ -46,6 +57,9 @@ impl From<Triangle> for PolyhedronFace {
             eids: [1, 3, 5, 5],
             fid: 0,
             num_vertices: 3,
+            large_vertices: Vec::new(),
+            large_vids: Vec::new(),
+            large_eids: Vec::new(),
         }
     }
 }
@@ -60,6 +74,9 @@ impl From<Segment<f32>> for PolyhedronFace {
             eids: [1, 1, 1, 1],
             fid: 0,
             num_vertices: 2,
+            large_vertices: Vec::new(),
+            large_vids: Vec::new(),
+            large_eids: Vec::new(),
         }
     }
 }
@@ -72,11 +89,77 @@ impl PolyhedronFace {
             eids: [0; 4],
             fid: 0,
             num_vertices: 0,
+            large_vertices: Vec::new(),
+            large_vids: Vec::new(),
+            large_eids: Vec::new(),
+        }
+    }
+
+    /// Removes all the vertices of this face.
+    pub fn clear(&mut self) {
+        self.num_vertices = 0;
+        self.large_vertices.clear();
+        self.large_vids.clear();
+        self.large_eids.clear();
+    }
+
+    /// Adds a vertex to this face, with the feature ID of the edge starting at this vertex.
+    pub fn push(&mut self, vertex: Point<f32>, vid: u32, eid: u32) {
+        if self.num_vertices < 4 {
+            self.vertices[self.num_vertices] = vertex;
+            self.vids[self.num_vertices] = vid;
+            self.eids[self.num_vertices] = eid;
+        } else {
+            if self.num_vertices == 4 {
+                // Move the vertices to the large storage.
+                self.large_vertices.extend_from_slice(&self.vertices);
+                self.large_vids.extend_from_slice(&self.vids);
+                self.large_eids.extend_from_slice(&self.eids);
+            }
+
+            self.large_vertices.push(vertex);
+            self.large_vids.push(vid);
+            self.large_eids.push(eid);
+        }
+
+        self.num_vertices += 1;
+    }
+
+    /// The vertices of this face.
+    pub fn points(&self) -> &[Point<f32>] {
+        if self.num_vertices <= 4 {
+            &self.vertices[..self.num_vertices]
+        } else {
+            &self.large_vertices
+        }
+    }
+
+    /// The feature IDs of the vertices of this face.
+    pub fn vertex_ids(&self) -> &[u32] {
+        if self.num_vertices <= 4 {
+            &self.vids[..self.num_vertices]
+        } else {
+            &self.large_vids
+        }
+    }
+
+    /// The feature IDs of the edges of this face.
+    pub fn edge_ids(&self) -> &[u32] {
+        if self.num_vertices <= 4 {
+            &self.eids[..self.num_vertices]
+        } else {
+            &self.large_eids
         }
     }
 
     pub fn transform_by(&mut self, iso: &Isometry<f32>) {
-        for p in &mut self.vertices[0..self.num_vertices] {
+        let vertices = if self.num_vertices <= 4 {
+            &mut self.vertices[..self.num_vertices]
+        } else {
+            &mut self.large_vertices[..]
+        };
+
+        for p in vertices {
             *p = iso * *p;
         }
     }
@@ -223,64 +306,34 @@ impl PolyhedronFace {
         pos21: &Isometry<f32>,
         manifold: &mut ContactManifold,
     ) {
+        let (vertices1, vids1, eids1) = (face1.points(), face1.vertex_ids(), face1.edge_ids());
+        let (vertices2, vids2, eids2) = (face2.points(), face2.vertex_ids(), face2.edge_ids());
+
         // Project the faces to a 2D plane for contact clipping.
         // The plane they are projected onto has normal sep_axis1
         // and contains the origin (this is numerically OK because
         // we are not working in world-space here).
+        // The projections are computed on the fly because the faces
+        // may have an arbitrary number of vertices.
         let basis = sep_axis1.orthonormal_basis();
-        let projected_face1 = [
-            Point2::new(
-                face1.vertices[0].coords.dot(&basis[0]),
-                face1.vertices[0].coords.dot(&basis[1]),
-            ),
-            Point2::new(
-                face1.vertices[1].coords.dot(&basis[0]),
-                face1.vertices[1].coords.dot(&basis[1]),
-            ),
-            Point2::new(
-                face1.vertices[2].coords.dot(&basis[0]),
-                face1.vertices[2].coords.dot(&basis[1]),
-            ),
-            Point2::new(
-                face1.vertices[3].coords.dot(&basis[0]),
-                face1.vertices[3].coords.dot(&basis[1]),
-            ),
-        ];
-        let projected_face2 = [
-            Point2::new(
-                face2.vertices[0].coords.dot(&basis[0]),
-                face2.vertices[0].coords.dot(&basis[1]),
-            ),
-            Point2::new(
-                face2.vertices[1].coords.dot(&basis[0]),
-                face2.vertices[1].coords.dot(&basis[1]),
-            ),
-            Point2::new(
-                face2.vertices[2].coords.dot(&basis[0]),
-                face2.vertices[2].coords.dot(&basis[1]),
-            ),
-            Point2::new(
-                face2.vertices[3].coords.dot(&basis[0]),
-                face2.vertices[3].coords.dot(&basis[1]),
-            ),
-        ];
+        let project =
+            |p: &Point<f32>| Point2::new(p.coords.dot(&basis[0]), p.coords.dot(&basis[1]));
 
         // Also find all the vertices located inside of the other projected face.
-        if face2.num_vertices > 2 {
-            let normal2 = (face2.vertices[2] - face2.vertices[1])
-                .cross(&(face2.vertices[0] - face2.vertices[1]));
+        if vertices2.len() > 2 {
+            let normal2 = (vertices2[2] - vertices2[1]).cross(&(vertices2[0] - vertices2[1]));
             let denom = normal2.dot(&sep_axis1);
 
             if !relative_eq!(denom, 0.0) {
-                let last_index2 = face2.num_vertices as usize - 1;
-                'point_loop1: for i in 0..face1.num_vertices as usize {
-                    let p1 = projected_face1[i];
+                let last_index2 = vertices2.len() - 1;
+                'point_loop1: for i in 0..vertices1.len() {
+                    let p1 = project(&vertices1[i]);
 
-                    let sign = (projected_face2[0] - projected_face2[last_index2])
-                        .perp(&(p1 - projected_face2[last_index2]));
+                    let sign = (project(&vertices2[0]) - project(&vertices2[last_index2]))
+                        .perp(&(p1 - project(&vertices2[last_index2])));
                     for j in 0..last_index2 {
-                        let new_sign = (projected_face2[j + 1] - projected_face2[j])
-                            .perp(&(p1 - projected_face2[j]));
+                        let new_sign = (project(&vertices2[j + 1]) - project(&vertices2[j]))
+                            .perp(&(p1 - project(&vertices2[j])));
                         if new_sign * sign < 0.0 {
                             // The point lies outside.
                             continue 'point_loop1;
@@ -289,9 +342,9 @@ impl PolyhedronFace {
 
                     // All the perp had the same sign: the point is inside of the other shapes projection.
                     // Output the contact.
-                    let dist = (face2.vertices[0] - face1.vertices[i]).dot(&normal2) / denom;
-                    let local_p1 = face1.vertices[i];
-                    let local_p2 = face1.vertices[i] + dist * sep_axis1;
+                    let dist = (vertices2[0] - vertices1[i]).dot(&normal2) / denom;
+                    let local_p1 = vertices1[i];
+                    let local_p2 = vertices1[i] + dist * sep_axis1;
 
                     if dist <= prediction_distance {
                         manifold.points.push(Contact {
@@ -299,7 +352,7 @@ impl PolyhedronFace {
                             local_p2: pos21 * local_p2,
                             impulse: 0.0,
                             tangent_impulse: Contact::zero_tangent_impulse(),
-                            fid1: face1.vids[i],
+                            fid1: vids1[i],
                             fid2: face2.fid,
                             dist,
                         });
@@ -308,21 +361,20 @@ impl PolyhedronFace {
             }
         }
 
-        if face1.num_vertices > 2 {
-            let normal1 = (face1.vertices[2] - face1.vertices[1])
-                .cross(&(face1.vertices[0] - face1.vertices[1]));
+        if vertices1.len() > 2 {
+            let normal1 = (vertices1[2] - vertices1[1]).cross(&(vertices1[0] - vertices1[1]));
 
             let denom = -normal1.dot(&sep_axis1);
             if !relative_eq!(denom, 0.0) {
-                let last_index1 = face1.num_vertices as usize - 1;
-                'point_loop2: for i in 0..face2.num_vertices as usize {
-                    let p2 = projected_face2[i];
+                let last_index1 = vertices1.len() - 1;
+                'point_loop2: for i in 0..vertices2.len() {
+                    let p2 = project(&vertices2[i]);
 
-                    let sign = (projected_face1[0] - projected_face1[last_index1])
-                        .perp(&(p2 - projected_face1[last_index1]));
+                    let sign = (project(&vertices1[0]) - project(&vertices1[last_index1]))
+                        .perp(&(p2 - project(&vertices1[last_index1])));
                     for j in 0..last_index1 {
-                        let new_sign = (projected_face1[j + 1] - projected_face1[j])
-                            .perp(&(p2 - projected_face1[j]));
+                        let new_sign = (project(&vertices1[j + 1]) - project(&vertices1[j]))
+                            .perp(&(p2 - project(&vertices1[j])));
 
                         if new_sign * sign < 0.0 {
                             // The point lies outside.
@@ -332,9 +384,9 @@ impl PolyhedronFace {
 
                     // All the perp had the same sign: the point is inside of the other shapes projection.
                     // Output the contact.
-                    let dist = (face1.vertices[0] - face2.vertices[i]).dot(&normal1) / denom;
-                    let local_p2 = face2.vertices[i];
-                    let local_p1 = face2.vertices[i] - dist * sep_axis1;
+                    let dist = (vertices1[0] - vertices2[i]).dot(&normal1) / denom;
+                    let local_p2 = vertices2[i];
+                    let local_p1 = vertices2[i] - dist * sep_axis1;
 
                     if true {
                         // dist <= prediction_distance {
@@ -344,7 +396,7 @@ impl PolyhedronFace {
                             impulse: 0.0,
                             tangent_impulse: Contact::zero_tangent_impulse(),
                             fid1: face1.fid,
-                            fid2: face2.vids[i],
+                            fid2: vids2[i],
                             dist,
                         });
                     }
@@ -354,28 +406,19 @@ impl PolyhedronFace {
 
         // Now we have to compute the intersection between all pairs of
         // edges from the face 1 and from the face2.
-        for j in 0..face2.num_vertices {
-            let projected_edge2 = [
-                projected_face2[j],
-                projected_face2[(j + 1) % face2.num_vertices],
-            ];
-
-            for i in 0..face1.num_vertices {
-                let projected_edge1 = [
-                    projected_face1[i],
-                    projected_face1[(i + 1) % face1.num_vertices],
-                ];
+        for j in 0..vertices2.len() {
+            let j_next = (j + 1) % vertices2.len();
+            let projected_edge2 = [project(&vertices2[j]), project(&vertices2[j_next])];
+
+            for i in 0..vertices1.len() {
+                let i_next = (i + 1) % vertices1.len();
+                let projected_edge1 = [project(&vertices1[i]), project(&vertices1[i_next])];
+
                 if let Some(bcoords) = closest_points_line2d(projected_edge1, projected_edge2) {
                     if bcoords.0 > 0.0 && bcoords.0 < 1.0 && bcoords.1 > 0.0 && bcoords.1 < 1.0 {
                         // Found a contact between the two edges.
-                        let edge1 = (
-                            face1.vertices[i],
-                            face1.vertices[(i + 1) % face1.num_vertices],
-                        );
-                        let edge2 = (
-                            face2.vertices[j],
-                            face2.vertices[(j + 1) % face2.num_vertices],
-                        );
+                        let edge1 = (vertices1[i], vertices1[i_next]);
+                        let edge2 = (vertices2[j], vertices2[j_next]);
                         let local_p1 = edge1.0 * (1.0 - bcoords.0) + edge1.1.coords * bcoords.0;
                         let local_p2 = edge2.0 * (1.0 - bcoords.1) + edge2.1.coords * bcoords.1;
                         let dist = (local_p2 - local_p1).dot(&sep_axis1);
@@ -386,8 +429,8 @@ impl PolyhedronFace {
                                 local_p2: pos21 * local_p2,
                                 impulse: 0.0,
                                 tangent_impulse: Contact::zero_tangent_impulse(),
-                                fid1: face1.eids[i],
-                                fid2: face2.eids[j],
+                                fid1: eids1[i],
+                                fid2: eids2[j],
                                 dist,
                             });
                         }
@@ -0,0 +1,73 @@
+use crate::geometry::proximity_detector::{
+    PrimitiveProximityDetectionContext, ProximityDetectionContext,
+};
+use crate::geometry::Proximity;
+use crate::ncollide::bounding_volume::BoundingVolume;
+
+/// Detects proximities between two composite shapes.
+///
+/// Each part of the first shape is tested against the parts of the second shape
+/// that intersect its AABB.
+pub fn detect_proximity_composite_shape_composite_shape(
+    ctxt: &mut ProximityDetectionContext,
+) -> Proximity {
+    let collider1 = &ctxt.colliders[ctxt.pair.pair.collider1];
+    let collider2 = &ctxt.colliders[ctxt.pair.pair.collider2];
+    let shape1 = collider1.shape();
+    let shape2 = collider2.shape();
+    let dispatcher = ctxt.dispatcher;
+    let prediction_distance = ctxt.prediction_distance;
+
+    /*
+     * Compute interferences.
+     */
+    let pos12 = collider1.position.inverse() * collider2.position;
+    // TODO: somehow precompute the AABB and reuse it?
+    let ls_aabb2 = shape2.compute_aabb(&pos12).loosened(prediction_distance);
+
+    let mut best_proximity = Proximity::Disjoint;
+
+    let is_composite =
+        shape1.map_parts_in_local_aabb(&ls_aabb2, &mut |_, part_pos1, part_shape1| {
+            if best_proximity == Proximity::Intersecting {
+                return;
+            }
+
+            let position1 = collider1.position() * part_pos1;
+            let pos21 = collider2.position.inverse() * position1;
+            let ls_part_aabb1 = part_shape1
+                .compute_aabb(&pos21)
+                .loosened(prediction_distance);
+
+            let _ =
+                shape2.map_parts_in_local_aabb(&ls_part_aabb1, &mut |_, part_pos2, part_shape2| {
+                    if best_proximity == Proximity::Intersecting {
+                        return;
+                    }
+
+                    let (proximity_detector, mut workspace2) = dispatcher
+                        .dispatch_primitives(part_shape1.shape_type(), part_shape2.shape_type());
+                    let position2 = collider2.position() * part_pos2;
+
+                    let mut ctxt2 = PrimitiveProximityDetectionContext {
+                        prediction_distance,
+                        collider1,
+                        collider2,
+                        shape1: part_shape1,
+                        shape2: part_shape2,
+                        position1: &position1,
+                        position2: &position2,
+                        workspace: workspace2.as_mut().map(|w| &mut **w),
+                    };
+
+                    match (proximity_detector.detect_proximity)(&mut ctxt2) {
+                        Proximity::Intersecting => best_proximity = Proximity::Intersecting,
+                        Proximity::WithinMargin => best_proximity = Proximity::WithinMargin,
+                        Proximity::Disjoint => {}
+                    }
+                });
+        });
+
+    assert!(is_composite, "Invalid shape types provided.");
+    best_proximity
+}
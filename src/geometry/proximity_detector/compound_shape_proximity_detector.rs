@@ -0,0 +1,72 @@
+use crate::geometry::proximity_detector::{
+    PrimitiveProximityDetectionContext, ProximityDetectionContext,
+};
+use crate::geometry::{Collider, Compound, Proximity};
+use crate::ncollide::bounding_volume::BoundingVolume;
+
+pub fn detect_proximity_compound_shape(ctxt: &mut ProximityDetectionContext) -> Proximity {
+    let collider1 = &ctxt.colliders[ctxt.pair.pair.collider1];
+    let collider2 = &ctxt.colliders[ctxt.pair.pair.collider2];
+
+    if let Some(compound1) = collider1.shape().as_compound() {
+        do_detect_proximity(compound1, collider1, collider2, ctxt)
+    } else if let Some(compound2) = collider2.shape().as_compound() {
+        do_detect_proximity(compound2, collider2, collider1, ctxt)
+    } else {
+        panic!("Invalid shape types provided.")
+    }
+}
+
+fn do_detect_proximity(
+    compound1: &Compound,
+    collider1: &Collider,
+    collider2: &Collider,
+    ctxt: &mut ProximityDetectionContext,
+) -> Proximity {
+    /*
+     * Compute interferences.
+     */
+    let pos12 = collider1.position.inverse() * collider2.position;
+    // TODO: somehow precompute the AABB and reuse it?
+    let ls_aabb2 = collider2
+        .shape()
+        .compute_aabb(&pos12)
+        .loosened(ctxt.prediction_distance);
+    let mut interferences = Vec::new();
+    compound1
+        .quadtree()
+        .intersect_aabb(&ls_aabb2, &mut interferences);
+
+    let mut best_proximity = Proximity::Disjoint;
+    let shape_type2 = collider2.shape().shape_type();
+
+    // Because of SIMD padding, the quadtree may return part indices greater than the max.
+    for (part_pos1, part_shape1) in interferences
+        .into_iter()
+        .filter_map(|i| compound1.shapes().get(i))
+    {
+        let (proximity_detector, mut workspace2) = ctxt
+            .dispatcher
+            .dispatch_primitives(part_shape1.shape_type(), shape_type2);
+        let position1 = collider1.position() * part_pos1;
+
+        let mut ctxt2 = PrimitiveProximityDetectionContext {
+            prediction_distance: ctxt.prediction_distance,
+            collider1,
+            collider2,
+            shape1: &**part_shape1,
+            shape2: collider2.shape(),
+            position1: &position1,
+            position2: collider2.position(),
+            workspace: workspace2.as_mut().map(|w| &mut **w),
+        };
+
+        match (proximity_detector.detect_proximity)(&mut ctxt2) {
+            Proximity::Intersecting => return Proximity::Intersecting,
+            Proximity::WithinMargin => best_proximity = Proximity::WithinMargin,
+            Proximity::Disjoint => {}
+        }
+    }
+
+    best_proximity
+}
@@ -59,9 +59,6 @@ pub fn detect_proximity<'a>(
      * Edge-Edge cases.
      *
      */
-    #[cfg(feature = "dim2")]
-    let sep3 = -f32::MAX; // This case does not exist in 2D.
-    #[cfg(feature = "dim3")]
     let sep3 =
         sat::cube_triangle_find_local_separating_edge_twoway(cube1, triangle2, &pos12, &pos21).0;
     if sep3 > prediction_distance {
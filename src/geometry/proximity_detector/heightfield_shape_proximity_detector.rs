@@ -0,0 +1,67 @@
+use crate::geometry::proximity_detector::{
+    PrimitiveProximityDetectionContext, ProximityDetectionContext,
+};
+use crate::geometry::{Collider, HeightField, Proximity, Shape};
+use crate::ncollide::bounding_volume::BoundingVolume;
+
+pub fn detect_proximity_heightfield_shape(ctxt: &mut ProximityDetectionContext) -> Proximity {
+    let collider1 = &ctxt.colliders[ctxt.pair.pair.collider1];
+    let collider2 = &ctxt.colliders[ctxt.pair.pair.collider2];
+
+    if let Some(heightfield1) = collider1.shape().as_heightfield() {
+        do_detect_proximity(heightfield1, collider1, collider2, ctxt)
+    } else if let Some(heightfield2) = collider2.shape().as_heightfield() {
+        do_detect_proximity(heightfield2, collider2, collider1, ctxt)
+    } else {
+        panic!("Invalid shape types provided.")
+    }
+}
+
+fn do_detect_proximity(
+    heightfield1: &HeightField,
+    collider1: &Collider,
+    collider2: &Collider,
+    ctxt: &mut ProximityDetectionContext,
+) -> Proximity {
+    /*
+     * Compute interferences.
+     */
+    let pos12 = collider1.position.inverse() * collider2.position;
+    // TODO: somehow precompute the AABB and reuse it?
+    let ls_aabb2 = collider2
+        .shape()
+        .compute_aabb(&pos12)
+        .loosened(ctxt.prediction_distance);
+
+    let mut best_proximity = Proximity::Disjoint;
+    let shape_type2 = collider2.shape().shape_type();
+
+    heightfield1.map_elements_in_local_aabb(&ls_aabb2, &mut |_, part1, _| {
+        if best_proximity == Proximity::Intersecting {
+            return;
+        }
+
+        let (proximity_detector, mut workspace2) = ctxt
+            .dispatcher
+            .dispatch_primitives(part1.shape_type(), shape_type2);
+
+        let mut ctxt2 = PrimitiveProximityDetectionContext {
+            prediction_distance: ctxt.prediction_distance,
+            collider1,
+            collider2,
+            shape1: part1,
+            shape2: collider2.shape(),
+            position1: collider1.position(),
+            position2: collider2.position(),
+            workspace: workspace2.as_mut().map(|w| &mut **w),
+        };
+
+        match (proximity_detector.detect_proximity)(&mut ctxt2) {
+            Proximity::Intersecting => best_proximity = Proximity::Intersecting,
+            Proximity::WithinMargin => best_proximity = Proximity::WithinMargin,
+            Proximity::Disjoint => {}
+        }
+    });
+
+    best_proximity
+}
@@ -2,8 +2,13 @@ pub use self::ball_ball_proximity_detector::detect_proximity_ball_ball;
 #[cfg(feature = "simd-is-enabled")]
 pub use self::ball_ball_proximity_detector::detect_proximity_ball_ball_simd;
 pub use self::ball_convex_proximity_detector::detect_proximity_ball_convex;
+pub use self::composite_shape_composite_shape_proximity_detector::detect_proximity_composite_shape_composite_shape;
+pub use self::compound_shape_proximity_detector::detect_proximity_compound_shape;
 pub use self::cuboid_cuboid_proximity_detector::detect_proximity_cuboid_cuboid;
+#[cfg(feature = "dim3")]
 pub use self::cuboid_triangle_proximity_detector::detect_proximity_cuboid_triangle;
+pub use self::heightfield_shape_proximity_detector::detect_proximity_heightfield_shape;
+#[cfg(feature = "dim2")]
 pub use self::polygon_polygon_proximity_detector::detect_proximity_polygon_polygon;
 pub use self::proximity_detector::{
     PrimitiveProximityDetectionContext, PrimitiveProximityDetector, ProximityDetectionContext,
@@ -14,17 +19,26 @@ pub use self::proximity_detector::{
     PrimitiveProximityDetectionContextSimd, ProximityDetectionContextSimd,
 };
 pub use self::proximity_dispatcher::{DefaultProximityDispatcher, ProximityDispatcher};
+pub use self::support_map_support_map_proximity_detector::detect_proximity_support_map_support_map;
 pub use self::trimesh_shape_proximity_detector::{
     detect_proximity_trimesh_shape, TrimeshShapeProximityDetectorWorkspace,
 };
+pub use self::voxels_shape_proximity_detector::detect_proximity_voxels_shape;
 
 mod ball_ball_proximity_detector;
 mod ball_convex_proximity_detector;
 mod ball_polygon_proximity_detector;
+mod composite_shape_composite_shape_proximity_detector;
+mod compound_shape_proximity_detector;
 mod cuboid_cuboid_proximity_detector;
 mod cuboid_polygon_proximity_detector;
+#[cfg(feature = "dim3")]
 mod cuboid_triangle_proximity_detector;
+mod heightfield_shape_proximity_detector;
+#[cfg(feature = "dim2")]
 mod polygon_polygon_proximity_detector;
 mod proximity_detector;
 mod proximity_dispatcher;
+mod support_map_support_map_proximity_detector;
 mod trimesh_shape_proximity_detector;
+mod voxels_shape_proximity_detector;
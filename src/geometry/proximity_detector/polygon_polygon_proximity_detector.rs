@@ -1,57 +1,60 @@
-#![allow(dead_code)]
-
 use crate::geometry::proximity_detector::PrimitiveProximityDetectionContext;
-use crate::geometry::{sat, Polygon, Proximity};
+use crate::geometry::{sat, Polygon, Proximity, ShapePolygon};
 use crate::math::Isometry;
 
+/// Detects proximities between two 2D shapes with a polygonal representation.
+///
+/// This supports polygons, cuboids, triangles, segments, and capsules.
 pub fn detect_proximity_polygon_polygon(
-    _ctxt: &mut PrimitiveProximityDetectionContext,
+    ctxt: &mut PrimitiveProximityDetectionContext,
 ) -> Proximity {
-    unimplemented!()
-    // if let (Some(polygon1), Some(polygon2)) = (ctxt.shape1.as_polygon(), ctxt.shape2.as_polygon()) {
-    //     detect_proximity(
-    //         ctxt.prediction_distance,
-    //         polygon1,
-    //         &ctxt.position1,
-    //         polygon2,
-    //         &ctxt.position2,
-    //     )
-    // } else {
-    //     unreachable!()
-    // }
+    if let (Some((polygon1, radius1)), Some((polygon2, radius2))) = (
+        Polygon::from_shape(ctxt.shape1),
+        Polygon::from_shape(ctxt.shape2),
+    ) {
+        detect_proximity(
+            ctxt.prediction_distance,
+            &polygon1,
+            radius1,
+            &ctxt.position1,
+            &polygon2,
+            radius2,
+            &ctxt.position2,
+        )
+    } else {
+        // One of the shapes is degenerate, e.g., a segment with two identical vertices.
+        Proximity::Disjoint
+    }
 }
 
 fn detect_proximity<'a>(
     prediction_distance: f32,
-    p1: &'a Polygon,
+    p1: &'a ShapePolygon<'a>,
+    radius1: f32,
     m1: &'a Isometry<f32>,
-    p2: &'a Polygon,
+    p2: &'a ShapePolygon<'a>,
+    radius2: f32,
     m2: &'a Isometry<f32>,
 ) -> Proximity {
     let m12 = m1.inverse() * m2;
     let m21 = m12.inverse();
+    let radii = radius1 + radius2;
 
     let sep1 = sat::polygon_polygon_compute_separation_features(p1, p2, &m12);
-    if sep1.0 > prediction_distance {
+    if sep1.0 > prediction_distance + radii {
         return Proximity::Disjoint;
     }
 
     let sep2 = sat::polygon_polygon_compute_separation_features(p2, p1, &m21);
-    if sep2.0 > prediction_distance {
+    if sep2.0 > prediction_distance + radii {
         return Proximity::Disjoint;
     }
 
-    if sep2.0 > sep1.0 {
-        if sep2.0 > 0.0 {
-            Proximity::WithinMargin
-        } else {
-            Proximity::Intersecting
-        }
+    // NOTE: with rounded shapes, the separation along the face normals is not exact
+    // near the vertices, so this may report a proximity slightly too early.
+    if sep1.0.max(sep2.0) > radii {
+        Proximity::WithinMargin
     } else {
-        if sep1.0 > 0.0 {
-            Proximity::WithinMargin
-        } else {
-            Proximity::Intersecting
-        }
+        Proximity::Intersecting
     }
 }
@@ -7,13 +7,17 @@ use crate::math::{SimdFloat, SIMD_WIDTH};
 use crate::pipeline::EventHandler;
 use std::any::Any;
 
+/// The proximity detection algorithm selected by a `ProximityDispatcher` for a pair of colliders.
 #[derive(Copy, Clone)]
 pub enum ProximityPhase {
+    /// A proximity detector working on the whole proximity pair, e.g., for composite shapes.
     NearPhase(ProximityDetector),
+    /// A proximity detector working on two primitive shapes.
     ExactPhase(PrimitiveProximityDetector),
 }
 
 impl ProximityPhase {
+    /// Runs the proximity detector and emits a proximity event if the proximity status changed.
     #[inline]
     pub fn detect_proximity(
         self,
@@ -54,6 +58,7 @@ impl ProximityPhase {
         context.pair.proximity = proximity;
     }
 
+    /// Runs the proximity detector on `SIMD_WIDTH` pairs at once.
     #[cfg(feature = "simd-is-enabled")]
     #[inline]
     pub fn detect_proximity_simd(
@@ -116,32 +121,55 @@ impl ProximityPhase {
     }
 }
 
+/// The data available to a primitive proximity detector.
 pub struct PrimitiveProximityDetectionContext<'a> {
+    /// The distance below which the shapes are considered `WithinMargin`.
     pub prediction_distance: f32,
+    /// The first collider involved in the proximity.
     pub collider1: &'a Collider,
+    /// The second collider involved in the proximity.
     pub collider2: &'a Collider,
+    /// The shape of the first collider, or a sub-shape of it.
     pub shape1: &'a dyn Shape,
+    /// The shape of the second collider, or a sub-shape of it.
     pub shape2: &'a dyn Shape,
+    /// The world-space position of `shape1`.
     pub position1: &'a Isometry<f32>,
+    /// The world-space position of `shape2`.
     pub position2: &'a Isometry<f32>,
+    /// The workspace returned by the dispatcher alongside this detector, if any.
     pub workspace: Option<&'a mut (dyn Any + Send + Sync)>,
 }
 
 #[cfg(feature = "simd-is-enabled")]
+/// The data available to a primitive proximity detector working on `SIMD_WIDTH` pairs at once.
 pub struct PrimitiveProximityDetectionContextSimd<'a, 'b> {
+    /// The distance below which the shapes are considered `WithinMargin`.
     pub prediction_distance: f32,
+    /// The first collider of each pair.
     pub colliders1: [&'a Collider; SIMD_WIDTH],
+    /// The second collider of each pair.
     pub colliders2: [&'a Collider; SIMD_WIDTH],
+    /// The first shape of each pair.
     pub shapes1: [&'a dyn Shape; SIMD_WIDTH],
+    /// The second shape of each pair.
     pub shapes2: [&'a dyn Shape; SIMD_WIDTH],
+    /// The world-space positions of `shapes1`.
     pub positions1: &'a Isometry<SimdFloat>,
+    /// The world-space positions of `shapes2`.
     pub positions2: &'a Isometry<SimdFloat>,
+    /// The workspace of each pair, if any.
     pub workspaces: &'a mut [Option<&'b mut (dyn Any + Send + Sync)>],
 }
 
+/// A proximity detector for a pair of primitive shapes.
+///
+/// Unset functions default to a detector that always returns `Proximity::Disjoint`.
 #[derive(Copy, Clone)]
 pub struct PrimitiveProximityDetector {
+    /// Detects the proximity of a single pair of shapes.
     pub detect_proximity: fn(&mut PrimitiveProximityDetectionContext) -> Proximity,
+    /// Detects the proximity of `SIMD_WIDTH` pairs of shapes at once.
     #[cfg(feature = "simd-is-enabled")]
     pub detect_proximity_simd:
         fn(&mut PrimitiveProximityDetectionContextSimd) -> [Proximity; SIMD_WIDTH],
@@ -169,24 +197,39 @@ impl Default for PrimitiveProximityDetector {
     }
 }
 
+/// The data available to a proximity detector.
 pub struct ProximityDetectionContext<'a> {
+    /// The dispatcher to use for selecting algorithms for sub-shapes.
     pub dispatcher: &'a dyn ProximityDispatcher,
+    /// The distance below which the shapes are considered `WithinMargin`.
     pub prediction_distance: f32,
+    /// The set of all colliders.
     pub colliders: &'a ColliderSet,
+    /// The proximity pair to update.
     pub pair: &'a mut ProximityPair,
 }
 
 #[cfg(feature = "simd-is-enabled")]
+/// The data available to a proximity detector working on `SIMD_WIDTH` pairs at once.
 pub struct ProximityDetectionContextSimd<'a, 'b> {
+    /// The dispatcher to use for selecting algorithms for sub-shapes.
     pub dispatcher: &'a dyn ProximityDispatcher,
+    /// The distance below which the shapes are considered `WithinMargin`.
     pub prediction_distance: f32,
+    /// The set of all colliders.
     pub colliders: &'a ColliderSet,
+    /// The proximity pairs to update.
     pub pairs: &'a mut [&'b mut ProximityPair],
 }
 
+/// A proximity detector for a pair of colliders, at least one of which is not a primitive shape.
+///
+/// Unset functions default to a detector that always returns `Proximity::Disjoint`.
 #[derive(Copy, Clone)]
 pub struct ProximityDetector {
+    /// Detects the proximity of a single proximity pair.
     pub detect_proximity: fn(&mut ProximityDetectionContext) -> Proximity,
+    /// Detects the proximity of `SIMD_WIDTH` proximity pairs at once.
     #[cfg(feature = "simd-is-enabled")]
     pub detect_proximity_simd: fn(&mut ProximityDetectionContextSimd) -> [Proximity; SIMD_WIDTH],
 }
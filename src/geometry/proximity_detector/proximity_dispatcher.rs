@@ -7,7 +7,7 @@ use std::any::Any;
 
 /// Trait implemented by structures responsible for selecting a collision-detection algorithm
 /// for a given pair of shapes.
-pub trait ProximityDispatcher {
+pub trait ProximityDispatcher: Send + Sync {
     /// Select the proximity detection algorithm for the given pair of primitive shapes.
     fn dispatch_primitives(
         &self,
@@ -54,13 +54,6 @@ impl ProximityDispatcher for DefaultProximityDispatcher {
                 },
                 None,
             ),
-            (ShapeType::Polygon, ShapeType::Polygon) => (
-                PrimitiveProximityDetector {
-                    detect_proximity: super::detect_proximity_polygon_polygon,
-                    ..PrimitiveProximityDetector::default()
-                },
-                None,
-            ),
             (ShapeType::Triangle, ShapeType::Ball) => (
                 PrimitiveProximityDetector {
                     detect_proximity: super::detect_proximity_ball_convex,
@@ -89,6 +82,7 @@ impl ProximityDispatcher for DefaultProximityDispatcher {
                 },
                 None,
             ),
+            #[cfg(feature = "dim3")]
             (ShapeType::Triangle, ShapeType::Cuboid) => (
                 PrimitiveProximityDetector {
                     detect_proximity: super::detect_proximity_cuboid_triangle,
@@ -96,6 +90,7 @@ impl ProximityDispatcher for DefaultProximityDispatcher {
                 },
                 None,
             ),
+            #[cfg(feature = "dim3")]
             (ShapeType::Cuboid, ShapeType::Triangle) => (
                 PrimitiveProximityDetector {
                     detect_proximity: super::detect_proximity_cuboid_triangle,
@@ -103,7 +98,39 @@ impl ProximityDispatcher for DefaultProximityDispatcher {
                 },
                 None,
             ),
-            _ => (PrimitiveProximityDetector::default(), None),
+            #[cfg(feature = "dim2")]
+            (ShapeType::Polygon, ShapeType::Ball) | (ShapeType::Ball, ShapeType::Polygon) => (
+                PrimitiveProximityDetector {
+                    detect_proximity: super::detect_proximity_ball_convex,
+                    ..PrimitiveProximityDetector::default()
+                },
+                None,
+            ),
+            #[cfg(feature = "dim2")]
+            (ShapeType::Polygon, _)
+            | (_, ShapeType::Polygon)
+            | (ShapeType::Triangle, ShapeType::Cuboid)
+            | (ShapeType::Cuboid, ShapeType::Triangle) => (
+                PrimitiveProximityDetector {
+                    detect_proximity: super::detect_proximity_polygon_polygon,
+                    ..PrimitiveProximityDetector::default()
+                },
+                None,
+            ),
+            (ShapeType::Ball, _) | (_, ShapeType::Ball) => (
+                PrimitiveProximityDetector {
+                    detect_proximity: super::detect_proximity_ball_convex,
+                    ..PrimitiveProximityDetector::default()
+                },
+                None,
+            ),
+            _ => (
+                PrimitiveProximityDetector {
+                    detect_proximity: super::detect_proximity_support_map_support_map,
+                    ..PrimitiveProximityDetector::default()
+                },
+                None,
+            ),
         }
     }
 
@@ -113,6 +140,13 @@ impl ProximityDispatcher for DefaultProximityDispatcher {
         shape2: ShapeType,
     ) -> (ProximityPhase, Option<Box<dyn Any + Send + Sync>>) {
         match (shape1, shape2) {
+            (_, _) if shape1.is_composite() && shape2.is_composite() => (
+                ProximityPhase::NearPhase(ProximityDetector {
+                    detect_proximity: super::detect_proximity_composite_shape_composite_shape,
+                    ..ProximityDetector::default()
+                }),
+                None,
+            ),
             (ShapeType::Trimesh, _) => (
                 ProximityPhase::NearPhase(ProximityDetector {
                     detect_proximity: super::detect_proximity_trimesh_shape,
@@ -127,6 +161,27 @@ impl ProximityDispatcher for DefaultProximityDispatcher {
                 }),
                 Some(Box::new(TrimeshShapeProximityDetectorWorkspace::new())),
             ),
+            (ShapeType::Compound, _) | (_, ShapeType::Compound) => (
+                ProximityPhase::NearPhase(ProximityDetector {
+                    detect_proximity: super::detect_proximity_compound_shape,
+                    ..ProximityDetector::default()
+                }),
+                None,
+            ),
+            (ShapeType::Voxels, _) | (_, ShapeType::Voxels) => (
+                ProximityPhase::NearPhase(ProximityDetector {
+                    detect_proximity: super::detect_proximity_voxels_shape,
+                    ..ProximityDetector::default()
+                }),
+                None,
+            ),
+            (ShapeType::HeightField, _) | (_, ShapeType::HeightField) => (
+                ProximityPhase::NearPhase(ProximityDetector {
+                    detect_proximity: super::detect_proximity_heightfield_shape,
+                    ..ProximityDetector::default()
+                }),
+                None,
+            ),
             _ => {
                 let (gen, workspace) = self.dispatch_primitives(shape1, shape2);
                 (ProximityPhase::ExactPhase(gen), workspace)
@@ -134,3 +189,228 @@ impl ProximityDispatcher for DefaultProximityDispatcher {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::dynamics::RigidBodyBuilder;
+    use crate::geometry::proximity_detector::{
+        detect_proximity_support_map_support_map, PrimitiveProximityDetectionContext,
+    };
+    use crate::geometry::{ColliderBuilder, ColliderShape, Proximity};
+    use crate::math::{Isometry, Point, Translation, Vector};
+    use crate::pipeline::test_world::TestWorld;
+    use na::Point3;
+    use std::collections::HashMap;
+
+    #[test]
+    fn support_map_detector_ignores_other_shapes() {
+        let co1 = ColliderBuilder::ball(0.5).build();
+        let co2 = ColliderBuilder::new(ColliderShape::compound(vec![(
+            Isometry::identity(),
+            ColliderShape::ball(0.5),
+        )]))
+        .build();
+        let pos = Isometry::identity();
+        let mut ctxt = PrimitiveProximityDetectionContext {
+            prediction_distance: 0.1,
+            collider1: &co1,
+            collider2: &co2,
+            shape1: co1.shape(),
+            shape2: co2.shape(),
+            position1: &pos,
+            position2: &pos,
+            workspace: None,
+        };
+
+        assert_eq!(
+            detect_proximity_support_map_support_map(&mut ctxt),
+            Proximity::Disjoint
+        );
+    }
+
+    #[test]
+    fn proximity_between_all_shape_types() {
+        // Every shape fits in a ball of radius 0.5 centered at the origin. The thin
+        // shapes (without volume) go through the origin and the others contain it.
+        let corners: Vec<_> = (0..1 << crate::math::DIM)
+            .map(|i| {
+                Point::from(Vector::from_fn(|k, _| {
+                    if i & (1 << k) != 0 {
+                        0.28
+                    } else {
+                        -0.28
+                    }
+                }))
+            })
+            .collect();
+        let voxels: Vec<_> = (0..1 << crate::math::DIM)
+            .map(|i| Point::from(Vector::from_fn(|k, _| -((i >> k) & 1))))
+            .collect();
+        let compound = ColliderShape::compound(vec![
+            (
+                Isometry::identity(),
+                ColliderShape::cuboid(Vector::repeat(0.2)),
+            ),
+            (
+                Isometry::new(Vector::x() * 0.3, na::zero()),
+                ColliderShape::ball(0.1),
+            ),
+        ]);
+
+        #[cfg(feature = "dim2")]
+        let shapes = vec![
+            (ColliderShape::ball(0.5), false),
+            (ColliderShape::convex_hull(&corners).unwrap(), false),
+            (ColliderShape::cuboid(Vector::repeat(0.28)), false),
+            (
+                ColliderShape::capsule(Point::new(0.0, -0.25), Point::new(0.0, 0.25), 0.2),
+                false,
+            ),
+            (
+                ColliderShape::segment(Point::new(-0.45, 0.0), Point::new(0.45, 0.0)),
+                true,
+            ),
+            (
+                ColliderShape::triangle(
+                    Point::new(-0.4, -0.2),
+                    Point::new(0.4, -0.2),
+                    Point::new(0.0, 0.4),
+                ),
+                true,
+            ),
+            (
+                ColliderShape::trimesh(
+                    corners.clone(),
+                    vec![Point3::new(0, 1, 3), Point3::new(0, 3, 2)],
+                ),
+                true,
+            ),
+            (
+                ColliderShape::heightfield(na::DVector::zeros(2), Vector::new(0.9, 1.0)),
+                true,
+            ),
+            (compound, false),
+            (ColliderShape::voxels(Vector::repeat(0.28), &voxels), false),
+        ];
+        #[cfg(feature = "dim3")]
+        let shapes = vec![
+            (ColliderShape::ball(0.5), false),
+            (ColliderShape::cuboid(Vector::repeat(0.28)), false),
+            (
+                ColliderShape::capsule(
+                    Point::new(0.0, -0.25, 0.0),
+                    Point::new(0.0, 0.25, 0.0),
+                    0.2,
+                ),
+                false,
+            ),
+            (
+                ColliderShape::segment(Point::new(-0.45, 0.0, 0.0), Point::new(0.45, 0.0, 0.0)),
+                true,
+            ),
+            (
+                ColliderShape::triangle(
+                    Point::new(-0.4, -0.2, 0.0),
+                    Point::new(0.4, -0.2, 0.0),
+                    Point::new(0.0, 0.4, 0.0),
+                ),
+                true,
+            ),
+            (
+                ColliderShape::trimesh(
+                    vec![
+                        Point::new(-0.35, 0.0, -0.35),
+                        Point::new(0.35, 0.0, -0.35),
+                        Point::new(0.35, 0.0, 0.35),
+                        Point::new(-0.35, 0.0, 0.35),
+                    ],
+                    vec![Point3::new(0, 2, 1), Point3::new(0, 3, 2)],
+                ),
+                true,
+            ),
+            (
+                ColliderShape::heightfield(na::DMatrix::zeros(2, 2), Vector::new(0.7, 1.0, 0.7)),
+                true,
+            ),
+            (ColliderShape::cylinder(0.3, 0.3), false),
+            (ColliderShape::cone(0.3, 0.3), false),
+            (ColliderShape::round_cylinder(0.25, 0.25, 0.1), false),
+            (ColliderShape::convex_hull(&corners).unwrap(), false),
+            (compound, false),
+            (ColliderShape::voxels(Vector::repeat(0.28), &voxels), false),
+        ];
+
+        #[cfg(feature = "dim2")]
+        let rotation = 0.7;
+        #[cfg(feature = "dim3")]
+        let rotation = Vector::new(0.3, 0.5, 0.7);
+
+        let mut world = TestWorld::new();
+        let mut expected = Vec::new();
+
+        for (i, (shape1, thin1)) in shapes.iter().enumerate() {
+            for (j, (shape2, thin2)) in shapes.iter().enumerate() {
+                // The second shape is either rotated around the origin of the first one, or
+                // moved far enough for them to be disjoint while their AABBs still overlap.
+                for &separated in &[false, true] {
+                    let mut origin = Vector::zeros();
+                    origin.x = (i * 2 + separated as usize) as f32 * 5.0;
+                    origin.y = j as f32 * 5.0;
+                    let shift = if separated {
+                        Vector::repeat(0.75)
+                    } else {
+                        Vector::zeros()
+                    };
+
+                    let rb = RigidBodyBuilder::new_static()
+                        .position(Translation::from(origin).into())
+                        .build();
+                    let body1 = world.insert(rb, None);
+                    let co = ColliderBuilder::new(shape1.clone()).build();
+                    let handle1 = world.insert_collider(co, body1);
+
+                    let rb = RigidBodyBuilder::new_dynamic()
+                        .position(Isometry::new(origin + shift, rotation))
+                        .build();
+                    let body2 = world.insert(rb, None);
+                    let co = ColliderBuilder::new(shape2.clone()).sensor(true).build();
+                    let handle2 = world.insert_collider(co, body2);
+
+                    expected.push((handle1, handle2, i, j, separated, *thin1 && *thin2));
+                }
+            }
+        }
+
+        world.step();
+
+        let proximities: HashMap<_, _> = world
+            .narrow_phase
+            .proximity_graph()
+            .interaction_pairs()
+            .map(|(h1, h2, pair)| ((h1.min(h2), h1.max(h2)), pair.proximity))
+            .collect();
+
+        for (handle1, handle2, i, j, separated, both_thin) in expected {
+            let proximity = proximities
+                .get(&(handle1.min(handle2), handle1.max(handle2)))
+                .copied()
+                .unwrap_or(Proximity::Disjoint);
+
+            // Two shapes without volume only touch each other.
+            let ok = if separated {
+                proximity == Proximity::Disjoint
+            } else if both_thin {
+                proximity != Proximity::Disjoint
+            } else {
+                proximity == Proximity::Intersecting
+            };
+            assert!(
+                ok,
+                "{:?} vs. {:?}: unexpected {:?}",
+                shapes[i].0.shape_type(),
+                shapes[j].0.shape_type(),
+                proximity
+            );
+        }
+    }
+}
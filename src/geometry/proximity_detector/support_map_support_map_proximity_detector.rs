@@ -0,0 +1,23 @@
+use crate::geometry::proximity_detector::PrimitiveProximityDetectionContext;
+use crate::geometry::Proximity;
+use ncollide::query;
+
+/// Detects proximities between two convex shapes using the GJK algorithm.
+///
+/// This supports any pair of shapes implementing the support-map trait. Other shapes are
+/// always reported as disjoint.
+pub fn detect_proximity_support_map_support_map(
+    ctxt: &mut PrimitiveProximityDetectionContext,
+) -> Proximity {
+    if let (Some(sm1), Some(sm2)) = (ctxt.shape1.as_support_map(), ctxt.shape2.as_support_map()) {
+        query::proximity_support_map_support_map(
+            ctxt.position1,
+            sm1,
+            ctxt.position2,
+            sm2,
+            ctxt.prediction_distance,
+        )
+    } else {
+        Proximity::Disjoint
+    }
+}
@@ -0,0 +1,68 @@
+use crate::geometry::proximity_detector::{
+    PrimitiveProximityDetectionContext, ProximityDetectionContext,
+};
+use crate::geometry::{Collider, Proximity, Shape, Voxels};
+use crate::math::Isometry;
+use crate::ncollide::bounding_volume::BoundingVolume;
+
+pub fn detect_proximity_voxels_shape(ctxt: &mut ProximityDetectionContext) -> Proximity {
+    let collider1 = &ctxt.colliders[ctxt.pair.pair.collider1];
+    let collider2 = &ctxt.colliders[ctxt.pair.pair.collider2];
+
+    if let Some(voxels1) = collider1.shape().as_voxels() {
+        do_detect_proximity(voxels1, collider1, collider2, ctxt)
+    } else if let Some(voxels2) = collider2.shape().as_voxels() {
+        do_detect_proximity(voxels2, collider2, collider1, ctxt)
+    } else {
+        panic!("Invalid shape types provided.")
+    }
+}
+
+fn do_detect_proximity(
+    voxels1: &Voxels,
+    collider1: &Collider,
+    collider2: &Collider,
+    ctxt: &mut ProximityDetectionContext,
+) -> Proximity {
+    /*
+     * Compute interferences.
+     */
+    let pos12 = collider1.position.inverse() * collider2.position;
+    // TODO: somehow precompute the AABB and reuse it?
+    let ls_aabb2 = collider2
+        .shape()
+        .compute_aabb(&pos12)
+        .loosened(ctxt.prediction_distance);
+    let mut interferences = Vec::new();
+    voxels1.voxels_intersecting_local_aabb(&ls_aabb2, &mut interferences);
+
+    let mut best_proximity = Proximity::Disjoint;
+    let voxel_shape1 = voxels1.voxel_shape();
+    let (proximity_detector, mut workspace2) = ctxt
+        .dispatcher
+        .dispatch_primitives(voxel_shape1.shape_type(), collider2.shape().shape_type());
+
+    for (_, voxel) in interferences {
+        let voxel_pos1 = Isometry::new(voxels1.voxel_center(&voxel).coords, na::zero());
+        let position1 = collider1.position() * voxel_pos1;
+
+        let mut ctxt2 = PrimitiveProximityDetectionContext {
+            prediction_distance: ctxt.prediction_distance,
+            collider1,
+            collider2,
+            shape1: &voxel_shape1,
+            shape2: collider2.shape(),
+            position1: &position1,
+            position2: collider2.position(),
+            workspace: workspace2.as_mut().map(|w| &mut **w),
+        };
+
+        match (proximity_detector.detect_proximity)(&mut ctxt2) {
+            Proximity::Intersecting => return Proximity::Intersecting,
+            Proximity::WithinMargin => best_proximity = Proximity::WithinMargin,
+            Proximity::Disjoint => {}
+        }
+    }
+
+    best_proximity
+}
@@ -0,0 +1,337 @@
+//! Geometric queries between two shapes.
+
+use crate::geometry::{Shape, AABB};
+use crate::math::{Isometry, Vector};
+use ncollide::bounding_volume::BoundingVolume;
+use ncollide::interpolation::RigidMotion;
+use ncollide::query;
+
+/// The result of a time-of-impact computation between two shapes.
+pub type TOI = ncollide::query::TOI<f32>;
+/// The contact between two shapes, with points expressed in world-space.
+pub(crate) type ShapeContact = ncollide::query::Contact<f32>;
+
+// The motion of a shape relative to the local frame of another moving shape.
+struct RelativeMotion<'a> {
+    motion1: &'a dyn RigidMotion<f32>,
+    motion2: &'a dyn RigidMotion<f32>,
+}
+
+impl<'a> RigidMotion<f32> for RelativeMotion<'a> {
+    fn position_at_time(&self, t: f32) -> Isometry<f32> {
+        self.motion1.position_at_time(t).inverse() * self.motion2.position_at_time(t)
+    }
+}
+
+// The radius of the smallest ball centered at the shape's local origin and containing the shape.
+fn local_bounding_radius(shape: &dyn Shape) -> f32 {
+    let aabb = shape.compute_aabb(&Isometry::identity());
+    aabb.mins.coords.abs().sup(&aabb.maxs.coords.abs()).norm()
+}
+
+// Expresses the witness point and normal of the first shape in the local-space of its parent.
+fn transform_toi1(toi: TOI, pos1: &Isometry<f32>) -> TOI {
+    TOI {
+        toi: toi.toi,
+        witness1: pos1 * toi.witness1,
+        witness2: toi.witness2,
+        normal1: pos1 * toi.normal1,
+        normal2: toi.normal2,
+        status: toi.status,
+    }
+}
+
+fn flip_toi(toi: TOI) -> TOI {
+    TOI {
+        toi: toi.toi,
+        witness1: toi.witness2,
+        witness2: toi.witness1,
+        normal1: toi.normal2,
+        normal2: toi.normal1,
+        status: toi.status,
+    }
+}
+
+fn flip_contact(contact: ShapeContact) -> ShapeContact {
+    ShapeContact::new(
+        contact.world2,
+        contact.world1,
+        -contact.normal,
+        contact.depth,
+    )
+}
+
+fn keep_earliest(result: &mut Option<TOI>, candidate: Option<TOI>) {
+    if let Some(candidate) = candidate {
+        if result
+            .as_ref()
+            .map(|r| candidate.toi < r.toi)
+            .unwrap_or(true)
+        {
+            *result = Some(candidate)
+        }
+    }
+}
+
+/// Computes the smallest time at which two shapes under translational movement are separated by a
+/// distance smaller or equal to `target_distance`.
+///
+/// Returns `None` if the shapes do not reach `target_distance` before `max_toi`, or if the
+/// pair of shapes is not supported. The witness points and normals of the returned `TOI` are
+/// expressed in the local-space of their respective shape.
+pub fn time_of_impact(
+    pos1: &Isometry<f32>,
+    vel1: &Vector<f32>,
+    shape1: &dyn Shape,
+    pos2: &Isometry<f32>,
+    vel2: &Vector<f32>,
+    shape2: &dyn Shape,
+    max_toi: f32,
+    target_distance: f32,
+) -> Option<TOI> {
+    if let (Some(sm1), Some(sm2)) = (shape1.as_support_map(), shape2.as_support_map()) {
+        return query::time_of_impact_support_map_support_map(
+            pos1,
+            vel1,
+            sm1,
+            pos2,
+            vel2,
+            sm2,
+            max_toi,
+            target_distance,
+        );
+    }
+
+    if shape1.is_composite() {
+        // Express the problem in the local-space of the composite shape.
+        let pos12 = pos1.inv_mul(pos2);
+        let vel12 = pos1.inverse_transform_vector(&(vel2 - vel1));
+        let start_aabb = shape2.compute_aabb(&pos12);
+        let mut end_aabb = start_aabb;
+        end_aabb.mins += vel12 * max_toi;
+        end_aabb.maxs += vel12 * max_toi;
+        let swept_aabb = start_aabb.merged(&end_aabb).loosened(target_distance);
+
+        let mut result = None;
+        let _ = shape1.map_parts_in_local_aabb(&swept_aabb, &mut |_, part_pos, part| {
+            let toi = time_of_impact(
+                part_pos,
+                &Vector::zeros(),
+                part,
+                &pos12,
+                &vel12,
+                shape2,
+                max_toi,
+                target_distance,
+            );
+            keep_earliest(&mut result, toi.map(|toi| transform_toi1(toi, part_pos)));
+        });
+
+        return result;
+    }
+
+    if shape2.is_composite() {
+        return time_of_impact(
+            pos2,
+            vel2,
+            shape2,
+            pos1,
+            vel1,
+            shape1,
+            max_toi,
+            target_distance,
+        )
+        .map(flip_toi);
+    }
+
+    None
+}
+
+/// Computes the smallest time at which two shapes under a rigid motion (translation and rotation)
+/// are separated by a distance smaller or equal to `target_distance`.
+///
+/// Returns `None` if the shapes do not reach `target_distance` before `max_toi`, or if the
+/// pair of shapes is not supported. The witness points and normals of the returned `TOI` are
+/// expressed in the local-space of their respective shape.
+///
+/// If one of the shapes is a composite shape (triangle mesh, heightfield, compound, or voxels), the
+/// parts it is tested against are selected from the start and end poses of the relative motion.
+pub fn nonlinear_time_of_impact(
+    motion1: &dyn RigidMotion<f32>,
+    shape1: &dyn Shape,
+    motion2: &dyn RigidMotion<f32>,
+    shape2: &dyn Shape,
+    max_toi: f32,
+    target_distance: f32,
+) -> Option<TOI> {
+    if let (Some(sm1), Some(sm2)) = (shape1.as_support_map(), shape2.as_support_map()) {
+        return query::nonlinear_time_of_impact_support_map_support_map(
+            motion1,
+            sm1,
+            motion2,
+            sm2,
+            max_toi,
+            target_distance,
+        );
+    }
+
+    if shape1.is_composite() {
+        let motion12 = RelativeMotion { motion1, motion2 };
+        let start = motion12.position_at_time(0.0);
+        let end = motion12.position_at_time(max_toi);
+        // Any point of the shape stays at a distance smaller than this radius
+        // from the interpolated origin of the shape.
+        let radius = local_bounding_radius(shape2) + target_distance;
+        let start_aabb =
+            AABB::from_half_extents(start.translation.vector.into(), Vector::repeat(radius));
+        let end_aabb =
+            AABB::from_half_extents(end.translation.vector.into(), Vector::repeat(radius));
+        let swept_aabb = start_aabb.merged(&end_aabb);
+
+        let mut result = None;
+        let _ = shape1.map_parts_in_local_aabb(&swept_aabb, &mut |_, part_pos, part| {
+            let toi = nonlinear_time_of_impact(
+                part_pos,
+                part,
+                &motion12,
+                shape2,
+                max_toi,
+                target_distance,
+            );
+            keep_earliest(&mut result, toi.map(|toi| transform_toi1(toi, part_pos)));
+        });
+
+        return result;
+    }
+
+    if shape2.is_composite() {
+        return nonlinear_time_of_impact(
+            motion2,
+            shape2,
+            motion1,
+            shape1,
+            max_toi,
+            target_distance,
+        )
+        .map(flip_toi);
+    }
+
+    None
+}
+
+/// Computes the deepest contact between two shapes, if they are closer than `prediction`.
+///
+/// Returns `None` if the shapes are too far apart, or if the pair of shapes is not supported.
+pub(crate) fn contact(
+    pos1: &Isometry<f32>,
+    shape1: &dyn Shape,
+    pos2: &Isometry<f32>,
+    shape2: &dyn Shape,
+    prediction: f32,
+) -> Option<ShapeContact> {
+    if let (Some(sm1), Some(sm2)) = (shape1.as_support_map(), shape2.as_support_map()) {
+        return query::contact_support_map_support_map(pos1, sm1, pos2, sm2, prediction);
+    }
+
+    if shape1.is_composite() {
+        let pos12 = pos1.inv_mul(pos2);
+        let aabb2 = shape2.compute_aabb(&pos12).loosened(prediction);
+        let mut result: Option<ShapeContact> = None;
+        let _ = shape1.map_parts_in_local_aabb(&aabb2, &mut |_, part_pos, part| {
+            if let Some(c) = contact(part_pos, part, &pos12, shape2, prediction) {
+                if result.as_ref().map(|r| c.depth > r.depth).unwrap_or(true) {
+                    result = Some(c)
+                }
+            }
+        });
+
+        return result.map(|c| {
+            ShapeContact::new(pos1 * c.world1, pos1 * c.world2, pos1 * c.normal, c.depth)
+        });
+    }
+
+    if shape2.is_composite() {
+        return contact(pos2, shape2, pos1, shape1, prediction).map(flip_contact);
+    }
+
+    None
+}
+
+/// Tests whether two shapes are intersecting.
+///
+/// Returns `false` if the pair of shapes is not supported.
+pub fn intersection_test(
+    pos1: &Isometry<f32>,
+    shape1: &dyn Shape,
+    pos2: &Isometry<f32>,
+    shape2: &dyn Shape,
+) -> bool {
+    contact(pos1, shape1, pos2, shape2, 0.0).is_some()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::geometry::ColliderShape;
+    use crate::math::{Isometry, Point, Vector};
+
+    #[test]
+    fn queries_against_compound_and_voxels() {
+        let cube = ColliderShape::cuboid(Vector::repeat(0.5));
+        let shapes = vec![
+            ColliderShape::compound(vec![
+                (Isometry::new(Vector::repeat(0.5), na::zero()), cube.clone()),
+                (
+                    Isometry::new(Vector::repeat(0.5) + Vector::y(), na::zero()),
+                    cube,
+                ),
+            ]),
+            ColliderShape::voxels(Vector::repeat(1.0), &[Point::origin()]),
+        ];
+        let ball = ColliderShape::ball(0.5);
+
+        for shape in shapes {
+            let shape = &*shape.0;
+            let pos1 = Isometry::identity();
+            let pos2 = Isometry::new(Vector::x() * 5.0, na::zero());
+            let vel2 = -Vector::x();
+
+            for flip in &[false, true] {
+                let toi = if *flip {
+                    super::time_of_impact(
+                        &pos2,
+                        &vel2,
+                        &*ball.0,
+                        &pos1,
+                        &Vector::zeros(),
+                        shape,
+                        10.0,
+                        0.0,
+                    )
+                } else {
+                    super::time_of_impact(
+                        &pos1,
+                        &Vector::zeros(),
+                        shape,
+                        &pos2,
+                        &vel2,
+                        &*ball.0,
+                        10.0,
+                        0.0,
+                    )
+                };
+                assert!(toi.is_some());
+                let toi = toi.unwrap();
+                // Both shapes extend up to `x = 1.0`.
+                assert!(toi.toi > 3.4 && toi.toi < 3.6);
+
+                let hit = pos2.translation.vector + vel2 * (toi.toi + 0.1);
+                assert!(super::intersection_test(
+                    &pos1,
+                    shape,
+                    &Isometry::new(hit, na::zero()),
+                    &*ball.0
+                ));
+            }
+        }
+    }
+}
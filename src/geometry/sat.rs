@@ -1,4 +1,6 @@
-use crate::geometry::{cuboid, Cuboid, Polygon, Segment, Triangle};
+#[cfg(feature = "dim2")]
+use crate::geometry::ShapePolygon;
+use crate::geometry::{cuboid, Cuboid, Segment, Triangle};
 use crate::math::{Isometry, Point, Vector, DIM};
 use crate::utils::WSign;
 use na::Unit;
@@ -16,18 +18,18 @@ pub fn support_map_support_map_compute_separation(
     (p2 - p1).dot(dir1)
 }
 
-#[allow(dead_code)]
+#[cfg(feature = "dim2")]
 pub fn polygon_polygon_compute_separation_features(
-    p1: &Polygon,
-    p2: &Polygon,
+    p1: &ShapePolygon,
+    p2: &ShapePolygon,
     m12: &Isometry<f32>,
 ) -> (f32, usize, usize) {
     let mut max_separation = -f32::MAX;
     let mut separation_features = (0, 0);
 
-    for (i, (p1, n1)) in p1.vertices.iter().zip(p1.normals.iter()).enumerate() {
+    for (i, (p1, n1)) in p1.vertices().iter().zip(p1.normals().iter()).enumerate() {
         let j = p2.support_point(&m12.inverse_transform_vector(&-n1));
-        let dpt = m12 * p2.vertices[j] - p1;
+        let dpt = m12 * p2.vertices()[j] - p1;
         let separation = dpt.dot(n1);
 
         if separation > max_separation {
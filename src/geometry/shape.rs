@@ -1,16 +1,21 @@
 use crate::dynamics::MassProperties;
-use crate::geometry::{Ball, Capsule, Cuboid, HeightField, Segment, Triangle, Trimesh};
+#[cfg(feature = "dim2")]
+use crate::geometry::Polygon;
+use crate::geometry::{
+    Ball, Capsule, Compound, Cuboid, HeightField, Segment, Triangle, Trimesh, Voxels,
+};
 use crate::math::Isometry;
 use downcast_rs::{impl_downcast, DowncastSync};
 #[cfg(feature = "serde-serialize")]
 use erased_serde::Serialize;
 use ncollide::bounding_volume::{HasBoundingVolume, AABB};
 use ncollide::query::{PointQuery, RayCast};
+use ncollide::shape::SupportMap;
 use num::Zero;
 use num_derive::FromPrimitive;
 #[cfg(feature = "dim3")]
 use {
-    crate::geometry::{Cone, Cylinder, PolygonalFeatureMap, RoundCylinder},
+    crate::geometry::{Cone, ConvexPolyhedron, Cylinder, PolygonalFeatureMap, RoundCylinder},
     ncollide::bounding_volume::BoundingVolume,
 };
 
@@ -54,6 +59,23 @@ pub enum ShapeType {
     RoundCylinder,
     // /// A cone with rounded corners.
     // RoundedCone,
+    /// A convex polyhedron.
+    #[cfg(feature = "dim3")]
+    ConvexPolyhedron,
+    /// A compound shape.
+    Compound,
+    /// A shape made of voxels.
+    Voxels,
+}
+
+impl ShapeType {
+    /// Is this the type of a shape made of several parts?
+    pub(crate) fn is_composite(self) -> bool {
+        matches!(
+            self,
+            ShapeType::Trimesh | ShapeType::HeightField | ShapeType::Compound | ShapeType::Voxels
+        )
+    }
 }
 
 /// Trait implemented by shapes usable by Rapier.
@@ -75,6 +97,11 @@ pub trait Shape: RayCast<f32> + PointQuery<f32> + DowncastSync {
     /// Gets the type tag of this shape.
     fn shape_type(&self) -> ShapeType;
 
+    /// Converts this shape to a support-map, if it is one.
+    fn as_support_map(&self) -> Option<&dyn SupportMap<f32>> {
+        None
+    }
+
     /// Converts this shape to a polygonal feature-map, if it is one.
     #[cfg(feature = "dim3")]
     fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, f32)> {
@@ -104,11 +131,22 @@ impl dyn Shape {
         self.downcast_ref()
     }
 
+    /// Converts this abstract shape to a segment, if it is one.
+    pub fn as_segment(&self) -> Option<&Segment> {
+        self.downcast_ref()
+    }
+
     /// Converts this abstract shape to a triangle, if it is one.
     pub fn as_triangle(&self) -> Option<&Triangle> {
         self.downcast_ref()
     }
 
+    /// Converts this abstract shape to a convex polygon, if it is one.
+    #[cfg(feature = "dim2")]
+    pub fn as_polygon(&self) -> Option<&Polygon> {
+        self.downcast_ref()
+    }
+
     /// Converts this abstract shape to a triangle mesh, if it is one.
     pub fn as_trimesh(&self) -> Option<&Trimesh> {
         self.downcast_ref()
@@ -119,6 +157,16 @@ impl dyn Shape {
         self.downcast_ref()
     }
 
+    /// Converts this abstract shape to a compound shape, if it is one.
+    pub fn as_compound(&self) -> Option<&Compound> {
+        self.downcast_ref()
+    }
+
+    /// Converts this abstract shape to a voxel shape, if it is one.
+    pub fn as_voxels(&self) -> Option<&Voxels> {
+        self.downcast_ref()
+    }
+
     /// Converts this abstract shape to a cylinder, if it is one.
     #[cfg(feature = "dim3")]
     pub fn as_cylinder(&self) -> Option<&Cylinder> {
@@ -136,6 +184,70 @@ impl dyn Shape {
     pub fn as_round_cylinder(&self) -> Option<&RoundCylinder> {
         self.downcast_ref()
     }
+
+    /// Converts this abstract shape to a convex polyhedron, if it is one.
+    #[cfg(feature = "dim3")]
+    pub fn as_convex_polyhedron(&self) -> Option<&ConvexPolyhedron> {
+        self.downcast_ref()
+    }
+
+    /// Is this shape composed of several parts?
+    ///
+    /// This is the case for triangle meshes, heightfields, compound shapes, and voxels.
+    pub(crate) fn is_composite(&self) -> bool {
+        self.as_trimesh().is_some()
+            || self.as_heightfield().is_some()
+            || self.as_compound().is_some()
+            || self.as_voxels().is_some()
+    }
+
+    /// Calls `f` on each part of this composite shape that may intersect the given local-space AABB.
+    ///
+    /// The closure is given the part index, the part position relative to this shape, and
+    /// the part itself. Returns `false` without calling `f` if this shape is not composite.
+    pub(crate) fn map_parts_in_local_aabb(
+        &self,
+        aabb: &AABB<f32>,
+        f: &mut dyn FnMut(usize, &Isometry<f32>, &dyn Shape),
+    ) -> bool {
+        if let Some(trimesh) = self.as_trimesh() {
+            let mut interferences = Vec::new();
+            trimesh.waabbs().intersect_aabb(aabb, &mut interferences);
+
+            // Because of SIMD padding, the quadtree may return triangle indices greater than the max.
+            for i in interferences {
+                if i < trimesh.num_triangles() {
+                    f(i, &Isometry::identity(), &trimesh.triangle(i));
+                }
+            }
+        } else if let Some(heightfield) = self.as_heightfield() {
+            heightfield.map_elements_in_local_aabb(aabb, &mut |i, part, _| {
+                f(i, &Isometry::identity(), part)
+            });
+        } else if let Some(compound) = self.as_compound() {
+            let mut interferences = Vec::new();
+            compound.quadtree().intersect_aabb(aabb, &mut interferences);
+
+            for i in interferences {
+                if let Some((part_pos, part)) = compound.shapes().get(i) {
+                    f(i, part_pos, &**part);
+                }
+            }
+        } else if let Some(voxels) = self.as_voxels() {
+            let mut interferences = Vec::new();
+            voxels.voxels_intersecting_local_aabb(aabb, &mut interferences);
+            let voxel_shape = voxels.voxel_shape();
+
+            for (i, voxel) in interferences {
+                let voxel_pos = Isometry::new(voxels.voxel_center(&voxel).coords, na::zero());
+                f(i, &voxel_pos, &voxel_shape);
+            }
+        } else {
+            return false;
+        }
+
+        true
+    }
 }
 
 impl Shape for Ball {
@@ -155,26 +267,35 @@ impl Shape for Ball {
     fn shape_type(&self) -> ShapeType {
         ShapeType::Ball
     }
+
+    fn as_support_map(&self) -> Option<&dyn SupportMap<f32>> {
+        Some(self as &dyn SupportMap<f32>)
+    }
 }
 
-// impl Shape for Polygon {
-//     #[cfg(feature = "serde-serialize")]
-//     fn as_serialize(&self) -> Option<&dyn Serialize> {
-//         Some(self as &dyn Serialize)
-//     }
-//
-//     fn compute_aabb(&self, position: &Isometry<f32>) -> AABB<f32> {
-//         self.aabb(position)
-//     }
-//
-//     fn mass_properties(&self, _density: f32) -> MassProperties {
-//         unimplemented!()
-//     }
-//
-//     fn shape_type(&self) -> ShapeType {
-//         ShapeType::Polygon
-//     }
-// }
+#[cfg(feature = "dim2")]
+impl Shape for Polygon {
+    #[cfg(feature = "serde-serialize")]
+    fn as_serialize(&self) -> Option<&dyn Serialize> {
+        Some(self as &dyn Serialize)
+    }
+
+    fn compute_aabb(&self, position: &Isometry<f32>) -> AABB<f32> {
+        self.aabb(position)
+    }
+
+    fn mass_properties(&self, density: f32) -> MassProperties {
+        MassProperties::from_polygon(density, self.vertices())
+    }
+
+    fn shape_type(&self) -> ShapeType {
+        ShapeType::Polygon
+    }
+
+    fn as_support_map(&self) -> Option<&dyn SupportMap<f32>> {
+        Some(self as &dyn SupportMap<f32>)
+    }
+}
 
 impl Shape for Cuboid {
     #[cfg(feature = "serde-serialize")]
@@ -194,6 +315,10 @@ impl Shape for Cuboid {
         ShapeType::Cuboid
     }
 
+    fn as_support_map(&self) -> Option<&dyn SupportMap<f32>> {
+        Some(self as &dyn SupportMap<f32>)
+    }
+
     #[cfg(feature = "dim3")]
     fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, f32)> {
         Some((self as &dyn PolygonalFeatureMap, 0.0))
@@ -218,6 +343,10 @@ impl Shape for Capsule {
         ShapeType::Capsule
     }
 
+    fn as_support_map(&self) -> Option<&dyn SupportMap<f32>> {
+        Some(self as &dyn SupportMap<f32>)
+    }
+
     #[cfg(feature = "dim3")]
     fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, f32)> {
         Some((&self.segment as &dyn PolygonalFeatureMap, self.radius))
@@ -242,6 +371,10 @@ impl Shape for Triangle {
         ShapeType::Triangle
     }
 
+    fn as_support_map(&self) -> Option<&dyn SupportMap<f32>> {
+        Some(self as &dyn SupportMap<f32>)
+    }
+
     #[cfg(feature = "dim3")]
     fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, f32)> {
         Some((self as &dyn PolygonalFeatureMap, 0.0))
@@ -266,6 +399,10 @@ impl Shape for Segment {
         ShapeType::Segment
     }
 
+    fn as_support_map(&self) -> Option<&dyn SupportMap<f32>> {
+        Some(self as &dyn SupportMap<f32>)
+    }
+
     #[cfg(feature = "dim3")]
     fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, f32)> {
         Some((self as &dyn PolygonalFeatureMap, 0.0))
@@ -310,6 +447,53 @@ impl Shape for HeightField {
     }
 }
 
+impl Shape for Compound {
+    #[cfg(feature = "serde-serialize")]
+    fn as_serialize(&self) -> Option<&dyn Serialize> {
+        Some(self as &dyn Serialize)
+    }
+
+    fn compute_aabb(&self, position: &Isometry<f32>) -> AABB<f32> {
+        self.aabb(position)
+    }
+
+    fn mass_properties(&self, density: f32) -> MassProperties {
+        self.shapes()
+            .iter()
+            .map(|(pos, shape)| shape.mass_properties(density).transform_by(pos))
+            .fold(MassProperties::zero(), |acc, props| acc + props)
+    }
+
+    fn shape_type(&self) -> ShapeType {
+        ShapeType::Compound
+    }
+}
+
+impl Shape for Voxels {
+    #[cfg(feature = "serde-serialize")]
+    fn as_serialize(&self) -> Option<&dyn Serialize> {
+        Some(self as &dyn Serialize)
+    }
+
+    fn compute_aabb(&self, position: &Isometry<f32>) -> AABB<f32> {
+        self.aabb(position)
+    }
+
+    fn mass_properties(&self, density: f32) -> MassProperties {
+        let voxel_props = MassProperties::from_cuboid(density, self.voxel_size() / 2.0);
+        self.voxels()
+            .map(|voxel| {
+                let pos = Isometry::new(self.voxel_center(&voxel).coords, na::zero());
+                voxel_props.transform_by(&pos)
+            })
+            .fold(MassProperties::zero(), |acc, props| acc + props)
+    }
+
+    fn shape_type(&self) -> ShapeType {
+        ShapeType::Voxels
+    }
+}
+
 #[cfg(feature = "dim3")]
 impl Shape for Cylinder {
     #[cfg(feature = "serde-serialize")]
@@ -329,6 +513,10 @@ impl Shape for Cylinder {
         ShapeType::Cylinder
     }
 
+    fn as_support_map(&self) -> Option<&dyn SupportMap<f32>> {
+        Some(self as &dyn SupportMap<f32>)
+    }
+
     #[cfg(feature = "dim3")]
     fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, f32)> {
         Some((self as &dyn PolygonalFeatureMap, 0.0))
@@ -354,6 +542,10 @@ impl Shape for Cone {
         ShapeType::Cone
     }
 
+    fn as_support_map(&self) -> Option<&dyn SupportMap<f32>> {
+        Some(self as &dyn SupportMap<f32>)
+    }
+
     #[cfg(feature = "dim3")]
     fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, f32)> {
         Some((self as &dyn PolygonalFeatureMap, 0.0))
@@ -382,6 +574,10 @@ impl Shape for RoundCylinder {
         ShapeType::RoundCylinder
     }
 
+    fn as_support_map(&self) -> Option<&dyn SupportMap<f32>> {
+        Some(self as &dyn SupportMap<f32>)
+    }
+
     #[cfg(feature = "dim3")]
     fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, f32)> {
         Some((
@@ -390,3 +586,31 @@ impl Shape for RoundCylinder {
         ))
     }
 }
+
+#[cfg(feature = "dim3")]
+impl Shape for ConvexPolyhedron {
+    #[cfg(feature = "serde-serialize")]
+    fn as_serialize(&self) -> Option<&dyn Serialize> {
+        Some(self as &dyn Serialize)
+    }
+
+    fn compute_aabb(&self, position: &Isometry<f32>) -> AABB<f32> {
+        ncollide::bounding_volume::point_cloud_aabb(position, self.points())
+    }
+
+    fn mass_properties(&self, density: f32) -> MassProperties {
+        MassProperties::from_convex_polyhedron(density, self.points(), self.indices())
+    }
+
+    fn shape_type(&self) -> ShapeType {
+        ShapeType::ConvexPolyhedron
+    }
+
+    fn as_support_map(&self) -> Option<&dyn SupportMap<f32>> {
+        Some(self as &dyn SupportMap<f32>)
+    }
+
+    fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, f32)> {
+        Some((self as &dyn PolygonalFeatureMap, 0.0))
+    }
+}
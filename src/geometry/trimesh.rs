@@ -1,10 +1,27 @@
 use crate::geometry::{PointProjection, Ray, RayIntersection, Triangle, WQuadtree};
-use crate::math::{Isometry, Point};
+use crate::math::{Isometry, Point, Vector};
 use na::Point3;
-use ncollide::bounding_volume::{HasBoundingVolume, AABB};
+use ncollide::bounding_volume::{BoundingVolume, HasBoundingVolume, AABB};
 use ncollide::query::{PointQuery, RayCast};
 use ncollide::shape::FeatureId;
 
+#[cfg(feature = "dim3")]
+bitflags::bitflags! {
+    #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+    /// Flags controlling the behavior of a triangle mesh.
+    ///
+    /// These flags only exist in 3D. The triangles of a 2D triangle mesh are solid so their
+    /// internal edges are never corrected. Use a heightfield for 2D terrains instead: the
+    /// contacts on its internal edges are always corrected.
+    pub struct TrimeshFlags: u8 {
+        /// Correct the contacts generated on the edges and vertices shared by several triangles.
+        ///
+        /// This prevents objects sliding on the mesh from bumping on the edges between its
+        /// triangles. The triangles are considered adjacent if they share vertex indices.
+        const FIX_INTERNAL_EDGES = 0b01;
+    }
+}
+
 #[derive(Clone)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 /// A triangle mesh.
@@ -13,9 +30,37 @@ pub struct Trimesh {
     aabb: AABB<f32>,
     vertices: Vec<Point<f32>>,
     indices: Vec<Point3<u32>>,
+    #[cfg(feature = "dim3")]
+    flags: TrimeshFlags,
+    // The triangles sharing a vertex with the i-th triangle are
+    // `adjacent_triangles[adjacency_offsets[i]..adjacency_offsets[i + 1]]`.
+    #[cfg(feature = "dim3")]
+    adjacency_offsets: Vec<u32>,
+    #[cfg(feature = "dim3")]
+    adjacent_triangles: Vec<u32>,
 }
 
 impl Trimesh {
+    /// Creates a new triangle mesh from a vertex buffer, an index buffer, and flags controlling
+    /// its behavior.
+    ///
+    /// The triangle adjacency is computed here if `TrimeshFlags::FIX_INTERNAL_EDGES` is set.
+    #[cfg(feature = "dim3")]
+    pub fn with_flags(
+        vertices: Vec<Point<f32>>,
+        indices: Vec<Point3<u32>>,
+        flags: TrimeshFlags,
+    ) -> Self {
+        let mut result = Self::new(vertices, indices);
+
+        if flags.contains(TrimeshFlags::FIX_INTERNAL_EDGES) {
+            result.compute_adjacency();
+        }
+
+        result.flags = flags;
+        result
+    }
+
     /// Creates a new triangle mesh from a vertex buffer and an index buffer.
     pub fn new(vertices: Vec<Point<f32>>, indices: Vec<Point3<u32>>) -> Self {
         assert!(
@@ -48,6 +93,60 @@ impl Trimesh {
             aabb,
             vertices,
             indices,
+            #[cfg(feature = "dim3")]
+            flags: TrimeshFlags::empty(),
+            #[cfg(feature = "dim3")]
+            adjacency_offsets: Vec::new(),
+            #[cfg(feature = "dim3")]
+            adjacent_triangles: Vec::new(),
+        }
+    }
+
+    #[cfg(feature = "dim3")]
+    fn compute_adjacency(&mut self) {
+        let mut vertex_triangles = vec![Vec::new(); self.vertices.len()];
+
+        for (i, idx) in self.indices.iter().enumerate() {
+            for k in 0..3 {
+                vertex_triangles[idx[k] as usize].push(i as u32);
+            }
+        }
+
+        self.adjacency_offsets.clear();
+        self.adjacent_triangles.clear();
+        self.adjacency_offsets.push(0);
+
+        for (i, idx) in self.indices.iter().enumerate() {
+            let mut adjacent: Vec<u32> = (0..3)
+                .flat_map(|k| vertex_triangles[idx[k] as usize].iter().copied())
+                .filter(|j| *j != i as u32)
+                .collect();
+            adjacent.sort_unstable();
+            adjacent.dedup();
+
+            self.adjacent_triangles.extend_from_slice(&adjacent);
+            self.adjacency_offsets
+                .push(self.adjacent_triangles.len() as u32);
+        }
+    }
+
+    /// The flags controlling the behavior of this triangle mesh.
+    #[cfg(feature = "dim3")]
+    pub fn flags(&self) -> TrimeshFlags {
+        self.flags
+    }
+
+    /// The indices of the triangles sharing at least one vertex with the `i`-th triangle.
+    ///
+    /// This is empty unless the mesh was created with `TrimeshFlags::FIX_INTERNAL_EDGES`.
+    #[cfg(feature = "dim3")]
+    pub(crate) fn adjacent_triangles(&self, i: usize) -> &[u32] {
+        if self.adjacency_offsets.is_empty() {
+            &[]
+        } else {
+            let start = self.adjacency_offsets[i] as usize;
+            let end = self.adjacency_offsets[i + 1] as usize;
+            &self.adjacent_triangles[start..end]
         }
     }
 
@@ -106,19 +205,107 @@ impl Trimesh {
     }
 }
 
+impl Trimesh {
+    /// Projects a point expressed in the local-space of this mesh on its closest triangle.
+    ///
+    /// Returns the projection and the index of the closest triangle, or `None` if this
+    /// mesh does not contain any triangle.
+    fn project_local_point(
+        &self,
+        pt: &Point<f32>,
+        solid: bool,
+    ) -> Option<(PointProjection, usize)> {
+        let num_triangles = self.num_triangles();
+
+        if num_triangles == 0 {
+            return None;
+        }
+
+        // Grow a region around the point until it intersects at least one triangle.
+        let mut candidates = Vec::new();
+        let min_radius = (self.aabb.half_extents().norm() / 16.0).max(f32::EPSILON);
+        let mut radius = self.aabb.distance_to_point(&Isometry::identity(), pt, true);
+
+        loop {
+            let region = AABB::from_half_extents(*pt, Vector::repeat(radius));
+            self.wquadtree.intersect_aabb(&region, &mut candidates);
+            candidates.retain(|i| *i < num_triangles);
+
+            if !candidates.is_empty() {
+                break;
+            }
+
+            if region.contains(&self.aabb) {
+                // Should not happen, unless the quadtree is inconsistent.
+                candidates.extend(0..num_triangles);
+                break;
+            }
+
+            radius = (radius * 2.0).max(min_radius);
+        }
+
+        let mut best: Option<(f32, PointProjection, usize)> = None;
+        let project_candidates = |candidates: &[usize], best: &mut Option<_>| {
+            for i in candidates {
+                let proj = self
+                    .triangle(*i)
+                    .project_point(&Isometry::identity(), pt, solid);
+                let dist = na::distance_squared(pt, &proj.point);
+
+                if best
+                    .as_ref()
+                    .map(|b: &(f32, _, _)| dist < b.0)
+                    .unwrap_or(true)
+                {
+                    *best = Some((dist, proj, *i));
+                }
+            }
+        };
+
+        project_candidates(&candidates, &mut best);
+
+        // The closest candidate found so far may not be the closest triangle, but
+        // the closest triangle is necessarily within this candidate's distance.
+        let best_dist = best.as_ref().map(|b| b.0.sqrt()).unwrap_or(0.0);
+
+        if best_dist > radius {
+            let region = AABB::from_half_extents(*pt, Vector::repeat(best_dist));
+            candidates.clear();
+            self.wquadtree.intersect_aabb(&region, &mut candidates);
+            candidates.retain(|i| *i < num_triangles);
+            project_candidates(&candidates, &mut best);
+        }
+
+        best.map(|(_, proj, i)| (proj, i))
+    }
+}
+
+// NOTE: projecting a point on a mesh without any triangle returns the point itself.
 impl PointQuery<f32> for Trimesh {
-    fn project_point(&self, _m: &Isometry<f32>, _pt: &Point<f32>, _solid: bool) -> PointProjection {
-        // TODO
-        unimplemented!()
+    fn project_point(&self, m: &Isometry<f32>, pt: &Point<f32>, solid: bool) -> PointProjection {
+        let local_pt = m.inverse_transform_point(pt);
+
+        match self.project_local_point(&local_pt, solid) {
+            Some((proj, _)) if solid && proj.is_inside => PointProjection::new(true, *pt),
+            Some((proj, _)) => PointProjection::new(proj.is_inside, m * proj.point),
+            None => PointProjection::new(false, *pt),
+        }
     }
 
     fn project_point_with_feature(
         &self,
-        _m: &Isometry<f32>,
-        _pt: &Point<f32>,
+        m: &Isometry<f32>,
+        pt: &Point<f32>,
     ) -> (PointProjection, FeatureId) {
-        // TODO
-        unimplemented!()
+        let local_pt = m.inverse_transform_point(pt);
+
+        match self.project_local_point(&local_pt, false) {
+            Some((proj, i)) => (
+                PointProjection::new(proj.is_inside, m * proj.point),
+                FeatureId::Face(i),
+            ),
+            None => (PointProjection::new(false, *pt), FeatureId::Unknown),
+        }
     }
 }
 
@@ -190,3 +377,81 @@ impl RayCast<f32> for Trimesh {
         false
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Trimesh;
+    use crate::math::{Isometry, Point, Vector};
+    use na::Point3;
+    use ncollide::query::PointQuery;
+    use ncollide::shape::FeatureId;
+
+    fn grid_mesh(n: u32) -> Trimesh {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        for i in 0..=n {
+            for j in 0..=n {
+                let (x, y) = (i as f32, j as f32);
+                #[cfg(feature = "dim2")]
+                vertices.push(Point::new(x + (y * 0.7).sin() * 0.3, y));
+                #[cfg(feature = "dim3")]
+                vertices.push(Point::new(x, (x * 0.5).sin() + (y * 0.3).cos(), y));
+            }
+        }
+
+        for i in 0..n {
+            for j in 0..n {
+                let a = i * (n + 1) + j;
+                let b = a + n + 1;
+                indices.push(Point3::new(a, b, a + 1));
+                indices.push(Point3::new(b, b + 1, a + 1));
+            }
+        }
+
+        Trimesh::new(vertices, indices)
+    }
+
+    #[test]
+    fn project_point_matches_exhaustive_search() {
+        let mesh = grid_mesh(10);
+        let pos = Isometry::new(Vector::repeat(1.0), na::zero());
+
+        for k in 0..200 {
+            let t = k as f32;
+            let pt = pos
+                * Point::from(Vector::from_fn(|i, _| {
+                    ((t * 0.37 + i as f32 * 1.3).sin() + 0.5) * 12.0
+                }));
+
+            let expected = mesh
+                .triangles()
+                .map(|tri| na::distance(&pt, &tri.project_point(&pos, &pt, false).point))
+                .fold(f32::MAX, f32::min);
+            let (proj, feature) = mesh.project_point_with_feature(&pos, &pt);
+            let dist = na::distance(&pt, &proj.point);
+
+            assert!((dist - expected).abs() < 1.0e-4, "{} != {}", dist, expected);
+            assert!(matches!(feature, FeatureId::Face(i) if i < mesh.num_triangles()));
+            assert!(
+                (na::distance(&pt, &mesh.project_point(&pos, &pt, false).point) - expected).abs()
+                    < 1.0e-4
+            );
+        }
+    }
+
+    #[test]
+    fn project_point_on_empty_mesh() {
+        let mut mesh = grid_mesh(1);
+        mesh.indices.clear();
+        let pt = Point::from(Vector::repeat(2.0));
+
+        let proj = mesh.project_point(&Isometry::identity(), &pt, true);
+        assert!(!proj.is_inside);
+        assert_eq!(proj.point, pt);
+
+        let (proj, feature) = mesh.project_point_with_feature(&Isometry::identity(), &pt);
+        assert_eq!(proj.point, pt);
+        assert!(matches!(feature, FeatureId::Unknown));
+    }
+}
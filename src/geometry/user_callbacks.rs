@@ -1,5 +1,5 @@
 use crate::dynamics::RigidBody;
-use crate::geometry::{Collider, SolverFlags};
+use crate::geometry::{Collider, ContactManifold, SolverFlags};
 
 /// Context given to custom collision filters to filter-out collisions.
 pub struct PairFilterContext<'a> {
@@ -55,3 +55,38 @@ pub trait ProximityPairFilter: Send + Sync {
     /// information for this pair.
     fn filter_proximity_pair(&self, context: &PairFilterContext) -> bool;
 }
+
+/// Context given to custom contact modifiers to modify the contacts seen by the constraints solver.
+pub struct ContactModificationContext<'a> {
+    /// The rigid-body attached to the first collider of the contact manifold.
+    pub rigid_body1: &'a RigidBody,
+    /// The rigid-body attached to the second collider of the contact manifold.
+    pub rigid_body2: &'a RigidBody,
+    /// The first collider of the contact manifold.
+    pub collider1: &'a Collider,
+    /// The second collider of the contact manifold.
+    pub collider2: &'a Collider,
+    /// The contact manifold to modify.
+    pub manifold: &'a mut ContactManifold,
+}
+
+/// User-defined modifier of the contact manifolds computed by the narrow-phase.
+///
+/// This can be used to apply custom logic in order to tweak the contacts before they
+/// are given to the constraints solver, e.g., to implement one-way platforms or conveyor belts.
+pub trait ContactModifier: Send + Sync {
+    /// Modifies the given contact manifold.
+    ///
+    /// This is called after the narrow-phase computed the contacts of a pair of colliders,
+    /// once for each of their contact manifolds having at least one active contact.
+    ///
+    /// Contacts can be ignored by the constraints solver with
+    /// `ContactManifold::retain_active_contacts`, and the `friction`, `restitution`, and
+    /// `tangent_velocity` of the manifold can be set. All these modifications only affect the
+    /// current timestep: at each timestep, the contacts are re-activated, the `friction` and
+    /// `restitution` are reset to the combined coefficients of the colliders, and the
+    /// `tangent_velocity` is reset to zero, before this is called.
+    ///
+    /// This does not affect the contact events generated by the narrow-phase.
+    fn modify_contacts(&self, context: &mut ContactModificationContext);
+}
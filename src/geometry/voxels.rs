@@ -0,0 +1,729 @@
+use crate::data::hashmap::HashMap;
+use crate::geometry::{Cuboid, PointProjection, Ray, RayIntersection};
+use crate::math::{Isometry, Point, Vector, DIM};
+use ncollide::bounding_volume::{BoundingVolume, AABB};
+use ncollide::query::{PointQuery, RayCast};
+use ncollide::shape::FeatureId;
+
+/// A shape made of voxels, i.e., identical cuboids aligned on a regular grid.
+///
+/// The voxel with grid coordinates `c` occupies the box between `c * voxel_size` and
+/// `(c + 1) * voxel_size` in the local-space of the shape. Voxels are stored sparsely so the
+/// grid is unbounded and voxels can be added or removed cheaply, e.g., for destructible terrains.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct Voxels {
+    voxel_size: Vector<f32>,
+    ids: HashMap<Point<i32>, usize>,
+    keys: Vec<Option<Point<i32>>>,
+    free_ids: Vec<usize>,
+    mins: Point<i32>,
+    maxs: Point<i32>,
+}
+
+impl Voxels {
+    /// Creates a new voxel shape from the size of its voxels and the grid coordinates of the
+    /// filled voxels.
+    ///
+    /// Panics if one component of `voxel_size` is not positive.
+    pub fn new(voxel_size: Vector<f32>, voxels: &[Point<i32>]) -> Self {
+        assert!(
+            voxel_size.iter().all(|s| *s > 0.0),
+            "The voxel size must be positive."
+        );
+
+        let mut result = Self {
+            voxel_size,
+            ids: HashMap::default(),
+            keys: Vec::new(),
+            free_ids: Vec::new(),
+            mins: Point::origin(),
+            maxs: Point::origin(),
+        };
+
+        for voxel in voxels {
+            let _ = result.insert(*voxel);
+        }
+
+        result
+    }
+
+    /// The size of each voxel of this shape.
+    pub fn voxel_size(&self) -> Vector<f32> {
+        self.voxel_size
+    }
+
+    /// The number of filled voxels.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Is this shape without any filled voxel?
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Is the voxel with the given grid coordinates filled?
+    pub fn contains(&self, voxel: &Point<i32>) -> bool {
+        self.ids.contains_key(voxel)
+    }
+
+    /// Fills the voxel with the given grid coordinates.
+    ///
+    /// Returns `false` if this voxel was already filled.
+    pub fn insert(&mut self, voxel: Point<i32>) -> bool {
+        if self.ids.contains_key(&voxel) {
+            return false;
+        }
+
+        if self.ids.is_empty() {
+            self.mins = voxel;
+            self.maxs = voxel;
+        } else {
+            self.mins = self.mins.inf(&voxel);
+            self.maxs = self.maxs.sup(&voxel);
+        }
+
+        let id = if let Some(id) = self.free_ids.pop() {
+            self.keys[id] = Some(voxel);
+            id
+        } else {
+            self.keys.push(Some(voxel));
+            self.keys.len() - 1
+        };
+
+        let _ = self.ids.insert(voxel, id);
+        true
+    }
+
+    /// Empties the voxel with the given grid coordinates.
+    ///
+    /// Returns `false` if this voxel was already empty.
+    pub fn remove(&mut self, voxel: &Point<i32>) -> bool {
+        if let Some(id) = self.ids.remove(voxel) {
+            self.keys[id] = None;
+            self.free_ids.push(id);
+
+            if (0..DIM).any(|i| voxel[i] == self.mins[i] || voxel[i] == self.maxs[i]) {
+                self.recompute_bounds();
+            }
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn recompute_bounds(&mut self) {
+        let mut voxels = self.keys.iter().filter_map(|key| *key);
+
+        if let Some(first) = voxels.next() {
+            let (mins, maxs) = voxels.fold((first, first), |(mins, maxs), voxel| {
+                (mins.inf(&voxel), maxs.sup(&voxel))
+            });
+            self.mins = mins;
+            self.maxs = maxs;
+        }
+    }
+
+    /// An iterator through the grid coordinates of all the filled voxels.
+    pub fn voxels(&self) -> impl Iterator<Item = Point<i32>> + '_ {
+        self.keys.iter().filter_map(|key| *key)
+    }
+
+    /// The grid coordinates of the voxel containing the given point, expressed in the
+    /// local-space of this shape.
+    pub fn voxel_at_point(&self, pt: &Point<f32>) -> Point<i32> {
+        let mut voxel = Point::origin();
+
+        for i in 0..DIM {
+            voxel[i] = (pt[i] / self.voxel_size[i]).floor() as i32;
+        }
+
+        voxel
+    }
+
+    /// The center of the voxel with the given grid coordinates, expressed in the local-space
+    /// of this shape.
+    pub fn voxel_center(&self, voxel: &Point<i32>) -> Point<f32> {
+        let mut center = Point::origin();
+
+        for i in 0..DIM {
+            center[i] = (voxel[i] as f32 + 0.5) * self.voxel_size[i];
+        }
+
+        center
+    }
+
+    /// The cuboid occupied by each voxel, centered at the origin.
+    pub fn voxel_shape(&self) -> Cuboid {
+        Cuboid::new(self.voxel_size / 2.0)
+    }
+
+    /// Compute the axis-aligned bounding box of this voxel shape.
+    pub fn aabb(&self, pos: &Isometry<f32>) -> AABB<f32> {
+        self.local_aabb().transform_by(pos)
+    }
+
+    fn local_aabb(&self) -> AABB<f32> {
+        if self.is_empty() {
+            return AABB::new(Point::origin(), Point::origin());
+        }
+
+        let mut mins = Point::origin();
+        let mut maxs = Point::origin();
+
+        for i in 0..DIM {
+            mins[i] = self.mins[i] as f32 * self.voxel_size[i];
+            maxs[i] = (self.maxs[i] + 1) as f32 * self.voxel_size[i];
+        }
+
+        AABB::new(mins, maxs)
+    }
+
+    pub(crate) fn voxel_id(&self, voxel: &Point<i32>) -> Option<usize> {
+        self.ids.get(voxel).copied()
+    }
+
+    /// Collects the identifiers and grid coordinates of the filled voxels intersecting the given
+    /// AABB expressed in the local-space of this shape.
+    pub(crate) fn voxels_intersecting_local_aabb(
+        &self,
+        aabb: &AABB<f32>,
+        out: &mut Vec<(usize, Point<i32>)>,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+
+        let mins = self.voxel_at_point(&aabb.mins).sup(&self.mins);
+        let maxs = self.voxel_at_point(&aabb.maxs).inf(&self.maxs);
+        let mut num_cells = 1.0f64;
+
+        for i in 0..DIM {
+            if mins[i] > maxs[i] {
+                return;
+            }
+
+            num_cells *= (maxs[i] - mins[i] + 1) as f64;
+        }
+
+        if num_cells > self.len() as f64 {
+            // There are less voxels than cells to test.
+            for (id, key) in self.keys.iter().enumerate() {
+                if let Some(voxel) = key {
+                    if (0..DIM).all(|i| voxel[i] >= mins[i] && voxel[i] <= maxs[i]) {
+                        out.push((id, *voxel));
+                    }
+                }
+            }
+
+            return;
+        }
+
+        let mut voxel = mins;
+
+        loop {
+            if let Some(id) = self.voxel_id(&voxel) {
+                out.push((id, voxel));
+            }
+
+            // Move to the next cell of the range.
+            let mut i = 0;
+            while i < DIM && voxel[i] == maxs[i] {
+                voxel[i] = mins[i];
+                i += 1;
+            }
+
+            if i == DIM {
+                break;
+            }
+
+            voxel[i] += 1;
+        }
+    }
+
+    // The closest point to `local_pt` on the boundary of the given voxels, with its squared
+    // distance and the identifier of its voxel.
+    //
+    // The boundary of the shape is made of the voxel faces without filled neighbor.
+    fn closest_boundary_point(
+        &self,
+        local_pt: &Point<f32>,
+        voxels: &[(usize, Point<i32>)],
+    ) -> Option<(f32, Point<f32>, usize)> {
+        let half_extents = self.voxel_size / 2.0;
+        let mut best: Option<(f32, Point<f32>, usize)> = None;
+
+        for (id, voxel) in voxels {
+            let center = self.voxel_center(voxel);
+            let mut clamped = *local_pt;
+
+            for i in 0..DIM {
+                clamped[i] = clamped[i]
+                    .max(center[i] - half_extents[i])
+                    .min(center[i] + half_extents[i]);
+            }
+
+            for i in 0..DIM {
+                for sign in &[-1.0f32, 1.0] {
+                    let mut neighbor = *voxel;
+                    neighbor[i] += *sign as i32;
+
+                    if self.contains(&neighbor) {
+                        continue;
+                    }
+
+                    let mut proj = clamped;
+                    proj[i] = center[i] + *sign * half_extents[i];
+                    let dist = na::distance_squared(local_pt, &proj);
+
+                    if best.as_ref().map(|b| dist < b.0).unwrap_or(true) {
+                        best = Some((dist, proj, *id));
+                    }
+                }
+            }
+        }
+
+        best
+    }
+}
+
+impl PointQuery<f32> for Voxels {
+    fn project_point(&self, m: &Isometry<f32>, pt: &Point<f32>, solid: bool) -> PointProjection {
+        let local_pt = m.inverse_transform_point(pt);
+
+        if solid && self.contains(&self.voxel_at_point(&local_pt)) {
+            return PointProjection::new(true, *pt);
+        }
+
+        self.project_point_with_feature(m, pt).0
+    }
+
+    fn project_point_with_feature(
+        &self,
+        m: &Isometry<f32>,
+        pt: &Point<f32>,
+    ) -> (PointProjection, FeatureId) {
+        let local_pt = m.inverse_transform_point(pt);
+
+        if self.is_empty() {
+            // An empty voxel shape has no boundary to project on.
+            return (PointProjection::new(false, *pt), FeatureId::Unknown);
+        }
+
+        let is_inside = self.contains(&self.voxel_at_point(&local_pt));
+        let aabb = self.local_aabb();
+        let mut radius =
+            self.voxel_size.max() + aabb.distance_to_point(&Isometry::identity(), &local_pt, true);
+        let mut candidates = Vec::new();
+
+        // Only visit the voxels in a growing neighborhood of the point. A boundary point closer
+        // than `radius` necessarily lies on a voxel intersecting this neighborhood.
+        let (_, proj, id) = loop {
+            let extents = Vector::repeat(radius);
+            let region = AABB::new(local_pt - extents, local_pt + extents);
+            candidates.clear();
+            self.voxels_intersecting_local_aabb(&region, &mut candidates);
+
+            let best = self.closest_boundary_point(&local_pt, &candidates);
+
+            match best {
+                Some(best) if best.0 <= radius * radius => break best,
+                Some(best) if region.contains(&aabb) => break best,
+                _ => radius *= 2.0,
+            }
+        };
+
+        (
+            PointProjection::new(is_inside, m * proj),
+            FeatureId::Face(id),
+        )
+    }
+}
+
+impl RayCast<f32> for Voxels {
+    fn toi_and_normal_with_ray(
+        &self,
+        m: &Isometry<f32>,
+        ray: &Ray,
+        max_toi: f32,
+        solid: bool,
+    ) -> Option<RayIntersection> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let ls_ray = ray.inverse_transform_by(m);
+        let aabb = self.local_aabb();
+
+        // Clip the ray with the AABB of the voxels.
+        let mut tmin = 0.0f32;
+        let mut tmax = max_toi;
+        let mut axis = None;
+
+        for i in 0..DIM {
+            if ls_ray.dir[i] == 0.0 {
+                if ls_ray.origin[i] < aabb.mins[i] || ls_ray.origin[i] > aabb.maxs[i] {
+                    return None;
+                }
+            } else {
+                let inv_dir = 1.0 / ls_ray.dir[i];
+                let t1 = (aabb.mins[i] - ls_ray.origin[i]) * inv_dir;
+                let t2 = (aabb.maxs[i] - ls_ray.origin[i]) * inv_dir;
+                let (t1, t2) = if t1 > t2 { (t2, t1) } else { (t1, t2) };
+
+                if t1 > tmin {
+                    tmin = t1;
+                    axis = Some(i);
+                }
+
+                tmax = tmax.min(t2);
+
+                if tmin > tmax {
+                    return None;
+                }
+            }
+        }
+
+        // Traverse the grid, one voxel at a time, starting from the entry point.
+        let mut voxel = self
+            .voxel_at_point(&ls_ray.point_at(tmin))
+            .sup(&self.mins)
+            .inf(&self.maxs);
+        let mut step = [0i32; DIM];
+        let mut t_next = [f32::INFINITY; DIM];
+        let mut t_delta = [f32::INFINITY; DIM];
+
+        for i in 0..DIM {
+            if ls_ray.dir[i] > 0.0 {
+                step[i] = 1;
+                t_next[i] =
+                    ((voxel[i] + 1) as f32 * self.voxel_size[i] - ls_ray.origin[i]) / ls_ray.dir[i];
+                t_delta[i] = self.voxel_size[i] / ls_ray.dir[i];
+            } else if ls_ray.dir[i] < 0.0 {
+                step[i] = -1;
+                t_next[i] =
+                    (voxel[i] as f32 * self.voxel_size[i] - ls_ray.origin[i]) / ls_ray.dir[i];
+                t_delta[i] = -self.voxel_size[i] / ls_ray.dir[i];
+            }
+        }
+
+        let mut t = tmin;
+        // Set if the ray starts inside of a filled voxel and the shape is not solid.
+        let mut exiting_voxel = None;
+
+        loop {
+            match (self.voxel_id(&voxel), exiting_voxel) {
+                (Some(id), None) => {
+                    if let Some(i) = axis {
+                        let mut normal = Vector::zeros();
+                        normal[i] = -step[i] as f32;
+                        return Some(RayIntersection::new(t, m * normal, FeatureId::Face(id)));
+                    } else if solid {
+                        return Some(RayIntersection::new(
+                            0.0,
+                            Vector::zeros(),
+                            FeatureId::Face(id),
+                        ));
+                    } else {
+                        exiting_voxel = Some(id);
+                    }
+                }
+                (Some(id), Some(_)) => exiting_voxel = Some(id),
+                (None, Some(id)) => {
+                    // The ray leaves the shape through the face shared with the last voxel.
+                    let i = axis.unwrap();
+                    let mut normal = Vector::zeros();
+                    normal[i] = -step[i] as f32;
+                    return Some(RayIntersection::new(t, m * normal, FeatureId::Face(id)));
+                }
+                (None, None) => {}
+            }
+
+            let mut i = 0;
+            for j in 1..DIM {
+                if t_next[j] < t_next[i] {
+                    i = j;
+                }
+            }
+
+            if t_next[i] > max_toi || (exiting_voxel.is_none() && t_next[i] > tmax) {
+                return None;
+            }
+
+            t = t_next[i];
+            t_next[i] += t_delta[i];
+            voxel[i] += step[i];
+            axis = Some(i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Voxels;
+    use crate::dynamics::RigidBodyBuilder;
+    use crate::geometry::{ColliderBuilder, ColliderShape, Ray};
+    use crate::math::{Isometry, Point, Vector};
+    use crate::pipeline::test_world::TestWorld;
+    use ncollide::bounding_volume::AABB;
+    use ncollide::query::{PointQuery, RayCast};
+    use ncollide::shape::FeatureId;
+
+    // A point with the given x and y coordinates, in the middle of the voxels along z.
+    fn point(x: f32, y: f32) -> Point<f32> {
+        let mut pt = Point::origin();
+        pt.x = x;
+        pt.y = y;
+        #[cfg(feature = "dim3")]
+        {
+            pt.z = 0.5;
+        }
+        pt
+    }
+
+    fn vector(x: f32, y: f32) -> Vector<f32> {
+        let mut v = Vector::zeros();
+        v.x = x;
+        v.y = y;
+        v
+    }
+
+    fn voxel(x: i32, y: i32) -> Point<i32> {
+        let mut voxel = Point::origin();
+        voxel.x = x;
+        voxel.y = y;
+        voxel
+    }
+
+    #[test]
+    fn voxels_ray_cast() {
+        let mut voxels = Voxels::new(Vector::repeat(1.0), &[voxel(0, 0), voxel(1, 0)]);
+        let m = Isometry::identity();
+
+        let ray = Ray::new(point(-1.0, 0.5), Vector::x());
+        let hit = voxels.toi_and_normal_with_ray(&m, &ray, f32::MAX, true);
+        let hit = hit.unwrap();
+        assert_eq!(hit.toi, 1.0);
+        assert_eq!(hit.normal, -Vector::x());
+        assert_eq!(hit.feature, FeatureId::Face(0));
+
+        let ray = Ray::new(point(5.0, 0.5), -Vector::x());
+        let hit = voxels.toi_and_normal_with_ray(&m, &ray, f32::MAX, true);
+        assert_eq!(hit.unwrap().toi, 3.0);
+        assert!(voxels
+            .toi_and_normal_with_ray(&m, &ray, 2.0, true)
+            .is_none());
+
+        // A ray starting inside of the voxels.
+        let ray = Ray::new(point(0.5, 0.5), Vector::x());
+        let hit = voxels.toi_and_normal_with_ray(&m, &ray, f32::MAX, true);
+        assert_eq!(hit.unwrap().toi, 0.0);
+        let hit = voxels.toi_and_normal_with_ray(&m, &ray, f32::MAX, false);
+        assert_eq!(hit.unwrap().toi, 1.5);
+
+        // A ray passing diagonally through a removed voxel.
+        let ray = Ray::new(point(-0.5, 1.2), vector(1.0, -0.5));
+        assert_eq!(
+            voxels
+                .toi_and_normal_with_ray(&m, &ray, f32::MAX, true)
+                .unwrap()
+                .feature,
+            FeatureId::Face(0)
+        );
+        assert!(voxels.remove(&voxel(0, 0)));
+        let hit = voxels.toi_and_normal_with_ray(&m, &ray, f32::MAX, true);
+        let hit = hit.unwrap();
+        assert!((hit.toi - 1.5).abs() < 1.0e-5);
+        assert_eq!(hit.normal, -Vector::x());
+        assert_eq!(hit.feature, FeatureId::Face(1));
+        assert!(voxels
+            .toi_and_normal_with_ray(&m, &Ray::new(point(-1.0, 1.5), Vector::x()), f32::MAX, true)
+            .is_none());
+    }
+
+    #[test]
+    fn voxels_point_projection_and_aabb_query() {
+        let voxels = Voxels::new(
+            Vector::repeat(1.0),
+            &[voxel(0, 0), voxel(1, 0), voxel(1, 1)],
+        );
+        let m = Isometry::identity();
+
+        let proj = voxels.project_point(&m, &point(3.0, 0.5), true);
+        assert!(!proj.is_inside);
+        assert_eq!(proj.point, point(2.0, 0.5));
+
+        let proj = voxels.project_point(&m, &point(0.5, 0.2), true);
+        assert!(proj.is_inside);
+        assert_eq!(proj.point, point(0.5, 0.2));
+
+        // The face shared by two voxels is not part of the boundary.
+        let proj = voxels.project_point(&m, &point(1.2, 0.3), false);
+        assert!(proj.is_inside);
+        assert_eq!(proj.point, point(1.2, 0.0));
+
+        let mut out = Vec::new();
+        let aabb = AABB::new(point(0.5, 0.5), point(1.5, 0.7));
+        voxels.voxels_intersecting_local_aabb(&aabb, &mut out);
+        assert_eq!(out.len(), 2);
+
+        // An AABB containing more cells than there are voxels.
+        out.clear();
+        let aabb = AABB::new(point(-100.0, -100.0), point(100.0, 100.0));
+        voxels.voxels_intersecting_local_aabb(&aabb, &mut out);
+        assert_eq!(
+            out,
+            vec![(0, voxel(0, 0)), (1, voxel(1, 0)), (2, voxel(1, 1))]
+        );
+    }
+
+    #[test]
+    fn voxels_point_projection_matches_exhaustive_search() {
+        let mut voxels = Vec::new();
+        for i in 0..20 {
+            voxels.push(voxel((i * 7) % 13, (i * 5) % 11 - 3));
+        }
+        let mut voxel_size = Vector::repeat(1.0);
+        voxel_size.x = 0.5;
+        voxel_size.y = 2.0;
+        let mut voxels = Voxels::new(voxel_size, &voxels);
+        let m = Isometry::identity();
+
+        for i in 0..200 {
+            let x = (i % 20) as f32 * 0.8 - 4.0;
+            let y = (i / 20) as f32 * 3.1 - 12.0;
+            let pt = point(x, y);
+            let all: Vec<_> = voxels
+                .keys
+                .iter()
+                .enumerate()
+                .filter_map(|(id, key)| key.map(|k| (id, k)))
+                .collect();
+            let expected = voxels.closest_boundary_point(&pt, &all).unwrap();
+            let proj = voxels.project_point(&m, &pt, false);
+            assert!((na::distance_squared(&pt, &proj.point) - expected.0).abs() < 1.0e-4);
+        }
+
+        // Removing the voxels on the boundary of the grid shrinks its AABB.
+        let aabb = voxels.local_aabb();
+        let mins = voxels.mins;
+        let to_remove: Vec<_> = voxels.voxels().filter(|v| v.x == mins.x).collect();
+        for voxel in &to_remove {
+            assert!(voxels.remove(voxel));
+        }
+        assert!(voxels.local_aabb().mins.x > aabb.mins.x);
+        assert_eq!(voxels.local_aabb().maxs, aabb.maxs);
+    }
+
+    #[test]
+    fn voxels_mut_updates_mass_properties_and_aabb() {
+        let mut world = TestWorld::with_gravity();
+
+        // Adding a voxel to a dynamic body doubles its mass.
+        let co = ColliderBuilder::voxels(Vector::repeat(1.0), &[voxel(0, 0)])
+            .density(1.0)
+            .build();
+        let body = world.insert(RigidBodyBuilder::new_dynamic().build(), Some(co));
+        let handle = world.bodies[body].colliders()[0];
+        assert!((world.bodies[body].mass() - 1.0).abs() < 1.0e-5);
+        {
+            let mut voxels = world
+                .colliders
+                .voxels_mut(handle, &mut world.bodies)
+                .unwrap();
+            assert!(voxels.insert(voxel(1, 0)));
+        }
+        let rb = &world.bodies[body];
+        assert!((rb.mass() - 2.0).abs() < 1.0e-5);
+        assert!((rb.mass_properties.local_com.x - 1.0).abs() < 1.0e-5);
+
+        // A voxel added far from the others to a static body supports a falling ball.
+        let mut world = TestWorld::with_gravity();
+        let co = ColliderBuilder::voxels(Vector::repeat(1.0), &[voxel(0, -1)]).build();
+        world.insert(RigidBodyBuilder::new_static().build(), Some(co));
+        world.step();
+        let handle = world.colliders.iter().next().unwrap().0;
+        assert!(world
+            .colliders
+            .voxels_mut(handle, &mut world.bodies)
+            .unwrap()
+            .insert(voxel(10, -1)));
+
+        let ball = RigidBodyBuilder::new_dynamic()
+            .position(Isometry::new(point(10.5, 1.0).coords, na::zero()))
+            .build();
+        let ball = world.insert(ball, Some(ColliderBuilder::ball(0.5).build()));
+        world.run(60);
+        let y = world.bodies[ball].position().translation.vector.y;
+        assert!((y - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn sliding_on_voxels_without_bumps() {
+        let mut world = TestWorld::with_gravity();
+
+        // A flat floor made of one layer of voxels, with its top at y = 0.
+        let mut floor = Vec::new();
+        for i in -5..40 {
+            #[cfg(feature = "dim2")]
+            floor.push(Point::new(i, -1));
+            #[cfg(feature = "dim3")]
+            for k in -3..3 {
+                floor.push(Point::new(i, -1, k));
+            }
+        }
+
+        let co = ColliderBuilder::voxels(Vector::repeat(1.0), &floor)
+            .friction(0.0)
+            .build();
+        let ground = world.insert(RigidBodyBuilder::new_static().build(), None);
+        let voxels_handle = world.insert_collider(co, ground);
+
+        let mut rb = RigidBodyBuilder::new_dynamic()
+            .position(Isometry::new(Vector::y() * 0.5, na::zero()))
+            .build();
+        rb.set_linvel(Vector::x() * 10.0, true);
+        let co = ColliderBuilder::new(ColliderShape::cuboid(Vector::repeat(0.5)))
+            .friction(0.0)
+            .build();
+        let handle = world.insert(rb, Some(co));
+
+        for _ in 0..120 {
+            world.step();
+
+            // The cuboid crosses many edges between voxels without being deflected.
+            let rb = &world.bodies[handle];
+            assert!(rb.linvel().y.abs() < 0.1);
+            assert!((rb.linvel().x - 10.0).abs() < 0.1);
+            assert!((rb.position().translation.vector.y - 0.5).abs() < 0.02);
+        }
+
+        // Dig a hole under the cuboid: it falls into it.
+        let x = world.bodies[handle].position().translation.vector.x.floor() as i32;
+        {
+            let mut voxels = world
+                .colliders
+                .voxels_mut(voxels_handle, &mut world.bodies)
+                .unwrap();
+            for i in x - 2..x + 3 {
+                #[cfg(feature = "dim2")]
+                assert!(voxels.remove(&Point::new(i, -1)));
+                #[cfg(feature = "dim3")]
+                for k in -3..3 {
+                    assert!(voxels.remove(&Point::new(i, -1, k)));
+                }
+            }
+        }
+
+        world
+            .bodies
+            .get_mut(handle)
+            .unwrap()
+            .set_linvel(Vector::zeros(), true);
+        world.run(30);
+
+        assert!(world.bodies[handle].position().translation.vector.y < -0.5);
+    }
+}
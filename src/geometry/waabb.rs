@@ -5,7 +5,7 @@ use ncollide::bounding_volume::AABB;
 use num::{One, Zero};
 use {
     crate::math::{SimdBool, SimdFloat},
-    simba::simd::{SimdPartialOrd, SimdValue},
+    simba::simd::{SimdComplexField, SimdPartialOrd, SimdValue},
 };
 
 #[derive(Debug, Copy, Clone)]
@@ -115,6 +115,10 @@ impl WAABB {
         self.maxs.replace(i, aabb.maxs);
     }
 
+    pub fn extract(&self, i: usize) -> AABB<f32> {
+        AABB::new(self.mins.extract(i), self.maxs.extract(i))
+    }
+
     pub fn intersects_ray(&self, ray: &WRay, max_toi: SimdFloat) -> SimdBool {
         let _0 = SimdFloat::zero();
         let _1 = SimdFloat::one();
@@ -151,6 +155,23 @@ impl WAABB {
         hit
     }
 
+    /// The distance between each AABB of `self` and the given point.
+    ///
+    /// The distance is zero for the AABBs containing the point.
+    pub fn distance_to_point(&self, point: &Point<SimdFloat>) -> SimdFloat {
+        let _0 = SimdFloat::zero();
+        let mut dist2 = _0;
+
+        for i in 0usize..DIM {
+            let d = (self.mins[i] - point[i])
+                .simd_max(point[i] - self.maxs[i])
+                .simd_max(_0);
+            dist2 += d * d;
+        }
+
+        dist2.simd_sqrt()
+    }
+
     #[cfg(feature = "dim2")]
     pub fn contains(&self, other: &WAABB) -> SimdBool {
         self.mins.x.simd_le(other.mins.x)
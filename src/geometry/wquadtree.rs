@@ -6,10 +6,11 @@ use crate::math::Vector;
 use crate::simd::{SimdFloat, SIMD_WIDTH};
 use ncollide::bounding_volume::BoundingVolume;
 use simba::simd::{SimdBool, SimdValue};
-use std::collections::VecDeque;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
 use std::ops::Range;
 
-pub trait IndexedData: Copy {
+pub trait IndexedData: Copy + PartialEq {
     fn default() -> Self;
     fn index(&self) -> usize;
 }
@@ -88,6 +89,8 @@ pub struct WQuadtree<T> {
     nodes: Vec<WQuadtreeNode>,
     dirty_nodes: VecDeque<u32>,
     proxies: Vec<WQuadtreeProxy<T>>,
+    // Number of nodes left empty by removals, and no longer reachable from the root.
+    num_detached_nodes: usize,
 }
 
 // FIXME: this should be generic too.
@@ -145,9 +148,194 @@ impl<T: IndexedData> WQuadtree<T> {
             nodes: Vec::new(),
             dirty_nodes: VecDeque::new(),
             proxies: Vec::new(),
+            num_detached_nodes: 0,
         }
     }
 
+    /// Checks if the given data has a proxy on this tree.
+    pub fn contains(&self, data: T) -> bool {
+        self.proxies
+            .get(data.index())
+            .map(|proxy| proxy.data == data && proxy.node != NodeIndex::invalid())
+            .unwrap_or(false)
+    }
+
+    /// Inserts a new proxy with the given AABB without rebuilding the tree.
+    ///
+    /// The proxy is added to the leaf that requires the smallest volume increase to contain it.
+    /// Full leaves are split by creating a new internal node.
+    pub fn insert(&mut self, data: T, aabb: AABB, dilation_factor: f32) {
+        let index = data.index();
+        if index >= self.proxies.len() {
+            self.proxies.resize(index + 1, WQuadtreeProxy::invalid());
+        }
+
+        self.proxies[index].data = data;
+
+        let dilation = (aabb.maxs - aabb.mins) * dilation_factor;
+        let aabb = AABB::new(aabb.mins - dilation, aabb.maxs + dilation);
+
+        if self.nodes.is_empty() {
+            self.nodes.push(WQuadtreeNode {
+                waabb: WAABB::new_invalid(),
+                children: [u32::MAX; 4],
+                parent: NodeIndex::invalid(),
+                leaf: false,
+                dirty: false,
+            });
+        }
+
+        // Go down the tree until we find a free slot or a full leaf.
+        let mut curr = 0;
+        let parent = loop {
+            let node = &self.nodes[curr as usize];
+
+            if let Some(lane) = node.children.iter().position(|c| *c == u32::MAX) {
+                if node.leaf {
+                    // Add the proxy to this leaf.
+                    let node = &mut self.nodes[curr as usize];
+                    node.children[lane] = index as u32;
+                    node.waabb.replace(lane, aabb);
+                    self.proxies[index].node = NodeIndex::new(curr, lane as u8);
+                } else {
+                    // Attach a new leaf to this internal node.
+                    let leaf_id = self.nodes.len() as u32;
+                    let mut leaf = WQuadtreeNode {
+                        waabb: WAABB::new_invalid(),
+                        children: [index as u32, u32::MAX, u32::MAX, u32::MAX],
+                        parent: NodeIndex::new(curr, lane as u8),
+                        leaf: true,
+                        dirty: false,
+                    };
+                    leaf.waabb.replace(0, aabb);
+                    self.nodes.push(leaf);
+
+                    let node = &mut self.nodes[curr as usize];
+                    node.children[lane] = leaf_id;
+                    node.waabb.replace(lane, aabb);
+                    self.proxies[index].node = NodeIndex::new(leaf_id, 0);
+                }
+
+                break self.nodes[curr as usize].parent;
+            } else if node.leaf {
+                // The leaf is full: replace it by an internal node with
+                // the old leaf and a new leaf as children.
+                let parent = node.parent;
+                let leaf_aabb = node.waabb.to_merged_aabb();
+                let internal_id = self.nodes.len() as u32;
+                let leaf_id = internal_id + 1;
+
+                let mut internal = WQuadtreeNode {
+                    waabb: WAABB::new_invalid(),
+                    children: [curr, leaf_id, u32::MAX, u32::MAX],
+                    parent,
+                    leaf: false,
+                    dirty: false,
+                };
+                internal.waabb.replace(0, leaf_aabb);
+                internal.waabb.replace(1, aabb);
+
+                let mut leaf = WQuadtreeNode {
+                    waabb: WAABB::new_invalid(),
+                    children: [index as u32, u32::MAX, u32::MAX, u32::MAX],
+                    parent: NodeIndex::new(internal_id, 1),
+                    leaf: true,
+                    dirty: false,
+                };
+                leaf.waabb.replace(0, aabb);
+
+                self.nodes.push(internal);
+                self.nodes.push(leaf);
+                self.nodes[curr as usize].parent = NodeIndex::new(internal_id, 0);
+                self.nodes[parent.index as usize].children[parent.lane as usize] = internal_id;
+                self.proxies[index].node = NodeIndex::new(leaf_id, 0);
+
+                break parent;
+            } else {
+                // Visit the child which volume would increase the least.
+                let mut best = 0;
+                let mut best_cost = f32::MAX;
+
+                for lane in 0..SIMD_WIDTH {
+                    let child_aabb = node.waabb.extract(lane);
+                    // NOTE: the AABB of an empty child is invalid.
+                    let child_volume = if child_aabb.mins.x <= child_aabb.maxs.x {
+                        child_aabb.extents().iter().product::<f32>()
+                    } else {
+                        0.0
+                    };
+                    let cost =
+                        child_aabb.merged(&aabb).extents().iter().product::<f32>() - child_volume;
+
+                    if cost < best_cost {
+                        best = lane;
+                        best_cost = cost;
+                    }
+                }
+
+                curr = node.children[best];
+            }
+        };
+
+        // Enlarge the ancestors' AABBs so they contain the new proxy.
+        let mut parent = parent;
+        while let Some(node) = self.nodes.get_mut(parent.index as usize) {
+            let lane = parent.lane as usize;
+            let lane_aabb = node.waabb.extract(lane);
+
+            if lane_aabb.contains(&aabb) {
+                break;
+            }
+
+            node.waabb.replace(lane, lane_aabb.merged(&aabb));
+            parent = node.parent;
+        }
+    }
+
+    /// Removes the proxy of the given data from this tree, if it exists.
+    ///
+    /// The AABBs of the ancestors of the removed proxy are refitted, and the nodes left empty
+    /// are detached from the tree. These nodes are only reclaimed when the tree is rebuilt,
+    /// which should be done once `self.is_fragmented()` returns `true`.
+    pub fn remove(&mut self, data: T) {
+        if !self.contains(data) {
+            return;
+        }
+
+        let proxy = &mut self.proxies[data.index()];
+        let mut slot = proxy.node;
+        *proxy = WQuadtreeProxy::invalid();
+
+        // The new AABB of the slot, or `None` if the slot must be emptied.
+        let mut slot_aabb = None;
+
+        while let Some(node) = self.nodes.get_mut(slot.index as usize) {
+            let lane = slot.lane as usize;
+
+            if let Some(aabb) = slot_aabb {
+                node.waabb.replace(lane, aabb);
+            } else {
+                node.children[lane] = u32::MAX;
+                node.waabb.replace(lane, AABB::new_invalid());
+            }
+
+            // NOTE: the root is never detached.
+            if slot.index != 0 && node.children.iter().all(|c| *c == u32::MAX) {
+                self.num_detached_nodes += 1;
+                slot_aabb = None;
+            } else {
+                slot_aabb = Some(node.waabb.to_merged_aabb());
+            }
+
+            slot = node.parent;
+        }
+    }
+
+    /// Checks if enough nodes were detached by removals to justify a rebuild of this tree.
+    pub fn is_fragmented(&self) -> bool {
+        self.num_detached_nodes * 2 > self.nodes.len()
+    }
+
     pub fn clear_and_rebuild(
         &mut self,
         data: impl ExactSizeIterator<Item = (T, AABB)>,
@@ -155,6 +343,8 @@ impl<T: IndexedData> WQuadtree<T> {
     ) {
         self.nodes.clear();
         self.proxies.clear();
+        self.dirty_nodes.clear();
+        self.num_detached_nodes = 0;
 
         // Create proxies.
         let mut indices = Vec::with_capacity(data.len());
@@ -380,6 +570,102 @@ impl<T: IndexedData> WQuadtree<T> {
             }
         }
     }
+
+    /// Visits the leaves of this tree by increasing distance of their AABB to `point`.
+    ///
+    /// The callback is called on the data of each visited leaf. It may return a new, smaller,
+    /// upper bound on the distance: the leaves and nodes with an AABB further than this bound
+    /// from `point` are skipped. The traversal starts with the upper bound `max_dist`.
+    pub fn visit_leaves_by_distance(
+        &self,
+        point: &Point<f32>,
+        max_dist: f32,
+        mut visitor: impl FnMut(T) -> Option<f32>,
+    ) {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let mut bound = max_dist;
+        let mut queue = BinaryHeap::new();
+        let wpoint = Point::splat(*point);
+        queue.push(WQuadtreeQueueEntry {
+            dist: 0.0,
+            id: 0,
+            leaf: false,
+        });
+
+        while let Some(entry) = queue.pop() {
+            if entry.dist > bound {
+                // All the remaining entries are further than the bound.
+                break;
+            }
+
+            if entry.leaf {
+                if let Some(new_bound) = visitor(self.proxies[entry.id as usize].data) {
+                    bound = bound.min(new_bound);
+                }
+                continue;
+            }
+
+            let node = self.nodes[entry.id as usize];
+            let dists = node.waabb.distance_to_point(&wpoint);
+
+            for ii in 0..SIMD_WIDTH {
+                // Invalid AABBs are infinitely far, so they are always skipped here.
+                let dist = dists.extract(ii);
+                if dist > bound {
+                    continue;
+                }
+
+                let child = node.children[ii];
+                let valid = if node.leaf {
+                    (child as usize) < self.proxies.len()
+                } else {
+                    (child as usize) < self.nodes.len()
+                };
+
+                if valid {
+                    queue.push(WQuadtreeQueueEntry {
+                        dist,
+                        id: child,
+                        leaf: node.leaf,
+                    });
+                }
+            }
+        }
+    }
+}
+
+// An entry of the priority queue used by `WQuadtree::visit_leaves_by_distance`.
+// The ordering is reversed so the `BinaryHeap` pops the closest entry first.
+struct WQuadtreeQueueEntry {
+    dist: f32,
+    id: u32,    // Index of a node, or of a proxy if `leaf` is `true`.
+    leaf: bool, // Is this entry a leaf of the tree?
+}
+
+impl PartialEq for WQuadtreeQueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for WQuadtreeQueueEntry {}
+
+impl PartialOrd for WQuadtreeQueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for WQuadtreeQueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .dist
+            .partial_cmp(&self.dist)
+            .unwrap_or(Ordering::Equal)
+    }
 }
 
 #[allow(dead_code)]
@@ -584,4 +870,51 @@ mod test {
             tree.clear_and_rebuild((0..k).map(|i| (i, aabb)), 0.0);
         }
     }
+
+    #[test]
+    fn incremental_insertion_and_removal() {
+        let mut tree = WQuadtree::new();
+
+        for i in 0..50 {
+            let center = Point::from(Vector::repeat(i as f32 * 2.0));
+            let aabb = AABB::from_half_extents(center, Vector::repeat(0.5));
+            tree.insert(i, aabb, 0.0);
+        }
+
+        for i in (0..50).step_by(2) {
+            tree.remove(i);
+        }
+
+        for i in 0..50 {
+            let center = Point::from(Vector::repeat(i as f32 * 2.0));
+            let mut found = Vec::new();
+            tree.intersect_aabb(&AABB::new(center, center), &mut found);
+
+            assert_eq!(tree.contains(i), i % 2 == 1);
+            if i % 2 == 1 {
+                assert_eq!(found, vec![i]);
+            } else {
+                assert!(found.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn removal_refits_ancestors() {
+        let mut tree = WQuadtree::new();
+        let aabbs: Vec<_> = (0..20)
+            .map(|i| {
+                let center = Point::from(Vector::repeat(i as f32 * 2.0));
+                AABB::from_half_extents(center, Vector::repeat(0.5))
+            })
+            .collect();
+        tree.clear_and_rebuild(aabbs.iter().copied().enumerate(), 0.0);
+
+        for i in 1..20 {
+            tree.remove(i);
+        }
+
+        assert_eq!(tree.nodes[0].waabb.to_merged_aabb(), aabbs[0]);
+        assert!(tree.is_fragmented());
+    }
 }
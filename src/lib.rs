@@ -144,6 +144,8 @@ pub mod math {
 
     /// The dimension of the physics simulated by this crate.
     pub const DIM: usize = 2;
+    /// The maximum number of angular degrees of freedom of a rigid body.
+    pub const ANG_DIM: usize = 1;
     /// The maximum number of point a contact manifold can hold.
     pub const MAX_MANIFOLD_POINTS: usize = 2;
     /// The dimension of the physics simulated by this crate, given as a type-level-integer.
@@ -186,6 +188,8 @@ pub mod math {
 
     /// The dimension of the physics simulated by this crate.
     pub const DIM: usize = 3;
+    /// The maximum number of angular degrees of freedom of a rigid body.
+    pub const ANG_DIM: usize = 3;
     /// The maximum number of point a contact manifold can hold.
     pub const MAX_MANIFOLD_POINTS: usize = 4;
     /// The dimension of the physics simulated by this crate, given as a type-level-integer.
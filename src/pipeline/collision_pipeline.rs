@@ -2,8 +2,8 @@
 
 use crate::dynamics::{JointSet, RigidBodySet};
 use crate::geometry::{
-    BroadPhase, BroadPhasePairEvent, ColliderPair, ColliderSet, ContactPairFilter, NarrowPhase,
-    ProximityPairFilter,
+    BroadPhase, BroadPhasePairEvent, ColliderPair, ColliderSet, ContactModifier, ContactPairFilter,
+    NarrowPhase, ProximityPairFilter,
 };
 use crate::pipeline::EventHandler;
 
@@ -45,9 +45,11 @@ impl CollisionPipeline {
         colliders: &mut ColliderSet,
         contact_pair_filter: Option<&dyn ContactPairFilter>,
         proximity_pair_filter: Option<&dyn ProximityPairFilter>,
+        contact_modifier: Option<&dyn ContactModifier>,
         events: &dyn EventHandler,
     ) {
         bodies.maintain_active_set();
+        bodies.update_modified_inactive_colliders(colliders);
         self.broadphase_collider_pairs.clear();
 
         broad_phase.update_aabbs(prediction_distance, bodies, colliders);
@@ -62,6 +64,7 @@ impl CollisionPipeline {
             bodies,
             colliders,
             contact_pair_filter,
+            contact_modifier,
             events,
         );
         narrow_phase.compute_proximities(
@@ -73,10 +76,12 @@ impl CollisionPipeline {
         );
 
         bodies.update_active_set_with_contacts(
+            0.0,
             colliders,
             narrow_phase.contact_graph(),
             self.empty_joints.joint_graph(),
             0,
+            events,
         );
 
         // // Update kinematic bodies velocities.
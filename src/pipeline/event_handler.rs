@@ -1,4 +1,5 @@
-use crate::geometry::{ContactEvent, ProximityEvent};
+use crate::dynamics::SleepEvent;
+use crate::geometry::{ContactEvent, ContactForceEvent, ProximityEvent};
 use crossbeam::channel::Sender;
 
 /// Trait implemented by structures responsible for handling events generated by the physics engine.
@@ -14,21 +15,38 @@ pub trait EventHandler: Send + Sync {
     /// A contact event is emitted when two collider start or stop touching, independently from the
     /// number of contact points involved.
     fn handle_contact_event(&self, event: ContactEvent);
+    /// Handle a contact force event.
+    ///
+    /// A contact force event is emitted after the constraints solver when the sum of the magnitudes
+    /// of the contact forces between two colliders exceeds the contact force event threshold
+    /// of one of them.
+    fn handle_contact_force_event(&self, event: ContactForceEvent);
+    /// Handle a sleep event.
+    ///
+    /// A sleep event is emitted when a dynamic rigid-body falls asleep or wakes up.
+    fn handle_sleep_event(&self, event: SleepEvent);
 }
 
 impl EventHandler for () {
     fn handle_proximity_event(&self, _event: ProximityEvent) {}
     fn handle_contact_event(&self, _event: ContactEvent) {}
+    fn handle_contact_force_event(&self, _event: ContactForceEvent) {}
+    fn handle_sleep_event(&self, _event: SleepEvent) {}
 }
 
 /// A physics event handler that collects events into a crossbeam channel.
 pub struct ChannelEventCollector {
     proximity_event_sender: Sender<ProximityEvent>,
     contact_event_sender: Sender<ContactEvent>,
+    contact_force_event_sender: Option<Sender<ContactForceEvent>>,
+    sleep_event_sender: Option<Sender<SleepEvent>>,
 }
 
 impl ChannelEventCollector {
     /// Initialize a new physics event handler from crossbeam channel senders.
+    ///
+    /// Contact force events and sleep events are not collected unless a sender is given with
+    /// `with_contact_force_event_sender` and `with_sleep_event_sender` respectively.
     pub fn new(
         proximity_event_sender: Sender<ProximityEvent>,
         contact_event_sender: Sender<ContactEvent>,
@@ -36,8 +54,25 @@ impl ChannelEventCollector {
         Self {
             proximity_event_sender,
             contact_event_sender,
+            contact_force_event_sender: None,
+            sleep_event_sender: None,
         }
     }
+
+    /// Sets the crossbeam channel sender the contact force events are sent to.
+    pub fn with_contact_force_event_sender(
+        mut self,
+        contact_force_event_sender: Sender<ContactForceEvent>,
+    ) -> Self {
+        self.contact_force_event_sender = Some(contact_force_event_sender);
+        self
+    }
+
+    /// Sets the crossbeam channel sender the sleep events are sent to.
+    pub fn with_sleep_event_sender(mut self, sleep_event_sender: Sender<SleepEvent>) -> Self {
+        self.sleep_event_sender = Some(sleep_event_sender);
+        self
+    }
 }
 
 impl EventHandler for ChannelEventCollector {
@@ -48,4 +83,16 @@ impl EventHandler for ChannelEventCollector {
     fn handle_contact_event(&self, event: ContactEvent) {
         let _ = self.contact_event_sender.send(event);
     }
+
+    fn handle_contact_force_event(&self, event: ContactForceEvent) {
+        if let Some(sender) = &self.contact_force_event_sender {
+            let _ = sender.send(event);
+        }
+    }
+
+    fn handle_sleep_event(&self, event: SleepEvent) {
+        if let Some(sender) = &self.sleep_event_sender {
+            let _ = sender.send(event);
+        }
+    }
 }
@@ -9,3 +9,5 @@ mod collision_pipeline;
 mod event_handler;
 mod physics_pipeline;
 mod query_pipeline;
+#[cfg(test)]
+pub(crate) mod test_world;
@@ -3,12 +3,12 @@
 use crate::counters::Counters;
 #[cfg(not(feature = "parallel"))]
 use crate::dynamics::IslandSolver;
-use crate::dynamics::{IntegrationParameters, JointSet, RigidBodySet};
+use crate::dynamics::{CCDSolver, IntegrationParameters, JointSet, RigidBodySet};
 #[cfg(feature = "parallel")]
 use crate::dynamics::{JointGraphEdge, ParallelIslandSolver as IslandSolver};
 use crate::geometry::{
     BroadPhase, BroadPhasePairEvent, ColliderPair, ColliderSet, ContactManifoldIndex,
-    ContactPairFilter, NarrowPhase, ProximityPairFilter,
+    ContactModifier, ContactPairFilter, NarrowPhase, ProximityPairFilter,
 };
 use crate::math::Vector;
 use crate::pipeline::EventHandler;
@@ -32,6 +32,7 @@ pub struct PhysicsPipeline {
     broadphase_collider_pairs: Vec<ColliderPair>,
     broad_phase_events: Vec<BroadPhasePairEvent>,
     solvers: Vec<IslandSolver>,
+    ccd_solver: CCDSolver,
 }
 
 impl Default for PhysicsPipeline {
@@ -56,6 +57,7 @@ impl PhysicsPipeline {
             joint_constraint_indices: Vec::new(),
             broadphase_collider_pairs: Vec::new(),
             broad_phase_events: Vec::new(),
+            ccd_solver: CCDSolver::new(),
         }
     }
 
@@ -71,12 +73,14 @@ impl PhysicsPipeline {
         joints: &mut JointSet,
         contact_pair_filter: Option<&dyn ContactPairFilter>,
         proximity_pair_filter: Option<&dyn ProximityPairFilter>,
+        contact_modifier: Option<&dyn ContactModifier>,
         events: &dyn EventHandler,
     ) {
         self.counters.step_started();
         broad_phase.maintain(colliders);
         narrow_phase.maintain(colliders, bodies);
         bodies.maintain_active_set();
+        bodies.update_modified_inactive_colliders(colliders);
 
         // Update kinematic bodies velocities.
         // TODO: what is the best place for this? It should at least be
@@ -116,6 +120,7 @@ impl PhysicsPipeline {
             bodies,
             colliders,
             contact_pair_filter,
+            contact_modifier,
             events,
         );
         narrow_phase.compute_proximities(
@@ -129,10 +134,12 @@ impl PhysicsPipeline {
 
         self.counters.stages.island_construction_time.start();
         bodies.update_active_set_with_contacts(
+            integration_parameters.dt(),
             colliders,
             narrow_phase.contact_graph(),
             joints.joint_graph(),
             integration_parameters.min_island_size,
+            events,
         );
         self.counters.stages.island_construction_time.pause();
 
@@ -164,6 +171,8 @@ impl PhysicsPipeline {
         });
         self.counters.stages.update_time.pause();
 
+        self.ccd_solver.init(integration_parameters, bodies);
+
         self.counters.solver.reset();
         self.counters.stages.solver_time.start();
         if self.solvers.len() < bodies.num_islands() {
@@ -231,6 +240,29 @@ impl PhysicsPipeline {
             });
         }
 
+        self.counters.stages.solver_time.pause();
+
+        narrow_phase.emit_contact_force_events(
+            integration_parameters.dt(),
+            bodies,
+            colliders,
+            events,
+        );
+
+        self.counters.stages.ccd_time.start();
+        self.ccd_solver.solve(
+            integration_parameters,
+            bodies,
+            colliders,
+            contact_pair_filter,
+            proximity_pair_filter,
+            events,
+            &mut self.counters,
+        );
+        self.counters.stages.ccd_time.pause();
+
+        self.counters.stages.solver_time.resume();
+
         // Update colliders positions and kinematic bodies positions.
         // FIXME: do this in the solver?
         bodies.foreach_active_body_mut_internal(|_, rb| {
@@ -292,6 +324,7 @@ mod test {
             &mut joints,
             None,
             None,
+            None,
             &(),
         );
     }
@@ -336,6 +369,7 @@ mod test {
             &mut joints,
             None,
             None,
+            None,
             &(),
         );
     }
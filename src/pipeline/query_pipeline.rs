@@ -1,13 +1,19 @@
+use crate::data::pubsub::Subscription;
 use crate::dynamics::RigidBodySet;
 use crate::geometry::{
-    Collider, ColliderHandle, ColliderSet, InteractionGroups, Ray, RayIntersection, WQuadtree,
+    Collider, ColliderHandle, ColliderSet, InteractionGroups, PointProjection, Ray,
+    RayIntersection, RemovedCollider, Shape, WQuadtree, AABB, TOI,
 };
+use crate::math::{Isometry, Point, Vector};
+use ncollide::bounding_volume::BoundingVolume;
 
 /// A pipeline for performing queries on all the colliders of a scene.
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 #[derive(Clone)]
 pub struct QueryPipeline {
     quadtree: WQuadtree<ColliderHandle>,
+    removed_colliders: Option<Subscription<RemovedCollider>>,
+    modified_colliders: Option<Subscription<ColliderHandle>>,
     tree_built: bool,
     dilation_factor: f32,
 }
@@ -23,21 +29,69 @@ impl QueryPipeline {
     pub fn new() -> Self {
         Self {
             quadtree: WQuadtree::new(),
+            removed_colliders: None,
+            modified_colliders: None,
             tree_built: false,
             dilation_factor: 0.01,
         }
     }
 
     /// Update the acceleration structure on the query pipeline.
-    pub fn update(&mut self, bodies: &RigidBodySet, colliders: &ColliderSet) {
+    ///
+    /// The acceleration structure is built the first time this is called. Subsequent calls
+    /// only remove the colliders removed from `colliders`, insert the new colliders, and refit
+    /// the modified colliders and the colliders attached to active dynamic or kinematic bodies.
+    /// The colliders attached to static or sleeping bodies moved by the user are refit once
+    /// these new positions have been taken into account by a timestep.
+    /// The acceleration structure is rebuilt when too many colliders have been removed.
+    pub fn update(&mut self, bodies: &RigidBodySet, colliders: &mut ColliderSet) {
+        // Ensure we already subscribed.
+        if self.removed_colliders.is_none() {
+            self.removed_colliders = Some(colliders.removed_colliders.subscribe());
+        }
+
+        if self.modified_colliders.is_none() {
+            self.modified_colliders = Some(colliders.modified_colliders.subscribe());
+        }
+
+        let removed = self.removed_colliders.take().unwrap();
+        let modified = self.modified_colliders.take().unwrap();
+
+        if self.tree_built {
+            for collider in colliders.removed_colliders.read(&removed) {
+                self.quadtree.remove(collider.handle);
+            }
+
+            if self.quadtree.is_fragmented() {
+                self.tree_built = false;
+            }
+        }
+
         if !self.tree_built {
             let data = colliders.iter().map(|(h, c)| (h, c.compute_aabb()));
             self.quadtree.clear_and_rebuild(data, self.dilation_factor);
-            // FIXME: uncomment this once we handle insertion/removals properly.
-            // self.tree_built = true;
-            return;
+            self.tree_built = true;
+        } else {
+            for handle in colliders.modified_colliders.read(&modified) {
+                if let Some(collider) = colliders.get(*handle) {
+                    if self.quadtree.contains(*handle) {
+                        self.quadtree.pre_update(*handle);
+                    } else {
+                        self.quadtree.insert(
+                            *handle,
+                            collider.compute_aabb(),
+                            self.dilation_factor,
+                        );
+                    }
+                }
+            }
         }
 
+        colliders.removed_colliders.ack(&removed);
+        colliders.modified_colliders.ack(&modified);
+        self.removed_colliders = Some(removed);
+        self.modified_colliders = Some(modified);
+
         for (_, body) in bodies
             .iter_active_dynamic()
             .chain(bodies.iter_active_kinematic())
@@ -131,4 +185,471 @@ impl QueryPipeline {
             }
         }
     }
+
+    /// Find the first collider hit by a shape moving with a constant linear velocity.
+    ///
+    /// # Parameters
+    /// - `shape_pos`: the initial position of the shape to cast.
+    /// - `shape_vel`: the constant linear velocity of the shape to cast.
+    /// - `shape`: the shape to cast.
+    /// - `max_toi`: the maximum time-of-impact that can be reported by this cast. This effectively
+    ///   limits the distance traveled by the shape to `shape_vel.norm() * max_toi`.
+    /// - `groups`: the interaction groups the colliders must be compatible with to be hit.
+    ///
+    /// The witness points and normals of the returned `TOI` are expressed in the local-space of
+    /// the cast shape (`witness1`, `normal1`) and of the collider shape (`witness2`, `normal2`).
+    pub fn cast_shape<'a>(
+        &self,
+        colliders: &'a ColliderSet,
+        shape_pos: &Isometry<f32>,
+        shape_vel: &Vector<f32>,
+        shape: &dyn Shape,
+        max_toi: f32,
+        groups: InteractionGroups,
+    ) -> Option<(ColliderHandle, &'a Collider, TOI)> {
+        let start_aabb = shape.compute_aabb(shape_pos);
+        let mut end_aabb = start_aabb;
+        end_aabb.mins += shape_vel * max_toi;
+        end_aabb.maxs += shape_vel * max_toi;
+        let swept_aabb = start_aabb.merged(&end_aabb);
+
+        // TODO: avoid allocation?
+        let mut inter = Vec::new();
+        self.quadtree.intersect_aabb(&swept_aabb, &mut inter);
+
+        let mut best = max_toi;
+        let mut result = None;
+
+        for handle in inter {
+            if let Some(collider) = colliders.get(handle) {
+                if collider.collision_groups.test(groups) {
+                    if let Some(toi) = crate::geometry::time_of_impact(
+                        shape_pos,
+                        shape_vel,
+                        shape,
+                        collider.position(),
+                        &Vector::zeros(),
+                        collider.shape(),
+                        best,
+                        0.0,
+                    ) {
+                        if result.is_none() || toi.toi < best {
+                            best = toi.toi;
+                            result = Some((handle, collider, toi));
+                        }
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Find the projection of a point on the closest collider.
+    ///
+    /// # Parameters
+    /// - `point`: the point to project.
+    /// - `solid`: if this is `true` a point inside of a shape will be projected on itself with
+    ///   a distance of zero. Otherwise, it will be projected on the boundary of the shape.
+    /// - `max_dist`: the maximum distance between the point and the projected point. Colliders
+    ///   further than this distance are ignored. Use `f32::MAX` for an unbounded distance.
+    /// - `groups`: the interaction groups the colliders must be compatible with.
+    pub fn project_point<'a>(
+        &self,
+        colliders: &'a ColliderSet,
+        point: &Point<f32>,
+        solid: bool,
+        max_dist: f32,
+        groups: InteractionGroups,
+    ) -> Option<(ColliderHandle, &'a Collider, PointProjection)> {
+        let mut best = max_dist;
+        let mut result = None;
+
+        // Visit the colliders from the closest AABB to the furthest, and stop as soon as
+        // the remaining AABBs are further than the closest projection found so far.
+        self.quadtree
+            .visit_leaves_by_distance(point, max_dist, |handle| {
+                let collider = colliders.get(handle)?;
+
+                if !collider.collision_groups.test(groups) {
+                    return None;
+                }
+
+                let proj = collider
+                    .shape()
+                    .project_point(collider.position(), point, solid);
+                let dist = na::distance(point, &proj.point);
+
+                if dist <= best && (result.is_none() || dist < best) {
+                    best = dist;
+                    result = Some((handle, collider, proj));
+                    Some(dist)
+                } else {
+                    None
+                }
+            });
+
+        result
+    }
+
+    /// Find all the colliders containing the given point and passes them to a callback.
+    ///
+    /// # Parameters
+    /// - `point`: the point to test.
+    /// - `groups`: the interaction groups the colliders must be compatible with.
+    /// - `callback`: function executed on each collider containing the point.
+    ///   There is no guarantees on the order the results will be yielded. If this callback returns `false`,
+    ///   this method will exit early, ignoring any further collider.
+    pub fn intersections_with_point<'a>(
+        &self,
+        colliders: &'a ColliderSet,
+        point: &Point<f32>,
+        groups: InteractionGroups,
+        mut callback: impl FnMut(ColliderHandle, &'a Collider) -> bool,
+    ) {
+        // TODO: avoid allocation?
+        let mut inter = Vec::new();
+        self.quadtree
+            .intersect_aabb(&AABB::new(*point, *point), &mut inter);
+
+        for handle in inter {
+            if let Some(collider) = colliders.get(handle) {
+                if collider.collision_groups.test(groups)
+                    && collider.shape().contains_point(collider.position(), point)
+                {
+                    if !callback(handle, collider) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Find all the colliders intersecting the given shape and passes them to a callback.
+    ///
+    /// # Parameters
+    /// - `shape_pos`: the position of the shape to test.
+    /// - `shape`: the shape to test.
+    /// - `groups`: the interaction groups the colliders must be compatible with.
+    /// - `callback`: function executed on each collider intersecting the shape.
+    ///   There is no guarantees on the order the results will be yielded. If this callback returns `false`,
+    ///   this method will exit early, ignoring any further collider.
+    pub fn intersections_with_shape<'a>(
+        &self,
+        colliders: &'a ColliderSet,
+        shape_pos: &Isometry<f32>,
+        shape: &dyn Shape,
+        groups: InteractionGroups,
+        mut callback: impl FnMut(ColliderHandle, &'a Collider) -> bool,
+    ) {
+        // TODO: avoid allocation?
+        let mut inter = Vec::new();
+        self.quadtree
+            .intersect_aabb(&shape.compute_aabb(shape_pos), &mut inter);
+
+        for handle in inter {
+            if let Some(collider) = colliders.get(handle) {
+                if collider.collision_groups.test(groups)
+                    && crate::geometry::intersection_test(
+                        shape_pos,
+                        shape,
+                        collider.position(),
+                        collider.shape(),
+                    )
+                {
+                    if !callback(handle, collider) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Find all the colliders with an AABB intersecting the given AABB and passes them to a callback.
+    ///
+    /// The AABBs tested are the ones stored in the acceleration structure of this pipeline, which
+    /// are slightly enlarged versions of the collider AABBs computed at the last `update`.
+    ///
+    /// # Parameters
+    /// - `aabb`: the AABB to test.
+    /// - `callback`: function executed on each collider with an AABB intersecting `aabb`.
+    ///   There is no guarantees on the order the results will be yielded. If this callback returns `false`,
+    ///   this method will exit early, ignoring any further collider.
+    pub fn colliders_with_aabb_intersecting_aabb(
+        &self,
+        aabb: &AABB,
+        mut callback: impl FnMut(ColliderHandle) -> bool,
+    ) {
+        // TODO: avoid allocation?
+        let mut inter = Vec::new();
+        self.quadtree.intersect_aabb(aabb, &mut inter);
+
+        for handle in inter {
+            if !callback(handle) {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::QueryPipeline;
+    use crate::dynamics::{RigidBodyBuilder, RigidBodySet};
+    use crate::geometry::ColliderHandle;
+    use crate::geometry::{Ball, ColliderBuilder, ColliderSet, InteractionGroups, AABB};
+    use crate::math::{Isometry, Point, Vector};
+    use crate::pipeline::test_world::TestWorld;
+
+    // Two balls of radius 0.5 centered at x = 3 and x = 6.
+    fn two_balls() -> (
+        RigidBodySet,
+        ColliderSet,
+        Vec<ColliderHandle>,
+        QueryPipeline,
+    ) {
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+
+        let mut handles = Vec::new();
+        for x in &[3.0, 6.0] {
+            #[cfg(feature = "dim2")]
+            let rb = RigidBodyBuilder::new_static().translation(*x, 0.0).build();
+            #[cfg(feature = "dim3")]
+            let rb = RigidBodyBuilder::new_static()
+                .translation(*x, 0.0, 0.0)
+                .build();
+            let body = bodies.insert(rb);
+            handles.push(colliders.insert(ColliderBuilder::ball(0.5).build(), body, &mut bodies));
+        }
+
+        let mut pipeline = QueryPipeline::new();
+        pipeline.update(&bodies, &mut colliders);
+        (bodies, colliders, handles, pipeline)
+    }
+
+    #[test]
+    fn cast_shape_hits_closest_collider() {
+        let (_, colliders, handles, pipeline) = two_balls();
+
+        let shape = Ball::new(0.5);
+        let (handle, _, toi) = pipeline
+            .cast_shape(
+                &colliders,
+                &Isometry::identity(),
+                &Vector::x(),
+                &shape,
+                10.0,
+                InteractionGroups::all(),
+            )
+            .unwrap();
+
+        assert_eq!(handle, handles[0]);
+        assert!((toi.toi - 2.0).abs() < 1.0e-3);
+        assert!(pipeline
+            .cast_shape(
+                &colliders,
+                &Isometry::identity(),
+                &Vector::x(),
+                &shape,
+                1.5,
+                InteractionGroups::all(),
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn point_queries() {
+        let (_, colliders, handles, pipeline) = two_balls();
+        let groups = InteractionGroups::all();
+
+        let point = Point::from(Vector::x() * 5.0);
+        let (handle, _, proj) = pipeline
+            .project_point(&colliders, &point, true, f32::MAX, groups)
+            .unwrap();
+        assert_eq!(handle, handles[1]);
+        assert!((proj.point.x - 5.5).abs() < 1.0e-5);
+        assert!(pipeline
+            .project_point(&colliders, &point, true, 0.4, groups)
+            .is_none());
+
+        let mut found = Vec::new();
+        let point = Point::from(Vector::x() * 3.2);
+        pipeline.intersections_with_point(&colliders, &point, groups, |handle, _| {
+            found.push(handle);
+            true
+        });
+        assert_eq!(found, vec![handles[0]]);
+    }
+
+    #[test]
+    fn project_point_on_closest_of_many_colliders() {
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+
+        // Insert the balls from the furthest to the closest to the origin.
+        let mut handles = Vec::new();
+        for i in (1..40).rev() {
+            let x = i as f32 * 2.0;
+            #[cfg(feature = "dim2")]
+            let rb = RigidBodyBuilder::new_static().translation(x, 0.0).build();
+            #[cfg(feature = "dim3")]
+            let rb = RigidBodyBuilder::new_static()
+                .translation(x, 0.0, 0.0)
+                .build();
+            let body = bodies.insert(rb);
+            handles.push(colliders.insert(ColliderBuilder::ball(0.5).build(), body, &mut bodies));
+        }
+
+        let mut pipeline = QueryPipeline::new();
+        pipeline.update(&bodies, &mut colliders);
+
+        let groups = InteractionGroups::all();
+        let (handle, _, proj) = pipeline
+            .project_point(&colliders, &Point::origin(), true, f32::MAX, groups)
+            .unwrap();
+        assert_eq!(handle, *handles.last().unwrap());
+        assert!((proj.point.x - 1.5).abs() < 1.0e-5);
+
+        // The closest ball is centered at x = 40.
+        let point = Point::from(Vector::x() * 40.8);
+        let (handle, _, _) = pipeline
+            .project_point(&colliders, &point, true, f32::MAX, groups)
+            .unwrap();
+        assert_eq!(handle, handles[19]);
+    }
+
+    #[test]
+    fn shape_and_aabb_intersections() {
+        let (_, colliders, handles, pipeline) = two_balls();
+        let groups = InteractionGroups::all();
+
+        let mut found = Vec::new();
+        let shape = Ball::new(1.0);
+        let shape_pos = Isometry::new(Vector::x() * 4.0, na::zero());
+        pipeline.intersections_with_shape(&colliders, &shape_pos, &shape, groups, |handle, _| {
+            found.push(handle);
+            true
+        });
+        assert_eq!(found, vec![handles[0]]);
+
+        found.clear();
+        let aabb = AABB::new(
+            Point::from(Vector::x() * 2.0),
+            Point::from(Vector::x() * 7.0),
+        );
+        pipeline.colliders_with_aabb_intersecting_aabb(&aabb, |handle| {
+            found.push(handle);
+            true
+        });
+        found.sort_by_key(|h| h.into_raw_parts());
+        assert_eq!(found, handles);
+    }
+
+    #[test]
+    fn incremental_update() {
+        let (mut bodies, mut colliders, handles, mut pipeline) = two_balls();
+        let groups = InteractionGroups::all();
+
+        let body = colliders[handles[0]].parent();
+        colliders.remove(handles[0], &mut bodies, true);
+        let new_handle = colliders.insert(ColliderBuilder::ball(0.5).build(), body, &mut bodies);
+        pipeline.update(&bodies, &mut colliders);
+
+        let mut found = Vec::new();
+        let point = Point::from(Vector::x() * 3.0);
+        pipeline.intersections_with_point(&colliders, &point, groups, |handle, _| {
+            found.push(handle);
+            true
+        });
+        assert_eq!(found, vec![new_handle]);
+
+        colliders.remove(new_handle, &mut bodies, true);
+        pipeline.update(&bodies, &mut colliders);
+        let (handle, _, _) = pipeline
+            .project_point(&colliders, &point, true, f32::MAX, groups)
+            .unwrap();
+        assert_eq!(handle, handles[1]);
+    }
+
+    #[test]
+    fn moved_static_body_is_refit() {
+        let mut world = TestWorld::new();
+        let mut pipeline = QueryPipeline::new();
+        let groups = InteractionGroups::all();
+
+        let body = world.bodies.insert(RigidBodyBuilder::new_static().build());
+        let handle = world.insert_collider(ColliderBuilder::ball(0.5).build(), body);
+        world.step();
+        pipeline.update(&world.bodies, &mut world.colliders);
+
+        let new_pos = Isometry::new(Vector::x() * 10.0, na::zero());
+        world
+            .bodies
+            .get_mut(body)
+            .unwrap()
+            .set_position(new_pos, true);
+        world.step();
+        pipeline.update(&world.bodies, &mut world.colliders);
+
+        let mut found = Vec::new();
+        let point = Point::from(Vector::x() * 10.2);
+        pipeline.intersections_with_point(&world.colliders, &point, groups, |handle, _| {
+            found.push(handle);
+            true
+        });
+        assert_eq!(found, vec![handle]);
+
+        let mut found = Vec::new();
+        pipeline.intersections_with_point(
+            &world.colliders,
+            &Point::origin(),
+            groups,
+            |handle, _| {
+                found.push(handle);
+                true
+            },
+        );
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn removals_rebuild_fragmented_tree() {
+        let (mut bodies, mut colliders, handles, mut pipeline) = two_balls();
+        let groups = InteractionGroups::all();
+        let body = colliders[handles[0]].parent();
+
+        let mut added = Vec::new();
+        for i in 0..100 {
+            let pos = Isometry::new(Vector::y() * (10.0 + i as f32 * 2.0), na::zero());
+            let collider = ColliderBuilder::ball(0.5).position(pos).build();
+            added.push(colliders.insert(collider, body, &mut bodies));
+        }
+
+        pipeline.update(&bodies, &mut colliders);
+
+        for handle in added {
+            colliders.remove(handle, &mut bodies, true);
+        }
+
+        pipeline.update(&bodies, &mut colliders);
+        assert!(!pipeline.quadtree.is_fragmented());
+
+        let mut found = Vec::new();
+        let aabb = AABB::new(
+            Point::from(Vector::y() * -100.0),
+            Point::from(Vector::y() * 300.0),
+        );
+        pipeline.colliders_with_aabb_intersecting_aabb(&aabb, |handle| {
+            found.push(handle);
+            true
+        });
+        assert!(found.is_empty());
+
+        let point = Point::from(Vector::x() * 5.0);
+        let (handle, _, _) = pipeline
+            .project_point(&colliders, &point, true, f32::MAX, groups)
+            .unwrap();
+        assert_eq!(handle, handles[1]);
+    }
 }
@@ -0,0 +1,112 @@
+//! A small physics world shared by the unit tests.
+
+use crate::dynamics::{
+    IntegrationParameters, JointSet, RigidBody, RigidBodyBuilder, RigidBodyHandle, RigidBodySet,
+};
+use crate::geometry::{
+    BroadPhase, Collider, ColliderBuilder, ColliderHandle, ColliderSet, ColliderShape,
+    ContactModifier, NarrowPhase,
+};
+use crate::math::Vector;
+use crate::pipeline::{EventHandler, PhysicsPipeline};
+
+/// All the structures needed to step a simulation with the `PhysicsPipeline`.
+pub(crate) struct TestWorld {
+    pub gravity: Vector<f32>,
+    pub params: IntegrationParameters,
+    pub pipeline: PhysicsPipeline,
+    pub broad_phase: BroadPhase,
+    pub narrow_phase: NarrowPhase,
+    pub bodies: RigidBodySet,
+    pub colliders: ColliderSet,
+    pub joints: JointSet,
+}
+
+impl TestWorld {
+    /// An empty world without gravity.
+    pub fn new() -> Self {
+        TestWorld {
+            gravity: Vector::zeros(),
+            params: IntegrationParameters::default(),
+            pipeline: PhysicsPipeline::new(),
+            broad_phase: BroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            joints: JointSet::new(),
+        }
+    }
+
+    /// An empty world with a gravity of `-9.81` along the `y` axis.
+    pub fn with_gravity() -> Self {
+        let mut world = Self::new();
+        world.gravity.y = -9.81;
+        world
+    }
+
+    /// Inserts a rigid-body, and attaches the given colliders to it.
+    pub fn insert(
+        &mut self,
+        body: RigidBody,
+        colliders: impl IntoIterator<Item = Collider>,
+    ) -> RigidBodyHandle {
+        let handle = self.bodies.insert(body);
+
+        for collider in colliders {
+            self.colliders.insert(collider, handle, &mut self.bodies);
+        }
+
+        handle
+    }
+
+    /// Inserts a collider attached to the given rigid-body.
+    pub fn insert_collider(
+        &mut self,
+        collider: Collider,
+        parent: RigidBodyHandle,
+    ) -> ColliderHandle {
+        self.colliders.insert(collider, parent, &mut self.bodies)
+    }
+
+    /// Inserts a static flat ground, as a cuboid of half-height `0.1` centered at the origin.
+    pub fn insert_ground(&mut self, half_width: f32) -> (RigidBodyHandle, ColliderHandle) {
+        let mut half_extents = Vector::repeat(half_width);
+        half_extents.y = 0.1;
+        let body = self.bodies.insert(RigidBodyBuilder::new_static().build());
+        let collider = ColliderBuilder::new(ColliderShape::cuboid(half_extents)).build();
+        (body, self.insert_collider(collider, body))
+    }
+
+    /// Executes one timestep, without any contact modifier or event handler.
+    pub fn step(&mut self) {
+        self.step_with(None, &())
+    }
+
+    /// Executes one timestep with the given contact modifier and event handler.
+    pub fn step_with(
+        &mut self,
+        contact_modifier: Option<&dyn ContactModifier>,
+        events: &dyn EventHandler,
+    ) {
+        self.pipeline.step(
+            &self.gravity,
+            &self.params,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.joints,
+            None,
+            None,
+            contact_modifier,
+            events,
+        );
+    }
+
+    /// Executes `num_steps` timesteps.
+    pub fn run(&mut self, num_steps: usize) {
+        for _ in 0..num_steps {
+            self.step();
+        }
+    }
+}
@@ -642,6 +642,18 @@ impl<N: SimdRealField> SdpMatrix3<N> {
         }
     }
 
+    /// Create a new diagonal SDP matrix with its diagonal set to the components of `elts`.
+    pub fn from_diagonal_elements(elts: Vector3<N>) -> Self {
+        Self {
+            m11: elts.x,
+            m12: N::zero(),
+            m13: N::zero(),
+            m22: elts.y,
+            m23: N::zero(),
+            m33: elts.z,
+        }
+    }
+
     /// Are all components of this matrix equal to zero?
     pub fn is_zero(&self) -> bool {
         self.m11.is_zero()
@@ -732,6 +744,18 @@ impl<N: SimdRealField> SdpMatrix3<N> {
             m33: self.m33 + elt,
         }
     }
+
+    /// Adds the components of `elts` to the corresponding diagonal components of `self`.
+    pub fn add_diagonal_elements(&self, elts: Vector3<N>) -> Self {
+        Self {
+            m11: self.m11 + elts.x,
+            m12: self.m12,
+            m13: self.m13,
+            m22: self.m22 + elts.y,
+            m23: self.m23,
+            m33: self.m33 + elts.z,
+        }
+    }
 }
 
 impl<N: Add<N>> Add<SdpMatrix3<N>> for SdpMatrix3<N> {
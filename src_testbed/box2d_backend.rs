@@ -120,6 +120,24 @@ impl Box2dWorld {
 
                     self.world.create_joint(&def);
                 }
+                JointParams::RevoluteJoint(params) => {
+                    let def = RevoluteJointDef {
+                        body_a,
+                        body_b,
+                        collide_connected: true,
+                        local_anchor_a: na_vec_to_b2_vec(params.local_anchor1.coords),
+                        local_anchor_b: na_vec_to_b2_vec(params.local_anchor2.coords),
+                        reference_angle: 0.0,
+                        enable_limit: params.limits_enabled,
+                        lower_angle: params.limits[0],
+                        upper_angle: params.limits[1],
+                        enable_motor: false,
+                        motor_speed: 0.0,
+                        max_motor_torque: 0.0,
+                    };
+
+                    self.world.create_joint(&def);
+                }
                 JointParams::FixedJoint(params) => {
                     let def = WeldJointDef {
                         body_a,
@@ -6,15 +6,14 @@ use kiss3d::window::Window;
 
 use na::Point3;
 
-use crate::math::Point;
+use crate::math::{Isometry, Point};
 use crate::objects::ball::Ball;
 use crate::objects::box_node::Box as BoxNode;
 use crate::objects::heightfield::HeightField;
 use crate::objects::node::{GraphicsNode, Node};
 use rapier::dynamics::{RigidBodyHandle, RigidBodySet};
-use rapier::geometry::{Collider, ColliderHandle, ColliderSet};
+use rapier::geometry::{Collider, ColliderHandle, ColliderSet, Shape};
 //use crate::objects::capsule::Capsule;
-//use crate::objects::convex::Convex;
 //#[cfg(feature = "dim3")]
 //use crate::objects::mesh::Mesh;
 //use crate::objects::plane::Plane;
@@ -23,6 +22,8 @@ use rapier::geometry::{Collider, ColliderHandle, ColliderSet};
 use crate::objects::capsule::Capsule;
 #[cfg(feature = "dim3")]
 use crate::objects::cone::Cone;
+#[cfg(feature = "dim2")]
+use crate::objects::convex::Convex;
 #[cfg(feature = "dim3")]
 use crate::objects::cylinder::Cylinder;
 use crate::objects::mesh::Mesh;
@@ -264,22 +265,56 @@ impl GraphicsManager {
         color: Point3<f32>,
         out: &mut Vec<Node>,
     ) {
-        let shape = collider.shape();
+        self.add_shape(
+            window,
+            handle,
+            collider.shape(),
+            Isometry::identity(),
+            color,
+            out,
+        )
+    }
+
+    fn add_shape(
+        &mut self,
+        window: &mut Window,
+        handle: ColliderHandle,
+        shape: &dyn Shape,
+        delta: Isometry<f32>,
+        color: Point3<f32>,
+        out: &mut Vec<Node>,
+    ) {
+        if let Some(compound) = shape.as_compound() {
+            for (pos, part) in compound.shapes() {
+                self.add_shape(window, handle, &**part, delta * pos, color, out)
+            }
+        }
 
         if let Some(ball) = shape.as_ball() {
-            out.push(Node::Ball(Ball::new(handle, ball.radius, color, window)))
+            out.push(Node::Ball(Ball::new(
+                handle,
+                delta,
+                ball.radius,
+                color,
+                window,
+            )))
         }
 
-        // Shape::Polygon(poly) => out.push(Node::Convex(Convex::new(
-        //     handle,
-        //     poly.vertices().to_vec(),
-        //     color,
-        //     window,
-        // ))),
+        #[cfg(feature = "dim2")]
+        if let Some(polygon) = shape.as_polygon() {
+            out.push(Node::Convex(Convex::new(
+                handle,
+                delta,
+                polygon.vertices().to_vec(),
+                color,
+                window,
+            )))
+        }
 
         if let Some(cuboid) = shape.as_cuboid() {
             out.push(Node::Box(BoxNode::new(
                 handle,
+                delta,
                 cuboid.half_extents,
                 color,
                 window,
@@ -287,12 +322,15 @@ impl GraphicsManager {
         }
 
         if let Some(capsule) = shape.as_capsule() {
-            out.push(Node::Capsule(Capsule::new(handle, capsule, color, window)))
+            out.push(Node::Capsule(Capsule::new(
+                handle, delta, capsule, color, window,
+            )))
         }
 
         if let Some(triangle) = shape.as_triangle() {
             out.push(Node::Mesh(Mesh::new(
                 handle,
+                delta,
                 vec![triangle.a, triangle.b, triangle.c],
                 vec![Point3::new(0, 1, 2)],
                 color,
@@ -303,6 +341,7 @@ impl GraphicsManager {
         if let Some(trimesh) = shape.as_trimesh() {
             out.push(Node::Mesh(Mesh::new(
                 handle,
+                delta,
                 trimesh.vertices().to_vec(),
                 trimesh
                     .indices()
@@ -330,6 +369,7 @@ impl GraphicsManager {
         {
             out.push(Node::Cylinder(Cylinder::new(
                 handle,
+                delta,
                 cylinder.half_height,
                 cylinder.radius,
                 color,
@@ -341,12 +381,25 @@ impl GraphicsManager {
         if let Some(cone) = shape.as_cone() {
             out.push(Node::Cone(Cone::new(
                 handle,
+                delta,
                 cone.half_height,
                 cone.radius,
                 color,
                 window,
             )))
         }
+
+        #[cfg(feature = "dim3")]
+        if let Some(convex) = shape.as_convex_polyhedron() {
+            out.push(Node::Mesh(Mesh::new(
+                handle,
+                delta,
+                convex.points().to_vec(),
+                convex.indices().to_vec(),
+                color,
+                window,
+            )))
+        }
     }
 
     /*
@@ -104,6 +104,12 @@ impl NPhysicsWorld {
                     );
                     nphysics_joints.insert(c);
                 }
+                #[cfg(feature = "dim2")]
+                JointParams::RevoluteJoint(params) => {
+                    let c =
+                        RevoluteConstraint::new(b1, b2, params.local_anchor1, params.local_anchor2);
+                    nphysics_joints.insert(c);
+                }
                 JointParams::PrismaticJoint(params) => {
                     let mut c = PrismaticConstraint::new(
                         b1,
@@ -9,11 +9,13 @@ pub struct Ball {
     base_color: Point3<f32>,
     gfx: GraphicsNode,
     collider: ColliderHandle,
+    delta: Isometry<f32>,
 }
 
 impl Ball {
     pub fn new(
         collider: ColliderHandle,
+        delta: Isometry<f32>,
         radius: f32,
         color: Point3<f32>,
         window: &mut Window,
@@ -28,6 +30,7 @@ impl Ball {
             base_color: color,
             gfx: node,
             collider,
+            delta,
         };
 
         // res.gfx.set_texture_from_file(&Path::new("media/kitten.png"), "kitten");
@@ -55,7 +58,7 @@ impl Ball {
             colliders,
             self.collider,
             &self.color,
-            &Isometry::identity(),
+            &self.delta,
         );
     }
 
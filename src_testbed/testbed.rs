@@ -18,13 +18,13 @@ use kiss3d::text::Font;
 use kiss3d::window::{State, Window};
 use na::{self, Point2, Point3, Vector3};
 use rapier::dynamics::{
-    ActivationStatus, IntegrationParameters, JointSet, RigidBodyHandle, RigidBodySet,
+    ActivationStatus, IntegrationParameters, JointSet, RigidBodyHandle, RigidBodySet, SleepEvent,
 };
 #[cfg(feature = "dim3")]
 use rapier::geometry::Ray;
 use rapier::geometry::{
-    BroadPhase, ColliderHandle, ColliderSet, ContactEvent, InteractionGroups, NarrowPhase,
-    ProximityEvent,
+    BroadPhase, ColliderHandle, ColliderSet, ContactEvent, ContactForceEvent, InteractionGroups,
+    NarrowPhase, ProximityEvent,
 };
 use rapier::math::Vector;
 use rapier::pipeline::{ChannelEventCollector, PhysicsPipeline, QueryPipeline};
@@ -165,12 +165,16 @@ impl PhysicsSnapshot {
 pub struct PhysicsEvents {
     pub contact_events: Receiver<ContactEvent>,
     pub proximity_events: Receiver<ProximityEvent>,
+    pub contact_force_events: Receiver<ContactForceEvent>,
+    pub sleep_events: Receiver<SleepEvent>,
 }
 
 impl PhysicsEvents {
     fn poll_all(&self) {
         while let Ok(_) = self.contact_events.try_recv() {}
         while let Ok(_) = self.proximity_events.try_recv() {}
+        while let Ok(_) = self.contact_force_events.try_recv() {}
+        while let Ok(_) = self.sleep_events.try_recv() {}
     }
 }
 
@@ -308,10 +312,16 @@ impl Testbed {
 
         let contact_channel = crossbeam::channel::unbounded();
         let proximity_channel = crossbeam::channel::unbounded();
-        let event_handler = ChannelEventCollector::new(proximity_channel.0, contact_channel.0);
+        let contact_force_channel = crossbeam::channel::unbounded();
+        let sleep_channel = crossbeam::channel::unbounded();
+        let event_handler = ChannelEventCollector::new(proximity_channel.0, contact_channel.0)
+            .with_contact_force_event_sender(contact_force_channel.0)
+            .with_sleep_event_sender(sleep_channel.0);
         let events = PhysicsEvents {
             contact_events: contact_channel.1,
             proximity_events: proximity_channel.1,
+            contact_force_events: contact_force_channel.1,
+            sleep_events: sleep_channel.1,
         };
         let physics = PhysicsState::new();
 
@@ -618,6 +628,7 @@ impl Testbed {
                                             &mut physics.joints,
                                             None,
                                             None,
+                                            None,
                                             event_handler,
                                         );
                                     });
@@ -634,12 +645,13 @@ impl Testbed {
                                     &mut self.physics.joints,
                                     None,
                                     None,
+                                    None,
                                     &self.event_handler,
                                 );
 
                                 self.physics
                                     .query_pipeline
-                                    .update(&self.physics.bodies, &self.physics.colliders);
+                                    .update(&self.physics.bodies, &mut self.physics.colliders);
                             }
 
                             #[cfg(all(feature = "dim2", feature = "other-backends"))]
@@ -1322,12 +1334,16 @@ impl State for Testbed {
             {
                 if self.state.flags.contains(TestbedStateFlags::SLEEP) {
                     for (_, mut body) in self.physics.bodies.iter_mut() {
-                        body.activation.threshold = ActivationStatus::default_threshold();
+                        body.activation.linear_threshold =
+                            ActivationStatus::default_linear_threshold();
+                        body.activation.angular_threshold =
+                            ActivationStatus::default_angular_threshold();
                     }
                 } else {
                     for (_, mut body) in self.physics.bodies.iter_mut() {
                         body.wake_up(true);
-                        body.activation.threshold = -1.0;
+                        body.activation.linear_threshold = -1.0;
+                        body.activation.angular_threshold = -1.0;
                     }
                 }
             }
@@ -1405,6 +1421,7 @@ impl State for Testbed {
                                 &mut physics.joints,
                                 None,
                                 None,
+                                None,
                                 event_handler,
                             );
                         });
@@ -1421,12 +1438,13 @@ impl State for Testbed {
                         &mut self.physics.joints,
                         None,
                         None,
+                        None,
                         &self.event_handler,
                     );
 
                     self.physics
                         .query_pipeline
-                        .update(&self.physics.bodies, &self.physics.colliders);
+                        .update(&self.physics.bodies, &mut self.physics.colliders);
 
                     for plugin in &mut self.plugins {
                         plugin.step(&mut self.physics)